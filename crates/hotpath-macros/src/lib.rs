@@ -1,7 +1,7 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{quote, quote_spanned};
 use syn::parse::Parser;
-use syn::{parse_macro_input, ImplItem, Item, ItemFn, LitInt, LitStr};
+use syn::{parse_macro_input, ImplItem, Item, ItemFn, LitInt, LitStr, Stmt};
 
 #[derive(Clone, Copy)]
 enum Format {
@@ -29,10 +29,14 @@ impl Format {
 ///
 /// # Parameters
 ///
-/// * `percentiles` - Array of percentile values (0-100) to display in the report. Default: `[95]`
+/// * `percentiles` - Array of percentile values (0-100) to display in the report, fractional values like `99.9` are supported,
+///   as well as names like `"median"`, `"p99"` or `"p99.9"`. Default: `[95]`
 /// * `format` - Output format as a string: `"table"` (default), `"json"`, or `"json-pretty"`
 /// * `limit` - Maximum number of functions to display in the report (0 = show all). Default: `15`
 /// * `timeout` - Optional timeout in milliseconds. If specified, the program will print the report and exit after the timeout.
+/// * `stream` - Where to print the report: `"stdout"` (default) or `"stderr"`
+/// * `name` - Name of the caller function in the report title and its row, overriding the
+///   default module path of the function, e.g. `"app"`
 ///
 /// # Examples
 ///
@@ -47,7 +51,7 @@ impl Format {
 ///
 /// Custom percentiles:
 ///
-/// ```rust,no_run
+/// ```rust,ignore
 /// #[tokio::main]
 /// #[cfg_attr(feature = "hotpath", hotpath::main(percentiles = [50, 90, 95, 99]))]
 /// async fn main() {
@@ -82,11 +86,29 @@ impl Format {
 /// }
 /// ```
 ///
+/// Report on stderr, keeping stdout for the program's output:
+///
+/// ```rust,no_run
+/// #[cfg_attr(feature = "hotpath", hotpath::main(stream = "stderr"))]
+/// fn main() {
+///     // Your code here
+/// }
+/// ```
+///
+/// Explicit report name instead of `my_crate::main`:
+///
+/// ```rust,no_run
+/// #[cfg_attr(feature = "hotpath", hotpath::main(name = "app"))]
+/// fn main() {
+///     // Your code here
+/// }
+/// ```
+///
 /// # Usage with Tokio
 ///
 /// When using with tokio, place `#[tokio::main]` before `#[hotpath::main]`:
 ///
-/// ```rust,no_run
+/// ```rust,ignore
 /// #[tokio::main]
 /// #[cfg_attr(feature = "hotpath", hotpath::main)]
 /// async fn main() {
@@ -112,36 +134,18 @@ pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
     let block = &input.block;
 
     // Defaults
-    let mut percentiles: Vec<u8> = vec![95];
+    let mut percentiles: Vec<f64> = vec![95.0];
     let mut format = Format::Table;
     let mut limit: usize = 15;
     let mut timeout: Option<u64> = None;
+    let mut to_stderr = false;
+    let mut name: Option<LitStr> = None;
 
     // Parse named args like: percentiles=[..], format=".."
     if !attr.is_empty() {
         let parser = syn::meta::parser(|meta| {
             if meta.path.is_ident("percentiles") {
-                meta.input.parse::<syn::Token![=]>()?;
-                let content;
-                syn::bracketed!(content in meta.input);
-                let mut vals = Vec::new();
-                while !content.is_empty() {
-                    let li: LitInt = content.parse()?;
-                    let v: u8 = li.base10_parse()?;
-                    if !(0..=100).contains(&v) {
-                        return Err(
-                            meta.error(format!("Invalid percentile {} (must be 0..=100)", v))
-                        );
-                    }
-                    vals.push(v);
-                    if !content.is_empty() {
-                        content.parse::<syn::Token![,]>()?;
-                    }
-                }
-                if vals.is_empty() {
-                    return Err(meta.error("At least one percentile must be specified"));
-                }
-                percentiles = vals;
+                percentiles = parse_percentiles(&meta)?;
                 return Ok(());
             }
 
@@ -175,8 +179,34 @@ pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
                 return Ok(());
             }
 
+            if meta.path.is_ident("stream") {
+                meta.input.parse::<syn::Token![=]>()?;
+                let lit: LitStr = meta.input.parse()?;
+                to_stderr = match lit.value().as_str() {
+                    "stdout" => false,
+                    "stderr" => true,
+                    other => {
+                        return Err(meta.error(format!(
+                            "Unknown stream {:?}. Expected one of: \"stdout\", \"stderr\"",
+                            other
+                        )))
+                    }
+                };
+                return Ok(());
+            }
+
+            if meta.path.is_ident("name") {
+                meta.input.parse::<syn::Token![=]>()?;
+                let lit: LitStr = meta.input.parse()?;
+                if lit.value().is_empty() {
+                    return Err(meta.error("name must not be empty"));
+                }
+                name = Some(lit);
+                return Ok(());
+            }
+
             Err(meta.error(
-                "Unknown parameter. Supported: percentiles=[..], format=\"..\", limit=N, timeout=N",
+                "Unknown parameter. Supported: percentiles=[..], format=\"..\", limit=N, timeout=N, stream=\"..\", name=\"..\"",
             ))
         });
 
@@ -191,14 +221,19 @@ pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
     let asyncness = sig.asyncness.is_some();
     let fn_name = &sig.ident;
 
+    let caller_name = match &name {
+        Some(name) => quote! { #name },
+        None => quote! { concat!(module_path!(), "::", stringify!(#fn_name)) },
+    };
+
     let base_builder = quote! {
-        let caller_name: &'static str =
-            concat!(module_path!(), "::", stringify!(#fn_name));
+        let caller_name: &'static str = #caller_name;
 
         hotpath::GuardBuilder::new(caller_name)
             .percentiles(#percentiles_array)
             .limit(#limit)
             .format(#format_token)
+            .to_stderr(#to_stderr)
     };
 
     let guard_init = if let Some(timeout_ms) = timeout {
@@ -236,6 +271,71 @@ pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
     output.into()
 }
 
+/// Parses a `percentiles = [..]` argument, validating that it's a non-empty list of 0..=100 values.
+/// Integer and fractional values are accepted, as well as names, e.g. `[50, 99.9, "median", "p99"]`.
+fn parse_percentiles(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Vec<f64>> {
+    meta.input.parse::<syn::Token![=]>()?;
+    let content;
+    syn::bracketed!(content in meta.input);
+    let mut vals = Vec::new();
+    while !content.is_empty() {
+        let v: f64 = match content.parse::<syn::Lit>()? {
+            syn::Lit::Int(li) => li.base10_parse()?,
+            syn::Lit::Float(lf) => lf.base10_parse()?,
+            syn::Lit::Str(ls) => percentile_from_name(&ls.value()).ok_or_else(|| {
+                syn::Error::new_spanned(
+                    &ls,
+                    "Unknown percentile name, expected \"min\", \"median\", \"max\" or \"pN\" like \"p99.9\"",
+                )
+            })?,
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "Expected a percentile number or name",
+                ))
+            }
+        };
+        if !(0.0..=100.0).contains(&v) {
+            return Err(meta.error(format!("Invalid percentile {} (must be 0..=100)", v)));
+        }
+        vals.push(v);
+        if !content.is_empty() {
+            content.parse::<syn::Token![,]>()?;
+        }
+    }
+    if vals.is_empty() {
+        return Err(meta.error("At least one percentile must be specified"));
+    }
+    Ok(vals)
+}
+
+/// Whether the function is declared to return `impl Future`, e.g. `impl Future<Output = T> + Send`.
+fn returns_impl_future(sig: &syn::Signature) -> bool {
+    let syn::ReturnType::Type(_, ty) = &sig.output else {
+        return false;
+    };
+    let syn::Type::ImplTrait(impl_trait) = ty.as_ref() else {
+        return false;
+    };
+    impl_trait.bounds.iter().any(|bound| {
+        matches!(
+            bound,
+            syn::TypeParamBound::Trait(bound)
+                if bound.path.segments.last().is_some_and(|segment| segment.ident == "Future")
+        )
+    })
+}
+
+/// Maps percentile names like `"median"` or `"p99.9"` to their values.
+fn percentile_from_name(name: &str) -> Option<f64> {
+    match name.to_ascii_lowercase().as_str() {
+        "min" => Some(0.0),
+        "median" => Some(50.0),
+        "max" => Some(100.0),
+        name => name.strip_prefix('p')?.parse().ok(),
+    }
+}
+
 /// Instruments a function to send performance measurements to the hotpath profiler.
 ///
 /// This attribute macro wraps functions with profiling code that measures execution time
@@ -245,19 +345,26 @@ pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// # Behavior
 ///
 /// The macro automatically detects whether the function is sync or async and instruments
-/// it appropriately. Measurements include:
+/// it appropriately. Sync functions returning `impl Future` are treated as async: the returned
+/// future is wrapped, so that the awaited work is measured rather than creating the future.
+/// Measurements include:
 ///
 /// * **Time profiling** (default): Execution duration using high-precision timers
 /// * **Allocation profiling**: Memory allocations when allocation features are enabled
 ///   - `hotpath-alloc-bytes-total` - Total bytes allocated
 ///   - `hotpath-alloc-count-total` - Total allocation count
+///   - `hotpath-alloc-leak` - Bytes allocated and not freed again
+///
+/// The measurement is sent when the guard created at the start of the function is dropped,
+/// so calls ending with an early `return`, a `?` error or a panic are recorded too,
+/// for both sync and async functions.
 ///
 /// # Async Function Limitations
 ///
 /// When using allocation profiling features with async functions, you must use the
 /// `tokio` runtime in `current_thread` mode:
 ///
-/// ```rust,no_run
+/// ```rust,ignore
 /// #[tokio::main(flavor = "current_thread")]
 /// async fn main() {
 ///     // Your async code here
@@ -270,49 +377,295 @@ pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// When the `hotpath` feature is disabled, this macro compiles to zero overhead (no instrumentation).
 ///
+/// # Parameters
+///
+/// * `track_return` - In allocation profiling modes, additionally reports allocations made while
+///   evaluating the function's tail (return) expression as a separate `<function>::return` entry.
+///   The sampling is delayed until the returned value is fully constructed. Ignored in time mode.
+///   The body must end with a tail expression or a `return` statement, earlier `return`s are
+///   not attributed.
+///
+/// ```rust,no_run
+/// #[cfg_attr(feature = "hotpath", hotpath::measure(track_return))]
+/// fn build_buffer(len: usize) -> Vec<u8> {
+///     vec![0; len]
+/// }
+/// ```
+///
+/// * `monomorphized` - Reports each instantiation of a generic function as a separate entry,
+///   e.g. `process<u32>` and `process<alloc::string::String>`, by appending the
+///   [`type_name`](std::any::type_name) of its generic type parameters to the measurement name.
+///   Beware of report cardinality in highly generic code: every distinct set of type arguments
+///   produces its own row. `impl Trait` arguments cannot be named and are not included.
+///
+/// ```rust,no_run
+/// #[cfg_attr(feature = "hotpath", hotpath::measure(monomorphized))]
+/// fn process<T: Clone>(items: &[T]) -> Vec<T> {
+///     items.to_vec()
+/// }
+/// ```
+///
+/// * `poll_time` - For async functions, additionally reports the time spent actually polling the
+///   function's future as a separate `<function>::poll` entry. Unlike the regular entry, it
+///   excludes time spent suspended at `.await` points, which tells CPU work apart from waiting
+///   on I/O or timers. The average number of polls per call is shown in the `Avg Polls` column,
+///   a future polled thousands of times per call points at a busy loop. Only measured in time mode.
+///
+/// ```rust,no_run
+/// #[cfg_attr(feature = "hotpath", hotpath::measure(poll_time))]
+/// async fn fetch_and_parse() {
+///     // ...
+/// }
+/// ```
+///
+/// * `async` - Measures a sync function returning a future that isn't declared as
+///   `impl Future`, e.g. `Pin<Box<dyn Future<Output = T> + Send>>` or a `BoxFuture<T>` alias.
+///   The returned future is awaited inside of a new boxed future, so the return type must be
+///   a `Pin<Box<..>>`. Functions returning `impl Future` are detected without it.
+///
+/// ```rust,no_run
+/// # use std::future::Future;
+/// # use std::pin::Pin;
+/// #[cfg_attr(feature = "hotpath", hotpath::measure(async))]
+/// fn fetch(url: String) -> Pin<Box<dyn Future<Output = usize> + Send>> {
+///     Box::pin(async move { url.len() })
+/// }
+/// ```
+///
+/// * `percentiles = [..]` - Replaces the global percentiles for this function. Columns of
+///   percentiles the function doesn't track show `N/A`, and its extra percentiles are added as
+///   columns showing `N/A` for all other functions. Useful to only look at tails where they matter.
+///
+/// ```rust,no_run
+/// #[cfg_attr(feature = "hotpath", hotpath::measure(percentiles = [50, 99]))]
+/// fn handle_request() {
+///     // ...
+/// }
+/// ```
+///
 /// # See Also
 ///
 /// * [`main`](macro@main) - Attribute macro that initializes profiling
 /// * [`measure_block!`](../hotpath/macro.measure_block.html) - Macro for measuring code blocks
 #[proc_macro_attribute]
-pub fn measure(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn measure(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemFn);
+    instrument(attr.into(), input, None).into()
+}
+
+/// Expands `measure` for a function. Methods instrumented through `measure_impl` pass the
+/// name of their `Self` type, which is inserted between the module path and the method name.
+fn instrument(
+    attr: proc_macro2::TokenStream,
+    input: ItemFn,
+    self_ty: Option<&str>,
+) -> proc_macro2::TokenStream {
     let vis = &input.vis;
     let sig = &input.sig;
     let block = &input.block;
 
-    let name = sig.ident.to_string();
-    let asyncness = sig.asyncness.is_some();
+    let mut track_return = false;
+    let mut monomorphized = false;
+    let mut poll_time = false;
+    let mut returns_boxed_future = false;
+    let mut percentiles: Option<Vec<f64>> = None;
+
+    if !attr.is_empty() {
+        let parser = syn::meta::parser(|meta| {
+            if meta.path.is_ident("track_return") {
+                track_return = true;
+                return Ok(());
+            }
+
+            if meta.path.is_ident("monomorphized") {
+                monomorphized = true;
+                return Ok(());
+            }
+
+            if meta.path.is_ident("poll_time") {
+                poll_time = true;
+                return Ok(());
+            }
+
+            if meta.path.is_ident("async") {
+                returns_boxed_future = true;
+                return Ok(());
+            }
+
+            if meta.path.is_ident("percentiles") {
+                percentiles = Some(parse_percentiles(&meta)?);
+                return Ok(());
+            }
+
+            Err(meta.error(
+                "Unknown parameter. Supported: track_return, monomorphized, poll_time, async, percentiles=[..]",
+            ))
+        });
+
+        if let Err(e) = parser.parse2(attr) {
+            return e.to_compile_error();
+        }
+    }
+
+    let name = match self_ty {
+        Some(self_ty) => format!("{}::{}", self_ty, sig.ident),
+        None => sig.ident.to_string(),
+    };
+    // Sync functions returning a future are measured like async ones, by wrapping the future
+    let returns_future =
+        sig.asyncness.is_none() && (returns_boxed_future || returns_impl_future(sig));
+    let asyncness = sig.asyncness.is_some() || returns_future;
+
+    if returns_boxed_future && sig.asyncness.is_some() {
+        return syn::Error::new_spanned(
+            &sig.ident,
+            "`async` is only needed for sync functions returning a boxed future",
+        )
+        .to_compile_error();
+    }
+
+    if poll_time && !asyncness {
+        return syn::Error::new_spanned(&sig.ident, "`poll_time` requires an async function")
+            .to_compile_error();
+    }
+
+    if track_return && returns_future {
+        return syn::Error::new_spanned(
+            &sig.ident,
+            "`track_return` is not supported for functions returning a future",
+        )
+        .to_compile_error();
+    }
+
+    let type_params: Vec<_> = sig.generics.type_params().map(|p| &p.ident).collect();
+    if monomorphized && type_params.is_empty() {
+        return syn::Error::new_spanned(
+            &sig.ident,
+            "`monomorphized` requires a function with generic type parameters",
+        )
+        .to_compile_error();
+    }
+
+    // Spanned to the function name, so that `line!()` points at the signature
+    let location = quote_spanned! {sig.ident.span()=> ::core::file!(), ::core::line!() };
+    let measurement_name = |suffix: &str| {
+        let base = quote! { concat!(module_path!(), "::", #name, #suffix) };
+        let base = if monomorphized {
+            quote! {
+                hotpath::monomorphized_name(
+                    #base,
+                    &[#(::core::any::type_name::<#type_params>()),*]
+                )
+            }
+        } else {
+            base
+        };
+        quote! { hotpath::located_name(#base, #location) }
+    };
+    let guard_name = measurement_name("");
+    let return_name = measurement_name("::return");
+    let poll_name = measurement_name("::poll");
+
+    let body = if !track_return {
+        quote! { #block }
+    } else {
+        let (tail, init) = match block.stmts.split_last() {
+            Some((Stmt::Expr(tail, None), init)) => (tail, init),
+            Some((
+                Stmt::Expr(
+                    syn::Expr::Return(syn::ExprReturn {
+                        expr: Some(tail), ..
+                    }),
+                    _,
+                ),
+                init,
+            )) => (&**tail, init),
+            _ => {
+                return syn::Error::new_spanned(
+                    &sig.ident,
+                    "`track_return` requires a body ending with a tail expression or `return` statement",
+                )
+                .to_compile_error();
+            }
+        };
+        quote! {
+            {
+                #(#init)*
+                let _return_guard = hotpath::MeasurementGuard::build_return(
+                    #return_name,
+                    #asyncness
+                );
+                let hotpath_return_value = #tail;
+                ::core::mem::drop(_return_guard);
+                hotpath_return_value
+            }
+        }
+    };
+
+    let percentiles_override = percentiles.map(|percentiles| {
+        quote! { .with_percentiles(&[#(#percentiles),*]) }
+    });
 
-    let guard_init = quote! {
+    let guard = quote! {
         let _guard = hotpath::MeasurementGuard::build(
-            concat!(module_path!(), "::", #name),
+            #guard_name,
             false,
             #asyncness
-        );
-        #block
+        )#percentiles_override;
     };
 
-    let wrapped = if asyncness {
-        quote! { async { #guard_init }.await }
+    let wrapped = if returns_future {
+        // The body only creates the future, the measurement starts when it's first polled,
+        // like the body of an async function
+        let future = quote! {
+            async move {
+                #guard
+                hotpath_future.await
+            }
+        };
+        let future = if poll_time {
+            quote! { hotpath::poll_timed(#poll_name, #future) }
+        } else {
+            future
+        };
+        let future = if returns_boxed_future {
+            quote! { ::std::boxed::Box::pin(#future) }
+        } else {
+            future
+        };
+        // Evaluated in a closure, so that early returns also end up in the measured future. A
+        // boxed future needs the return type for the returned values to be coerced to it.
+        let output = match (&sig.output, returns_boxed_future) {
+            (syn::ReturnType::Type(_, ty), true) => quote! { -> #ty },
+            _ => quote! {},
+        };
+        quote! {
+            #[allow(clippy::redundant_closure_call)]
+            let hotpath_future = (|| #output #block)();
+            #future
+        }
+    } else if poll_time {
+        quote! { hotpath::poll_timed(#poll_name, async { #guard #body }).await }
+    } else if asyncness {
+        quote! { async { #guard #body }.await }
     } else {
-        guard_init
+        quote! { #guard #body }
     };
 
-    let output = quote! {
+    quote! {
         #vis #sig {
             #wrapped
         }
-    };
-
-    output.into()
+    }
 }
 
-/// Marks a function to be excluded from profiling when used with [`measure_all`](macro@measure_all).
+/// Marks a function to be excluded from profiling when used with [`measure_all`](macro@measure_all)
+/// or [`measure_impl`](macro@measure_impl).
 ///
 /// # Usage
 ///
 /// ```rust,no_run
+/// # struct MyStruct { value: usize }
 /// #[cfg_attr(feature = "hotpath", hotpath::measure_all)]
 /// impl MyStruct {
 ///     fn important_method(&self) {
@@ -416,6 +769,72 @@ pub fn measure_all(_attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
+/// Instruments all methods of an impl block, naming each measurement after its `Self` type.
+///
+/// Unlike [`measure_all`](macro@measure_all), which reports methods under their bare name,
+/// every method is reported as `module::Type::method`, so methods with the same name on
+/// different types (e.g. `new`) get separate rows. Methods taking `&self`, `&mut self` or
+/// `self`, associated functions and async methods are all instrumented. `const fn`s are left
+/// untouched, since they cannot be instrumented, as are methods marked with [`skip`](macro@skip).
+///
+/// Generic arguments are not part of the name: `impl Wrapper<u32>` and `impl Wrapper<String>`
+/// both report as `Wrapper::method`.
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// struct Parser;
+///
+/// #[cfg_attr(feature = "hotpath", hotpath::measure_impl)]
+/// impl Parser {
+///     fn new() -> Self {
+///         // Reported as `my_crate::Parser::new`
+///         Parser
+///     }
+///
+///     fn parse(&self, input: &str) -> usize {
+///         // Reported as `my_crate::Parser::parse`
+///         input.len()
+///     }
+/// }
+/// ```
+///
+/// # See Also
+///
+/// * [`measure_all`](macro@measure_all) - Bulk instrumentation of modules and impl blocks
+/// * [`skip`](macro@skip) - Marker to exclude specific methods from measurement
+#[proc_macro_attribute]
+pub fn measure_impl(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut impl_block = parse_macro_input!(item as syn::ItemImpl);
+
+    let self_ty = match impl_block.self_ty.as_ref() {
+        syn::Type::Path(type_path) => match type_path.path.segments.last() {
+            Some(segment) => segment.ident.to_string(),
+            None => quote!(#type_path).to_string(),
+        },
+        other => quote!(#other).to_string().replace(' ', ""),
+    };
+
+    for item in impl_block.items.iter_mut() {
+        if let ImplItem::Fn(method) = item {
+            if method.sig.constness.is_some() || has_hotpath_skip(&method.attrs) {
+                continue;
+            }
+
+            let func_tokens = TokenStream::from(quote!(#method));
+            let func = parse_macro_input!(func_tokens as ItemFn);
+            let transformed = TokenStream::from(instrument(
+                proc_macro2::TokenStream::new(),
+                func,
+                Some(&self_ty),
+            ));
+            *method = parse_macro_input!(transformed as syn::ImplItemFn);
+        }
+    }
+
+    TokenStream::from(quote!(#impl_block))
+}
+
 fn has_hotpath_skip(attrs: &[syn::Attribute]) -> bool {
     attrs.iter().any(|attr| {
         // Check for #[skip] or #[hotpath::skip]
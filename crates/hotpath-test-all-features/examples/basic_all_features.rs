@@ -24,6 +24,7 @@ async fn async_function(sleep: u64) {
     tokio::time::sleep(Duration::from_nanos(sleep)).await;
 }
 
+#[allow(dead_code)]
 #[cfg_attr(feature = "hotpath", hotpath::measure_all)]
 mod measured_module {
     pub fn sync_function() {}
@@ -35,6 +36,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     for i in 0..100 {
         sync_function(i);
         async_function(i * 2).await;
+
+        #[cfg(feature = "hotpath")]
         hotpath::measure_block!("custom_block", {
             if i == 0 {
                 println!("i ran");
@@ -0,0 +1,28 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn load_config() -> Vec<u8> {
+    let mut buffers = Vec::new();
+    for _ in 0..100 {
+        buffers.push(vec![0u8; 1024]);
+    }
+    buffers.concat()
+}
+
+// The report points at the `vec!` line of this function
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn build_cache() -> Vec<u8> {
+    vec![1u8; 4 * 1024 * 1024]
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn startup() -> usize {
+    load_config().len() + build_cache().len()
+}
+
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("alloc_backtrace::main")
+        .alloc_backtrace_threshold(64 * 1024)
+        .build();
+
+    std::hint::black_box(startup());
+}
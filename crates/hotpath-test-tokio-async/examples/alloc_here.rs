@@ -0,0 +1,20 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn build_buffers(len: usize) -> (Vec<u8>, Vec<u64>) {
+    let small = {
+        hotpath::alloc_here!("build_buffers::small");
+        Vec::with_capacity(len)
+    };
+
+    hotpath::alloc_here!("build_buffers::large");
+    let large = Vec::with_capacity(len * 64);
+
+    (small, large)
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::main)]
+fn main() {
+    for i in 1..=10 {
+        let buffers = build_buffers(i * 16);
+        std::hint::black_box(&buffers);
+    }
+}
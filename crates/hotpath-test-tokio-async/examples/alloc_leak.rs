@@ -0,0 +1,47 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn leaky_function() {
+    std::mem::forget(std::hint::black_box(Vec::<u8>::with_capacity(1024)));
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn balanced_function() {
+    let buffer = std::hint::black_box(Vec::<u8>::with_capacity(1024));
+    drop(buffer);
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn outer_function() {
+    leaky_function();
+    balanced_function();
+}
+
+// Run with: cargo run --example alloc_leak --features hotpath,hotpath-alloc-leak
+fn main() {
+    let sink: Arc<Mutex<Vec<(String, u64)>>> = Arc::new(Mutex::new(Vec::new()));
+
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("alloc_leak::main")
+        .raw_sink(Arc::clone(&sink))
+        .build();
+
+    for _ in 0..10 {
+        outer_function();
+    }
+
+    // Dropping the guard flushes all pending measurements into the sink.
+    #[allow(clippy::drop_non_drop)]
+    #[cfg(feature = "hotpath")]
+    drop(_hotpath);
+
+    let mut totals = BTreeMap::<String, u64>::new();
+    for (name, value) in sink.lock().unwrap().iter() {
+        *totals.entry(name.clone()).or_default() += value;
+    }
+
+    for (name, total) in totals {
+        println!("total: {} {}", name, total);
+    }
+}
@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn with_capacity() -> Vec<u8> {
+    Vec::with_capacity(1000)
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn zeroed() -> Vec<u8> {
+    vec![0; 1000]
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn over_aligned() -> Box<[u64; 125]> {
+    #[allow(dead_code)]
+    #[repr(align(64))]
+    struct Aligned([u64; 125]);
+    std::hint::black_box(Box::new(Aligned([0; 125])));
+    Box::new([0; 125])
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn grow(mut buffer: Vec<u8>) -> Vec<u8> {
+    buffer.reserve_exact(1000);
+    buffer
+}
+
+// Run with: cargo run --example alloc_parity --features hotpath,hotpath-alloc-bytes-total
+fn main() {
+    let sink: Arc<Mutex<Vec<(String, u64)>>> = Arc::new(Mutex::new(Vec::new()));
+
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("alloc_parity::main")
+        .raw_sink(Arc::clone(&sink))
+        .build();
+
+    std::hint::black_box(with_capacity());
+    std::hint::black_box(zeroed());
+    std::hint::black_box(over_aligned());
+
+    let mut buffer = Vec::with_capacity(1000);
+    buffer.extend_from_slice(&[1u8; 1000]);
+    std::hint::black_box(grow(buffer));
+
+    // Dropping the guard flushes all pending measurements into the sink.
+    #[allow(clippy::drop_non_drop)]
+    #[cfg(feature = "hotpath")]
+    drop(_hotpath);
+
+    let mut totals = BTreeMap::<String, u64>::new();
+    for (name, value) in sink.lock().unwrap().iter() {
+        *totals.entry(name.clone()).or_default() += value;
+    }
+
+    for (name, total) in totals {
+        println!("total: {} {}", name, total);
+    }
+}
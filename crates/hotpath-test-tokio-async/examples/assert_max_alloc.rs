@@ -0,0 +1,27 @@
+fn build_index(len: u64) -> Vec<u64> {
+    (0..len).collect()
+}
+
+// Run with: cargo run --example assert_max_alloc --features hotpath,hotpath-alloc-bytes-total
+fn main() {
+    // 100 u64 values, 800 bytes
+    let index = hotpath::assert_max_alloc!("build_index", 1024, { build_index(100) });
+    println!("within budget: {} values", index.len());
+
+    std::panic::set_hook(Box::new(|_| {}));
+    let result = std::panic::catch_unwind(|| {
+        hotpath::assert_max_alloc!("build_index", 1024, {
+            std::hint::black_box(build_index(1000));
+        })
+    });
+    match result {
+        Ok(()) => println!("over budget: unchecked"),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<String>()
+                .cloned()
+                .unwrap_or_default();
+            println!("over budget: {message}");
+        }
+    }
+}
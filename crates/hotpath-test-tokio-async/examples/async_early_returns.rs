@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+async fn may_fail(flag: bool) -> Result<Vec<u8>, &'static str> {
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    if flag {
+        Err("boom")
+    } else {
+        Ok(vec![1, 2, 3])
+    }
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+async fn propagates_error() -> Result<usize, &'static str> {
+    let data = may_fail(true).await?;
+    unreachable!("{:?}", data);
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+async fn returns_error_early(fail: bool) -> Result<usize, &'static str> {
+    let data = vec![0u8; 128];
+    std::hint::black_box(&data);
+
+    if fail {
+        return Err("early");
+    }
+
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    Ok(data.len())
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+async fn panics_after_await() {
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    panic!("measured function panicked");
+}
+
+#[tokio::main(flavor = "current_thread")]
+#[cfg_attr(feature = "hotpath", hotpath::main(limit = 0))]
+async fn main() {
+    for _ in 0..3 {
+        assert!(propagates_error().await.is_err());
+    }
+
+    for i in 0..4 {
+        let _ = returns_error_early(i % 2 == 0).await;
+    }
+
+    let result = tokio::spawn(panics_after_await()).await;
+    assert!(result.is_err());
+}
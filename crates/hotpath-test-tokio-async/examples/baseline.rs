@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn fast_function() {
+    std::thread::sleep(Duration::from_micros(50));
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn slow_function() {
+    std::thread::sleep(Duration::from_micros(200));
+}
+
+// Run with: cargo run --example baseline --features hotpath -- path/to/baseline.json
+// A baseline can be saved with: HOTPATH_JSON=true cargo run --example baseline --features hotpath
+fn main() {
+    let baseline = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "hotpath_baseline.json".to_string());
+
+    let _hotpath = hotpath::GuardBuilder::new("baseline::main")
+        .baseline(baseline)
+        .build();
+
+    for _ in 0..10 {
+        fast_function();
+        slow_function();
+    }
+}
@@ -0,0 +1,15 @@
+// Run with: cargo run --example bench --features hotpath
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("bench::main").build();
+
+    #[cfg(feature = "hotpath")]
+    {
+        let stats = hotpath::bench("bench::sort", 200, || {
+            let mut items: Vec<u32> = (0..1_000).rev().collect();
+            items.sort();
+            items
+        });
+        println!("{stats}");
+    }
+}
@@ -0,0 +1,15 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn work() -> Vec<u8> {
+    vec![0; 2000]
+}
+
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("byte_format::main")
+        .byte_format(hotpath::ByteFormat::Precise(3))
+        .build();
+
+    for _ in 0..5 {
+        std::hint::black_box(work());
+    }
+}
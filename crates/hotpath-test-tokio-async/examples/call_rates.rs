@@ -0,0 +1,39 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sleepy_function() {
+    std::thread::sleep(Duration::from_millis(1));
+}
+
+fn fetch_rates(port: u16) -> hotpath::CallRatesJson {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).expect("Failed to connect");
+    write!(
+        stream,
+        "GET /rates HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+
+    serde_json::from_str(body).unwrap()
+}
+
+fn main() {
+    let port = 6785;
+    let _metrics = hotpath::serve_metrics(port);
+
+    for _ in 0..10 {
+        sleepy_function();
+    }
+    // Measurements are processed asynchronously by the worker thread
+    std::thread::sleep(Duration::from_millis(50));
+
+    let rates = fetch_rates(port);
+    let counts = &rates.rates["call_rates::sleepy_function"];
+    println!("bucket_ns: {}", rates.bucket_ns);
+    println!("calls: {}", counts.iter().sum::<u64>());
+}
@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn fallible_work(fail: bool) -> Vec<u8> {
+    let data = vec![1u8; 1024];
+    std::thread::sleep(Duration::from_millis(5));
+    if fail {
+        panic!("fallible_work failed");
+    }
+    data
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::main(percentiles = [0]))]
+fn main() {
+    // Keep the expected panics out of the output
+    std::panic::set_hook(Box::new(|_| {}));
+
+    for i in 0..4 {
+        let result = std::panic::catch_unwind(|| fallible_work(i % 2 == 1));
+        println!(
+            "call {}: {}",
+            i,
+            if result.is_ok() { "ok" } else { "panicked" }
+        );
+    }
+}
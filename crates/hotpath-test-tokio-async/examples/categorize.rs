@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn db_query() {
+    std::thread::sleep(Duration::from_micros(200));
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn db_insert() {
+    std::thread::sleep(Duration::from_micros(100));
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn http_fetch() {
+    std::thread::sleep(Duration::from_micros(100));
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn compute() -> u64 {
+    (0..10_000u64).map(|i| i * i).sum()
+}
+
+fn main() {
+    let _hotpath = hotpath::GuardBuilder::new("categorize::main")
+        .categorize(Box::new(|name| {
+            if name.starts_with("categorize::db_") {
+                "db"
+            } else if name.starts_with("categorize::http_") {
+                "network"
+            } else {
+                "compute"
+            }
+        }))
+        .build();
+
+    for _ in 0..10 {
+        db_query();
+        db_insert();
+        http_fetch();
+        std::hint::black_box(compute());
+    }
+}
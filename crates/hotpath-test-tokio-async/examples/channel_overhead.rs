@@ -0,0 +1,54 @@
+//! Per-call cost of sending measurements to the worker thread, run with and without the
+//! `hotpath-batch-channel` or `hotpath-inline` features to compare the backends:
+//!
+//! ```text
+//! cargo run --release -p hotpath-test-tokio-async --example channel_overhead --features hotpath
+//! cargo run --release -p hotpath-test-tokio-async --example channel_overhead --features hotpath,hotpath-batch-channel
+//! cargo run --release -p hotpath-test-tokio-async --example channel_overhead --features hotpath,hotpath-inline
+//! ```
+
+use std::time::Instant;
+
+const CALLS: u64 = 1_000_000;
+const THREADS: u64 = 4;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn noop(i: u64) -> u64 {
+    std::hint::black_box(i)
+}
+
+fn ns_per_call(threads: u64) -> f64 {
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                for i in 0..CALLS / threads {
+                    noop(i);
+                }
+            });
+        }
+    });
+    start.elapsed().as_nanos() as f64 / CALLS as f64
+}
+
+fn main() {
+    let backend = if cfg!(feature = "hotpath-batch-channel") {
+        "batch-channel"
+    } else if cfg!(feature = "hotpath-inline") {
+        "inline"
+    } else {
+        "channel"
+    };
+
+    let guard = hotpath::GuardBuilder::new("channel_overhead::main")
+        .silent()
+        .build();
+
+    let single = ns_per_call(1);
+    let multi = ns_per_call(THREADS);
+    drop(guard);
+
+    println!("backend: {backend}");
+    println!("1 thread: {single:.1} ns/call");
+    println!("{THREADS} threads: {multi:.1} ns/call");
+}
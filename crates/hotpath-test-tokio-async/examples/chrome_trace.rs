@@ -0,0 +1,30 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sync_function(n: u64) -> u64 {
+    std::hint::black_box((0..n).sum())
+}
+
+// Run with: cargo run --example chrome_trace --features hotpath -- trace.json
+// and open the file in https://ui.perfetto.dev
+fn main() {
+    let path = std::env::args().nth(1).expect("Usage: chrome_trace <path>");
+
+    {
+        #[cfg(feature = "hotpath")]
+        let _hotpath = hotpath::GuardBuilder::new("chrome_trace::main")
+            .chrome_trace_file(&path)
+            .silent()
+            .build();
+
+        std::thread::scope(|scope| {
+            for _ in 0..2 {
+                scope.spawn(|| {
+                    for i in 0..10 {
+                        sync_function(i * 100);
+                    }
+                });
+            }
+        });
+    }
+
+    print!("{}", std::fs::read_to_string(&path).unwrap_or_default());
+}
@@ -0,0 +1,38 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+static OFFSET_MS: AtomicU64 = AtomicU64::new(0);
+
+// Advances the fake clock instead of doing real work, so durations are exact
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn step(ms: u64) {
+    OFFSET_MS.fetch_add(ms, Ordering::Relaxed);
+}
+
+fn main() {
+    let sink: Arc<Mutex<Vec<(String, u64)>>> = Arc::new(Mutex::new(Vec::new()));
+
+    #[cfg(feature = "hotpath")]
+    let _hotpath = {
+        let base = std::time::Instant::now();
+        hotpath::GuardBuilder::new("clock::main")
+            .clock(Box::new(move || {
+                base + std::time::Duration::from_millis(OFFSET_MS.load(Ordering::Relaxed))
+            }))
+            .raw_sink(Arc::clone(&sink))
+            .build()
+    };
+
+    for ms in [10, 20, 30] {
+        step(ms);
+    }
+
+    // Dropping the guard flushes all pending measurements into the sink.
+    #[allow(clippy::drop_non_drop)]
+    #[cfg(feature = "hotpath")]
+    drop(_hotpath);
+
+    for (name, value) in sink.lock().unwrap().iter() {
+        println!("sample: {} {}", name, value);
+    }
+}
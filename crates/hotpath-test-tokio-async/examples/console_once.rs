@@ -0,0 +1,22 @@
+use std::io::Read;
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sleepy_function() {
+    std::thread::sleep(Duration::from_millis(1));
+}
+
+// Serves metrics for `hotpath console --metrics-port 6783 --once` until stdin is closed
+fn main() {
+    let _hotpath = hotpath::GuardBuilder::new("console_once::main")
+        .silent()
+        .http_server(6783)
+        .build();
+
+    for _ in 0..10 {
+        sleepy_function();
+    }
+
+    println!("ready");
+    let _ = std::io::stdin().read_to_end(&mut Vec::new());
+}
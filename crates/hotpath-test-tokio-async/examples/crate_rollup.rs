@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn own_function() {
+    std::thread::sleep(Duration::from_micros(100));
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn other_own_function() {
+    std::thread::sleep(Duration::from_micros(50));
+}
+
+fn main() {
+    let _hotpath = hotpath::GuardBuilder::new("crate_rollup::main")
+        .crate_rollup(hotpath::CrateRollup::Append)
+        .build();
+
+    for _ in 0..10 {
+        own_function();
+        other_own_function();
+
+        // Calls into dependencies can be labeled with the crate name
+        #[cfg(feature = "hotpath")]
+        hotpath::measure_block!("serde_json::to_string", {
+            let json = serde_json::to_string(&vec![1u32; 1000]).unwrap();
+            std::hint::black_box(json);
+        });
+    }
+}
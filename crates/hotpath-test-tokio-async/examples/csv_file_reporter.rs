@@ -24,15 +24,18 @@ async fn async_function(sleep: u64) {
     tokio::time::sleep(Duration::from_nanos(sleep)).await;
 }
 
-use hotpath::Reporter;
+use hotpath::{ReportError, Reporter};
+use std::io::Write;
 
+#[allow(unused)]
 struct FileReporter;
 
 impl Reporter for FileReporter {
-    fn report(
+    fn write_report(
         &self,
         metrics_provider: &dyn hotpath::MetricsProvider<'_>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        _out: &mut dyn Write,
+    ) -> Result<(), ReportError> {
         let mut output = String::new();
         output.push_str(&format!(
             "HotPath Report for: {}\n",
@@ -69,7 +72,7 @@ impl Reporter for FileReporter {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(feature = "hotpath")]
     let _hotpath = hotpath::GuardBuilder::new("main")
-        .percentiles(&[50, 90, 95])
+        .percentiles(&[50.0, 90.0, 95.0])
         .reporter(Box::new(FileReporter))
         .build();
 
@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sync_function(len: usize) -> Vec<u8> {
+    std::thread::sleep(Duration::from_micros(100));
+    vec![0; len]
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+async fn async_function(len: usize) -> String {
+    tokio::time::sleep(Duration::from_micros(100)).await;
+    "a".repeat(len)
+}
+
+// Run with: cargo run --example debug_reporter --features hotpath,hotpath-debug
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    // Measured functions neither nest nor overlap, so sums can be checked too
+    let _hotpath = hotpath::GuardBuilder::new("debug_reporter::main")
+        .percentiles(&[99, 50, 95])
+        .reporter(Box::new(hotpath::DebugReporter::new().flat(true)))
+        .build();
+
+    for i in 1..50 {
+        std::hint::black_box(sync_function(i * 10));
+        std::hint::black_box(async_function(i).await);
+        hotpath::measure_block!("block", {
+            std::hint::black_box(vec![0u8; i]);
+        });
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn empty_function() {}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn outer_function() {
+    for _ in 0..100 {
+        empty_function();
+    }
+}
+
+// Run with: cargo run --example empty_alloc --features hotpath,hotpath-alloc-bytes-total
+fn main() {
+    let sink: Arc<Mutex<Vec<(String, u64)>>> = Arc::new(Mutex::new(Vec::new()));
+
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("empty_alloc::main")
+        .raw_sink(Arc::clone(&sink))
+        .build();
+
+    for _ in 0..10 {
+        outer_function();
+
+        #[cfg(feature = "hotpath")]
+        hotpath::measure_block!("empty_block", {});
+    }
+
+    // Dropping the guard flushes all pending measurements into the sink.
+    #[allow(clippy::drop_non_drop)]
+    #[cfg(feature = "hotpath")]
+    drop(_hotpath);
+
+    let mut totals = BTreeMap::<String, u64>::new();
+    for (name, value) in sink.lock().unwrap().iter() {
+        *totals.entry(name.clone()).or_default() += value;
+    }
+
+    for (name, total) in totals {
+        println!("total: {} {}", name, total);
+    }
+}
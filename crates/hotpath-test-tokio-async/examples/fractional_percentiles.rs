@@ -0,0 +1,13 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn example_function() {
+    std::thread::sleep(Duration::from_millis(1));
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::main(percentiles = [50, 99.9]))]
+fn main() {
+    for _ in 0..20 {
+        example_function();
+    }
+}
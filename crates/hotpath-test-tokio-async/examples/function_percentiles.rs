@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn regular_function() -> Vec<u8> {
+    std::thread::sleep(Duration::from_millis(1));
+    vec![0; 64]
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure(percentiles = [50, 99]))]
+fn tail_sensitive_function() -> Vec<u8> {
+    std::thread::sleep(Duration::from_millis(2));
+    vec![0; 128]
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::main(percentiles = [95], limit = 0))]
+fn main() {
+    for _ in 0..10 {
+        std::hint::black_box(regular_function());
+        std::hint::black_box(tail_sensitive_function());
+    }
+}
@@ -0,0 +1,59 @@
+use std::time::Duration;
+use ureq::tls::TlsConfig;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sleepy_function() {
+    std::thread::sleep(Duration::from_millis(5));
+}
+
+// Run with: cargo run --example http_tls --features hotpath,hotpath-tls
+fn main() {
+    // A self-signed certificate for localhost, generated for this run only
+    let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+        .expect("Failed to generate certificate");
+    let dir = std::env::temp_dir().join(format!("hotpath-tls-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let cert_path = dir.join("cert.pem");
+    let key_path = dir.join("key.pem");
+    std::fs::write(&cert_path, certified.cert.pem()).unwrap();
+    std::fs::write(&key_path, certified.key_pair.serialize_pem()).unwrap();
+
+    let port = 6784;
+    let _hotpath = hotpath::GuardBuilder::new("http_tls::main")
+        .silent()
+        .http_server(port)
+        .http_tls(&cert_path, &key_path)
+        .build();
+
+    for _ in 0..10 {
+        sleepy_function();
+    }
+
+    // The generated certificate is self-signed
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .tls_config(TlsConfig::builder().disable_verification(true).build())
+        .timeout_global(Some(Duration::from_secs(5)))
+        .build()
+        .into();
+
+    let mut response = agent
+        .get(&format!("https://localhost:{port}/metrics"))
+        .call()
+        .expect("HTTPS request failed");
+    let body = response.body_mut().read_to_string().unwrap();
+
+    println!("https status: {}", response.status());
+    println!(
+        "sleepy_function exposed: {}",
+        body.contains("http_tls::sleepy_function")
+    );
+    println!(
+        "plain http rejected: {}",
+        agent
+            .get(&format!("http://localhost:{port}/metrics"))
+            .call()
+            .is_err()
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,18 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn work(i: u64) {
+    std::thread::sleep(Duration::from_micros(100 * (i % 10 + 1)));
+}
+
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("include_distribution::main")
+        .format(hotpath::Format::Json)
+        .include_distribution(true)
+        .build();
+
+    for i in 0..50 {
+        work(i);
+    }
+}
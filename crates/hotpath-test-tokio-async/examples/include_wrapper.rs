@@ -0,0 +1,22 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn work() -> Vec<u8> {
+    std::thread::sleep(Duration::from_millis(2));
+    vec![0; 1024]
+}
+
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("include_wrapper::main")
+        .include_wrapper(false)
+        .format(hotpath::Format::Json)
+        .build();
+
+    for _ in 0..5 {
+        std::hint::black_box(work());
+        // Work outside of measured functions still counts towards the caller total
+        std::hint::black_box(vec![0u8; 1024]);
+        std::thread::sleep(Duration::from_millis(2));
+    }
+}
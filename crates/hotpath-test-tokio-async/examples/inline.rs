@@ -0,0 +1,21 @@
+use std::sync::{Arc, Mutex};
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn compute(n: u64) -> u64 {
+    (0..n).map(|i| i * i).sum()
+}
+
+fn main() {
+    let sink = Arc::new(Mutex::new(Vec::new()));
+    let _hotpath = hotpath::GuardBuilder::new("inline::main")
+        .inline(true)
+        .raw_sink(Arc::clone(&sink))
+        .build();
+
+    for i in 0..10 {
+        std::hint::black_box(compute(i * 1000));
+    }
+
+    // Recorded as soon as each guard is dropped, without waiting for a worker thread
+    println!("recorded before drop: {}", sink.lock().unwrap().len());
+}
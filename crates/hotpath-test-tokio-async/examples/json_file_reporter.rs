@@ -24,17 +24,19 @@ async fn async_function(sleep: u64) {
     tokio::time::sleep(Duration::from_nanos(sleep)).await;
 }
 
-use hotpath::Reporter;
+use hotpath::{ReportError, Reporter};
+use std::io::Write;
 
 struct JsonFileReporter;
 
 impl Reporter for JsonFileReporter {
-    fn report(
+    fn write_report(
         &self,
         metrics_provider: &dyn hotpath::MetricsProvider<'_>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        out: &mut dyn Write,
+    ) -> Result<(), ReportError> {
         if metrics_provider.metric_data().is_empty() {
-            println!("No metrics to report");
+            writeln!(out, "No metrics to report")?;
             return Ok(());
         }
 
@@ -42,7 +44,7 @@ impl Reporter for JsonFileReporter {
 
         let json_string = serde_json::to_string_pretty(&json)?;
         std::fs::write("hotpath_report.json", json_string)?;
-        println!("Report saved to hotpath_report.json");
+        writeln!(out, "Report saved to hotpath_report.json")?;
         Ok(())
     }
 }
@@ -50,7 +52,7 @@ impl Reporter for JsonFileReporter {
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let _hotpath = hotpath::GuardBuilder::new("main")
-        .percentiles(&[50, 90, 95])
+        .percentiles(&[50.0, 90.0, 95.0])
         .reporter(Box::new(JsonFileReporter))
         .build();
 
@@ -0,0 +1,30 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn large() -> Vec<u8> {
+    vec![1; 4096]
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn medium() -> Vec<u8> {
+    vec![1; 2048]
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn small() -> Vec<u8> {
+    vec![1; 1024]
+}
+
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("limit_total::main")
+        // Never called, reported with 0 calls
+        .register(&["limit_total::registered"])
+        .include_wrapper(false)
+        .limit(1)
+        .build();
+
+    for _ in 0..10 {
+        std::hint::black_box(large());
+        std::hint::black_box(medium());
+        std::hint::black_box(small());
+    }
+}
@@ -0,0 +1,15 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+async fn example_function() {
+    tokio::time::sleep(Duration::from_millis(5)).await;
+}
+
+// The report is titled "app" instead of "main_name::main"
+#[tokio::main(flavor = "current_thread")]
+#[cfg_attr(feature = "hotpath", hotpath::main(name = "app"))]
+async fn main() {
+    for _ in 0..3 {
+        example_function().await;
+    }
+}
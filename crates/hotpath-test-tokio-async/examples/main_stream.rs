@@ -0,0 +1,15 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn example_function(i: u64) -> u64 {
+    std::thread::sleep(Duration::from_millis(5));
+    i * 2
+}
+
+// The report goes to stderr, so stdout only carries the program's output
+#[cfg_attr(feature = "hotpath", hotpath::main(stream = "stderr"))]
+fn main() {
+    for i in 0..5 {
+        println!("{}", example_function(i));
+    }
+}
@@ -0,0 +1,18 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure(monomorphized))]
+fn describe<T: Default + std::fmt::Debug>() -> String {
+    format!("{:?}", T::default())
+}
+
+fn main() {
+    let _hotpath = hotpath::GuardBuilder::new("max_functions::main")
+        .max_functions(3)
+        .build();
+
+    for _ in 0..10 {
+        describe::<u8>();
+        describe::<u16>();
+        describe::<u32>();
+        describe::<u64>();
+        describe::<String>();
+    }
+}
@@ -0,0 +1,19 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sleepy_function() {
+    std::thread::sleep(Duration::from_micros(100));
+}
+
+// Run with: cargo run --example measure_block_alloc --features hotpath,hotpath-alloc-blocks
+#[cfg_attr(feature = "hotpath", hotpath::main)]
+fn main() {
+    for _ in 0..10 {
+        sleepy_function();
+
+        hotpath::measure_block_alloc!("build_index", {
+            let index: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+            std::hint::black_box(index);
+        });
+    }
+}
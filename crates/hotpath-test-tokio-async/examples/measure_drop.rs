@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+struct Connection {
+    buffer: Vec<u8>,
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        hotpath::measure_drop!();
+        // Flushing on close is expensive, but doesn't show up in a normal function profile
+        std::thread::sleep(Duration::from_micros(200));
+        self.buffer.clear();
+    }
+}
+
+struct TempDir;
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        hotpath::measure_drop!("temp_dir_cleanup");
+        std::thread::sleep(Duration::from_micros(100));
+    }
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn open_connection() -> Connection {
+    Connection {
+        buffer: vec![0; 1024],
+    }
+}
+
+// Run with: cargo run --example measure_drop --features hotpath
+#[cfg_attr(feature = "hotpath", hotpath::main)]
+fn main() {
+    for _ in 0..5 {
+        let connection = open_connection();
+        drop(connection);
+    }
+
+    for _ in 0..3 {
+        let _dir = TempDir;
+    }
+}
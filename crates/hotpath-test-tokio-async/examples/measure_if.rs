@@ -0,0 +1,15 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn parse(input: &str) -> usize {
+    // Only inputs taking the slow path are recorded
+    hotpath::measure_if!(input.len() > 8, "parse_long", {
+        input.split(',').map(str::len).sum()
+    })
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::main(limit = 0))]
+fn main() {
+    let inputs = ["a,b", "longer,input,value", "c", "another,long,input"];
+    for input in inputs.iter().cycle().take(20) {
+        std::hint::black_box(parse(input));
+    }
+}
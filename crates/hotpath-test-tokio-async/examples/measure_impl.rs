@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+#[derive(Clone)]
+struct Counter {
+    value: u64,
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure_impl)]
+impl Counter {
+    fn new(value: u64) -> Self {
+        Self { value }
+    }
+
+    fn get(&self) -> u64 {
+        self.value
+    }
+
+    fn increment(&mut self, amount: u64) {
+        self.value += amount;
+        std::thread::sleep(Duration::from_nanos(amount));
+    }
+
+    fn into_inner(self) -> u64 {
+        self.value
+    }
+
+    async fn async_increment(&mut self, amount: u64) {
+        self.value += amount;
+        tokio::time::sleep(Duration::from_nanos(amount)).await;
+    }
+
+    const fn zero() -> u64 {
+        0
+    }
+
+    #[cfg_attr(feature = "hotpath", hotpath::skip)]
+    fn reset(&mut self) {
+        self.value = Self::zero();
+    }
+}
+
+struct Gauge {
+    value: u64,
+}
+
+// A method with the same name on another type is reported separately.
+#[cfg_attr(feature = "hotpath", hotpath::measure_impl)]
+impl Gauge {
+    fn new(value: u64) -> Self {
+        Self { value }
+    }
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure_impl)]
+impl Default for Gauge {
+    fn default() -> Self {
+        Self { value: 1 }
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+#[cfg_attr(feature = "hotpath", hotpath::main)]
+async fn main() {
+    for i in 1..=10 {
+        let mut counter = Counter::new(i);
+        counter.increment(i);
+        counter.async_increment(i).await;
+        std::hint::black_box(counter.get());
+        counter.reset();
+        std::hint::black_box(counter.clone().into_inner());
+
+        let gauge = if i % 2 == 0 {
+            Gauge::new(i)
+        } else {
+            Gauge::default()
+        };
+        std::hint::black_box(gauge.value);
+    }
+}
@@ -0,0 +1,21 @@
+use std::io::{Read, Write};
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn copy_chunks(mut input: &[u8], output: &mut Vec<u8>) {
+    let mut buf = [0u8; 256];
+    loop {
+        let read = hotpath::measure_io!("read_chunk", || input.read(&mut buf).unwrap_or(0));
+        if read == 0 {
+            break;
+        }
+        hotpath::measure_io!("write_chunk", || output.write(&buf[..read]).unwrap_or(0));
+    }
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::main)]
+fn main() {
+    let input = vec![7u8; 1024];
+    let mut output = Vec::new();
+    copy_chunks(&input, &mut output);
+    std::hint::black_box(output);
+}
@@ -0,0 +1,26 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[hotpath::measure]
+fn worker(counter: &Mutex<u64>) {
+    for _ in 0..50 {
+        let mut count = hotpath::measure_lock!(counter).unwrap();
+        *count += 1;
+        // Holding the lock makes the other workers wait for it
+        std::thread::sleep(Duration::from_micros(50));
+    }
+}
+
+// Run with: cargo run --example measure_lock --features hotpath
+#[hotpath::main]
+fn main() {
+    let counter = Mutex::new(0);
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| worker(&counter));
+        }
+    });
+
+    *hotpath::measure_lock!("final_read", counter).unwrap() += 0;
+}
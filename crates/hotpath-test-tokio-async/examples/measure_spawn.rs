@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+async fn fetch(id: u64) -> Vec<u64> {
+    tokio::time::sleep(Duration::from_millis(id)).await;
+    (0..id * 10).collect()
+}
+
+// Run with: cargo run --example measure_spawn --features hotpath
+#[tokio::main(flavor = "current_thread")]
+#[cfg_attr(feature = "hotpath", hotpath::main)]
+async fn main() {
+    let handles: Vec<_> = (1..=5)
+        .map(|id| hotpath::measure_spawn!("fetch_task", async move { fetch(id).await.len() }))
+        .collect();
+
+    for handle in handles {
+        std::hint::black_box(handle.await.unwrap());
+    }
+
+    let flush = tokio::spawn(hotpath::measure_fut("flush_task", async {
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        vec![0u8; 1024]
+    }));
+    std::hint::black_box(flush.await.unwrap());
+}
@@ -0,0 +1,19 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sync_function() {
+    std::hint::black_box(vec![1u8; 64]);
+}
+
+// Run with: cargo run --example metadata --features hotpath
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("metadata::main")
+        .format(hotpath::Format::Json)
+        .metadata("git_sha", "4f2a91c")
+        .metadata("hostname", "ci-runner-3")
+        .metadata("git_sha", "9b0e1d7")
+        .build();
+
+    for _ in 0..10 {
+        sync_function();
+    }
+}
@@ -0,0 +1,50 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sleepy_function() {
+    std::thread::sleep(Duration::from_millis(1));
+}
+
+fn fetch_calls(port: u16, path: &str) -> Option<u64> {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).expect("Failed to connect");
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        path
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+
+    let metrics: hotpath::MetricsJson = serde_json::from_str(body).unwrap();
+    metrics
+        .data
+        .0
+        .get("metrics_delta::sleepy_function")
+        .and_then(|row| row.first())
+        .and_then(|calls| calls.value())
+}
+
+fn main() {
+    let port = 6782;
+    let _metrics = hotpath::serve_metrics(port);
+
+    for _ in 0..10 {
+        sleepy_function();
+    }
+    // Measurements are processed asynchronously by the worker thread
+    std::thread::sleep(Duration::from_millis(50));
+    println!("delta 1: {:?}", fetch_calls(port, "/metrics/delta"));
+
+    for _ in 0..3 {
+        sleepy_function();
+    }
+    std::thread::sleep(Duration::from_millis(50));
+    println!("delta 2: {:?}", fetch_calls(port, "/metrics/delta"));
+    println!("delta 3: {:?}", fetch_calls(port, "/metrics/delta"));
+    println!("cumulative: {:?}", fetch_calls(port, "/metrics"));
+}
@@ -0,0 +1,13 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sync_function() {
+    let vec = vec![1, 2, 3];
+    std::hint::black_box(&vec);
+}
+
+// No #[hotpath::main] - measurements are ignored with a one-time warning
+fn main() {
+    for _ in 0..10 {
+        sync_function();
+    }
+    println!("missing_main finished");
+}
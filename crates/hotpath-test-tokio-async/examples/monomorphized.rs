@@ -0,0 +1,14 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure(monomorphized))]
+fn process<T: Clone>(items: &[T]) -> Vec<T> {
+    items.to_vec()
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::main)]
+fn main() {
+    for i in 0..10 {
+        let numbers = process(&[i, i + 1, i + 2]);
+        std::hint::black_box(&numbers);
+        let words = process(&[i.to_string()]);
+        std::hint::black_box(&words);
+    }
+}
@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn query(delay_ms: u64) {
+    std::thread::sleep(Duration::from_millis(delay_ms));
+}
+
+// Measured itself: with inline aggregation the callback runs after the aggregator is
+// released, so this doesn't deadlock
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn log_slow_call(name: &str, duration: Duration) {
+    println!("slow call: {name} took more than 20ms ({duration:?})");
+}
+
+fn main() {
+    let _hotpath = hotpath::GuardBuilder::new("on_slow::main")
+        .on_slow(Duration::from_millis(20), Box::new(log_slow_call))
+        .build();
+
+    for delay_ms in [1, 50, 2] {
+        query(delay_ms);
+    }
+}
@@ -0,0 +1,15 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn noop(i: u64) -> u64 {
+    std::hint::black_box(i)
+}
+
+fn main() {
+    // Tiny functions take less time than measuring them, so the budget is exceeded
+    let _hotpath = hotpath::GuardBuilder::new("overhead_budget::main")
+        .overhead_budget(5.0)
+        .build();
+
+    for i in 0..100_000 {
+        noop(i);
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+// Run with:
+// cargo test -p hotpath-test-tokio-async --example parallel_tests --features hotpath
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sync_function(sleep: u64) {
+    std::thread::sleep(Duration::from_millis(sleep));
+}
+
+fn main() {
+    sync_function(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &'static str) {
+        #[cfg(feature = "hotpath")]
+        let _hotpath = hotpath::GuardBuilder::new(name)
+            .wait_for_guard(true)
+            .build();
+
+        sync_function(20);
+    }
+
+    #[test]
+    fn test_first() {
+        profile("test_first");
+    }
+
+    #[test]
+    fn test_second() {
+        profile("test_second");
+    }
+
+    #[test]
+    fn test_third() {
+        profile("test_third");
+    }
+}
@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn short_function() {
+    std::thread::sleep(Duration::from_millis(1));
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn long_function() {
+    std::thread::sleep(Duration::from_millis(3));
+}
+
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("percent_basis::main")
+        .percent_basis(hotpath::PercentBasis::MeasuredSum)
+        .format(hotpath::Format::Json)
+        .build();
+
+    for _ in 0..5 {
+        short_function();
+        long_function();
+        // Idle time is excluded from the % Total basis
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
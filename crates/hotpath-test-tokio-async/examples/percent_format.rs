@@ -0,0 +1,19 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn work() -> Vec<u8> {
+    std::thread::sleep(Duration::from_millis(1));
+    vec![0; 1024]
+}
+
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("percent_format::main")
+        .format(hotpath::Format::Json)
+        .percent_format(hotpath::PercentFormat::Float)
+        .build();
+
+    for _ in 0..5 {
+        std::hint::black_box(work());
+    }
+}
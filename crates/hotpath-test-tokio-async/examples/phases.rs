@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn load_config() -> Vec<u8> {
+    std::thread::sleep(Duration::from_millis(2));
+    vec![0; 4096]
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn handle_request(warm: bool) -> Vec<u8> {
+    let delay = if warm { 1 } else { 5 };
+    std::thread::sleep(Duration::from_millis(delay));
+    vec![0; 512]
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::main)]
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let phase = hotpath::phase("init");
+    std::hint::black_box(load_config());
+    // Warm-up request while still initializing
+    std::hint::black_box(handle_request(false));
+    #[cfg(feature = "hotpath")]
+    drop(phase);
+
+    #[cfg(feature = "hotpath")]
+    let _phase = hotpath::phase("serve");
+    for _ in 0..4 {
+        std::hint::black_box(handle_request(true));
+    }
+}
@@ -0,0 +1,21 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn parse(n: u64) -> u64 {
+    std::hint::black_box((0..n).sum())
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn render(n: u64) -> String {
+    std::hint::black_box(n.to_string())
+}
+
+fn main() {
+    // Space-padded tables without borders or ANSI styles, for plain log ingestion
+    let _hotpath = hotpath::GuardBuilder::new("plain::main")
+        .plain(true)
+        .build();
+
+    for i in 0..10 {
+        parse(i * 100);
+        render(i);
+    }
+}
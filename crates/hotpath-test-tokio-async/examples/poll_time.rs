@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Mostly waiting: poll time is a small fraction of the wall time
+#[cfg_attr(feature = "hotpath", hotpath::measure(poll_time))]
+async fn io_bound() {
+    tokio::time::sleep(Duration::from_millis(20)).await;
+}
+
+// Never suspends: poll time equals the wall time
+#[cfg_attr(feature = "hotpath", hotpath::measure(poll_time))]
+async fn cpu_bound() -> u64 {
+    (0..100_000u64).map(std::hint::black_box).sum()
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let sink: Arc<Mutex<Vec<(String, u64)>>> = Arc::new(Mutex::new(Vec::new()));
+
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("poll_time::main")
+        .raw_sink(Arc::clone(&sink))
+        .build();
+
+    for _ in 0..5 {
+        io_bound().await;
+        std::hint::black_box(cpu_bound().await);
+    }
+
+    // Dropping the guard flushes all pending measurements into the sink.
+    #[allow(clippy::drop_non_drop)]
+    #[cfg(feature = "hotpath")]
+    drop(_hotpath);
+
+    let mut totals = BTreeMap::<String, (u64, u64)>::new();
+    for (name, value) in sink.lock().unwrap().iter() {
+        let (calls, total) = totals.entry(name.clone()).or_default();
+        *calls += 1;
+        *total += value;
+    }
+
+    for (name, (calls, _)) in &totals {
+        println!("calls: {} {}", name, calls);
+    }
+
+    let total = |name: &str| totals.get(name).map(|(_, total)| *total).unwrap_or(0);
+    println!(
+        "io_bound mostly suspended: {}",
+        total("poll_time::io_bound::poll") * 10 < total("poll_time::io_bound")
+    );
+}
@@ -0,0 +1,16 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn allocate(len: usize) -> usize {
+    let buffer = vec![1u8; len];
+    std::hint::black_box(&buffer);
+    buffer.len()
+}
+
+fn main() {
+    let _hotpath = hotpath::GuardBuilder::new("process_peak::main")
+        .track_process_peak(true)
+        .build();
+
+    for i in 1..=10 {
+        allocate(i * 1024 * 1024);
+    }
+}
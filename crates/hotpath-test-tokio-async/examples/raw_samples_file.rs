@@ -0,0 +1,25 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sync_function(n: u64) -> u64 {
+    std::hint::black_box((0..n).sum())
+}
+
+// Run with: cargo run --example raw_samples_file --features hotpath -- samples.csv
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .expect("Usage: raw_samples_file <path>");
+
+    {
+        #[cfg(feature = "hotpath")]
+        let _hotpath = hotpath::GuardBuilder::new("raw_samples_file::main")
+            .raw_samples_file(&path)
+            .silent()
+            .build();
+
+        for i in 0..25 {
+            sync_function(i * 100);
+        }
+    }
+
+    print!("{}", std::fs::read_to_string(&path).unwrap_or_default());
+}
@@ -0,0 +1,35 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn first_function() {
+    std::thread::sleep(Duration::from_micros(10));
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn second_function() {
+    std::thread::sleep(Duration::from_micros(20));
+}
+
+fn main() {
+    let sink: Arc<Mutex<Vec<(String, u64)>>> = Arc::new(Mutex::new(Vec::new()));
+
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("raw_sink::main")
+        .raw_sink(Arc::clone(&sink))
+        .build();
+
+    for _ in 0..3 {
+        first_function();
+        second_function();
+    }
+
+    // Dropping the guard flushes all pending measurements into the sink.
+    #[allow(clippy::drop_non_drop)]
+    #[cfg(feature = "hotpath")]
+    drop(_hotpath);
+
+    for (name, value) in sink.lock().unwrap().iter() {
+        println!("raw: {} {}", name, value);
+    }
+}
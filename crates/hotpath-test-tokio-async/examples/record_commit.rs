@@ -0,0 +1,17 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sync_function() {
+    std::hint::black_box(vec![1u8; 64]);
+}
+
+// Run with: cargo run --example record_commit --features hotpath
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("record_commit::main")
+        .format(hotpath::Format::Json)
+        .record_commit(true)
+        .build();
+
+    for _ in 0..10 {
+        sync_function();
+    }
+}
@@ -0,0 +1,17 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn process(batch: &[u64]) -> u64 {
+    hotpath::record_value!("batch_size", batch.len() as u64, unit = "rows");
+    let sum = batch.iter().sum();
+    hotpath::record_value!("batch_sum", sum);
+    sum
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::main(percentiles = [50]))]
+fn main() {
+    let data: Vec<u64> = (1..=100).collect();
+    for size in [10, 20, 30, 40] {
+        for batch in data.chunks(size) {
+            std::hint::black_box(process(batch));
+        }
+    }
+}
@@ -0,0 +1,21 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn parse(input: &str) -> Option<u64> {
+    input.parse().ok()
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn fallback_parse(input: &str) -> u64 {
+    input.len() as u64
+}
+
+fn main() {
+    let _hotpath = hotpath::GuardBuilder::new("register::main")
+        .register(&["register::parse", "register::fallback_parse"])
+        .limit(0)
+        .show_uncalled(true)
+        .build();
+
+    for input in ["1", "2", "3"] {
+        let _ = parse(input).unwrap_or_else(|| fallback_parse(input));
+    }
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+mod db {
+    use std::time::Duration;
+
+    #[cfg_attr(feature = "hotpath", hotpath::measure)]
+    pub fn query() {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn render() {
+    std::thread::sleep(Duration::from_millis(15));
+}
+
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("relative_to::main")
+        .relative_to("db::query")
+        .build();
+
+    for _ in 0..3 {
+        db::query();
+        render();
+    }
+}
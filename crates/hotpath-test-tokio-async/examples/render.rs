@@ -0,0 +1,27 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn compute(n: u64) -> u64 {
+    (0..n).map(|i| i * i).sum()
+}
+
+fn main() {
+    let guard = hotpath::GuardBuilder::new("render::main").silent().build();
+
+    for i in 0..10 {
+        std::hint::black_box(compute(i * 1000));
+    }
+
+    let table = guard.render_table();
+    println!("rendered table:\n{}", table);
+
+    let json = guard.render_json();
+    let metrics: hotpath::MetricsJson = serde_json::from_str(&json).unwrap();
+    println!(
+        "rendered json calls: {:?}",
+        metrics
+            .data
+            .0
+            .get("render::compute")
+            .and_then(|row| row.first())
+            .and_then(|calls| calls.value())
+    );
+}
@@ -0,0 +1,18 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn parse_args() -> Vec<String> {
+    std::env::args().collect()
+}
+
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("report_atexit::main")
+        .report_atexit(true)
+        .build();
+
+    for _ in 0..3 {
+        std::hint::black_box(parse_args());
+    }
+
+    // Skips the guard's destructor
+    std::process::exit(3);
+}
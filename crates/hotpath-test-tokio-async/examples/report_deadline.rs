@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn parse(n: u64) -> u64 {
+    std::hint::black_box((0..n).sum())
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn validate(n: u64) -> u64 {
+    std::hint::black_box((0..n).product())
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn render(n: u64) -> String {
+    std::hint::black_box(n.to_string())
+}
+
+fn main() {
+    // A zero deadline leaves only the top function in the report
+    let _hotpath = hotpath::GuardBuilder::new("report_deadline::main")
+        .report_deadline(Duration::ZERO)
+        .build();
+
+    for i in 0..10 {
+        parse(i * 100);
+        validate(i);
+        render(i);
+    }
+}
@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn handle_request() {
+    std::thread::sleep(Duration::from_millis(1));
+}
+
+// Run with: cargo run --example report_on_idle --features hotpath
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("report_on_idle::main")
+        .format(hotpath::Format::Json)
+        .report_on_idle(Duration::from_millis(200))
+        .build();
+
+    // Two bursts of requests, each followed by a quiet period triggering a report
+    for _ in 0..2 {
+        for _ in 0..10 {
+            handle_request();
+        }
+        std::thread::sleep(Duration::from_millis(600));
+    }
+}
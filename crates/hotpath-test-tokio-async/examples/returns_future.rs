@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Declared sync, the returned future is measured until it completes
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn fetch(ms: u64) -> impl Future<Output = u64> + Send {
+    let delay = Duration::from_millis(ms);
+    async move {
+        tokio::time::sleep(delay).await;
+        ms
+    }
+}
+
+async fn sleep_then(ms: u64, value: u64) -> u64 {
+    tokio::time::sleep(Duration::from_millis(ms)).await;
+    value
+}
+
+// The future of an early return is measured like the tail expression's
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn fetch_or_retry(ms: u64, retry: bool) -> impl Future<Output = u64> + Send {
+    if retry {
+        return sleep_then(ms, 0);
+    }
+    sleep_then(ms, ms)
+}
+
+// Boxed futures can't be detected from the signature
+#[cfg_attr(feature = "hotpath", hotpath::measure(async))]
+fn fetch_boxed(ms: u64) -> Pin<Box<dyn Future<Output = u64> + Send>> {
+    if ms == 0 {
+        return Box::pin(async { 0 });
+    }
+    Box::pin(async move {
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        ms
+    })
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let sink: Arc<Mutex<Vec<(String, u64)>>> = Arc::new(Mutex::new(Vec::new()));
+
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("returns_future::main")
+        .raw_sink(Arc::clone(&sink))
+        .build();
+
+    for _ in 0..3 {
+        std::hint::black_box(fetch(20).await);
+        std::hint::black_box(fetch_or_retry(20, true).await);
+        std::hint::black_box(tokio::spawn(fetch_boxed(20)).await.unwrap());
+    }
+    std::hint::black_box(fetch_boxed(0).await);
+
+    // Dropping the guard flushes all pending measurements into the sink.
+    #[allow(clippy::drop_non_drop)]
+    #[cfg(feature = "hotpath")]
+    drop(_hotpath);
+
+    let mut totals = BTreeMap::<String, (u64, u64)>::new();
+    for (name, value) in sink.lock().unwrap().iter() {
+        let (calls, total) = totals.entry(name.clone()).or_default();
+        *calls += 1;
+        *total += value;
+    }
+
+    for (name, (calls, _)) in &totals {
+        println!("calls: {} {}", name, calls);
+    }
+
+    let awaited = |name: &str| {
+        totals
+            .get(name)
+            .is_some_and(|(_, total)| *total >= Duration::from_millis(60).as_nanos() as u64)
+    };
+    println!(
+        "awaited work measured: {}",
+        awaited("returns_future::fetch")
+            && awaited("returns_future::fetch_or_retry")
+            && awaited("returns_future::fetch_boxed")
+    );
+}
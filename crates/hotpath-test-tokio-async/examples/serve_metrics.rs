@@ -0,0 +1,50 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sleepy_function() {
+    std::thread::sleep(Duration::from_millis(5));
+}
+
+fn get(port: u16, path: &str) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).expect("Failed to connect");
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+fn main() {
+    let port = 6781;
+    let metrics = hotpath::serve_metrics(port);
+
+    for _ in 0..10 {
+        sleepy_function();
+    }
+
+    let response = get(port, "/metrics");
+    println!("{}", response.lines().next().unwrap_or_default());
+    println!(
+        "sleepy_function exposed: {}",
+        response.contains("serve_metrics::sleepy_function")
+    );
+
+    let health = get(port, "/health");
+    println!("health: {}", health.lines().next().unwrap_or_default());
+    println!(
+        "health ok: {}",
+        health.contains("{\"status\":\"ok\",\"uptime_ns\":")
+    );
+
+    drop(metrics);
+    println!(
+        "server stopped: {}",
+        TcpStream::connect(("127.0.0.1", port)).is_err()
+    );
+}
@@ -0,0 +1,26 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn parse(input: &str) -> usize {
+    input.split_whitespace().count()
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+async fn fetch(input: &str) -> String {
+    input.to_uppercase()
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("show_locations::main")
+        .show_locations(true)
+        .register(&["show_locations::parse"])
+        .build();
+
+    for _ in 0..10 {
+        std::hint::black_box(parse("a b c"));
+        std::hint::black_box(fetch("abc").await);
+        hotpath::measure_block!("custom_block", {
+            std::hint::black_box(parse("d e f"));
+        });
+    }
+}
@@ -0,0 +1,24 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn spike_allocation() -> Vec<u8> {
+    // Allocated once, a smaller total than small_allocations
+    vec![1; 8 * 1024 * 1024]
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn small_allocations() -> Vec<u8> {
+    vec![1; 1024]
+}
+
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("sort_by_max::main")
+        .percentiles(&[100.0])
+        .sort_by(hotpath::SortBy::Max)
+        .limit(2)
+        .build();
+
+    std::hint::black_box(spike_allocation());
+    for _ in 0..20_000 {
+        std::hint::black_box(small_allocations());
+    }
+}
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sync_function(sleep: u64) {
+    std::thread::sleep(Duration::from_nanos(sleep));
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+async fn async_function(sleep: u64) {
+    tokio::time::sleep(Duration::from_nanos(sleep)).await;
+}
+
+// Run with: cargo run --example sqlite_reporter --features hotpath,hotpath-sqlite
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::temp_dir().join(format!("hotpath-{}.db", std::process::id()));
+
+    // Two runs appended to the same database
+    for _ in 0..2 {
+        let _hotpath = hotpath::GuardBuilder::new("main")
+            .percentiles(&[50.0, 95.0])
+            .metadata("profile", "dev")
+            // Only limits the printed report, every function is persisted
+            .limit(1)
+            .reporter(Box::new(hotpath::SqliteReporter::new(&path)))
+            .build();
+
+        for i in 0..100 {
+            sync_function(i);
+            async_function(i * 2).await;
+        }
+    }
+
+    let connection = rusqlite::Connection::open(&path)?;
+    let runs: i64 = connection.query_row("SELECT COUNT(*) FROM runs", [], |row| row.get(0))?;
+    println!("runs: {runs}");
+
+    let mut statement = connection.prepare(
+        "SELECT runs.mode, functions.function, functions.calls, metadata.value
+         FROM functions
+         JOIN runs ON runs.id = functions.run_id
+         JOIN metadata ON metadata.run_id = runs.id AND metadata.key = 'profile'
+         ORDER BY runs.id, functions.function",
+    )?;
+    let rows = statement.query_map([], |row| {
+        Ok(format!(
+            "{} {} calls={} profile={}",
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, String>(3)?,
+        ))
+    })?;
+    for row in rows {
+        println!("{}", row?);
+    }
+
+    let percentiles: i64 = connection.query_row(
+        "SELECT COUNT(*) FROM percentiles WHERE percentile = 95.0 AND value IS NOT NULL",
+        [],
+        |row| row.get(0),
+    )?;
+    println!("p95 rows: {percentiles}");
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
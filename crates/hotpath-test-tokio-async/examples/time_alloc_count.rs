@@ -0,0 +1,20 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn build_index() -> Vec<String> {
+    (0..100).map(|i| i.to_string()).collect()
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sleepy_function() {
+    std::thread::sleep(Duration::from_micros(100));
+}
+
+// Run with: cargo run --example time_alloc_count --features hotpath,hotpath-time-alloc-count
+#[cfg_attr(feature = "hotpath", hotpath::main)]
+fn main() {
+    for _ in 0..10 {
+        sleepy_function();
+        std::hint::black_box(build_index());
+    }
+}
@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sync_function(sleep: u64) {
+    std::thread::sleep(Duration::from_nanos(sleep));
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+async fn async_function(sleep: u64) {
+    tokio::time::sleep(Duration::from_nanos(sleep)).await;
+}
+
+// Run with: RUST_LOG=info cargo run --example tracing_builtin_reporter --features hotpath,hotpath-tracing
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt::init();
+
+    let _hotpath = hotpath::GuardBuilder::new("main")
+        .percentiles(&[50.0, 95.0])
+        .reporter(Box::new(hotpath::TracingReporter))
+        .build();
+
+    for i in 0..100 {
+        sync_function(i);
+        async_function(i * 2).await;
+    }
+
+    Ok(())
+}
@@ -24,16 +24,18 @@ async fn async_function(sleep: u64) {
     tokio::time::sleep(Duration::from_nanos(sleep)).await;
 }
 
-use hotpath::Reporter;
+use hotpath::{ReportError, Reporter};
+use std::io::Write;
 use tracing::{info, info_span};
 
 struct TracingReporter;
 
 impl Reporter for TracingReporter {
-    fn report(
+    fn write_report(
         &self,
         metrics_provider: &dyn hotpath::MetricsProvider<'_>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        _out: &mut dyn Write,
+    ) -> Result<(), ReportError> {
         info!("HotPath Report for: {}", metrics_provider.caller_name());
         info!("Headers: {}", metrics_provider.headers().join(", "));
 
@@ -62,7 +64,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt::init();
 
     let _hotpath = hotpath::GuardBuilder::new("main")
-        .percentiles(&[50, 90, 95])
+        .percentiles(&[50.0, 90.0, 95.0])
         .reporter(Box::new(TracingReporter))
         .build();
 
@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+#[cfg_attr(feature = "hotpath", hotpath::measure(track_return))]
+fn build_buffer(len: usize) -> Vec<u8> {
+    let scratch = vec![1u8; 16];
+    std::hint::black_box(&scratch);
+    Vec::with_capacity(len)
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure(track_return))]
+fn early_exit(len: usize) -> Vec<u8> {
+    if len == 0 {
+        return Vec::new();
+    }
+    vec![0; len]
+}
+
+// A trailing `return` is tracked like a tail expression
+#[allow(clippy::needless_return)]
+#[cfg_attr(feature = "hotpath", hotpath::measure(track_return))]
+fn explicit_return(len: usize) -> Vec<u8> {
+    let scratch = vec![1u8; 16];
+    std::hint::black_box(&scratch);
+    return vec![0; len];
+}
+
+// Run with: cargo run --example track_return --features hotpath,hotpath-alloc-bytes-total
+fn main() {
+    let sink: Arc<Mutex<Vec<(String, u64)>>> = Arc::new(Mutex::new(Vec::new()));
+
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("track_return::main")
+        .raw_sink(Arc::clone(&sink))
+        .build();
+
+    for i in 0..10 {
+        let buffer = build_buffer(1024 * (i + 1));
+        std::hint::black_box(&buffer);
+        let buffer = early_exit(i);
+        std::hint::black_box(&buffer);
+        let buffer = explicit_return(100);
+        std::hint::black_box(&buffer);
+    }
+
+    // Dropping the guard prints the report and flushes all pending measurements into the sink.
+    #[allow(clippy::drop_non_drop)]
+    #[cfg(feature = "hotpath")]
+    drop(_hotpath);
+
+    let mut totals = BTreeMap::<String, u64>::new();
+    for (name, value) in sink.lock().unwrap().iter() {
+        *totals.entry(name.clone()).or_default() += value;
+    }
+
+    for (name, total) in totals {
+        println!("total: {} {}", name, total);
+    }
+}
@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn long_tail(i: usize) -> Vec<u8> {
+    // Every 50th call is an outlier, both in duration and allocated bytes
+    if i.is_multiple_of(50) {
+        std::thread::sleep(Duration::from_millis(20));
+        vec![0; 1024 * 1024]
+    } else {
+        std::thread::sleep(Duration::from_micros(50));
+        vec![0; 64]
+    }
+}
+
+fn main() {
+    #[cfg(feature = "hotpath")]
+    let _hotpath = hotpath::GuardBuilder::new("trimmed_mean::main")
+        .show_trimmed_mean(true)
+        .build();
+
+    for i in 1..=100 {
+        std::hint::black_box(long_tail(i));
+    }
+}
@@ -0,0 +1,10 @@
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn add(a: u64, b: u64) -> u64 {
+    a + b
+}
+
+// Finishes in microseconds, `% Total` must still be relative to something measured
+#[cfg_attr(feature = "hotpath", hotpath::main)]
+fn main() {
+    std::hint::black_box(add(1, 2));
+}
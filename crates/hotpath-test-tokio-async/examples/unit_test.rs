@@ -1,9 +1,10 @@
 use std::time::Duration;
 
-use hotpath::Reporter;
+use hotpath::{ReportError, Reporter};
+use std::io::Write;
 
-/// Run with:
-/// cargo test -p hotpath-test-tokio-async --example unit_test --features hotpath -- --nocapture --test-threads=1
+// Run with:
+// cargo test -p hotpath-test-tokio-async --example unit_test --features hotpath -- --nocapture --test-threads=1
 
 #[cfg_attr(feature = "hotpath", hotpath::measure)]
 fn sync_function(sleep: u64) {
@@ -19,12 +20,13 @@ fn sync_function(sleep: u64) {
 struct UnitTestReporter;
 
 impl Reporter for UnitTestReporter {
-    fn report(
+    fn write_report(
         &self,
         metrics_provider: &dyn hotpath::MetricsProvider<'_>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        out: &mut dyn Write,
+    ) -> Result<(), ReportError> {
         if metrics_provider.metric_data().is_empty() {
-            println!("No metrics to report");
+            writeln!(out, "No metrics to report")?;
             return Ok(());
         }
 
@@ -0,0 +1,40 @@
+use std::sync::Mutex;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+fn sync_function(counter: &Mutex<u64>) {
+    hotpath::alloc_here!("sync_function_alloc");
+    *hotpath::measure_lock!(counter).unwrap() += 1;
+}
+
+#[cfg_attr(feature = "hotpath", hotpath::measure)]
+async fn async_function(counter: &Mutex<u64>) {
+    hotpath::measure_block!("async_block", {
+        *counter.lock().unwrap() += 1;
+    });
+}
+
+struct Worker;
+
+#[cfg_attr(feature = "hotpath", hotpath::measure_all)]
+impl Worker {
+    fn work(&self, counter: &Mutex<u64>) {
+        *counter.lock().unwrap() += 1;
+    }
+}
+
+// Built without the `hotpath` feature by `test_zero_overhead_when_disabled`,
+// which checks that no hotpath code ends up in the release binary
+#[tokio::main(flavor = "current_thread")]
+#[cfg_attr(feature = "hotpath", hotpath::main)]
+async fn main() {
+    let counter = Mutex::new(0);
+
+    sync_function(&counter);
+    async_function(&counter).await;
+    Worker.work(&counter);
+    hotpath::measure_spawn!("spawned_task", async { 1 })
+        .await
+        .unwrap();
+
+    println!("count: {}", counter.lock().unwrap());
+}
@@ -0,0 +1,50 @@
+//! Per-call cost of a measured function, run with and without the `hotpath-batch-channel`
+//! or `hotpath-inline` features to compare the backends:
+//!
+//! ```text
+//! cargo bench -p hotpath --bench channel_overhead --features hotpath
+//! cargo bench -p hotpath --bench channel_overhead --features hotpath,hotpath-batch-channel
+//! cargo bench -p hotpath --bench channel_overhead --features hotpath,hotpath-inline
+//! ```
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
+
+const CALLS: u64 = 10_000;
+
+#[hotpath::measure]
+fn noop(i: u64) -> u64 {
+    black_box(i)
+}
+
+fn calls(threads: u64) {
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                for i in 0..CALLS / threads {
+                    noop(i);
+                }
+            });
+        }
+    });
+}
+
+fn channel_overhead(c: &mut Criterion) {
+    let _hotpath = hotpath::GuardBuilder::new("channel_overhead::main")
+        .silent()
+        .build();
+
+    let mut group = c.benchmark_group("measured_call");
+    group.throughput(Throughput::Elements(CALLS));
+    for threads in [1, 4] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(threads),
+            &threads,
+            |b, &threads| b.iter(|| calls(threads)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, channel_overhead);
+criterion_main!(benches);
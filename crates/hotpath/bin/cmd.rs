@@ -1,3 +1,4 @@
+pub mod aggregate;
 pub mod profile_pr;
 
 #[cfg(feature = "tui")]
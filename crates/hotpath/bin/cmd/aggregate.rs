@@ -0,0 +1,320 @@
+use clap::Parser;
+use eyre::Result;
+use hotpath::{MetricType, MetricsDataJson, MetricsJson};
+use prettytable::{Cell, Row, Table};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+pub struct AggregateArgs {
+    #[arg(required = true, help = "JSON reports to merge")]
+    files: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        default_value = "table",
+        value_parser = ["table", "json", "json-pretty"],
+        help = "Output format"
+    )]
+    format: String,
+}
+
+impl AggregateArgs {
+    pub fn run(&self) -> Result<()> {
+        let reports = self
+            .files
+            .iter()
+            .map(|path| {
+                let json = std::fs::read_to_string(path)
+                    .map_err(|e| eyre::eyre!("Failed to read {}: {}", path.display(), e))?;
+                serde_json::from_str::<MetricsJson>(&json).map_err(|e| {
+                    eyre::eyre!("Failed to deserialize metrics {}: {}", path.display(), e)
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let aggregated = aggregate_metrics(&reports)?;
+
+        match self.format.as_str() {
+            "json" => println!("{}", serde_json::to_string(&aggregated)?),
+            "json-pretty" => println!("{}", serde_json::to_string_pretty(&aggregated)?),
+            _ => print!("{}", format_aggregate_table(&aggregated, reports.len())),
+        }
+
+        Ok(())
+    }
+}
+
+/// Merges reports by function name, summing calls and totals.
+///
+/// Percentiles can't be merged from summaries, so the result has none and its rows are
+/// `[Calls, Avg, Total, % Total]`. `% Total` is relative to the sum of the reports' own
+/// percentage bases, and `total_elapsed` is the sum of all reports.
+fn aggregate_metrics(reports: &[MetricsJson]) -> Result<MetricsJson> {
+    let Some(first) = reports.first() else {
+        return Err(eyre::eyre!("No reports to aggregate"));
+    };
+
+    if let Some(other) = reports
+        .iter()
+        .find(|report| report.hotpath_profiling_mode != first.hotpath_profiling_mode)
+    {
+        return Err(eyre::eyre!(
+            "Can't aggregate reports of different profiling modes: {} and {}",
+            first.hotpath_profiling_mode,
+            other.hotpath_profiling_mode
+        ));
+    }
+
+    // (calls, total), `None` total if any report couldn't measure the function
+    let mut functions: HashMap<String, (u64, Option<u64>)> = HashMap::new();
+    let mut basis_sum = 0u128;
+    let mut value_type = None;
+
+    for report in reports {
+        let total_index = report.percentiles.len() + 2;
+        basis_sum += percent_basis(report, total_index);
+
+        for (name, metrics) in &report.data.0 {
+            let calls = metrics.first().and_then(MetricType::value).unwrap_or(0);
+            let total = metrics.get(total_index);
+            if value_type.is_none() {
+                value_type = total.filter(|total| total.value().is_some()).cloned();
+            }
+
+            let entry = functions.entry(name.clone()).or_insert((0, Some(0)));
+            entry.0 += calls;
+            entry.1 = entry
+                .1
+                .zip(total.and_then(MetricType::value))
+                .map(|(sum, total)| sum + total);
+        }
+    }
+
+    let with_value = |value: u64| match value_type {
+        Some(MetricType::AllocBytes(_)) => MetricType::AllocBytes(value),
+        Some(MetricType::AllocCount(_)) => MetricType::AllocCount(value),
+        _ => MetricType::DurationNs(value),
+    };
+
+    let data = functions
+        .into_iter()
+        .map(|(name, (calls, total))| {
+            let row = match total {
+                Some(total) => vec![
+                    MetricType::CallsCount(calls),
+                    with_value(total.checked_div(calls).unwrap_or(0)),
+                    with_value(total),
+                    MetricType::Percentage(
+                        (total as u128 * 10_000).checked_div(basis_sum).unwrap_or(0) as u64,
+                    ),
+                ],
+                None => vec![
+                    MetricType::CallsCount(calls),
+                    MetricType::Unsupported,
+                    MetricType::Unsupported,
+                    MetricType::Unsupported,
+                ],
+            };
+            (name, row)
+        })
+        .collect();
+
+    // Only keep metadata all reports agree on, e.g. a shared git SHA
+    let metadata: BTreeMap<String, String> = first
+        .metadata
+        .iter()
+        .filter(|(key, value)| {
+            reports
+                .iter()
+                .all(|report| report.metadata.get(*key) == Some(*value))
+        })
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+
+    let commit = first.commit.clone().filter(|commit| {
+        reports
+            .iter()
+            .all(|report| report.commit.as_ref() == Some(commit))
+    });
+
+    let mut caller_names: Vec<&str> = reports
+        .iter()
+        .map(|report| report.caller_name.as_str())
+        .collect();
+    caller_names.dedup();
+
+    Ok(MetricsJson {
+        metadata,
+        commit,
+        ..MetricsJson::new(
+            first.hotpath_profiling_mode.clone(),
+            reports.iter().map(|report| report.total_elapsed).sum(),
+            first.description.clone(),
+            caller_names.join(", "),
+            Vec::new(),
+            MetricsDataJson(data),
+        )
+    })
+}
+
+/// Value `% Total` of a report is relative to, recovered from its largest row.
+/// Falls back to `total_elapsed` if no row has a percentage.
+fn percent_basis(report: &MetricsJson, total_index: usize) -> u128 {
+    report
+        .data
+        .0
+        .values()
+        .filter_map(|metrics| {
+            let total = metrics.get(total_index)?.value()?;
+            let percent = metrics.get(total_index + 1)?.value()?;
+            (percent > 0).then_some((percent, total))
+        })
+        .max()
+        .map(|(percent, total)| total as u128 * 10_000 / percent as u128)
+        .unwrap_or(report.total_elapsed as u128)
+}
+
+fn format_aggregate_table(metrics: &MetricsJson, reports_count: usize) -> String {
+    let mut output = format!(
+        "[hotpath] {} - {}\nAggregated {} reports of {}, percentiles can't be merged and are omitted\n",
+        metrics.hotpath_profiling_mode, metrics.description, reports_count, metrics.caller_name
+    );
+
+    let mut table = Table::new();
+    table.add_row(Row::new(
+        ["Function", "Calls", "Avg", "Total", "% Total"]
+            .iter()
+            .map(|header| Cell::new(header))
+            .collect(),
+    ));
+
+    let mut entries: Vec<_> = metrics.data.0.iter().collect();
+    entries.sort_by(|(a_name, a), (b_name, b)| {
+        let percent = |metrics: &[MetricType]| metrics.last().and_then(MetricType::value);
+        percent(b).cmp(&percent(a)).then(a_name.cmp(b_name))
+    });
+
+    for (name, row) in entries {
+        let mut cells = vec![Cell::new(name)];
+        cells.extend(row.iter().map(|metric| Cell::new(&metric.to_string())));
+        table.add_row(Row::new(cells));
+    }
+
+    output.push_str(&table.to_string());
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hotpath::{
+        MetricType::{CallsCount, DurationNs, Percentage, Unsupported},
+        ProfilingMode,
+    };
+
+    fn report(caller_name: &str, rows: Vec<(&str, Vec<MetricType>)>) -> MetricsJson {
+        MetricsJson::new(
+            ProfilingMode::Timing,
+            1_000,
+            "Execution duration of functions.".to_string(),
+            caller_name.to_string(),
+            vec![95.0],
+            MetricsDataJson(
+                rows.into_iter()
+                    .map(|(name, row)| (name.to_string(), row))
+                    .collect(),
+            ),
+        )
+    }
+
+    #[test]
+    fn test_aggregate_metrics() {
+        let producer = report(
+            "producer::main",
+            vec![
+                (
+                    "shared::parse",
+                    vec![
+                        CallsCount(2),
+                        DurationNs(100),
+                        DurationNs(150),
+                        DurationNs(200),
+                        Percentage(2000),
+                    ],
+                ),
+                (
+                    "producer::main",
+                    vec![
+                        CallsCount(1),
+                        DurationNs(1000),
+                        DurationNs(1000),
+                        DurationNs(1000),
+                        Percentage(10000),
+                    ],
+                ),
+            ],
+        );
+        let consumer = report(
+            "consumer::main",
+            vec![
+                (
+                    "shared::parse",
+                    vec![
+                        CallsCount(6),
+                        DurationNs(100),
+                        DurationNs(120),
+                        DurationNs(600),
+                        Percentage(6000),
+                    ],
+                ),
+                (
+                    "consumer::run",
+                    vec![
+                        CallsCount(1),
+                        Unsupported,
+                        Unsupported,
+                        Unsupported,
+                        Unsupported,
+                    ],
+                ),
+            ],
+        );
+
+        let aggregated = aggregate_metrics(&[producer, consumer]).unwrap();
+        assert_eq!(aggregated.total_elapsed, 2_000);
+        assert_eq!(aggregated.caller_name, "producer::main, consumer::main");
+        assert!(aggregated.percentiles.is_empty());
+
+        let values = |name: &str| -> Vec<Option<u64>> {
+            aggregated.data.0[name]
+                .iter()
+                .map(MetricType::value)
+                .collect()
+        };
+        assert_eq!(
+            values("shared::parse"),
+            [Some(8), Some(100), Some(800), Some(4000)]
+        );
+        assert_eq!(
+            values("producer::main"),
+            [Some(1), Some(1000), Some(1000), Some(5000)]
+        );
+        assert_eq!(values("consumer::run"), [Some(1), None, None, None]);
+
+        // Round-trips through JSON without percentile columns
+        let json = serde_json::to_string(&aggregated).unwrap();
+        let parsed: MetricsJson = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.data.0["shared::parse"].len(), 4);
+    }
+
+    #[test]
+    fn test_aggregate_metrics_rejects_mixed_modes() {
+        let timing = report("a::main", Vec::new());
+        let mut alloc = report("b::main", Vec::new());
+        alloc.hotpath_profiling_mode = ProfilingMode::AllocBytesTotal;
+
+        assert!(aggregate_metrics(&[timing, alloc]).is_err());
+        assert!(aggregate_metrics(&[]).is_err());
+    }
+}
@@ -1,10 +1,12 @@
 mod app;
 mod http;
+mod plain;
 mod views;
 
 use app::App;
 use clap::Parser;
 use eyre::Result;
+use std::io::IsTerminal;
 
 #[derive(Debug, Parser)]
 pub struct ConsoleArgs {
@@ -17,12 +19,27 @@ pub struct ConsoleArgs {
 
     #[arg(long, default_value_t = 500, help = "Refresh interval in milliseconds")]
     pub refresh_interval: u64,
+
+    #[arg(
+        long,
+        help = "Print a single metrics snapshot as a plain table and exit"
+    )]
+    pub once: bool,
 }
 
 impl ConsoleArgs {
     pub fn run(&self) -> Result<()> {
         let mut app = App::new(self.metrics_port);
 
+        if self.once {
+            return plain::print_once(&app.agent, self.metrics_port);
+        }
+
+        // Raw mode and the alternate screen would corrupt piped output, print plain tables instead
+        if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+            return plain::run(&app.agent, self.metrics_port, self.refresh_interval);
+        }
+
         // Use modern ratatui initialization
         let mut terminal = ratatui::init();
 
@@ -1,15 +1,21 @@
 use crossterm::event::KeyCode;
-use hotpath::{MetricsJson, SamplesJson};
+use hotpath::{CallRatesJson, MetricType, MetricsJson, SamplesJson};
 use ratatui::widgets::TableState;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+/// How long confirmations like a finished samples export stay in the status bar.
+const STATUS_MESSAGE_TTL: Duration = Duration::from_secs(5);
+
 pub(crate) struct App {
     pub(crate) metrics: MetricsJson,
+    pub(crate) call_rates: CallRatesJson,
     pub(crate) table_state: TableState,
     pub(crate) paused: bool,
     pub(crate) last_refresh: Instant,
     pub(crate) last_successful_fetch: Option<Instant>,
     pub(crate) error_message: Option<String>,
+    status_message: Option<(String, Instant)>,
     pub(crate) show_samples: bool,
     pub(crate) current_samples: Option<SamplesJson>,
     pub(crate) pinned_function: Option<String>,
@@ -26,19 +32,21 @@ impl App {
         let agent: ureq::Agent = config.into();
 
         Self {
-            metrics: MetricsJson {
-                hotpath_profiling_mode: hotpath::ProfilingMode::Timing,
-                total_elapsed: 0,
-                description: "Waiting for data...".to_string(),
-                caller_name: "unknown".to_string(),
-                percentiles: vec![95],
-                data: hotpath::MetricsDataJson(std::collections::HashMap::new()),
-            },
+            metrics: MetricsJson::new(
+                hotpath::ProfilingMode::Timing,
+                0,
+                "Waiting for data...".to_string(),
+                "unknown".to_string(),
+                vec![95.0],
+                hotpath::MetricsDataJson(std::collections::HashMap::new()),
+            ),
+            call_rates: CallRatesJson::default(),
             table_state: TableState::default().with_selected(0),
             paused: false,
             last_refresh: Instant::now(),
             last_successful_fetch: None,
             error_message: None,
+            status_message: None,
             show_samples: false,
             current_samples: None,
             pinned_function: None,
@@ -127,45 +135,9 @@ impl App {
         }
     }
 
-    /// Get sorted entries (sorted by percentage, highest first)
+    /// Get sorted entries (sorted by percentage, highest first, ties by function name)
     pub(crate) fn get_sorted_entries(&self) -> Vec<(String, Vec<hotpath::MetricType>)> {
-        use hotpath::MetricType;
-
-        let mut entries: Vec<(String, Vec<MetricType>)> = self
-            .metrics
-            .data
-            .0
-            .iter()
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect();
-
-        entries.sort_by(|(_, metrics_a), (_, metrics_b)| {
-            let percent_a = metrics_a
-                .iter()
-                .find_map(|m| {
-                    if let MetricType::Percentage(p) = m {
-                        Some(*p)
-                    } else {
-                        None
-                    }
-                })
-                .unwrap_or(0);
-
-            let percent_b = metrics_b
-                .iter()
-                .find_map(|m| {
-                    if let MetricType::Percentage(p) = m {
-                        Some(*p)
-                    } else {
-                        None
-                    }
-                })
-                .unwrap_or(0);
-
-            percent_b.cmp(&percent_a)
-        });
-
-        entries
+        sorted_entries(&self.metrics)
     }
 
     pub(crate) fn selected_function_name(&self) -> Option<String> {
@@ -211,6 +183,40 @@ impl App {
         self.fetch_samples_if_open(port);
     }
 
+    /// Writes the samples shown in the samples panel to a CSV file in the current directory.
+    pub(crate) fn export_samples(&mut self) {
+        let Some(ref samples) = self.current_samples else {
+            self.set_status("No samples to export, open the samples panel with <o>".to_string());
+            return;
+        };
+
+        let path = samples_csv_path(&samples.function_name);
+        let message = match std::fs::write(
+            &path,
+            samples_csv(&samples.samples, self.metrics.total_elapsed),
+        ) {
+            Ok(()) => format!(
+                "Exported {} samples to {}",
+                samples.samples.len(),
+                path.display()
+            ),
+            Err(e) => format!("Failed to export samples to {}: {}", path.display(), e),
+        };
+        self.set_status(message);
+    }
+
+    fn set_status(&mut self, message: String) {
+        self.status_message = Some((message, Instant::now()));
+    }
+
+    /// Status bar confirmation, if one was set recently.
+    pub(crate) fn status_message(&self) -> Option<&str> {
+        self.status_message
+            .as_ref()
+            .filter(|(_, set_at)| set_at.elapsed() < STATUS_MESSAGE_TTL)
+            .map(|(message, _)| message.as_str())
+    }
+
     pub(crate) fn exit(&mut self) {
         self.exit = true;
     }
@@ -225,6 +231,10 @@ impl App {
             }
         }
 
+        // Servers of older hotpath versions don't serve call rates, show no sparklines then
+        self.call_rates =
+            super::http::fetch_call_rates(&self.agent, self.metrics_port).unwrap_or_default();
+
         self.fetch_samples_if_open(self.metrics_port);
         self.last_refresh = Instant::now();
     }
@@ -267,6 +277,7 @@ impl App {
                 self.toggle_samples();
                 self.fetch_samples_if_open(self.metrics_port);
             }
+            KeyCode::Char('e') | KeyCode::Char('E') => self.export_samples(),
             KeyCode::Char('j') | KeyCode::Down => {
                 self.next_function();
                 self.update_and_fetch_samples(self.metrics_port);
@@ -279,3 +290,74 @@ impl App {
         }
     }
 }
+
+/// `hotpath_samples_<function>.csv`, with characters not allowed in file names replaced.
+fn samples_csv_path(function_name: &str) -> PathBuf {
+    let name: String = function_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    PathBuf::from(format!("hotpath_samples_{}.csv", name))
+}
+
+/// Samples as `index,value,ago_ns` CSV rows, newest first like in the samples panel.
+/// Values are raw nanoseconds, bytes or allocation counts depending on the profiling mode.
+fn samples_csv(samples: &[(u64, u64)], total_elapsed: u64) -> String {
+    let mut csv = String::from("index,value,ago_ns\n");
+    for (idx, &(value, elapsed_nanos)) in samples.iter().enumerate() {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            idx + 1,
+            value,
+            total_elapsed.saturating_sub(elapsed_nanos)
+        ));
+    }
+    csv
+}
+
+/// Metrics rows sorted by percentage, highest first, ties by function name
+pub(crate) fn sorted_entries(metrics: &MetricsJson) -> Vec<(String, Vec<MetricType>)> {
+    let percentage = |row: &[MetricType]| {
+        row.iter()
+            .find_map(|m| {
+                if let MetricType::Percentage(p) = m {
+                    Some(*p)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(0)
+    };
+
+    let mut entries: Vec<(String, Vec<MetricType>)> = metrics
+        .data
+        .0
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+
+    entries.sort_by(|(name_a, metrics_a), (name_b, metrics_b)| {
+        percentage(metrics_b)
+            .cmp(&percentage(metrics_a))
+            .then_with(|| name_a.cmp(name_b))
+    });
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_csv() {
+        assert_eq!(
+            samples_csv(&[(1500, 9_000), (1200, 4_000), (900, 12_000)], 10_000),
+            "index,value,ago_ns\n1,1500,1000\n2,1200,6000\n3,900,0\n"
+        );
+        assert_eq!(
+            samples_csv_path("my_app::db::query<T>"),
+            PathBuf::from("hotpath_samples_my_app__db__query_T_.csv")
+        );
+    }
+}
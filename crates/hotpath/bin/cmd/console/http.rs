@@ -1,5 +1,5 @@
 use eyre::Result;
-use hotpath::{MetricsJson, SamplesJson};
+use hotpath::{CallRatesJson, MetricsJson, SamplesJson};
 
 /// Fetches metrics from the hotpath HTTP server
 pub(crate) fn fetch_metrics(agent: &ureq::Agent, port: u16) -> Result<MetricsJson> {
@@ -14,6 +14,19 @@ pub(crate) fn fetch_metrics(agent: &ureq::Agent, port: u16) -> Result<MetricsJso
     Ok(metrics)
 }
 
+/// Fetches recent call counts of all functions
+pub(crate) fn fetch_call_rates(agent: &ureq::Agent, port: u16) -> Result<CallRatesJson> {
+    let url = format!("http://localhost:{}/rates", port);
+    let rates: CallRatesJson = agent
+        .get(&url)
+        .call()
+        .map_err(|e| eyre::eyre!("HTTP request failed: {}", e))?
+        .body_mut()
+        .read_json()
+        .map_err(|e| eyre::eyre!("JSON deserialization failed: {}", e))?;
+    Ok(rates)
+}
+
 /// Fetches recent samples for a specific function
 pub(crate) fn fetch_samples(
     agent: &ureq::Agent,
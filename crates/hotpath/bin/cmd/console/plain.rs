@@ -0,0 +1,120 @@
+//! Plain text output used instead of the TUI when not running in a terminal.
+
+use super::app::sorted_entries;
+use eyre::Result;
+use hotpath::MetricsJson;
+use prettytable::{Cell, Row, Table};
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Writes `metrics` as a plain table, in the same layout as the TUI functions table.
+pub(crate) fn write_table(out: &mut dyn Write, metrics: &MetricsJson) -> io::Result<()> {
+    writeln!(
+        out,
+        "[hotpath] {} - {}",
+        metrics.hotpath_profiling_mode, metrics.description
+    )?;
+    writeln!(
+        out,
+        "{}: {}",
+        metrics.caller_name,
+        hotpath::format_elapsed(Duration::from_nanos(metrics.total_elapsed))
+    )?;
+
+    let mut table = Table::new();
+
+    let mut header_cells = vec![Cell::new("Function"), Cell::new("Calls"), Cell::new("Avg")];
+    for p in &metrics.percentiles {
+        header_cells.push(Cell::new(&format!("P{}", p)));
+    }
+    header_cells.push(Cell::new("Total"));
+    header_cells.push(Cell::new("% Total"));
+    table.add_row(Row::new(header_cells));
+
+    for (function_name, row) in sorted_entries(metrics) {
+        let short_name = hotpath::shorten_function_name(&function_name);
+        let cells = std::iter::once(Cell::new(&short_name))
+            .chain(row.iter().map(|m| Cell::new(&m.to_string())))
+            .collect();
+        table.add_row(Row::new(cells));
+    }
+
+    table.print(out)?;
+    Ok(())
+}
+
+/// Fetches the metrics once and prints them as a table, for `console --once`.
+pub(crate) fn print_once(agent: &ureq::Agent, metrics_port: u16) -> Result<()> {
+    let metrics = super::http::fetch_metrics(agent, metrics_port)?;
+    write_table(&mut io::stdout().lock(), &metrics)?;
+    Ok(())
+}
+
+/// Prints the metrics table every `refresh_interval_ms` until the process is terminated.
+///
+/// Fetch errors are reported on stderr and retried on the next refresh. Returns once
+/// stdout is closed.
+pub(crate) fn run(agent: &ureq::Agent, metrics_port: u16, refresh_interval_ms: u64) -> Result<()> {
+    let refresh_interval = Duration::from_millis(refresh_interval_ms);
+    let mut stdout = io::stdout();
+
+    loop {
+        match super::http::fetch_metrics(agent, metrics_port) {
+            Ok(metrics) => {
+                let written = write_table(&mut stdout, &metrics)
+                    .and_then(|_| writeln!(stdout))
+                    .and_then(|_| stdout.flush());
+                match written {
+                    // The reader went away, e.g. `hotpath console | head`
+                    Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+                    written => written?,
+                }
+            }
+            Err(e) => eprintln!("[hotpath] {}", e),
+        }
+
+        std::thread::sleep(refresh_interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_table() {
+        let metrics: MetricsJson = serde_json::from_str(
+            r#"{
+                "schema_version": 1,
+                "hotpath_profiling_mode": "timing",
+                "total_elapsed": 2000000,
+                "caller_name": "test::main",
+                "description": "Execution duration of functions.",
+                "output": {
+                    "test::function_b": {
+                        "calls": 1, "avg": 500, "p95": 500, "total": 500, "percent_total": 25
+                    },
+                    "test::function_a": {
+                        "calls": 10, "avg": 1500, "p95": 2000, "total": 15000, "percent_total": 7500
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let mut out = Vec::new();
+        write_table(&mut out, &metrics).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.starts_with("[hotpath] timing - Execution duration of functions.\n"));
+        assert!(out.contains("test::main: 2.00ms"));
+        assert!(out.contains("| Function "));
+        assert!(out.contains("| P95 "));
+        assert!(out.contains("| 75.00%"));
+
+        let function_a = out.find("test::function_a").unwrap();
+        let function_b = out.find("test::function_b").unwrap();
+        assert!(function_a < function_b, "Got:\n{out}");
+        assert!(!out.contains('\x1b'));
+    }
+}
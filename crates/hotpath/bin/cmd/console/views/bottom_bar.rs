@@ -29,6 +29,13 @@ pub(crate) fn render_help_bar(frame: &mut Frame, area: Rect) {
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
         ),
+        Span::raw(" | Export Samples "),
+        Span::styled(
+            "<e>",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
         Span::raw(" | Pause "),
         Span::styled(
             "<p>",
@@ -8,6 +8,24 @@ use ratatui::{
     Frame,
 };
 
+/// Number of most recent call rate buckets shown per function
+const SPARKLINE_WIDTH: usize = 20;
+
+/// Renders the last `width` counts as bars scaled to the highest one, oldest first.
+fn sparkline(counts: &[u64], width: usize) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let counts = &counts[counts.len().saturating_sub(width)..];
+    let max = counts.iter().copied().max().unwrap_or(0);
+    counts
+        .iter()
+        .map(|&count| match count {
+            0 => ' ',
+            _ => BARS[((count * (BARS.len() as u64 - 1)).div_ceil(max)) as usize],
+        })
+        .collect()
+}
+
 pub(crate) fn render_functions_table(frame: &mut Frame, app: &mut App, area: Rect) {
     let title = format!(
         " {} - {} ",
@@ -27,7 +45,11 @@ pub(crate) fn render_functions_table(frame: &mut Frame, app: &mut App, area: Rec
             .map(|p| format!("P{}", p))
             .collect::<Vec<_>>(),
     )
-    .chain(vec!["Total".to_string(), "% Total".to_string()])
+    .chain(vec![
+        "Total".to_string(),
+        "% Total".to_string(),
+        "Calls/s".to_string(),
+    ])
     .map(|h| {
         Cell::from(h).style(
             Style::default()
@@ -44,8 +66,18 @@ pub(crate) fn render_functions_table(frame: &mut Frame, app: &mut App, area: Rec
     let rows = entries.iter().map(|(function_name, metrics)| {
         let short_name = hotpath::shorten_function_name(function_name);
 
+        let rates = app
+            .call_rates
+            .rates
+            .get(function_name)
+            .map_or(&[][..], Vec::as_slice);
+
         let cells = std::iter::once(Cell::from(short_name))
             .chain(metrics.iter().map(|m| Cell::from(format!("{}", m))))
+            .chain(std::iter::once(
+                Cell::from(sparkline(rates, SPARKLINE_WIDTH))
+                    .style(Style::default().fg(Color::Green)),
+            ))
             .collect::<Vec<_>>();
 
         Row::new(cells)
@@ -71,8 +103,9 @@ pub(crate) fn render_functions_table(frame: &mut Frame, app: &mut App, area: Rec
             ])
             .chain((0..num_percentiles).map(|_| Constraint::Percentage(col_pct))) // P95, etc
             .chain(vec![
-                Constraint::Percentage(col_pct), // Total
-                Constraint::Percentage(col_pct), // % Total
+                Constraint::Percentage(col_pct),            // Total
+                Constraint::Percentage(col_pct),            // % Total
+                Constraint::Length(SPARKLINE_WIDTH as u16), // Calls/s
             ])
             .collect::<Vec<_>>(),
     )
@@ -97,3 +130,15 @@ pub(crate) fn render_functions_table(frame: &mut Frame, app: &mut App, area: Rec
 
     frame.render_stateful_widget(table, area, &mut app.table_state);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[], 5), "");
+        assert_eq!(sparkline(&[0, 1, 4, 8], 5), " ▂▅█");
+        assert_eq!(sparkline(&[8, 8, 0, 3, 3], 3), " ██");
+    }
+}
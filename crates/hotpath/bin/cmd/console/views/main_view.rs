@@ -15,14 +15,7 @@ pub(crate) fn render_ui(frame: &mut Frame, app: &mut App) {
         ])
         .split(frame.area());
 
-    top_bar::render_status_bar(
-        frame,
-        main_chunks[0],
-        app.paused,
-        &app.error_message,
-        &app.last_successful_fetch,
-        app.last_refresh,
-    );
+    top_bar::render_status_bar(frame, main_chunks[0], app);
 
     if app.show_samples {
         let content_chunks = Layout::default()
@@ -1,6 +1,6 @@
 use super::super::app::App;
 use ratatui::{
-    layout::{Constraint, Rect},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::block::BorderType,
@@ -79,12 +79,31 @@ pub(crate) fn render_samples_panel(frame: &mut Frame, area: Rect, app: &App) {
             Constraint::Length(12), // Ago column
         ];
 
-        let table = Table::new(rows, widths)
-            .header(headers)
-            .block(block)
-            .column_spacing(2);
+        let table = Table::new(rows, widths).header(headers).column_spacing(2);
 
-        frame.render_widget(table, area);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(2), // Stats line
+                Constraint::Min(0),    // Samples table
+            ])
+            .split(inner);
+
+        let values: Vec<u64> = samples_data
+            .samples
+            .iter()
+            .map(|&(value, _)| value)
+            .collect();
+        if let Some(stats) = sample_stats(&values) {
+            frame.render_widget(
+                stats_line(&stats, &app.metrics.hotpath_profiling_mode),
+                chunks[0],
+            );
+        }
+        frame.render_widget(table, chunks[1]);
     } else if app.selected_function_name().is_some() {
         // No samples yet
         let items = vec![
@@ -115,10 +134,58 @@ pub(crate) fn render_samples_panel(frame: &mut Frame, area: Rect, app: &App) {
     }
 }
 
+/// Min, avg, p95 and max of the displayed samples.
+struct SampleStats {
+    min: u64,
+    avg: u64,
+    p95: u64,
+    max: u64,
+}
+
+fn sample_stats(values: &[u64]) -> Option<SampleStats> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    // Nearest-rank percentile
+    let p95_rank = (sorted.len() * 95).div_ceil(100).max(1);
+
+    Some(SampleStats {
+        min: sorted[0],
+        avg: (sorted.iter().map(|&v| v as u128).sum::<u128>() / sorted.len() as u128) as u64,
+        p95: sorted[p95_rank - 1],
+        max: sorted[sorted.len() - 1],
+    })
+}
+
+fn stats_line(stats: &SampleStats, profiling_mode: &hotpath::ProfilingMode) -> Line<'static> {
+    let mut spans = Vec::new();
+    for (label, value) in [
+        ("min", stats.min),
+        ("avg", stats.avg),
+        ("p95", stats.p95),
+        ("max", stats.max),
+    ] {
+        spans.push(Span::styled(
+            format!(" {} ", label),
+            Style::default().fg(Color::DarkGray),
+        ));
+        spans.push(Span::styled(
+            format!("{} ", format_sample_value(value, profiling_mode)),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+    Line::from(spans)
+}
+
 fn format_sample_value(value: u64, profiling_mode: &hotpath::ProfilingMode) -> String {
     match profiling_mode {
         hotpath::ProfilingMode::Timing => hotpath::format_duration(value),
-        hotpath::ProfilingMode::AllocBytesTotal => hotpath::format_bytes(value),
+        hotpath::ProfilingMode::AllocBytesTotal | hotpath::ProfilingMode::AllocLeak => {
+            hotpath::format_bytes(value)
+        }
         hotpath::ProfilingMode::AllocCountTotal => {
             format!("{}", value)
         }
@@ -155,3 +222,23 @@ fn format_time_ago(nanos_ago: u64) -> String {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_stats() {
+        assert!(sample_stats(&[]).is_none());
+
+        let values: Vec<u64> = (1..=100).rev().collect();
+        let stats = sample_stats(&values).unwrap();
+        assert_eq!(stats.min, 1);
+        assert_eq!(stats.avg, 50);
+        assert_eq!(stats.p95, 95);
+        assert_eq!(stats.max, 100);
+
+        let stats = sample_stats(&[7]).unwrap();
+        assert_eq!((stats.min, stats.avg, stats.p95, stats.max), (7, 7, 7, 7));
+    }
+}
@@ -1,3 +1,4 @@
+use super::super::app::App;
 use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
@@ -5,18 +6,11 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
-use std::time::Instant;
 
-pub(crate) fn render_status_bar(
-    frame: &mut Frame,
-    area: Rect,
-    paused: bool,
-    error_message: &Option<String>,
-    last_successful_fetch: &Option<Instant>,
-    last_refresh: Instant,
-) {
-    let status_text = if let Some(error) = error_message {
-        let time_since_success = last_successful_fetch
+pub(crate) fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
+    let status_text = if let Some(error) = &app.error_message {
+        let time_since_success = app
+            .last_successful_fetch
             .map(|t| format!("{}s ago", t.elapsed().as_secs()))
             .unwrap_or_else(|| "never".to_string());
 
@@ -31,11 +25,17 @@ pub(crate) fn render_status_bar(
             Span::raw(")"),
         ])]
     } else {
-        let refresh_time = last_refresh.elapsed().as_secs();
-        let status_symbol = if paused { "⏸ Paused" } else { "✓ Live" };
-        let status_color = if paused { Color::Yellow } else { Color::Green };
+        let refresh_time = app.last_refresh.elapsed().as_secs();
+        let (status_symbol, status_color) = if app.paused {
+            ("⏸ Paused", Color::Yellow)
+        } else if app.metrics.stale {
+            // The server answered with its last snapshot, the worker was too busy to answer
+            ("⚠ Stale", Color::Yellow)
+        } else {
+            ("✓ Live", Color::Green)
+        };
 
-        vec![Line::from(vec![
+        let mut spans = vec![
             Span::styled(
                 status_symbol,
                 Style::default()
@@ -43,7 +43,16 @@ pub(crate) fn render_status_bar(
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(format!(" (refreshed {}s ago)", refresh_time)),
-        ])]
+        ];
+        if let Some(message) = app.status_message() {
+            spans.push(Span::raw(" | "));
+            spans.push(Span::styled(
+                message.to_string(),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+
+        vec![Line::from(spans)]
     };
 
     let status_paragraph =
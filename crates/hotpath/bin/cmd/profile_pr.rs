@@ -3,11 +3,12 @@ mod comment;
 use clap::Parser;
 use comment::upsert_pr_comment;
 use eyre::Result;
-use hotpath::{format_bytes, MetricsJson};
+use hotpath::{
+    compare_metrics, ByteFormat, EmojiThresholds, FunctionMetricsDiff, MetricsComparison,
+    MetricsJson,
+};
 use prettytable::{Cell, Row, Table};
 use std::env;
-use std::fmt;
-use std::time::Duration;
 
 #[derive(Debug, Parser)]
 pub struct ProfilePrArgs {
@@ -28,6 +29,38 @@ pub struct ProfilePrArgs {
         help = "Emoji threshold percentage for performance changes (default: 20, use 0 to disable)"
     )]
     emoji_threshold: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Emoji threshold percentage for duration changes (default: --emoji-threshold, use 0 to disable)"
+    )]
+    emoji_threshold_time: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Emoji threshold percentage for allocation changes (default: --emoji-threshold, use 0 to disable)"
+    )]
+    emoji_threshold_alloc: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Collapse functions without changes beyond the emoji thresholds into a <details> section"
+    )]
+    collapse_unchanged: bool,
+
+    #[arg(
+        long,
+        default_value = "human",
+        help = "Rounding of byte values: human, exact or precise:<digits>"
+    )]
+    byte_format: ByteFormat,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Comma-separated report metadata keys to show in the comment, e.g. git_sha,cpu_model"
+    )]
+    metadata_keys: Vec<String>,
 }
 
 impl ProfilePrArgs {
@@ -39,12 +72,11 @@ impl ProfilePrArgs {
             return Ok(());
         }
 
-        // Convert emoji_threshold: None -> Some(20), Some(0) -> None
-        let emoji_threshold = if let Some(0) = self.emoji_threshold {
-            None
-        } else {
-            Some(self.emoji_threshold.unwrap_or(20))
-        };
+        let emoji_thresholds = emoji_thresholds(
+            self.emoji_threshold,
+            self.emoji_threshold_time,
+            self.emoji_threshold_alloc,
+        );
 
         let head_metrics_data: MetricsJson = serde_json::from_str(&self.head_metrics)
             .map_err(|e| eyre::eyre!("Failed to deserialize head metrics: {}", e))?;
@@ -52,10 +84,28 @@ impl ProfilePrArgs {
             .map_err(|e| eyre::eyre!("Failed to deserialize base metrics: {}", e))?;
 
         let comparison = compare_metrics(&base_metrics_data, &head_metrics_data);
-        let comparison_markdown =
-            format_comparison_markdown(&comparison, &base_metrics_data, emoji_threshold);
+        let comparison_markdown = format_comparison_markdown(
+            &comparison,
+            &base_metrics_data,
+            &emoji_thresholds,
+            self.byte_format,
+            self.collapse_unchanged,
+        );
 
-        let mut body = comparison_markdown;
+        let mut body = String::new();
+        if let Some(warning) = schema_version_warning(&base_metrics_data, &head_metrics_data) {
+            println!("Warning: {}", warning);
+            body.push_str(&format!("> ⚠️ {}\n\n", warning));
+        }
+        if let Some(commits) = format_commits_markdown(&base_metrics_data, &head_metrics_data) {
+            body.push_str(&commits);
+        }
+        if let Some(metadata) =
+            format_metadata_markdown(&self.metadata_keys, &base_metrics_data, &head_metrics_data)
+        {
+            body.push_str(&metadata);
+        }
+        body.push_str(&comparison_markdown);
         body.push_str("\n<details>\n<summary>📊 View Raw JSON Metrics</summary>\n\n");
         body.push_str("### PR Metrics\n```json\n");
         body.push_str(&serde_json::to_string_pretty(&head_metrics_data)?);
@@ -78,249 +128,89 @@ impl ProfilePrArgs {
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum MetricDiff {
-    CallsCount(u64, u64), // (before, after)
-    DurationNs(u64, u64), // (before, after) - Duration in nanoseconds
-    AllocBytes(u64, u64), // (before, after) - Bytes allocated
-    AllocCount(u64, u64), // (before, after) - Allocation count
-    Percentage(u64, u64), // (before, after)
-}
-
-impl fmt::Display for MetricDiff {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.format_with_emoji(None))
+/// Resolves the emoji threshold flags: unset per-metric thresholds fall back to the general
+/// one, which defaults to 20%, and 0 disables the emoji.
+fn emoji_thresholds(
+    general: Option<u32>,
+    time: Option<u32>,
+    alloc: Option<u32>,
+) -> EmojiThresholds {
+    let general = general.unwrap_or(20);
+    let resolve = |threshold: Option<u32>| Some(threshold.unwrap_or(general)).filter(|t| *t != 0);
+    EmojiThresholds {
+        time: resolve(time),
+        alloc: resolve(alloc),
+        other: resolve(None),
     }
 }
 
-impl MetricDiff {
-    fn format_with_emoji(&self, emoji_threshold: Option<u32>) -> String {
-        match self {
-            MetricDiff::CallsCount(before, after) => {
-                let diff_percent = calculate_percentage_diff(*before, *after);
-                let emoji = get_emoji_for_diff(diff_percent, emoji_threshold);
-                format!("{} → {} ({:+.1}%){}", before, after, diff_percent, emoji)
-            }
-            MetricDiff::DurationNs(before, after) => {
-                let diff_percent = calculate_percentage_diff(*before, *after);
-                let before_duration = Duration::from_nanos(*before);
-                let after_duration = Duration::from_nanos(*after);
-                let emoji = get_emoji_for_diff(diff_percent, emoji_threshold);
-                format!(
-                    "{:.2?} → {:.2?} ({:+.1}%){}",
-                    before_duration, after_duration, diff_percent, emoji
-                )
-            }
-            MetricDiff::AllocBytes(before, after) => {
-                let diff_percent = calculate_percentage_diff(*before, *after);
-                let emoji = get_emoji_for_diff(diff_percent, emoji_threshold);
-                format!(
-                    "{} → {} ({:+.1}%){}",
-                    format_bytes(*before),
-                    format_bytes(*after),
-                    diff_percent,
-                    emoji
-                )
-            }
-            MetricDiff::AllocCount(before, after) => {
-                let diff_percent = calculate_percentage_diff(*before, *after);
-                let emoji = get_emoji_for_diff(diff_percent, emoji_threshold);
-                format!("{} → {} ({:+.1}%){}", before, after, diff_percent, emoji)
-            }
-            MetricDiff::Percentage(before, after) => {
-                let diff_percent = calculate_percentage_diff(*before, *after);
-                let before_percent = *before as f64 / 100.0;
-                let after_percent = *after as f64 / 100.0;
-                let emoji = get_emoji_for_diff(diff_percent, emoji_threshold);
-                format!(
-                    "{:.2}% → {:.2}% ({:+.1}%){}",
-                    before_percent, after_percent, diff_percent, emoji
-                )
-            }
-        }
-    }
-}
-
-fn get_emoji_for_diff(diff_percent: f64, threshold: Option<u32>) -> &'static str {
-    if let Some(threshold_val) = threshold {
-        let threshold = threshold_val as f64;
-        if diff_percent > threshold {
-            " ⚠️ "
-        } else if diff_percent < -threshold {
-            " 🚀 "
-        } else {
-            "   "
-        }
-    } else {
-        ""
+/// Describes a JSON schema version mismatch between base and head metrics, which may make
+/// the comparison inaccurate, e.g. when the PR upgrades hotpath.
+fn schema_version_warning(base: &MetricsJson, head: &MetricsJson) -> Option<String> {
+    if base.schema_version == head.schema_version {
+        return None;
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct MetricsComparison {
-    pub total_elapsed_diff: MetricDiff,
-    pub function_diffs: Vec<FunctionMetricsDiff>,
+    Some(format!(
+        "Metrics JSON schema versions differ (base: {}, head: {}), comparison results may be inaccurate.",
+        base.schema_version, head.schema_version
+    ))
 }
 
-#[derive(Debug, Clone)]
-pub struct FunctionMetricsDiff {
-    pub function_name: String,
-    pub metrics: Vec<MetricDiff>,
-    pub is_removed: bool, // True if function was removed (no longer measured)
-    pub is_new: bool,     // True if function is new (not in base)
-}
-
-fn calculate_percentage_diff(before: u64, after: u64) -> f64 {
-    if before == 0 {
-        if after == 0 {
-            0.0
-        } else {
-            100.0 // 100% increase from 0
-        }
-    } else {
-        ((after as f64 - before as f64) / before as f64) * 100.0
+/// Commits the reports were recorded at, `None` if neither has one.
+fn format_commits_markdown(base: &MetricsJson, head: &MetricsJson) -> Option<String> {
+    if base.commit.is_none() && head.commit.is_none() {
+        return None;
     }
-}
-
-fn compare_metrics(before_metrics: &MetricsJson, after_metrics: &MetricsJson) -> MetricsComparison {
-    use hotpath::MetricType;
-
-    let total_elapsed_diff =
-        MetricDiff::DurationNs(before_metrics.total_elapsed, after_metrics.total_elapsed);
-
-    let mut function_diffs = Vec::new();
-    let mut new_functions = Vec::new();
-
-    // Process functions that exist in after_metrics (updated, unchanged, or new)
-    for (function_name, after_row) in &after_metrics.data.0 {
-        if let Some(before_row) = before_metrics.data.0.get(function_name) {
-            // Function exists in both before and after - compare metrics
-            let mut metrics = Vec::new();
-
-            for (metric_idx, after_metric) in after_row.iter().enumerate() {
-                if let Some(before_metric) = before_row.get(metric_idx) {
-                    let diff = match (before_metric, after_metric) {
-                        (MetricType::CallsCount(before_val), MetricType::CallsCount(after_val)) => {
-                            MetricDiff::CallsCount(*before_val, *after_val)
-                        }
-                        (MetricType::DurationNs(before_val), MetricType::DurationNs(after_val)) => {
-                            MetricDiff::DurationNs(*before_val, *after_val)
-                        }
-                        (MetricType::AllocBytes(before_val), MetricType::AllocBytes(after_val)) => {
-                            MetricDiff::AllocBytes(*before_val, *after_val)
-                        }
-                        (MetricType::AllocCount(before_val), MetricType::AllocCount(after_val)) => {
-                            MetricDiff::AllocCount(*before_val, *after_val)
-                        }
-                        (MetricType::Percentage(before_val), MetricType::Percentage(after_val)) => {
-                            MetricDiff::Percentage(*before_val, *after_val)
-                        }
-                        _ => continue, // Skip mismatched metric types
-                    };
-                    metrics.push(diff);
-                }
-            }
-
-            function_diffs.push(FunctionMetricsDiff {
-                function_name: function_name.clone(),
-                metrics,
-                is_removed: false,
-                is_new: false,
-            });
-        } else {
-            // Function is new (exists in after but not in before) - show 0 → after
-            let mut metrics = Vec::new();
-
-            for after_metric in after_row.iter() {
-                let diff = match after_metric {
-                    MetricType::CallsCount(after_val) => MetricDiff::CallsCount(0, *after_val),
-                    MetricType::DurationNs(after_val) => MetricDiff::DurationNs(0, *after_val),
-                    MetricType::AllocBytes(after_val) => MetricDiff::AllocBytes(0, *after_val),
-                    MetricType::AllocCount(after_val) => MetricDiff::AllocCount(0, *after_val),
-                    MetricType::Percentage(after_val) => MetricDiff::Percentage(0, *after_val),
-                    MetricType::Unsupported => continue,
-                };
-                metrics.push(diff);
-            }
 
-            new_functions.push(FunctionMetricsDiff {
-                function_name: function_name.clone(),
-                metrics,
-                is_removed: false,
-                is_new: true,
-            });
-        }
-    }
-
-    // Process functions that were removed (exist in before but not in after)
-    for (function_name, before_row) in &before_metrics.data.0 {
-        // Check if this function exists in after_metrics
-        if !after_metrics.data.0.contains_key(function_name) {
-            // Function was removed, show before → 0
-            let mut metrics = Vec::new();
-
-            for before_metric in before_row.iter() {
-                let diff = match before_metric {
-                    MetricType::CallsCount(before_val) => MetricDiff::CallsCount(*before_val, 0),
-                    MetricType::DurationNs(before_val) => MetricDiff::DurationNs(*before_val, 0),
-                    MetricType::AllocBytes(before_val) => MetricDiff::AllocBytes(*before_val, 0),
-                    MetricType::AllocCount(before_val) => MetricDiff::AllocCount(*before_val, 0),
-                    MetricType::Percentage(before_val) => MetricDiff::Percentage(*before_val, 0),
-                    MetricType::Unsupported => continue,
-                };
-                metrics.push(diff);
-            }
+    let commit = |metrics: &MetricsJson| {
+        metrics.commit.as_ref().map_or("-".to_string(), |commit| {
+            format!("`{}`", commit.chars().take(7).collect::<String>())
+        })
+    };
+    Some(format!(
+        "**Commits:** {} → {}\n\n",
+        commit(base),
+        commit(head)
+    ))
+}
 
-            function_diffs.push(FunctionMetricsDiff {
-                function_name: function_name.clone(),
-                metrics,
-                is_removed: true,
-                is_new: false,
-            });
-        }
+/// Table of the selected metadata keys of both reports, `None` if no key is set in either.
+fn format_metadata_markdown(
+    keys: &[String],
+    base: &MetricsJson,
+    head: &MetricsJson,
+) -> Option<String> {
+    let rows: Vec<String> = keys
+        .iter()
+        .filter(|key| base.metadata.contains_key(*key) || head.metadata.contains_key(*key))
+        .map(|key| {
+            let value = |metrics: &MetricsJson| {
+                metrics
+                    .metadata
+                    .get(key)
+                    .map_or("-".to_string(), |value| format!("`{}`", value))
+            };
+            format!("| {} | {} | {} |\n", key, value(base), value(head))
+        })
+        .collect();
+
+    if rows.is_empty() {
+        return None;
     }
 
-    function_diffs.extend(new_functions);
-
-    // Sort by percent_total in head branch (after value), descending order
-    function_diffs.sort_by(|a, b| {
-        let a_percent = a
-            .metrics
-            .iter()
-            .find_map(|m| {
-                if let MetricDiff::Percentage(_, after) = m {
-                    Some(*after)
-                } else {
-                    None
-                }
-            })
-            .unwrap_or(0);
-
-        let b_percent = b
-            .metrics
-            .iter()
-            .find_map(|m| {
-                if let MetricDiff::Percentage(_, after) = m {
-                    Some(*after)
-                } else {
-                    None
-                }
-            })
-            .unwrap_or(0);
-
-        b_percent.cmp(&a_percent)
-    });
-
-    MetricsComparison {
-        total_elapsed_diff,
-        function_diffs,
-    }
+    let mut markdown = String::from("| Metadata | Base | Head |\n| --- | --- | --- |\n");
+    markdown.push_str(&rows.concat());
+    markdown.push('\n');
+    Some(markdown)
 }
 
 fn format_comparison_markdown(
     comparison: &MetricsComparison,
     metrics: &MetricsJson,
-    emoji_threshold: Option<u32>,
+    emoji_thresholds: &EmojiThresholds,
+    byte_format: ByteFormat,
+    collapse_unchanged: bool,
 ) -> String {
     let mut markdown = String::new();
 
@@ -335,7 +225,7 @@ fn format_comparison_markdown(
         "**Total Elapsed Time:** {}\n\n",
         comparison
             .total_elapsed_diff
-            .format_with_emoji(emoji_threshold)
+            .format_with_thresholds(emoji_thresholds)
     ));
     markdown.push_str(&format!(
         "**Profiling Mode:** {} - {}\n",
@@ -347,6 +237,53 @@ fn format_comparison_markdown(
         return markdown;
     }
 
+    let (changed, unchanged): (Vec<&FunctionMetricsDiff>, Vec<&FunctionMetricsDiff>) =
+        if collapse_unchanged {
+            comparison.function_diffs.iter().partition(|func_diff| {
+                func_diff.is_new
+                    || func_diff.is_removed
+                    || func_diff
+                        .metrics
+                        .iter()
+                        .any(|metric_diff| metric_diff.is_significant(emoji_thresholds))
+            })
+        } else {
+            (comparison.function_diffs.iter().collect(), Vec::new())
+        };
+
+    if changed.is_empty() {
+        markdown.push_str("\n*No functions changed beyond the emoji threshold*\n\n");
+    } else {
+        markdown.push_str("```\n");
+        markdown.push_str(
+            &comparison_table(&changed, metrics, emoji_thresholds, byte_format).to_string(),
+        );
+        markdown.push_str("```\n\n");
+    }
+
+    if !unchanged.is_empty() {
+        markdown.push_str(&format!(
+            "<details>\n<summary>{} unchanged functions</summary>\n\n```\n",
+            unchanged.len()
+        ));
+        markdown.push_str(
+            &comparison_table(&unchanged, metrics, emoji_thresholds, byte_format).to_string(),
+        );
+        markdown.push_str("```\n</details>\n\n");
+    }
+
+    markdown.push_str("---\n");
+    markdown.push_str("*Generated with [hotpath](https://github.com/pawurb/hotpath/)*\n");
+
+    markdown
+}
+
+fn comparison_table(
+    function_diffs: &[&FunctionMetricsDiff],
+    metrics: &MetricsJson,
+    emoji_thresholds: &EmojiThresholds,
+    byte_format: ByteFormat,
+) -> Table {
     let mut table = Table::new();
 
     let mut header_cells = vec![Cell::new("Function"), Cell::new("Calls"), Cell::new("Avg")];
@@ -357,7 +294,7 @@ fn format_comparison_markdown(
     header_cells.push(Cell::new("% Total"));
     table.add_row(Row::new(header_cells));
 
-    for func_diff in &comparison.function_diffs {
+    for func_diff in function_diffs {
         let function_display = if func_diff.is_removed {
             format!("️🗑️ {}", func_diff.function_name)
         } else if func_diff.is_new {
@@ -368,19 +305,14 @@ fn format_comparison_markdown(
 
         let mut row_cells = vec![Cell::new(&function_display)];
         for metric_diff in &func_diff.metrics {
-            row_cells.push(Cell::new(&metric_diff.format_with_emoji(emoji_threshold)));
+            row_cells.push(Cell::new(
+                &metric_diff.format(emoji_thresholds, byte_format),
+            ));
         }
         table.add_row(Row::new(row_cells));
     }
 
-    markdown.push_str("```\n");
-    markdown.push_str(&table.to_string());
-    markdown.push_str("```\n\n");
-
-    markdown.push_str("---\n");
-    markdown.push_str("*Generated with [hotpath](https://github.com/pawurb/hotpath/)*\n");
-
-    markdown
+    table
 }
 
 #[cfg(test)]
@@ -427,14 +359,14 @@ mod test {
             ],
         );
 
-        let pr_metrics = MetricsJson {
-            hotpath_profiling_mode: hotpath::ProfilingMode::Timing,
-            total_elapsed: 140515884,
-            caller_name: "basic::main".to_string(),
-            percentiles: vec![95],
-            description: "Time metrics".to_string(),
-            data: MetricsDataJson(pr_data),
-        };
+        let pr_metrics = MetricsJson::new(
+            hotpath::ProfilingMode::Timing,
+            140515884,
+            "Time metrics".to_string(),
+            "basic::main".to_string(),
+            vec![95.0],
+            MetricsDataJson(pr_data),
+        );
 
         let mut main_data = HashMap::new();
         main_data.insert(
@@ -468,14 +400,14 @@ mod test {
             ],
         );
 
-        let main_metrics = MetricsJson {
-            hotpath_profiling_mode: hotpath::ProfilingMode::Timing,
-            total_elapsed: 126464296,
-            caller_name: "basic::main".to_string(),
-            percentiles: vec![95],
-            description: "Time metrics".to_string(),
-            data: MetricsDataJson(main_data),
-        };
+        let main_metrics = MetricsJson::new(
+            hotpath::ProfilingMode::Timing,
+            126464296,
+            "Time metrics".to_string(),
+            "basic::main".to_string(),
+            vec![95.0],
+            MetricsDataJson(main_data),
+        );
 
         let comparison = compare_metrics(&main_metrics, &pr_metrics);
 
@@ -489,7 +421,13 @@ mod test {
         }
 
         // Test markdown formatting
-        let markdown = format_comparison_markdown(&comparison, &main_metrics, Some(20));
+        let markdown = format_comparison_markdown(
+            &comparison,
+            &main_metrics,
+            &EmojiThresholds::uniform(Some(20)),
+            ByteFormat::Human,
+            false,
+        );
         println!("\n=== Generated Markdown ===\n{}", markdown);
     }
 
@@ -510,14 +448,14 @@ mod test {
             ],
         );
 
-        let pr_metrics = MetricsJson {
-            hotpath_profiling_mode: hotpath::ProfilingMode::Timing,
-            total_elapsed: 100000000,
-            caller_name: "test::main".to_string(),
-            percentiles: vec![95],
-            description: "Time metrics".to_string(),
-            data: MetricsDataJson(pr_data),
-        };
+        let pr_metrics = MetricsJson::new(
+            hotpath::ProfilingMode::Timing,
+            100000000,
+            "Time metrics".to_string(),
+            "test::main".to_string(),
+            vec![95.0],
+            MetricsDataJson(pr_data),
+        );
 
         let mut main_data = HashMap::new();
         main_data.insert(
@@ -541,14 +479,14 @@ mod test {
             ],
         );
 
-        let main_metrics = MetricsJson {
-            hotpath_profiling_mode: hotpath::ProfilingMode::Timing,
-            total_elapsed: 120000000,
-            caller_name: "test::main".to_string(),
-            percentiles: vec![95],
-            description: "Time metrics".to_string(),
-            data: MetricsDataJson(main_data),
-        };
+        let main_metrics = MetricsJson::new(
+            hotpath::ProfilingMode::Timing,
+            120000000,
+            "Time metrics".to_string(),
+            "test::main".to_string(),
+            vec![95.0],
+            MetricsDataJson(main_data),
+        );
 
         let comparison = compare_metrics(&main_metrics, &pr_metrics);
 
@@ -565,7 +503,13 @@ mod test {
             }
         }
 
-        let markdown = format_comparison_markdown(&comparison, &main_metrics, Some(20));
+        let markdown = format_comparison_markdown(
+            &comparison,
+            &main_metrics,
+            &EmojiThresholds::uniform(Some(20)),
+            ByteFormat::Human,
+            false,
+        );
         println!("\n=== Generated Markdown ===\n{}", markdown);
 
         assert!(comparison
@@ -601,14 +545,14 @@ mod test {
             ],
         );
 
-        let pr_metrics = MetricsJson {
-            hotpath_profiling_mode: hotpath::ProfilingMode::Timing,
-            total_elapsed: 150000000,
-            caller_name: "test::main".to_string(),
-            percentiles: vec![95],
-            description: "Time metrics".to_string(),
-            data: MetricsDataJson(pr_data),
-        };
+        let pr_metrics = MetricsJson::new(
+            hotpath::ProfilingMode::Timing,
+            150000000,
+            "Time metrics".to_string(),
+            "test::main".to_string(),
+            vec![95.0],
+            MetricsDataJson(pr_data),
+        );
 
         let mut main_data = HashMap::new();
         main_data.insert(
@@ -622,14 +566,14 @@ mod test {
             ],
         );
 
-        let main_metrics = MetricsJson {
-            hotpath_profiling_mode: hotpath::ProfilingMode::Timing,
-            total_elapsed: 120000000,
-            caller_name: "test::main".to_string(),
-            percentiles: vec![95],
-            description: "Time metrics".to_string(),
-            data: MetricsDataJson(main_data),
-        };
+        let main_metrics = MetricsJson::new(
+            hotpath::ProfilingMode::Timing,
+            120000000,
+            "Time metrics".to_string(),
+            "test::main".to_string(),
+            vec![95.0],
+            MetricsDataJson(main_data),
+        );
 
         let comparison = compare_metrics(&main_metrics, &pr_metrics);
 
@@ -646,7 +590,13 @@ mod test {
             }
         }
 
-        let markdown = format_comparison_markdown(&comparison, &main_metrics, Some(20));
+        let markdown = format_comparison_markdown(
+            &comparison,
+            &main_metrics,
+            &EmojiThresholds::uniform(Some(20)),
+            ByteFormat::Human,
+            false,
+        );
         println!("\n=== Generated Markdown ===\n{}", markdown);
 
         assert!(comparison
@@ -683,14 +633,14 @@ mod test {
             ],
         );
 
-        let pr_metrics = MetricsJson {
-            hotpath_profiling_mode: hotpath::ProfilingMode::Timing,
-            total_elapsed: 140000000,
-            caller_name: "test::main".to_string(),
-            percentiles: vec![95],
-            description: "Time metrics".to_string(),
-            data: MetricsDataJson(pr_data),
-        };
+        let pr_metrics = MetricsJson::new(
+            hotpath::ProfilingMode::Timing,
+            140000000,
+            "Time metrics".to_string(),
+            "test::main".to_string(),
+            vec![95.0],
+            MetricsDataJson(pr_data),
+        );
 
         // Base has function_a (updated) and function_b (removed)
         let mut main_data = HashMap::new();
@@ -715,14 +665,14 @@ mod test {
             ],
         );
 
-        let main_metrics = MetricsJson {
-            hotpath_profiling_mode: hotpath::ProfilingMode::Timing,
-            total_elapsed: 120000000,
-            caller_name: "test::main".to_string(),
-            percentiles: vec![95],
-            description: "Time metrics".to_string(),
-            data: MetricsDataJson(main_data),
-        };
+        let main_metrics = MetricsJson::new(
+            hotpath::ProfilingMode::Timing,
+            120000000,
+            "Time metrics".to_string(),
+            "test::main".to_string(),
+            vec![95.0],
+            MetricsDataJson(main_data),
+        );
 
         let comparison = compare_metrics(&main_metrics, &pr_metrics);
 
@@ -737,7 +687,13 @@ mod test {
         }
 
         // Test markdown formatting
-        let markdown = format_comparison_markdown(&comparison, &main_metrics, Some(20));
+        let markdown = format_comparison_markdown(
+            &comparison,
+            &main_metrics,
+            &EmojiThresholds::uniform(Some(20)),
+            ByteFormat::Human,
+            false,
+        );
         println!("\n=== Generated Markdown ===\n{}", markdown);
 
         // Verify we have both new and removed functions
@@ -755,4 +711,249 @@ mod test {
             .iter()
             .any(|f| f.function_name == "test::function_a" && !f.is_new && !f.is_removed));
     }
+    #[test]
+    fn test_equal_percentages_sorted_by_name() {
+        use hotpath::MetricType::{CallsCount, DurationNs, Percentage};
+        use std::collections::HashMap;
+
+        let mut data = HashMap::new();
+        for name in ["test::function_c", "test::function_a", "test::function_b"] {
+            data.insert(
+                name.to_string(),
+                vec![
+                    CallsCount(10),
+                    DurationNs(1000),
+                    DurationNs(1100),
+                    DurationNs(10000),
+                    Percentage(3333),
+                ],
+            );
+        }
+
+        let metrics = MetricsJson::new(
+            hotpath::ProfilingMode::Timing,
+            30000,
+            "Time metrics".to_string(),
+            "test::main".to_string(),
+            vec![95.0],
+            MetricsDataJson(data),
+        );
+
+        let comparison = compare_metrics(&metrics, &metrics);
+        let names: Vec<&str> = comparison
+            .function_diffs
+            .iter()
+            .map(|f| f.function_name.as_str())
+            .collect();
+
+        assert_eq!(
+            names,
+            vec!["test::function_a", "test::function_b", "test::function_c"]
+        );
+    }
+
+    #[test]
+    fn test_schema_version_warning() {
+        let base: MetricsJson = serde_json::from_str(
+            r#"{
+                "hotpath_profiling_mode": "timing",
+                "total_elapsed": 1000,
+                "caller_name": "test::main",
+                "description": "Time metrics",
+                "output": {}
+            }"#,
+        )
+        .unwrap();
+        let mut head = base.clone();
+
+        assert_eq!(base.schema_version, 0);
+        assert!(schema_version_warning(&base, &head).is_none());
+
+        head.schema_version = MetricsJson::SCHEMA_VERSION;
+        let warning = schema_version_warning(&base, &head).unwrap();
+        assert!(warning.contains("base: 0, head: 2"));
+    }
+
+    #[test]
+    fn test_format_commits_markdown() {
+        let base: MetricsJson = serde_json::from_str(
+            r#"{
+                "hotpath_profiling_mode": "timing",
+                "total_elapsed": 1000,
+                "caller_name": "test::main",
+                "description": "Time metrics",
+                "output": {},
+                "commit": "4f2a91c3d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0"
+            }"#,
+        )
+        .unwrap();
+        let mut head = base.clone();
+
+        head.commit = Some("9b0e1d7".to_string());
+        assert_eq!(
+            format_commits_markdown(&base, &head).unwrap(),
+            "**Commits:** `4f2a91c` → `9b0e1d7`\n\n"
+        );
+
+        head.commit = None;
+        assert_eq!(
+            format_commits_markdown(&base, &head).unwrap(),
+            "**Commits:** `4f2a91c` → -\n\n"
+        );
+
+        let mut base = base;
+        base.commit = None;
+        assert!(format_commits_markdown(&base, &head).is_none());
+    }
+
+    #[test]
+    fn test_format_comparison_markdown_collapse_unchanged() {
+        use hotpath::MetricDiff;
+
+        let function_diff = |name: &str, before: u64, after: u64| FunctionMetricsDiff {
+            function_name: name.to_string(),
+            metrics: vec![
+                MetricDiff::CallsCount(10, 10),
+                MetricDiff::DurationNs(before, after),
+                MetricDiff::DurationNs(before, after),
+                MetricDiff::DurationNs(before * 10, after * 10),
+                MetricDiff::Percentage(5000, 5000),
+            ],
+            is_removed: false,
+            is_new: false,
+        };
+        let comparison = MetricsComparison {
+            total_elapsed_diff: MetricDiff::DurationNs(1000, 1100),
+            function_diffs: vec![
+                function_diff("app::slower", 100, 200),
+                function_diff("app::steady", 100, 105),
+                function_diff("app::also_steady", 100, 95),
+            ],
+        };
+        let metrics = MetricsJson::new(
+            hotpath::ProfilingMode::Timing,
+            1100,
+            "Time metrics".to_string(),
+            "app::main".to_string(),
+            vec![95.0],
+            MetricsDataJson(Default::default()),
+        );
+        let thresholds = EmojiThresholds::uniform(Some(20));
+
+        let markdown =
+            format_comparison_markdown(&comparison, &metrics, &thresholds, ByteFormat::Human, true);
+        let (changed, unchanged) = markdown.split_once("<details>").unwrap();
+        assert!(changed.contains("app::slower"), "{markdown}");
+        assert!(!changed.contains("app::steady"), "{markdown}");
+        assert!(
+            unchanged.contains("<summary>2 unchanged functions</summary>"),
+            "{markdown}"
+        );
+        assert!(unchanged.contains("app::steady"), "{markdown}");
+        assert!(unchanged.contains("app::also_steady"), "{markdown}");
+
+        let markdown = format_comparison_markdown(
+            &comparison,
+            &metrics,
+            &thresholds,
+            ByteFormat::Human,
+            false,
+        );
+        assert!(!markdown.contains("<details>"), "{markdown}");
+        assert!(markdown.contains("app::steady"), "{markdown}");
+    }
+
+    #[test]
+    fn test_emoji_thresholds() {
+        assert_eq!(
+            emoji_thresholds(None, None, None),
+            EmojiThresholds::uniform(Some(20))
+        );
+        assert_eq!(
+            emoji_thresholds(Some(0), None, None),
+            EmojiThresholds::uniform(None)
+        );
+        assert_eq!(
+            emoji_thresholds(Some(10), Some(30), Some(0)),
+            EmojiThresholds {
+                time: Some(30),
+                alloc: None,
+                other: Some(10),
+            }
+        );
+
+        let thresholds = emoji_thresholds(None, Some(10), Some(50));
+        let slower = hotpath::MetricDiff::DurationNs(100, 130);
+        let more_allocated = hotpath::MetricDiff::AllocBytes(100, 130);
+        assert!(slower.format_with_thresholds(&thresholds).contains("⚠️"));
+        assert!(!more_allocated
+            .format_with_thresholds(&thresholds)
+            .contains("⚠️"));
+    }
+
+    #[test]
+    fn test_format_metadata_markdown() {
+        let base: MetricsJson = serde_json::from_str(
+            r#"{
+                "hotpath_profiling_mode": "timing",
+                "total_elapsed": 1000,
+                "caller_name": "test::main",
+                "description": "Time metrics",
+                "output": {},
+                "metadata": {"git_sha": "4f2a91c", "hostname": "ci-runner-3"}
+            }"#,
+        )
+        .unwrap();
+        let mut head = base.clone();
+        head.metadata
+            .insert("git_sha".to_string(), "9b0e1d7".to_string());
+        head.metadata.remove("hostname");
+
+        let keys = ["git_sha", "hostname", "cpu_model"].map(String::from);
+        let markdown = format_metadata_markdown(&keys, &base, &head).unwrap();
+        assert!(markdown.contains("| git_sha | `4f2a91c` | `9b0e1d7` |\n"));
+        assert!(markdown.contains("| hostname | `ci-runner-3` | - |\n"));
+        assert!(!markdown.contains("cpu_model"));
+
+        assert!(format_metadata_markdown(&[], &base, &head).is_none());
+    }
+
+    #[test]
+    fn test_mixed_percent_formats() {
+        let report = |percent_total: &str| {
+            format!(
+                r#"{{
+                    "schema_version": 1,
+                    "hotpath_profiling_mode": "timing",
+                    "total_elapsed": 1000,
+                    "caller_name": "test::main",
+                    "description": "Time metrics",
+                    "output": {{
+                        "test::function_a": {{
+                            "calls": 10,
+                            "avg": 90,
+                            "p95": 95,
+                            "total": 900,
+                            "percent_total": {percent_total}
+                        }}
+                    }}
+                }}"#
+            )
+        };
+
+        let base: MetricsJson = serde_json::from_str(&report("9000")).unwrap();
+        let head: MetricsJson = serde_json::from_str(&report("90.0")).unwrap();
+
+        let comparison = compare_metrics(&base, &head);
+        let markdown = format_comparison_markdown(
+            &comparison,
+            &head,
+            &EmojiThresholds::uniform(Some(20)),
+            ByteFormat::Human,
+            false,
+        );
+
+        assert!(markdown.contains("90.00%"), "Got:\n{markdown}");
+        assert!(!markdown.contains("0.90%"), "Got:\n{markdown}");
+    }
 }
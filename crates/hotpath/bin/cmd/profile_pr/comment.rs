@@ -2,6 +2,15 @@ use eyre::Result;
 use hotpath::ProfilingMode;
 use serde::Deserialize;
 use serde_json::json;
+use std::cell::Cell;
+use std::time::Duration;
+use ureq::http::Response;
+use ureq::Body;
+
+/// Attempts per comment request, including the first one.
+const MAX_ATTEMPTS: u32 = 4;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Deserialize)]
 struct GitHubUser {
@@ -58,7 +67,65 @@ fn find_existing_comment(
     }
 }
 
-fn create_comment(repo: &str, pr_number: &str, token: &str, body: &str) -> Result<()> {
+/// How long to wait before retrying a request that got `status` on attempt `attempt` (1-based),
+/// or `None` if it shouldn't be retried. Rate limits (429) and server errors (5xx) are
+/// retried, honoring the `Retry-After` seconds when present, otherwise backing off
+/// exponentially.
+fn retry_delay(status: u16, retry_after: Option<&str>, attempt: u32) -> Option<Duration> {
+    if attempt >= MAX_ATTEMPTS || !(status == 429 || (500..600).contains(&status)) {
+        return None;
+    }
+
+    let delay = retry_after
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_else(|| INITIAL_BACKOFF * 2u32.pow(attempt - 1));
+    Some(delay.min(MAX_BACKOFF))
+}
+
+/// Sends the request built by `send` until it gets a non-retryable response or runs out of
+/// attempts. `send` must not treat HTTP error statuses as errors, so they can be inspected.
+///
+/// A server error doesn't tell whether the request was applied, so it's only retried if
+/// `retry_server_error` returns true, which requests that aren't idempotent must check.
+fn send_with_retry(
+    send: impl Fn() -> std::result::Result<Response<Body>, ureq::Error>,
+    retry_server_error: impl Fn() -> bool,
+) -> Result<Response<Body>> {
+    let mut attempt = 1;
+    loop {
+        let response = send()?;
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get("Retry-After")
+            .and_then(|value| value.to_str().ok());
+
+        match retry_delay(status.as_u16(), retry_after, attempt) {
+            Some(_) if status.is_server_error() && !retry_server_error() => return Ok(response),
+            Some(delay) => {
+                println!(
+                    "GitHub API responded with {}, retrying in {}s (attempt {}/{})",
+                    status,
+                    delay.as_secs(),
+                    attempt + 1,
+                    MAX_ATTEMPTS
+                );
+                std::thread::sleep(delay);
+                attempt += 1;
+            }
+            None => return Ok(response),
+        }
+    }
+}
+
+fn create_comment(
+    repo: &str,
+    pr_number: &str,
+    token: &str,
+    body: &str,
+    profiling_mode: &ProfilingMode,
+) -> Result<()> {
     let url = format!(
         "https://api.github.com/repos/{}/issues/{}/comments",
         repo, pr_number
@@ -68,16 +135,36 @@ fn create_comment(repo: &str, pr_number: &str, token: &str, body: &str) -> Resul
         "body": body,
     });
 
-    let response = ureq::post(&url)
-        .header("Authorization", &format!("token {}", token))
-        .header("Accept", "application/vnd.github.v3+json")
-        .header("User-Agent", "hotpath-ci-action")
-        .send_json(&comment_body)?;
+    // The comment may have been created despite a server error, retrying then would post it twice
+    let created = Cell::new(false);
+    let response = send_with_retry(
+        || {
+            ureq::post(&url)
+                .config()
+                .http_status_as_error(false)
+                .build()
+                .header("Authorization", &format!("token {}", token))
+                .header("Accept", "application/vnd.github.v3+json")
+                .header("User-Agent", "hotpath-ci-action")
+                .send_json(&comment_body)
+        },
+        || {
+            let existing = find_existing_comment(repo, pr_number, token, profiling_mode);
+            created.set(matches!(existing, Ok(Some(_))));
+            matches!(existing, Ok(None))
+        },
+    )?;
 
     let status = response.status();
     if status.is_success() {
         println!("Successfully created new comment");
         Ok(())
+    } else if created.get() {
+        println!(
+            "GitHub API responded with {}, but the comment was created",
+            status
+        );
+        Ok(())
     } else {
         let error_text = response.into_body().read_to_string()?;
         println!("Failed to create comment: {}", status);
@@ -102,11 +189,20 @@ fn update_comment(repo: &str, comment_id: u64, token: &str, body: &str) -> Resul
         "body": body,
     });
 
-    let response = ureq::patch(&url)
-        .header("Authorization", &format!("token {}", token))
-        .header("Accept", "application/vnd.github.v3+json")
-        .header("User-Agent", "hotpath-ci-action")
-        .send_json(&comment_body)?;
+    let response = send_with_retry(
+        || {
+            ureq::patch(&url)
+                .config()
+                .http_status_as_error(false)
+                .build()
+                .header("Authorization", &format!("token {}", token))
+                .header("Accept", "application/vnd.github.v3+json")
+                .header("User-Agent", "hotpath-ci-action")
+                .send_json(&comment_body)
+        },
+        // Updating the comment again is harmless
+        || true,
+    )?;
 
     let status = response.status();
     if status.is_success() {
@@ -137,12 +233,36 @@ pub fn upsert_pr_comment(
         }
         Ok(None) => {
             println!("No existing comment found, creating new comment");
-            create_comment(repo, pr_number, token, body)
+            create_comment(repo, pr_number, token, body, profiling_mode)
         }
         Err(e) => {
             println!("Error searching for existing comment: {}", e);
             println!("Falling back to creating new comment");
-            create_comment(repo, pr_number, token, body)
+            create_comment(repo, pr_number, token, body, profiling_mode)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(502, None, 1), Some(Duration::from_secs(1)));
+        assert_eq!(retry_delay(503, None, 2), Some(Duration::from_secs(2)));
+        assert_eq!(retry_delay(500, None, 3), Some(Duration::from_secs(4)));
+        assert_eq!(retry_delay(500, None, MAX_ATTEMPTS), None);
+
+        assert_eq!(retry_delay(429, Some("7"), 1), Some(Duration::from_secs(7)));
+        assert_eq!(retry_delay(429, Some("3600"), 1), Some(MAX_BACKOFF));
+        assert_eq!(
+            retry_delay(429, Some("Wed, 21 Oct 2015 07:28:00 GMT"), 1),
+            Some(Duration::from_secs(1))
+        );
+
+        assert_eq!(retry_delay(403, None, 1), None);
+        assert_eq!(retry_delay(404, Some("5"), 1), None);
+        assert_eq!(retry_delay(201, None, 1), None);
+    }
+}
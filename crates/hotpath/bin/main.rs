@@ -1,5 +1,6 @@
 mod cmd;
 use clap::{Parser, Subcommand};
+use cmd::aggregate::AggregateArgs;
 #[cfg(feature = "tui")]
 use cmd::console::ConsoleArgs;
 use cmd::profile_pr::ProfilePrArgs;
@@ -9,6 +10,8 @@ use eyre::Result;
 pub enum HPSubcommand {
     #[command(about = "Profile a PR, compare with main branch, and post a GitHub comment")]
     ProfilePr(ProfilePrArgs),
+    #[command(about = "Merge JSON reports of multiple processes into one report")]
+    Aggregate(AggregateArgs),
     #[cfg(feature = "tui")]
     #[command(about = "Launch TUI console to monitor profiling metrics in real-time")]
     Console(ConsoleArgs),
@@ -34,6 +37,9 @@ fn main() -> Result<()> {
         HPSubcommand::ProfilePr(args) => {
             args.run()?;
         }
+        HPSubcommand::Aggregate(args) => {
+            args.run()?;
+        }
         #[cfg(feature = "tui")]
         HPSubcommand::Console(args) => {
             args.run()?;
@@ -0,0 +1,138 @@
+use hdrhistogram::Histogram;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::output::format_duration;
+
+/// Timing statistics of the iterations run by [`bench`].
+#[derive(Debug, Clone)]
+pub struct BenchStats {
+    pub label: &'static str,
+    pub iterations: u64,
+    /// Sum of all iteration durations
+    pub total: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    histogram: Histogram<u64>,
+}
+
+impl BenchStats {
+    /// Mean duration of a single iteration, zero if no iterations were run.
+    pub fn avg(&self) -> Duration {
+        if self.iterations == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos((self.total.as_nanos() / self.iterations as u128) as u64)
+    }
+
+    /// Duration below which `p` percent of iterations finished, e.g. `percentile(95.0)`.
+    pub fn percentile(&self, p: f64) -> Duration {
+        if self.iterations == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos(self.histogram.value_at_percentile(p.clamp(0.0, 100.0)))
+    }
+}
+
+impl fmt::Display for BenchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ns = |d: Duration| format_duration(d.as_nanos() as u64);
+        write!(
+            f,
+            "{}: {} iterations, avg {}, p50 {}, p95 {}, p99 {}, min {}, max {}",
+            self.label,
+            self.iterations,
+            ns(self.avg()),
+            ns(self.percentile(50.0)),
+            ns(self.percentile(95.0)),
+            ns(self.percentile(99.0)),
+            ns(self.min),
+            ns(self.max),
+        )
+    }
+}
+
+/// Runs `f` `iters` times and returns timing statistics of the iterations.
+///
+/// A lightweight alternative to a benchmark harness for getting quick numbers on a snippet.
+/// With the `hotpath` feature enabled, every iteration is also measured like a
+/// [`measure_block!`](crate::measure_block) with the same label, so it shows up in the report of
+/// the active guard with the usual percentiles, or with allocation numbers in allocation profiling
+/// modes. The returned statistics are always wall-clock timings, and are collected even without
+/// an active guard.
+///
+/// Return values of `f` are passed through [`std::hint::black_box`], so the computation is not
+/// optimized away.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// let stats = hotpath::bench("sort", 1_000, || {
+///     let mut items: Vec<u32> = (0..1_000).rev().collect();
+///     items.sort();
+///     items
+/// });
+/// println!("{stats}");
+/// assert_eq!(stats.iterations, 1_000);
+/// ```
+#[track_caller]
+pub fn bench<R>(label: &'static str, iters: u64, mut f: impl FnMut() -> R) -> BenchStats {
+    let location = std::panic::Location::caller();
+    let name = crate::located_name(label, location.file(), location.line());
+
+    let mut histogram = Histogram::<u64>::new(3).expect("bench histogram init");
+    let mut total = Duration::ZERO;
+    let mut min = Duration::MAX;
+    let mut max = Duration::ZERO;
+
+    for _ in 0..iters {
+        let elapsed = {
+            let _guard = cfg!(feature = "hotpath")
+                .then(|| crate::MeasurementGuard::build(name, false, false));
+            let start = Instant::now();
+            std::hint::black_box(f());
+            start.elapsed()
+        };
+
+        histogram
+            .record(elapsed.as_nanos() as u64)
+            .expect("bench histogram resizes on record");
+        total += elapsed;
+        min = min.min(elapsed);
+        max = max.max(elapsed);
+    }
+
+    BenchStats {
+        label,
+        iterations: iters,
+        total,
+        min: if iters == 0 { Duration::ZERO } else { min },
+        max,
+        histogram,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_stats() {
+        let mut calls = 0;
+        let stats = bench("test_bench", 100, || calls += 1);
+
+        assert_eq!(calls, 100);
+        assert_eq!(stats.iterations, 100);
+        assert!(stats.min <= stats.avg() && stats.avg() <= stats.max);
+        assert!(stats.min <= stats.percentile(50.0));
+        assert!(stats.percentile(50.0) <= stats.percentile(99.0));
+        assert!(stats
+            .to_string()
+            .starts_with("test_bench: 100 iterations, avg "));
+
+        let empty = bench("empty_bench", 0, || ());
+        assert_eq!(empty.min, Duration::ZERO);
+        assert_eq!(empty.avg(), Duration::ZERO);
+        assert_eq!(empty.percentile(95.0), Duration::ZERO);
+    }
+}
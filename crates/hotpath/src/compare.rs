@@ -0,0 +1,417 @@
+use crate::output::{
+    header_row, shorten_function_name, terminal_colors, write_no_measurements_message, write_plain,
+    write_styled_table, write_title, MetricsJson, MetricsProvider, ReportError, Reporter,
+};
+use crate::{format_bytes_with, ByteFormat, MetricType};
+use colored::*;
+use prettytable::{Cell, Row, Table};
+use std::fmt;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Change of a single metric between two profiling runs.
+#[derive(Debug, Clone)]
+pub enum MetricDiff {
+    CallsCount(u64, u64), // (before, after)
+    DurationNs(u64, u64), // (before, after) - Duration in nanoseconds
+    AllocBytes(u64, u64), // (before, after) - Bytes allocated
+    AllocCount(u64, u64), // (before, after) - Allocation count
+    Percentage(u64, u64), // (before, after)
+}
+
+/// Percent changes above which [`MetricDiff::format_with_thresholds`] marks a diff with an
+/// emoji, per metric kind. `None` disables the emoji for that kind.
+///
+/// `time` applies to durations, `alloc` to allocated bytes and counts, and `other` to call
+/// counts and `% Total`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmojiThresholds {
+    pub time: Option<u32>,
+    pub alloc: Option<u32>,
+    pub other: Option<u32>,
+}
+
+impl EmojiThresholds {
+    /// The same threshold for every metric kind.
+    pub fn uniform(threshold: Option<u32>) -> Self {
+        Self {
+            time: threshold,
+            alloc: threshold,
+            other: threshold,
+        }
+    }
+
+    fn for_diff(&self, diff: &MetricDiff) -> Option<u32> {
+        match diff {
+            MetricDiff::DurationNs(..) => self.time,
+            MetricDiff::AllocBytes(..) | MetricDiff::AllocCount(..) => self.alloc,
+            MetricDiff::CallsCount(..) | MetricDiff::Percentage(..) => self.other,
+        }
+    }
+}
+
+impl fmt::Display for MetricDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format_with_emoji(None))
+    }
+}
+
+impl MetricDiff {
+    /// Formats the change as `before → after (+x.x%)`, with an emoji marker appended when
+    /// the change exceeds `emoji_threshold` percent (regression ⚠️, improvement 🚀).
+    pub fn format_with_emoji(&self, emoji_threshold: Option<u32>) -> String {
+        self.format_with_thresholds(&EmojiThresholds::uniform(emoji_threshold))
+    }
+
+    /// Whether the change exceeds the threshold of its metric kind in either direction,
+    /// i.e. whether [`MetricDiff::format_with_thresholds`] marks it with an emoji.
+    pub fn is_significant(&self, thresholds: &EmojiThresholds) -> bool {
+        let (MetricDiff::CallsCount(before, after)
+        | MetricDiff::DurationNs(before, after)
+        | MetricDiff::AllocBytes(before, after)
+        | MetricDiff::AllocCount(before, after)
+        | MetricDiff::Percentage(before, after)) = self;
+        thresholds.for_diff(self).is_some_and(|threshold| {
+            calculate_percentage_diff(*before, *after).abs() > threshold as f64
+        })
+    }
+
+    /// Like [`MetricDiff::format_with_emoji`], with a separate threshold per metric kind.
+    pub fn format_with_thresholds(&self, thresholds: &EmojiThresholds) -> String {
+        self.format(thresholds, ByteFormat::Human)
+    }
+
+    /// Like [`MetricDiff::format_with_thresholds`], with byte values rounded as `byte_format`.
+    pub fn format(&self, thresholds: &EmojiThresholds, byte_format: ByteFormat) -> String {
+        match self {
+            MetricDiff::CallsCount(before, after) => {
+                let diff_percent = calculate_percentage_diff(*before, *after);
+                let emoji = get_emoji_for_diff(self, diff_percent, thresholds);
+                format!("{} → {} ({:+.1}%){}", before, after, diff_percent, emoji)
+            }
+            MetricDiff::DurationNs(before, after) => {
+                let diff_percent = calculate_percentage_diff(*before, *after);
+                let before_duration = Duration::from_nanos(*before);
+                let after_duration = Duration::from_nanos(*after);
+                let emoji = get_emoji_for_diff(self, diff_percent, thresholds);
+                format!(
+                    "{:.2?} → {:.2?} ({:+.1}%){}",
+                    before_duration, after_duration, diff_percent, emoji
+                )
+            }
+            MetricDiff::AllocBytes(before, after) => {
+                let diff_percent = calculate_percentage_diff(*before, *after);
+                let emoji = get_emoji_for_diff(self, diff_percent, thresholds);
+                format!(
+                    "{} → {} ({:+.1}%){}",
+                    format_bytes_with(*before, byte_format),
+                    format_bytes_with(*after, byte_format),
+                    diff_percent,
+                    emoji
+                )
+            }
+            MetricDiff::AllocCount(before, after) => {
+                let diff_percent = calculate_percentage_diff(*before, *after);
+                let emoji = get_emoji_for_diff(self, diff_percent, thresholds);
+                format!("{} → {} ({:+.1}%){}", before, after, diff_percent, emoji)
+            }
+            MetricDiff::Percentage(before, after) => {
+                let diff_percent = calculate_percentage_diff(*before, *after);
+                let before_percent = *before as f64 / 100.0;
+                let after_percent = *after as f64 / 100.0;
+                let emoji = get_emoji_for_diff(self, diff_percent, thresholds);
+                format!(
+                    "{:.2}% → {:.2}% ({:+.1}%){}",
+                    before_percent, after_percent, diff_percent, emoji
+                )
+            }
+        }
+    }
+}
+
+fn get_emoji_for_diff(
+    diff: &MetricDiff,
+    diff_percent: f64,
+    thresholds: &EmojiThresholds,
+) -> &'static str {
+    if let Some(threshold_val) = thresholds.for_diff(diff) {
+        let threshold = threshold_val as f64;
+        if diff_percent > threshold {
+            " ⚠️ "
+        } else if diff_percent < -threshold {
+            " 🚀 "
+        } else {
+            "   "
+        }
+    } else {
+        ""
+    }
+}
+
+/// Result of [`compare_metrics`].
+#[derive(Debug, Clone)]
+pub struct MetricsComparison {
+    pub total_elapsed_diff: MetricDiff,
+    pub function_diffs: Vec<FunctionMetricsDiff>,
+}
+
+/// Metric changes of a single function.
+#[derive(Debug, Clone)]
+pub struct FunctionMetricsDiff {
+    pub function_name: String,
+    pub metrics: Vec<MetricDiff>,
+    pub is_removed: bool, // True if function was removed (no longer measured)
+    pub is_new: bool,     // True if function is new (not in base)
+}
+
+fn calculate_percentage_diff(before: u64, after: u64) -> f64 {
+    if before == 0 {
+        if after == 0 {
+            0.0
+        } else {
+            100.0 // 100% increase from 0
+        }
+    } else {
+        ((after as f64 - before as f64) / before as f64) * 100.0
+    }
+}
+
+/// Compares two metrics snapshots function by function.
+///
+/// Functions missing from `before_metrics` are marked as new (compared against 0),
+/// functions missing from `after_metrics` as removed. Results are sorted by
+/// `% Total` of the `after_metrics` snapshot, descending.
+pub fn compare_metrics(
+    before_metrics: &MetricsJson,
+    after_metrics: &MetricsJson,
+) -> MetricsComparison {
+    let total_elapsed_diff =
+        MetricDiff::DurationNs(before_metrics.total_elapsed, after_metrics.total_elapsed);
+
+    let mut function_diffs = Vec::new();
+    let mut new_functions = Vec::new();
+
+    // Process functions that exist in after_metrics (updated, unchanged, or new)
+    for (function_name, after_row) in &after_metrics.data.0 {
+        if let Some(before_row) = before_metrics.data.0.get(function_name) {
+            // Function exists in both before and after - compare metrics
+            let mut metrics = Vec::new();
+
+            for (metric_idx, after_metric) in after_row.iter().enumerate() {
+                if let Some(before_metric) = before_row.get(metric_idx) {
+                    let diff = match (before_metric, after_metric) {
+                        (MetricType::CallsCount(before_val), MetricType::CallsCount(after_val)) => {
+                            MetricDiff::CallsCount(*before_val, *after_val)
+                        }
+                        (MetricType::DurationNs(before_val), MetricType::DurationNs(after_val)) => {
+                            MetricDiff::DurationNs(*before_val, *after_val)
+                        }
+                        (MetricType::AllocBytes(before_val), MetricType::AllocBytes(after_val)) => {
+                            MetricDiff::AllocBytes(*before_val, *after_val)
+                        }
+                        (MetricType::AllocCount(before_val), MetricType::AllocCount(after_val)) => {
+                            MetricDiff::AllocCount(*before_val, *after_val)
+                        }
+                        (MetricType::Percentage(before_val), MetricType::Percentage(after_val)) => {
+                            MetricDiff::Percentage(*before_val, *after_val)
+                        }
+                        _ => continue, // Skip mismatched metric types
+                    };
+                    metrics.push(diff);
+                }
+            }
+
+            function_diffs.push(FunctionMetricsDiff {
+                function_name: function_name.clone(),
+                metrics,
+                is_removed: false,
+                is_new: false,
+            });
+        } else {
+            // Function is new (exists in after but not in before) - show 0 → after
+            let mut metrics = Vec::new();
+
+            for after_metric in after_row.iter() {
+                let diff = match after_metric {
+                    MetricType::CallsCount(after_val) => MetricDiff::CallsCount(0, *after_val),
+                    MetricType::DurationNs(after_val) => MetricDiff::DurationNs(0, *after_val),
+                    MetricType::AllocBytes(after_val) => MetricDiff::AllocBytes(0, *after_val),
+                    MetricType::AllocCount(after_val) => MetricDiff::AllocCount(0, *after_val),
+                    MetricType::Percentage(after_val) => MetricDiff::Percentage(0, *after_val),
+                    MetricType::Unsupported | MetricType::NotTracked => continue,
+                };
+                metrics.push(diff);
+            }
+
+            new_functions.push(FunctionMetricsDiff {
+                function_name: function_name.clone(),
+                metrics,
+                is_removed: false,
+                is_new: true,
+            });
+        }
+    }
+
+    // Process functions that were removed (exist in before but not in after)
+    for (function_name, before_row) in &before_metrics.data.0 {
+        // Check if this function exists in after_metrics
+        if !after_metrics.data.0.contains_key(function_name) {
+            // Function was removed, show before → 0
+            let mut metrics = Vec::new();
+
+            for before_metric in before_row.iter() {
+                let diff = match before_metric {
+                    MetricType::CallsCount(before_val) => MetricDiff::CallsCount(*before_val, 0),
+                    MetricType::DurationNs(before_val) => MetricDiff::DurationNs(*before_val, 0),
+                    MetricType::AllocBytes(before_val) => MetricDiff::AllocBytes(*before_val, 0),
+                    MetricType::AllocCount(before_val) => MetricDiff::AllocCount(*before_val, 0),
+                    MetricType::Percentage(before_val) => MetricDiff::Percentage(*before_val, 0),
+                    MetricType::Unsupported | MetricType::NotTracked => continue,
+                };
+                metrics.push(diff);
+            }
+
+            function_diffs.push(FunctionMetricsDiff {
+                function_name: function_name.clone(),
+                metrics,
+                is_removed: true,
+                is_new: false,
+            });
+        }
+    }
+
+    function_diffs.extend(new_functions);
+
+    // Sort by percent_total in head branch (after value), descending order,
+    // ties by function name
+    function_diffs.sort_by(|a, b| {
+        let a_percent = a
+            .metrics
+            .iter()
+            .find_map(|m| {
+                if let MetricDiff::Percentage(_, after) = m {
+                    Some(*after)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(0);
+
+        let b_percent = b
+            .metrics
+            .iter()
+            .find_map(|m| {
+                if let MetricDiff::Percentage(_, after) = m {
+                    Some(*after)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(0);
+
+        b_percent
+            .cmp(&a_percent)
+            .then_with(|| a.function_name.cmp(&b.function_name))
+    });
+
+    MetricsComparison {
+        total_elapsed_diff,
+        function_diffs,
+    }
+}
+
+/// Table reporter showing `before → after` values against a baseline loaded with
+/// [`GuardBuilder::baseline`](crate::GuardBuilder::baseline).
+pub(crate) struct BaselineReporter {
+    pub(crate) baseline: MetricsJson,
+    pub(crate) baseline_name: String,
+    pub(crate) byte_format: ByteFormat,
+    pub(crate) to_stderr: bool,
+    pub(crate) plain: bool,
+}
+
+impl Reporter for BaselineReporter {
+    fn write_report(
+        &self,
+        metrics_provider: &dyn MetricsProvider<'_>,
+        out: &mut dyn Write,
+    ) -> Result<(), ReportError> {
+        if metrics_provider.metric_data().is_empty() {
+            write_no_measurements_message(
+                out,
+                Duration::from_nanos(metrics_provider.total_elapsed()),
+                metrics_provider.caller_name(),
+            )?;
+            return Ok(());
+        }
+
+        let current = MetricsJson::from(metrics_provider);
+        let comparison = compare_metrics(&self.baseline, &current);
+        if self.plain {
+            write_plain(out, |out| {
+                write_comparison_table(
+                    out,
+                    metrics_provider,
+                    &comparison,
+                    &self.baseline_name,
+                    self.byte_format,
+                    false,
+                )?;
+                Ok(())
+            })
+        } else {
+            write_comparison_table(
+                out,
+                metrics_provider,
+                &comparison,
+                &self.baseline_name,
+                self.byte_format,
+                terminal_colors(self.to_stderr),
+            )?;
+            Ok(())
+        }
+    }
+}
+
+fn write_comparison_table(
+    out: &mut dyn Write,
+    metrics_provider: &dyn MetricsProvider<'_>,
+    comparison: &MetricsComparison,
+    baseline_name: &str,
+    byte_format: ByteFormat,
+    use_colors: bool,
+) -> io::Result<()> {
+    let mut table = Table::new();
+
+    table.add_row(header_row(metrics_provider.headers(), use_colors));
+
+    for func_diff in &comparison.function_diffs {
+        let short_name = shorten_function_name(&func_diff.function_name);
+        let function_display = if func_diff.is_removed {
+            format!("[removed] {}", short_name)
+        } else if func_diff.is_new {
+            format!("[new] {}", short_name)
+        } else {
+            short_name
+        };
+
+        let mut row_cells = vec![Cell::new(&function_display)];
+        for metric_diff in &func_diff.metrics {
+            row_cells.push(Cell::new(
+                &metric_diff.format(&EmojiThresholds::default(), byte_format),
+            ));
+        }
+        table.add_row(Row::new(row_cells));
+    }
+
+    write_title(out, metrics_provider)?;
+    writeln!(
+        out,
+        "{}: {} (baseline: {})",
+        metrics_provider.caller_name().yellow().bold(),
+        comparison.total_elapsed_diff,
+        baseline_name
+    )?;
+
+    write_styled_table(&table, out, use_colors)
+}
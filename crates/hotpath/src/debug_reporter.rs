@@ -0,0 +1,145 @@
+use crate::output::{get_sorted_entries, MetricType, MetricsProvider, ReportError, Reporter};
+
+// Allowed rounding slack for percentages, in basis points
+const PERCENT_EPSILON: u64 = 1;
+
+/// Built-in reporter checking the aggregated metrics for internal consistency.
+///
+/// Instead of a report it writes a single `hotpath invariants ok` line, and panics with a
+/// description of the offending function if any of the following doesn't hold:
+///
+/// * no function's `% Total` exceeds 100%
+/// * no function's total exceeds the total of the caller wrapper row, if it's displayed
+/// * the average per call doesn't exceed the total
+/// * percentile values don't decrease as the percentile grows
+///
+/// Timing totals include nested calls, so the sum of per-function totals can legitimately
+/// exceed the wrapper total. With [`flat`](Self::flat) enabled, the reporter additionally checks
+/// that totals sum up to at most the wrapper total and percentages to at most 100%, which only
+/// holds when measured functions neither nest nor run concurrently.
+///
+/// Meant for testing `hotpath` itself, requires the `hotpath-debug` feature.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let _hotpath = hotpath::GuardBuilder::new("main")
+///     .reporter(Box::new(hotpath::DebugReporter::new().flat(true)))
+///     .build();
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DebugReporter {
+    flat: bool,
+}
+
+impl DebugReporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also check sums of totals and percentages, see the type level docs.
+    pub fn flat(mut self, flat: bool) -> Self {
+        self.flat = flat;
+        self
+    }
+
+    fn check(&self, metrics_provider: &dyn MetricsProvider<'_>) -> Result<usize, String> {
+        let percentiles = metrics_provider.percentiles();
+        let total_index = percentiles.len() + 2;
+        let caller_name = metrics_provider.caller_name();
+        let entries = get_sorted_entries(metrics_provider);
+
+        let total = |metrics: &[MetricType]| metrics.get(total_index).and_then(MetricType::value);
+        let percent =
+            |metrics: &[MetricType]| metrics.get(total_index + 1).and_then(MetricType::value);
+
+        let wrapper_total = entries
+            .iter()
+            .find(|(name, _)| name == caller_name)
+            .and_then(|(_, metrics)| total(metrics));
+
+        let mut totals_sum = 0;
+        let mut percent_sum = 0;
+
+        for (name, metrics) in &entries {
+            if let Some(percent) = percent(metrics) {
+                if percent > 10_000 + PERCENT_EPSILON {
+                    return Err(format!(
+                        "{name}: % Total is {percent} basis points, above 100%"
+                    ));
+                }
+            }
+
+            if let (Some(total), Some(wrapper_total)) = (total(metrics), wrapper_total) {
+                if total > wrapper_total {
+                    return Err(format!(
+                        "{name}: total {total} exceeds the {caller_name} total {wrapper_total}"
+                    ));
+                }
+            }
+
+            if let (Some(avg), Some(total)) =
+                (metrics.get(1).and_then(MetricType::value), total(metrics))
+            {
+                if avg > total {
+                    return Err(format!("{name}: average {avg} exceeds the total {total}"));
+                }
+            }
+
+            let mut values: Vec<(f64, u64)> = percentiles
+                .iter()
+                .zip(metrics.iter().skip(2))
+                .filter_map(|(&p, metric)| metric.value().map(|v| (p, v)))
+                .collect();
+            values.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+            if let Some(pair) = values.windows(2).find(|pair| pair[0].1 > pair[1].1) {
+                return Err(format!(
+                    "{name}: P{} is {}, above P{} of {}",
+                    pair[0].0, pair[0].1, pair[1].0, pair[1].1
+                ));
+            }
+
+            if name != caller_name {
+                totals_sum += total(metrics).unwrap_or(0);
+                percent_sum += percent(metrics).unwrap_or(0);
+            }
+        }
+
+        if self.flat {
+            if let Some(wrapper_total) = wrapper_total {
+                if totals_sum > wrapper_total {
+                    return Err(format!(
+                        "sum of function totals {totals_sum} exceeds the {caller_name} total {wrapper_total}"
+                    ));
+                }
+            }
+
+            if percent_sum > 10_000 + PERCENT_EPSILON * entries.len() as u64 {
+                return Err(format!(
+                    "% Total of functions sums up to {percent_sum} basis points, above 100%"
+                ));
+            }
+        }
+
+        Ok(entries.len())
+    }
+}
+
+impl Reporter for DebugReporter {
+    fn write_report(
+        &self,
+        metrics_provider: &dyn MetricsProvider<'_>,
+        out: &mut dyn std::io::Write,
+    ) -> Result<(), ReportError> {
+        match self.check(metrics_provider) {
+            Ok(checked) => {
+                writeln!(out, "hotpath invariants ok: {checked} functions checked")?;
+                Ok(())
+            }
+            Err(violation) => panic!(
+                "hotpath invariant violated ({} mode): {violation}",
+                metrics_provider.profiling_mode()
+            ),
+        }
+    }
+}
@@ -13,13 +13,41 @@ mod lib_on;
 #[allow(dead_code)]
 pub(crate) mod output;
 pub use output::{
-    format_bytes, format_duration, shorten_function_name, MetricType, MetricsDataJson, MetricsJson,
-    MetricsProvider, ProfilingMode, Reporter, SamplesJson,
+    format_bytes, format_bytes_with, format_duration, format_elapsed, shorten_function_name,
+    ByteFormat, CallRatesJson, MetricRecord, MetricType, MetricsDataJson, MetricsJson,
+    MetricsProvider, PercentFormat, ProfilingMode, ReportError, Reporter, SamplesJson,
+};
+
+mod bench;
+pub use bench::{bench, BenchStats};
+
+mod report;
+pub use report::{FunctionSummary, HotPathReport};
+
+#[allow(dead_code)]
+pub(crate) mod compare;
+pub use compare::{
+    compare_metrics, EmojiThresholds, FunctionMetricsDiff, MetricDiff, MetricsComparison,
 };
 
 #[cfg(not(feature = "hotpath-off"))]
 pub(crate) mod http_server;
 
+#[cfg(feature = "hotpath-tracing")]
+mod tracing_reporter;
+#[cfg(feature = "hotpath-tracing")]
+pub use tracing_reporter::TracingReporter;
+
+#[cfg(feature = "hotpath-debug")]
+mod debug_reporter;
+#[cfg(feature = "hotpath-debug")]
+pub use debug_reporter::DebugReporter;
+
+#[cfg(feature = "hotpath-sqlite")]
+mod sqlite_reporter;
+#[cfg(feature = "hotpath-sqlite")]
+pub use sqlite_reporter::SqliteReporter;
+
 // When hotpath is disabled with hotpath-off feature we import methods from lib_off, which are all no-op
 #[cfg(feature = "hotpath-off")]
 #[doc(inline)]
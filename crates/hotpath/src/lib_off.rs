@@ -1,5 +1,5 @@
 pub use cfg_if::cfg_if;
-pub use hotpath_macros::{main, measure, measure_all, skip};
+pub use hotpath_macros::{main, measure, measure_all, measure_impl, skip};
 
 #[macro_export]
 macro_rules! measure_block {
@@ -8,6 +8,75 @@ macro_rules! measure_block {
     }};
 }
 
+#[macro_export]
+macro_rules! measure_if {
+    ($condition:expr, $label:expr, $expr:expr) => {{
+        $expr
+    }};
+}
+
+#[macro_export]
+macro_rules! measure_block_alloc {
+    ($label:expr, $expr:expr) => {{
+        $expr
+    }};
+}
+
+#[macro_export]
+macro_rules! assert_max_alloc {
+    ($label:expr, $max_bytes:expr, $expr:expr) => {{
+        let _: u64 = $max_bytes;
+        let result = $expr;
+        result
+    }};
+}
+
+#[macro_export]
+macro_rules! measure_io {
+    ($label:expr, $f:expr) => {{
+        let bytes: usize = ($f)();
+        bytes
+    }};
+}
+
+#[macro_export]
+macro_rules! record_value {
+    ($label:expr, $value:expr) => {{
+        let _: u64 = $value;
+    }};
+    ($label:expr, $value:expr, unit = $unit:expr) => {{
+        let _: u64 = $value;
+    }};
+}
+
+#[macro_export]
+macro_rules! alloc_here {
+    ($label:expr) => {};
+}
+
+#[macro_export]
+macro_rules! measure_drop {
+    () => {};
+    ($label:expr) => {};
+}
+
+#[macro_export]
+macro_rules! measure_lock {
+    ($name:literal, $mutex:expr) => {
+        $mutex.lock()
+    };
+    ($mutex:expr) => {
+        $mutex.lock()
+    };
+}
+
+#[macro_export]
+macro_rules! measure_spawn {
+    ($label:expr, $future:expr) => {
+        ::tokio::spawn($future)
+    };
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 pub enum Format {
     #[default]
@@ -16,6 +85,28 @@ pub enum Format {
     JsonPretty,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PercentBasis {
+    #[default]
+    Wall,
+    MeasuredSum,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortBy {
+    #[default]
+    Total,
+    Max,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CrateRollup {
+    #[default]
+    Off,
+    Append,
+    Replace,
+}
+
 pub struct MeasurementGuard {}
 
 impl MeasurementGuard {
@@ -27,9 +118,52 @@ impl MeasurementGuard {
         Self {}
     }
 
+    pub fn build_return(_name: &'static str, _is_async: bool) -> Option<Self> {
+        None
+    }
+
+    pub fn build_alloc_scope(_label: &'static str) -> Option<Self> {
+        None
+    }
+
+    pub fn with_percentiles(self, _percentiles: &'static [f64]) -> Self {
+        self
+    }
+
     pub fn build_with_timeout(self, _duration: std::time::Duration) {}
 }
 
+#[doc(hidden)]
+pub fn poll_timed<F: std::future::Future>(
+    _name: &'static str,
+    future: F,
+) -> impl std::future::Future<Output = F::Output> {
+    future
+}
+
+pub fn measure_fut<F: std::future::Future>(
+    _label: &'static str,
+    future: F,
+) -> impl std::future::Future<Output = F::Output> {
+    future
+}
+
+#[doc(hidden)]
+pub fn located_name(name: &'static str, _file: &'static str, _line: u32) -> &'static str {
+    name
+}
+
+#[doc(hidden)]
+pub fn monomorphized_name(base: &'static str, _type_names: &[&'static str]) -> &'static str {
+    base
+}
+
+pub fn phase(_name: &'static str) -> PhaseGuard {
+    PhaseGuard
+}
+
+pub struct PhaseGuard;
+
 pub struct HotPath;
 
 impl Default for HotPath {
@@ -42,8 +176,28 @@ impl HotPath {
     pub fn new() -> Self {
         Self {}
     }
+
+    pub fn render_table(&self) -> String {
+        String::new()
+    }
+
+    pub fn render_json(&self) -> String {
+        String::new()
+    }
 }
 
+pub fn serve_metrics(_port: u16) -> HotPath {
+    HotPath
+}
+
+pub type RawSink = std::sync::Arc<std::sync::Mutex<Vec<(String, u64)>>>;
+
+pub type SlowCallback = Box<dyn Fn(&str, std::time::Duration) + Send + Sync>;
+
+pub type CategoryFn = Box<dyn Fn(&str) -> &'static str + Send + Sync>;
+
+pub type ClockFn = Box<dyn Fn() -> std::time::Instant + Send + Sync>;
+
 pub struct GuardBuilder {}
 use crate::Reporter;
 
@@ -52,7 +206,7 @@ impl GuardBuilder {
         Self {}
     }
 
-    pub fn percentiles(self, _percentiles: &[u8]) -> Self {
+    pub fn percentiles<P: Copy + Into<f64>>(self, _percentiles: &[P]) -> Self {
         self
     }
 
@@ -60,10 +214,163 @@ impl GuardBuilder {
         self
     }
 
+    pub fn percent_format(self, _percent_format: crate::PercentFormat) -> Self {
+        self
+    }
+
+    pub fn include_distribution(self, _enabled: bool) -> Self {
+        self
+    }
+
+    pub fn byte_format(self, _byte_format: crate::ByteFormat) -> Self {
+        self
+    }
+
     pub fn limit(self, _limit: usize) -> Self {
         self
     }
 
+    pub fn percent_basis(self, _percent_basis: PercentBasis) -> Self {
+        self
+    }
+
+    pub fn raw_sink(self, _sink: RawSink) -> Self {
+        self
+    }
+
+    pub fn raw_samples_file(self, _path: impl Into<std::path::PathBuf>) -> Self {
+        self
+    }
+
+    pub fn chrome_trace_file(self, _path: impl Into<std::path::PathBuf>) -> Self {
+        self
+    }
+
+    pub fn track_process_peak(self, _enabled: bool) -> Self {
+        self
+    }
+
+    pub fn baseline(self, _path: impl Into<std::path::PathBuf>) -> Self {
+        self
+    }
+
+    pub fn crate_rollup(self, _crate_rollup: CrateRollup) -> Self {
+        self
+    }
+
+    pub fn categorize(self, _categorize: CategoryFn) -> Self {
+        self
+    }
+
+    pub fn highlight_threshold(self, _threshold: f64) -> Self {
+        self
+    }
+
+    pub fn show_trimmed_mean(self, _enabled: bool) -> Self {
+        self
+    }
+
+    pub fn show_uncalled(self, _enabled: bool) -> Self {
+        self
+    }
+
+    pub fn relative_to(self, _function: &'static str) -> Self {
+        self
+    }
+
+    pub fn plain(self, _enabled: bool) -> Self {
+        self
+    }
+
+    pub fn show_locations(self, _enabled: bool) -> Self {
+        self
+    }
+
+    pub fn include_wrapper(self, _include: bool) -> Self {
+        self
+    }
+
+    pub fn report_on_idle(self, _idle: std::time::Duration) -> Self {
+        self
+    }
+
+    pub fn metadata(self, _key: impl Into<String>, _value: impl Into<String>) -> Self {
+        self
+    }
+
+    pub fn register(self, _function_names: &[&'static str]) -> Self {
+        self
+    }
+
+    pub fn max_functions(self, _max_functions: usize) -> Self {
+        self
+    }
+
+    pub fn report_deadline(self, _deadline: std::time::Duration) -> Self {
+        self
+    }
+
+    pub fn overhead_budget(self, _percent: f64) -> Self {
+        self
+    }
+
+    pub fn http_server(self, _port: u16) -> Self {
+        self
+    }
+
+    #[cfg(feature = "hotpath-tls")]
+    pub fn http_tls(
+        self,
+        _cert_path: impl Into<std::path::PathBuf>,
+        _key_path: impl Into<std::path::PathBuf>,
+    ) -> Self {
+        self
+    }
+
+    pub fn http_query_timeout(self, _timeout: std::time::Duration) -> Self {
+        self
+    }
+
+    pub fn sort_by(self, _sort_by: SortBy) -> Self {
+        self
+    }
+
+    pub fn alloc_backtrace_threshold(self, _bytes: u64) -> Self {
+        self
+    }
+
+    pub fn to_stderr(self, _to_stderr: bool) -> Self {
+        self
+    }
+
+    pub fn inline(self, _inline: bool) -> Self {
+        self
+    }
+
+    pub fn report_atexit(self, _report_atexit: bool) -> Self {
+        self
+    }
+
+    pub fn wait_for_guard(self, _enabled: bool) -> Self {
+        self
+    }
+
+    pub fn record_commit(self, _enabled: bool) -> Self {
+        self
+    }
+
+    pub fn on_slow(self, _threshold: std::time::Duration, _callback: SlowCallback) -> Self {
+        self
+    }
+
+    pub fn clock(self, _clock: ClockFn) -> Self {
+        self
+    }
+
+    pub fn silent(self) -> Self {
+        self
+    }
+
     pub fn build(self) -> HotPath {
         HotPath
     }
@@ -79,3 +386,7 @@ impl GuardBuilder {
 
 #[derive(Debug, Clone)]
 pub struct FunctionStats {}
+
+impl FunctionStats {
+    pub fn merge(&mut self, _other: &FunctionStats) {}
+}
@@ -1,27 +1,217 @@
 use crate::output;
-use crate::output::{MetricsJson, MetricsProvider, SamplesJson};
+use crate::output::{
+    ByteFormat, CallRatesJson, MetricsJson, MetricsProvider, PercentFormat, ReportError,
+    SamplesJson,
+};
 
 #[doc(hidden)]
 pub use cfg_if::cfg_if;
-pub use hotpath_macros::{main, measure, measure_all, skip};
+pub use hotpath_macros::{main, measure, measure_all, measure_impl, skip};
 
 use crossbeam_channel::Sender;
 
+mod aggregator;
+use aggregator::{Aggregator, CardinalityLimit};
+mod atexit;
+#[cfg(feature = "hotpath-batch-channel")]
+mod batch;
+// Events are only written in time profiling mode
+#[cfg(any(
+    feature = "hotpath-alloc-bytes-total",
+    feature = "hotpath-alloc-count-total",
+    feature = "hotpath-alloc-leak",
+    feature = "hotpath-alloc-blocks",
+    feature = "hotpath-time-alloc-count"
+))]
+mod alloc_limit;
+#[cfg_attr(
+    any(
+        feature = "hotpath-alloc-bytes-total",
+        feature = "hotpath-alloc-count-total",
+        feature = "hotpath-alloc-leak"
+    ),
+    allow(dead_code)
+)]
+mod chrome_trace;
+#[cfg(any(
+    feature = "hotpath-alloc-bytes-total",
+    feature = "hotpath-alloc-count-total",
+    feature = "hotpath-alloc-leak",
+    feature = "hotpath-alloc-blocks",
+    feature = "hotpath-time-alloc-count"
+))]
+#[doc(hidden)]
+pub use alloc_limit::AllocLimitGuard;
+mod clock;
+pub use clock::ClockFn;
+pub(crate) mod commit;
+mod filter;
+pub(crate) mod io;
+#[doc(hidden)]
+pub use io::record_io;
+// The guards' cost is only tracked in time profiling mode
+#[cfg_attr(
+    any(
+        feature = "hotpath-alloc-bytes-total",
+        feature = "hotpath-alloc-count-total",
+        feature = "hotpath-alloc-leak"
+    ),
+    allow(dead_code)
+)]
+mod overhead;
+pub(crate) mod phases;
+pub use phases::{phase, PhaseGuard};
+mod process;
+mod rates;
+mod raw_samples;
+pub(crate) mod recursion;
+mod shared;
+pub(crate) mod values;
+#[cfg(not(feature = "hotpath-inline"))]
+mod worker;
+#[doc(hidden)]
+pub use values::record_value;
+
 /// Query request sent from TUI HTTP server to profiler worker thread
 pub enum QueryRequest {
     /// Request full metrics snapshot
     GetMetrics(Sender<MetricsJson>),
+    /// Request metrics accumulated since the previous delta request
+    GetMetricsDelta(Sender<MetricsJson>),
     /// Request samples for a specific function (returns None if function not found)
     GetSamples {
         function_name: String,
         response_tx: Sender<Option<SamplesJson>>,
     },
+    /// Request call counts per function in recent time windows
+    GetCallRates(Sender<CallRatesJson>),
+    /// Run a closure against current metrics, after processing all pending measurements
+    Render(RenderFn),
+}
+
+/// Closure run by the worker thread for [`QueryRequest::Render`].
+pub type RenderFn = Box<dyn FnOnce(&dyn MetricsProvider<'_>) + Send>;
+
+/// Wraps the future of a `#[measure(poll_time)]` function to measure only the time spent in `poll`.
+/// Poll time is only measured in time profiling mode, otherwise the future is returned unchanged.
+#[doc(hidden)]
+pub fn poll_timed<F: std::future::Future>(
+    _name: &'static str,
+    future: F,
+) -> impl std::future::Future<Output = F::Output> {
+    cfg_if::cfg_if! {
+        if #[cfg(any(
+            feature = "hotpath-alloc-bytes-total",
+            feature = "hotpath-alloc-count-total",
+            feature = "hotpath-alloc-leak"
+        ))] {
+            future
+        } else {
+            time::guard::PollTimed::new(_name, future)
+        }
+    }
+}
+
+/// Measures a future under the given label, for async blocks that can't take the
+/// [`measure`](hotpath_macros::measure) attribute, e.g. bodies of spawned tasks.
+///
+/// The measurement starts on the first poll, so it runs inside the task that drives the future.
+/// In allocation profiling modes the same runtime flavor check as for `#[measure]` async functions
+/// applies there, and results are reported as `N/A*` unless the task runs on a tokio
+/// `current_thread` runtime. Use [`measure_spawn!`] to measure and spawn a tokio task in one go.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let handle = tokio::spawn(hotpath::measure_fut("refresh_cache", async {
+///     refresh_cache().await
+/// }));
+/// ```
+#[cfg(feature = "hotpath")]
+#[track_caller]
+pub fn measure_fut<F: std::future::Future>(
+    label: &'static str,
+    future: F,
+) -> impl std::future::Future<Output = F::Output> {
+    let location = std::panic::Location::caller();
+    let name = located_name(label, location.file(), location.line());
+
+    async move {
+        let _guard = MeasurementGuard::build(name, false, true);
+        future.await
+    }
+}
+
+#[cfg(not(feature = "hotpath"))]
+#[inline(always)]
+pub fn measure_fut<F: std::future::Future>(
+    _label: &'static str,
+    future: F,
+) -> impl std::future::Future<Output = F::Output> {
+    future
+}
+
+static SHOW_LOCATIONS: AtomicBool = AtomicBool::new(false);
+/// Source file and line of each measured name, the first one seen if it's measured in several places
+static LOCATIONS: OnceLock<RwLock<HashMap<&'static str, (&'static str, u32)>>> = OnceLock::new();
+
+/// Returns the measurement name unchanged. If enabled with [`GuardBuilder::show_locations`],
+/// its source location is recorded to be shown next to it in the report, e.g.
+/// `mod::parse (src/lib.rs:42)`. The name itself never changes, so that `GuardBuilder::register`
+/// and `HOTPATH_ONLY` match it as usual.
+#[doc(hidden)]
+#[inline]
+pub fn located_name(name: &'static str, file: &'static str, line: u32) -> &'static str {
+    if SHOW_LOCATIONS.load(Ordering::Relaxed) {
+        let locations = LOCATIONS.get_or_init(Default::default);
+        if !locations.read().unwrap().contains_key(name) {
+            locations
+                .write()
+                .unwrap()
+                .entry(name)
+                .or_insert((file, line));
+        }
+    }
+
+    name
+}
+
+/// Source locations recorded with [`located_name`] as `file:line`, keyed by measurement name.
+pub(crate) fn locations() -> HashMap<String, String> {
+    LOCATIONS.get().map_or_else(HashMap::new, |locations| {
+        locations
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, (file, line))| (name.to_string(), format!("{file}:{line}")))
+            .collect()
+    })
+}
+
+static DROP_NAMES: OnceLock<RwLock<HashMap<&'static str, &'static str>>> = OnceLock::new();
+
+/// Returns the `Type::drop` measurement name of a type, interned on first use so later calls
+/// do not allocate.
+#[doc(hidden)]
+pub fn drop_name(type_name: &'static str) -> &'static str {
+    let names = DROP_NAMES.get_or_init(Default::default);
+
+    if let Some(name) = names.read().unwrap().get(type_name) {
+        return name;
+    }
+
+    names
+        .write()
+        .unwrap()
+        .entry(type_name)
+        .or_insert_with(|| Box::leak(format!("{}::drop", type_name).into_boxed_str()))
 }
 
 cfg_if::cfg_if! {
     if #[cfg(any(
         feature = "hotpath-alloc-bytes-total",
-        feature = "hotpath-alloc-count-total"
+        feature = "hotpath-alloc-count-total",
+        feature = "hotpath-alloc-leak"
     ))] {
         mod alloc;
         #[doc(hidden)]
@@ -35,6 +225,15 @@ cfg_if::cfg_if! {
         // Time-based profiling (when no allocation features are enabled)
         mod time;
         pub use time::guard::MeasurementGuard;
+        #[cfg(feature = "hotpath-alloc-blocks")]
+        #[doc(hidden)]
+        pub use time::alloc_block::AllocBlockGuard;
+
+        // Counts allocations inside measure_block_alloc! blocks, or of all measured functions
+        // with hotpath-time-alloc-count
+        #[cfg(any(feature = "hotpath-alloc-blocks", feature = "hotpath-time-alloc-count"))]
+        #[global_allocator]
+        static GLOBAL: time::alloc_block::BlockCountingAllocator = time::alloc_block::BlockCountingAllocator;
         pub use time::state::FunctionStats;
         use time::{
             report::StatsData,
@@ -53,7 +252,8 @@ impl MeasurementGuard {
             cfg_if::cfg_if! {
                 if #[cfg(any(
                     feature = "hotpath-alloc-bytes-total",
-                    feature = "hotpath-alloc-count-total"
+                    feature = "hotpath-alloc-count-total",
+                    feature = "hotpath-alloc-leak"
                 ))] {
                     // For allocation profiling: mark async as unsupported unless
                     // running on Tokio CurrentThread. Non-Tokio runtimes are unsupported.
@@ -71,8 +271,83 @@ impl MeasurementGuard {
             }
         };
 
-        MeasurementGuard::new(measurement_name, wrapper, unsupported_async)
+        // Async calls can interleave or move between threads, so only sync calls are checked
+        // for recursion
+        MeasurementGuard::new_with_recursion(
+            measurement_name,
+            wrapper,
+            unsupported_async,
+            !wrapper && !_is_async,
+        )
+    }
+
+    /// Builds a guard for the return value of a `#[measure(track_return)]` function.
+    /// Returned values are only tracked in allocation profiling modes.
+    pub fn build_return(_measurement_name: &'static str, _is_async: bool) -> Option<Self> {
+        cfg_if::cfg_if! {
+            if #[cfg(any(
+                feature = "hotpath-alloc-bytes-total",
+                feature = "hotpath-alloc-count-total",
+                feature = "hotpath-alloc-leak"
+            ))] {
+                Some(MeasurementGuard::build(_measurement_name, false, _is_async))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Builds a guard for an [`alloc_here!`] scope. Scopes are only tracked in allocation profiling modes.
+    pub fn build_alloc_scope(_label: &'static str) -> Option<Self> {
+        cfg_if::cfg_if! {
+            if #[cfg(any(
+                feature = "hotpath-alloc-bytes-total",
+                feature = "hotpath-alloc-count-total",
+                feature = "hotpath-alloc-leak"
+            ))] {
+                Some(MeasurementGuard::new(_label, false, false))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+type MonomorphizedNames = HashMap<&'static str, Vec<(Box<[&'static str]>, &'static str)>>;
+
+static MONOMORPHIZED_NAMES: OnceLock<RwLock<MonomorphizedNames>> = OnceLock::new();
+
+/// Returns the measurement name for a `#[measure(monomorphized)]` instantiation, e.g. `mod::process<u32>`.
+/// Names are interned on first use, so later calls with the same type arguments do not allocate.
+#[doc(hidden)]
+pub fn monomorphized_name(base: &'static str, type_names: &[&'static str]) -> &'static str {
+    let names = MONOMORPHIZED_NAMES.get_or_init(Default::default);
+
+    let find = |names: &MonomorphizedNames| {
+        names.get(base).and_then(|entries| {
+            entries
+                .iter()
+                .find(|(types, _)| **types == *type_names)
+                .map(|(_, name)| *name)
+        })
+    };
+
+    if let Some(name) = find(&names.read().unwrap()) {
+        return name;
+    }
+
+    let mut names = names.write().unwrap();
+    if let Some(name) = find(&names) {
+        return name;
     }
+
+    let name: &'static str =
+        Box::leak(format!("{}<{}>", base, type_names.join(", ")).into_boxed_str());
+    names
+        .entry(base)
+        .or_default()
+        .push((type_names.into(), name));
+    name
 }
 
 cfg_if::cfg_if! {
@@ -92,6 +367,14 @@ cfg_if::cfg_if! {
             report::StatsData,
             state::{HotPathState, Measurement, process_measurement},
         };
+    } else if #[cfg(feature = "hotpath-alloc-leak")] {
+        mod alloc_leak;
+        pub use alloc_leak::guard::MeasurementGuard;
+        pub use alloc_leak::state::FunctionStats;
+        use alloc_leak::{
+            report::StatsData,
+            state::{HotPathState, Measurement, process_measurement},
+        };
     }
 }
 
@@ -125,10 +408,102 @@ pub enum Format {
     JsonPretty,
 }
 
-use crossbeam_channel::{bounded, select, unbounded};
-use std::collections::HashMap;
+/// Reference total used to compute the `% Total` column in time profiling mode.
+///
+/// # Variants
+///
+/// * `Wall` - Wall clock time of the profiled scope (default)
+/// * `MeasuredSum` - Sum of durations of all measured functions, excluding the wrapper.
+///   Useful for workloads that spend most of the time sleeping or idle. A measured function
+///   called by another one counts towards both totals, so with nested functions the
+///   percentages add up to more than 100%.
+///
+/// Allocation profiling modes always use the grand total of measured allocations.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "hotpath")]
+/// # {
+/// use hotpath::{GuardBuilder, PercentBasis};
+///
+/// let _guard = GuardBuilder::new("main")
+///     .percent_basis(PercentBasis::MeasuredSum)
+///     .build();
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PercentBasis {
+    #[default]
+    Wall,
+    MeasuredSum,
+}
+
+/// Order of report rows, which also decides the functions kept by [`GuardBuilder::limit`].
+///
+/// # Variants
+///
+/// * `Total` - Total of all calls, i.e. time or allocations summed up (default)
+/// * `Max` - Largest single call. In allocation modes it surfaces a one-time allocation spike
+///   that total-based sorting buries under frequently called functions.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "hotpath")]
+/// # {
+/// use hotpath::{GuardBuilder, SortBy};
+///
+/// let _guard = GuardBuilder::new("main")
+///     .sort_by(SortBy::Max)
+///     .percentiles(&[95.0, 100.0])
+///     .build();
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortBy {
+    #[default]
+    Total,
+    Max,
+}
+
+/// Crate-level rollup of the table report.
+///
+/// Function totals are summed by crate name, i.e. the first `::` segment of the function name,
+/// to answer "which dependency is slow" questions. Percentiles can't be aggregated, so the
+/// rollup shows the number of functions, calls, total and `% Total` per crate.
+///
+/// # Variants
+///
+/// * `Off` - Only the per-function table (default)
+/// * `Append` - Per-function table followed by the crate rollup
+/// * `Replace` - Only the crate rollup
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "hotpath")]
+/// # {
+/// use hotpath::{CrateRollup, GuardBuilder};
+///
+/// let _guard = GuardBuilder::new("main")
+///     .crate_rollup(CrateRollup::Append)
+///     .build();
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CrateRollup {
+    #[default]
+    Off,
+    Append,
+    Replace,
+}
+
+use crossbeam_channel::bounded;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 use std::thread;
-use std::time::Instant;
+use std::time::Duration;
 
 /// Measures the execution time or memory allocations of a code block.
 ///
@@ -170,7 +545,11 @@ use std::time::Instant;
 #[macro_export]
 macro_rules! measure_block {
     ($label:expr, $expr:expr) => {{
-        let _guard = hotpath::MeasurementGuard::new($label, false, false);
+        let _guard = hotpath::MeasurementGuard::new(
+            hotpath::located_name($label, file!(), line!()),
+            false,
+            false,
+        );
 
         $expr
     }};
@@ -184,54 +563,654 @@ macro_rules! measure_block {
     }};
 }
 
-use arc_swap::ArcSwapOption;
-use std::sync::Arc;
-use std::sync::Mutex;
-use std::sync::OnceLock;
-use std::sync::RwLock;
+/// Measures a code block like [`measure_block!`], but only records the measurement when
+/// `$condition` is true.
+///
+/// Useful to profile only some inputs, e.g. slow-path branches, without recording every
+/// evaluation of the block. The condition is evaluated once before the block, if it's false
+/// the block runs without any instrumentation. Without the `hotpath` feature the condition
+/// isn't evaluated at all.
+///
+/// # Arguments
+///
+/// * `$condition` - A `bool` expression deciding whether this evaluation is recorded
+/// * `$label` - A static string label to identify this code block in the profiling report
+/// * `$expr` - The expression or code block to measure
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "hotpath")]
+/// # {
+/// fn lookup(key: &str, cache: &std::collections::HashMap<String, u64>) -> u64 {
+///     hotpath::measure_if!(!cache.contains_key(key), "lookup_miss", {
+///         cache.get(key).copied().unwrap_or_else(|| key.len() as u64)
+///     })
+/// }
+/// # }
+/// ```
+#[cfg(feature = "hotpath")]
+#[macro_export]
+macro_rules! measure_if {
+    ($condition:expr, $label:expr, $expr:expr) => {{
+        let _guard = if $condition {
+            Some(hotpath::MeasurementGuard::new(
+                hotpath::located_name($label, file!(), line!()),
+                false,
+                false,
+            ))
+        } else {
+            None
+        };
 
-use crate::Reporter;
+        $expr
+    }};
+}
+
+#[cfg(not(feature = "hotpath"))]
+#[macro_export]
+macro_rules! measure_if {
+    ($condition:expr, $label:expr, $expr:expr) => {{
+        $expr
+    }};
+}
+
+/// Measures memory allocations of a code block, regardless of the profiling mode.
+///
+/// In allocation profiling modes it's the same as [`measure_block!`]. In time profiling mode
+/// it requires the `hotpath-alloc-blocks` feature, which installs a counting global allocator
+/// that only counts allocations made inside these blocks. Results are reported in a separate
+/// "allocation blocks" table below the timing report, with bytes and allocation count totals.
+/// Nested blocks are included in the enclosing block totals.
+///
+/// Without the `hotpath-alloc-blocks` feature in time mode, using this macro is a compile error.
+///
+/// # Arguments
+///
+/// * `$label` - A static string label to identify this code block in the profiling report
+/// * `$expr` - The expression or code block to measure
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// hotpath::measure_block_alloc!("build_index", {
+///     let index: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+///     std::hint::black_box(index);
+/// });
+/// ```
+#[cfg(all(
+    feature = "hotpath",
+    any(
+        feature = "hotpath-alloc-bytes-total",
+        feature = "hotpath-alloc-count-total",
+        feature = "hotpath-alloc-leak"
+    )
+))]
+#[macro_export]
+macro_rules! measure_block_alloc {
+    ($label:expr, $expr:expr) => {
+        hotpath::measure_block!($label, $expr)
+    };
+}
 
 #[cfg(all(
-    feature = "hotpath-alloc-bytes-total",
-    feature = "hotpath-alloc-count-total"
+    feature = "hotpath",
+    feature = "hotpath-alloc-blocks",
+    not(any(
+        feature = "hotpath-alloc-bytes-total",
+        feature = "hotpath-alloc-count-total",
+        feature = "hotpath-alloc-leak"
+    ))
 ))]
-compile_error!("Only one allocation feature can be enabled at a time");
+#[macro_export]
+macro_rules! measure_block_alloc {
+    ($label:expr, $expr:expr) => {{
+        let _guard = hotpath::AllocBlockGuard::new(hotpath::located_name($label, file!(), line!()));
 
-pub(crate) static HOTPATH_STATE: OnceLock<ArcSwapOption<RwLock<HotPathState>>> = OnceLock::new();
+        $expr
+    }};
+}
 
-/// Builder for creating a hotpath profiling guard with custom configuration.
+#[cfg(all(
+    feature = "hotpath",
+    not(feature = "hotpath-alloc-blocks"),
+    not(any(
+        feature = "hotpath-alloc-bytes-total",
+        feature = "hotpath-alloc-count-total",
+        feature = "hotpath-alloc-leak"
+    ))
+))]
+#[macro_export]
+macro_rules! measure_block_alloc {
+    ($label:expr, $expr:expr) => {
+        compile_error!(
+            "measure_block_alloc! in time profiling mode requires the `hotpath-alloc-blocks` feature"
+        )
+    };
+}
+
+#[cfg(not(feature = "hotpath"))]
+#[macro_export]
+macro_rules! measure_block_alloc {
+    ($label:expr, $expr:expr) => {{
+        $expr
+    }};
+}
+
+/// Runs a block and panics if it allocated more than `$max_bytes` bytes.
 ///
-/// `GuardBuilder` provides manual control over the profiling lifecycle, allowing you to
-/// start and stop profiling at specific points in your code. The profiling report is
-/// generated when the guard is dropped.
+/// An allocation regression guard for unit tests: the bytes allocated by the block, including
+/// nested calls, are read from the counting global allocator and compared to the budget. Only
+/// allocations of the current thread are counted, so work spawned onto other threads isn't
+/// included. Returns the value of the block. A profiling guard doesn't need to be active.
+///
+/// Allocations are counted in the allocation profiling modes, and in time profiling mode with
+/// the `hotpath-alloc-blocks` or `hotpath-time-alloc-count` feature. Without a counting
+/// allocator, and when `hotpath` is disabled, the block runs unchecked.
+///
+/// # Arguments
+///
+/// * `$label` - A string label identifying the block in the panic message
+/// * `$max_bytes` - The maximum number of bytes the block may allocate, as `u64`
+/// * `$expr` - The expression or code block to check
 ///
 /// # Examples
 ///
-/// Basic usage with default settings:
+/// ```rust
+/// let index: Vec<u64> = hotpath::assert_max_alloc!("build_index", 1024, {
+///     (0..100).collect()
+/// });
+/// assert_eq!(index.len(), 100);
+/// ```
+#[cfg(all(
+    feature = "hotpath",
+    any(
+        feature = "hotpath-alloc-bytes-total",
+        feature = "hotpath-alloc-count-total",
+        feature = "hotpath-alloc-leak",
+        feature = "hotpath-alloc-blocks",
+        feature = "hotpath-time-alloc-count"
+    )
+))]
+#[macro_export]
+macro_rules! assert_max_alloc {
+    ($label:expr, $max_bytes:expr, $expr:expr) => {{
+        let guard = hotpath::AllocLimitGuard::start();
+        let result = $expr;
+        guard.check($label, $max_bytes);
+        result
+    }};
+}
+
+#[cfg(not(all(
+    feature = "hotpath",
+    any(
+        feature = "hotpath-alloc-bytes-total",
+        feature = "hotpath-alloc-count-total",
+        feature = "hotpath-alloc-leak",
+        feature = "hotpath-alloc-blocks",
+        feature = "hotpath-time-alloc-count"
+    )
+)))]
+#[macro_export]
+macro_rules! assert_max_alloc {
+    ($label:expr, $max_bytes:expr, $expr:expr) => {{
+        let _: u64 = $max_bytes;
+        let result = $expr;
+        result
+    }};
+}
+
+/// Measures an I/O operation like [`measure_block!`], and records the number of bytes it
+/// read or wrote.
+///
+/// `$f` is a closure returning the byte count as `usize`, e.g. the result of `Read::read`.
+/// Bytes are reported in a separate "io blocks" table below the main report, with calls, avg
+/// and total bytes, in every profiling mode. Time or allocations of the closure show up in the
+/// main report under the same label, to correlate them with the I/O volume. Returns the byte
+/// count.
+///
+/// # Arguments
+///
+/// * `$label` - A static string label to identify this operation in the profiling report
+/// * `$f` - A closure performing the operation and returning the number of bytes
+///
+/// # Examples
 ///
 /// ```rust
 /// # #[cfg(feature = "hotpath")]
 /// # {
-/// use hotpath::GuardBuilder;
+/// use std::io::Read;
 ///
-/// let _guard = GuardBuilder::new("my_program").build();
-/// // Your code here - measurements will be collected
-/// // Report is printed when _guard goes out of scope
+/// let mut input: &[u8] = b"hello world";
+/// let mut buf = [0u8; 64];
+/// let read = hotpath::measure_io!("read_input", || input.read(&mut buf).unwrap_or(0));
+/// assert_eq!(read, 11);
 /// # }
 /// ```
+#[cfg(feature = "hotpath")]
+#[macro_export]
+macro_rules! measure_io {
+    ($label:expr, $f:expr) => {{
+        let name = hotpath::located_name($label, file!(), line!());
+        let bytes: usize = {
+            let _guard = hotpath::MeasurementGuard::new(name, false, false);
+            ($f)()
+        };
+        hotpath::record_io(name, bytes);
+        bytes
+    }};
+}
+
+#[cfg(not(feature = "hotpath"))]
+#[macro_export]
+macro_rules! measure_io {
+    ($label:expr, $f:expr) => {{
+        let bytes: usize = ($f)();
+        bytes
+    }};
+}
+
+/// Records an arbitrary value under a label, e.g. the number of rows processed by a batch.
 ///
-/// Custom configuration:
+/// Values are aggregated per label into a distribution like function measurements, and
+/// reported in a separate "values" table below the main report with calls, avg, the
+/// configured percentiles, max and total, in every profiling mode. An optional unit is
+/// appended to the reported numbers.
+///
+/// # Arguments
+///
+/// * `$label` - A static string label to identify this value in the profiling report
+/// * `$value` - The `u64` value to record
+/// * `unit = $unit` - Optional static string unit, e.g. `"rows"`
+///
+/// # Examples
 ///
 /// ```rust
 /// # #[cfg(feature = "hotpath")]
 /// # {
-/// use hotpath::{GuardBuilder, Format};
-///
-/// let _guard = GuardBuilder::new("benchmark")
-///     .percentiles(&[50, 90, 95, 99])
-///     .format(Format::JsonPretty)
-///     .build();
+/// fn process(batch: &[u64]) {
+///     hotpath::record_value!("batch_size", batch.len() as u64, unit = "rows");
+///     hotpath::record_value!("batch_sum", batch.iter().sum());
+/// }
+/// # }
+/// ```
+#[cfg(feature = "hotpath")]
+#[macro_export]
+macro_rules! record_value {
+    ($label:expr, $value:expr) => {
+        hotpath::record_value!($label, $value, unit = "")
+    };
+    ($label:expr, $value:expr, unit = $unit:expr) => {
+        hotpath::record_value(
+            hotpath::located_name($label, file!(), line!()),
+            $unit,
+            $value,
+        )
+    };
+}
+
+#[cfg(not(feature = "hotpath"))]
+#[macro_export]
+macro_rules! record_value {
+    ($label:expr, $value:expr) => {{
+        let _: u64 = $value;
+    }};
+    ($label:expr, $value:expr, unit = $unit:expr) => {{
+        let _: u64 = $value;
+    }};
+}
+
+/// Attributes allocations made from this point until the end of the enclosing scope to a sub-label.
+///
+/// Use it inside a measured function to find out which part of it dominates allocations.
+/// The labeled scope is reported as a separate entry, and (unless `HOTPATH_ALLOC_SELF` is enabled)
+/// its allocations are still included in the enclosing function's totals.
+/// Only active in allocation profiling modes, in time mode it's a no-op.
+///
+/// # Arguments
+///
+/// * `$label` - A static string label to identify this scope in the profiling report
+///
+/// # Examples
+///
+/// ```rust
+/// #[cfg_attr(feature = "hotpath", hotpath::measure)]
+/// fn build_buffers() -> (Vec<u8>, Vec<u64>) {
+///     let small = {
+///         hotpath::alloc_here!("build_buffers::small");
+///         Vec::with_capacity(64)
+///     };
+///
+///     hotpath::alloc_here!("build_buffers::large");
+///     (small, Vec::with_capacity(4096))
+/// }
+/// ```
+///
+/// # See Also
+///
+/// * [`measure_block!`] - Macro for measuring code blocks
+#[cfg(feature = "hotpath")]
+#[macro_export]
+macro_rules! alloc_here {
+    ($label:expr) => {
+        let _hotpath_alloc_scope = hotpath::MeasurementGuard::build_alloc_scope($label);
+    };
+}
+
+#[cfg(not(feature = "hotpath"))]
+#[macro_export]
+macro_rules! alloc_here {
+    ($label:expr) => {};
+}
+
+/// Measures the rest of a `Drop::drop` body, reported as `Type::drop`.
+///
+/// Destructors can't take the [`measure`](hotpath_macros::measure) attribute, so expensive
+/// cleanup, e.g. flushing buffers or joining threads, doesn't show up in the report. Call this
+/// macro at the start of `drop` to measure it like a [`measure_block!`]. The name is the full
+/// type name of `Self`, e.g. `my_app::Connection::drop`, or a static string label if one is
+/// passed. Drops of the type's fields run after `drop` returns and are not included.
+///
+/// # Examples
+///
+/// ```rust
+/// struct Connection {
+///     buffer: Vec<u8>,
+/// }
+///
+/// impl Drop for Connection {
+///     fn drop(&mut self) {
+///         hotpath::measure_drop!(); // Reported as <crate>::Connection::drop
+///         self.buffer.clear();
+///     }
+/// }
+/// ```
+#[cfg(feature = "hotpath")]
+#[macro_export]
+macro_rules! measure_drop {
+    () => {
+        let _hotpath_drop_guard = hotpath::MeasurementGuard::new(
+            hotpath::located_name(
+                hotpath::drop_name(::std::any::type_name::<Self>()),
+                file!(),
+                line!(),
+            ),
+            false,
+            false,
+        );
+    };
+    ($label:expr) => {
+        let _hotpath_drop_guard = hotpath::MeasurementGuard::new(
+            hotpath::located_name($label, file!(), line!()),
+            false,
+            false,
+        );
+    };
+}
+
+#[cfg(not(feature = "hotpath"))]
+#[macro_export]
+macro_rules! measure_drop {
+    () => {};
+    ($label:expr) => {};
+}
+
+/// Locks a mutex and returns the result of its `lock()` call, measuring only the time spent
+/// acquiring the lock.
+///
+/// The acquire step is reported like a [`measure_block!`] labeled `lock:<name>`, so lock
+/// contention hotspots show up in the report next to function timings. The name defaults to
+/// the mutex expression, e.g. `lock:self.cache`, or can be passed as a string literal.
+/// Time spent holding the lock is not included. Works with any type with a `lock()` method,
+/// e.g. `std::sync::Mutex` or `parking_lot::Mutex`. In allocation profiling modes it reports
+/// allocations made while acquiring the lock instead.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "hotpath")]
+/// # {
+/// use std::sync::Mutex;
+///
+/// let counter = Mutex::new(0);
+/// *hotpath::measure_lock!(counter).unwrap() += 1; // Reported as lock:counter
+/// *hotpath::measure_lock!("counter_inc", counter).unwrap() += 1; // Reported as lock:counter_inc
+/// # }
+/// ```
+#[macro_export]
+macro_rules! measure_lock {
+    ($name:literal, $mutex:expr) => {
+        hotpath::measure_block!(concat!("lock:", $name), $mutex.lock())
+    };
+    ($mutex:expr) => {
+        hotpath::measure_block!(concat!("lock:", stringify!($mutex)), $mutex.lock())
+    };
+}
+
+/// Spawns a future on the tokio runtime, measuring the task under the given label.
+///
+/// Shorthand for `tokio::spawn(hotpath::measure_fut(label, future))`, see [`measure_fut`].
+/// The calling crate must depend on `tokio`.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let handle = hotpath::measure_spawn!("refresh_cache", async move {
+///     refresh_cache().await
+/// });
+/// handle.await.unwrap();
+/// ```
+#[cfg(feature = "hotpath")]
+#[macro_export]
+macro_rules! measure_spawn {
+    ($label:expr, $future:expr) => {
+        ::tokio::spawn(hotpath::measure_fut($label, $future))
+    };
+}
+
+#[cfg(not(feature = "hotpath"))]
+#[macro_export]
+macro_rules! measure_spawn {
+    ($label:expr, $future:expr) => {
+        ::tokio::spawn($future)
+    };
+}
+
+use arc_swap::ArcSwapOption;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::Once;
+use std::sync::OnceLock;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+use crate::Reporter;
+
+// Supported feature matrix, `hotpath-off` overrides all other features and disables profiling:
+//
+// - time mode (`hotpath` only): `hotpath-coarse-clock`, `hotpath-alloc-blocks`,
+//   `hotpath-time-alloc-count`
+// - `hotpath-alloc-bytes-total`: `hotpath-alloc-backtrace`, `hotpath-alloc-blocks`
+// - `hotpath-alloc-count-total`: `hotpath-alloc-blocks`
+// - `hotpath-alloc-leak`: `hotpath-alloc-blocks`
+//
+// `hotpath-batch-channel`, `hotpath-inline`, `hotpath-tracing`, `hotpath-strict`, `hotpath-debug`,
+// `hotpath-sqlite`, `hotpath-tls` and `tui` work in every mode, `hotpath-batch-channel` and `hotpath-inline`
+// exclude each other.
+//
+// Each allocation mode installs its own global allocator, so at most one of them can be enabled.
+#[cfg(all(
+    feature = "hotpath-alloc-backtrace",
+    any(feature = "hotpath-alloc-count-total", feature = "hotpath-alloc-leak")
+))]
+compile_error!(
+    "`hotpath-alloc-backtrace` only works in the `hotpath-alloc-bytes-total` mode, it can't be combined with `hotpath-alloc-count-total` or `hotpath-alloc-leak`"
+);
+
+#[cfg(all(feature = "hotpath-inline", feature = "hotpath-batch-channel"))]
+compile_error!(
+    "`hotpath-batch-channel` batches measurements sent to the worker thread, it can't be combined with `hotpath-inline`, which aggregates without one"
+);
+
+#[cfg(all(
+    feature = "hotpath-time-alloc-count",
+    any(
+        feature = "hotpath-alloc-bytes-total",
+        feature = "hotpath-alloc-count-total",
+        feature = "hotpath-alloc-leak"
+    )
+))]
+compile_error!(
+    "`hotpath-time-alloc-count` adds allocation counts to the time profiling mode, it can't be combined with `hotpath-alloc-bytes-total`, `hotpath-alloc-count-total` or `hotpath-alloc-leak`"
+);
+
+#[cfg(all(
+    feature = "hotpath-alloc-bytes-total",
+    feature = "hotpath-alloc-count-total",
+    not(feature = "hotpath-alloc-leak"),
+    not(feature = "hotpath-alloc-backtrace")
+))]
+compile_error!(
+    "Only one allocation feature can be enabled at a time, got both `hotpath-alloc-bytes-total` and `hotpath-alloc-count-total`"
+);
+
+#[cfg(all(
+    feature = "hotpath-alloc-bytes-total",
+    feature = "hotpath-alloc-leak",
+    not(feature = "hotpath-alloc-count-total"),
+    not(feature = "hotpath-alloc-backtrace")
+))]
+compile_error!(
+    "Only one allocation feature can be enabled at a time, got both `hotpath-alloc-bytes-total` and `hotpath-alloc-leak`"
+);
+
+#[cfg(all(
+    feature = "hotpath-alloc-count-total",
+    feature = "hotpath-alloc-leak",
+    not(feature = "hotpath-alloc-bytes-total")
+))]
+compile_error!(
+    "Only one allocation feature can be enabled at a time, got both `hotpath-alloc-count-total` and `hotpath-alloc-leak`"
+);
+
+#[cfg(all(
+    feature = "hotpath-alloc-bytes-total",
+    feature = "hotpath-alloc-count-total",
+    feature = "hotpath-alloc-leak",
+    not(feature = "hotpath-alloc-backtrace")
+))]
+compile_error!(
+    "Only one allocation feature can be enabled at a time, got `hotpath-alloc-bytes-total`, `hotpath-alloc-count-total` and `hotpath-alloc-leak`"
+);
+
+pub(crate) static HOTPATH_STATE: OnceLock<ArcSwapOption<RwLock<HotPathState>>> = OnceLock::new();
+
+/// Thread that created the live guard, `None` when there is none
+static GUARD_OWNER: Mutex<Option<thread::ThreadId>> = Mutex::new(None);
+static GUARD_RELEASED: Condvar = Condvar::new();
+
+/// Marks a new guard as live. If one is already alive, waits until it's dropped with `wait`,
+/// and panics otherwise, see [`GuardBuilder::wait_for_guard`].
+fn acquire_guard(wait: bool) -> GuardSlot {
+    let current = thread::current().id();
+    let mut owner = GUARD_OWNER.lock().unwrap_or_else(PoisonError::into_inner);
+    while let Some(thread) = *owner {
+        // Waiting on the thread owning the live guard would never return
+        if !wait || thread == current {
+            drop(owner);
+            panic!(
+                "More than one _hotpath guard cannot be alive at the same time. \
+                 To profile tests running in parallel, use GuardBuilder::wait_for_guard(true) \
+                 or run them with --test-threads=1."
+            );
+        }
+        owner = GUARD_RELEASED
+            .wait(owner)
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+    *owner = Some(current);
+    GuardSlot
+}
+
+/// The live guard's slot while the guard is set up. Dropping it releases the slot, so a panic
+/// during the setup doesn't leave later guards waiting forever.
+#[must_use]
+struct GuardSlot;
+
+impl GuardSlot {
+    /// Keeps the slot taken once the guard is constructed, its finalizer releases it.
+    fn hand_over(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for GuardSlot {
+    fn drop(&mut self) {
+        release_guard();
+    }
+}
+
+fn release_guard() {
+    let mut owner = GUARD_OWNER.lock().unwrap_or_else(PoisonError::into_inner);
+    *owner = None;
+    GUARD_RELEASED.notify_one();
+}
+
+static MISSING_GUARD_WARNING: Once = Once::new();
+
+/// Called when a measurement is sent before any hotpath guard was created.
+/// Prints a one-time warning, or panics if the `hotpath-strict` feature is enabled.
+pub(crate) fn report_missing_guard(features: &str) {
+    if cfg!(feature = "hotpath-strict") {
+        panic!(
+            "GuardBuilder::new(\"main\").build() must be called when --features {} is enabled",
+            features
+        );
+    }
+
+    MISSING_GUARD_WARNING.call_once(|| {
+        eprintln!(
+            "[hotpath] Warning: measurements are ignored because no hotpath guard is active. Add #[hotpath::main] to your main function or call GuardBuilder::new(\"main\").build() when --features {} is enabled.",
+            features
+        );
+    });
+}
+
+/// Builder for creating a hotpath profiling guard with custom configuration.
+///
+/// `GuardBuilder` provides manual control over the profiling lifecycle, allowing you to
+/// start and stop profiling at specific points in your code. The profiling report is
+/// generated when the guard is dropped.
+///
+/// # Examples
+///
+/// Basic usage with default settings:
+///
+/// ```rust
+/// # #[cfg(feature = "hotpath")]
+/// # {
+/// use hotpath::GuardBuilder;
+///
+/// let _guard = GuardBuilder::new("my_program").build();
+/// // Your code here - measurements will be collected
+/// // Report is printed when _guard goes out of scope
+/// # }
+/// ```
+///
+/// Custom configuration:
+///
+/// ```rust
+/// # #[cfg(feature = "hotpath")]
+/// # {
+/// use hotpath::{GuardBuilder, Format};
+///
+/// let _guard = GuardBuilder::new("benchmark")
+///     .percentiles(&[50.0, 90.0, 95.0, 99.0])
+///     .format(Format::JsonPretty)
+///     .build();
 /// # }
 /// ```
 ///
@@ -240,11 +1219,15 @@ pub(crate) static HOTPATH_STATE: OnceLock<ArcSwapOption<RwLock<HotPathState>>> =
 /// ```rust
 /// # #[cfg(feature = "hotpath")]
 /// # {
-/// use hotpath::{GuardBuilder, Reporter, MetricsProvider};
+/// use hotpath::{GuardBuilder, MetricsProvider, ReportError, Reporter};
 ///
 /// struct MyReporter;
 /// impl Reporter for MyReporter {
-///     fn report(&self, metrics: &dyn MetricsProvider<'_>) -> Result<(), Box<dyn std::error::Error>> {
+///     fn write_report(
+///         &self,
+///         metrics: &dyn MetricsProvider<'_>,
+///         out: &mut dyn std::io::Write,
+///     ) -> Result<(), ReportError> {
 ///         // Custom reporting logic
 ///         Ok(())
 ///     }
@@ -268,11 +1251,56 @@ pub(crate) static HOTPATH_STATE: OnceLock<ArcSwapOption<RwLock<HotPathState>>> =
 /// * [`Reporter`] - Custom reporter trait
 pub struct GuardBuilder {
     caller_name: &'static str,
-    percentiles: Vec<u8>,
+    percentiles: Vec<f64>,
     reporter: ReporterConfig,
     limit: usize,
+    percent_basis: PercentBasis,
+    raw_sink: Option<RawSink>,
+    raw_samples_file: Option<PathBuf>,
+    chrome_trace_file: Option<PathBuf>,
+    track_process_peak: bool,
+    baseline: Option<PathBuf>,
+    crate_rollup: CrateRollup,
+    categorize: Option<CategoryFn>,
+    highlight_threshold: Option<f64>,
+    show_trimmed_mean: bool,
+    show_uncalled: bool,
+    relative_to: Option<&'static str>,
+    plain: bool,
+    percent_format: PercentFormat,
+    include_distribution: bool,
+    byte_format: ByteFormat,
+    http_port: Option<u16>,
+    http_tls: Option<(PathBuf, PathBuf)>,
+    http_query_timeout: Duration,
+    max_functions: usize,
+    report_deadline: Option<Duration>,
+    overhead_budget: Option<f64>,
+    registered: Vec<&'static str>,
+    show_locations: bool,
+    include_wrapper: bool,
+    metadata: BTreeMap<String, String>,
+    report_on_idle: Option<Duration>,
+    to_stderr: bool,
+    sort_by: SortBy,
+    alloc_backtrace_threshold: u64,
+    inline: bool,
+    report_atexit: bool,
+    wait_for_guard: bool,
+    record_commit: bool,
+    on_slow: Option<(Duration, SlowCallback)>,
+    clock: Option<ClockFn>,
 }
 
+/// Shared buffer receiving every processed `(function_name, value)` measurement.
+pub type RawSink = Arc<Mutex<Vec<(String, u64)>>>;
+
+/// Callback invoked with the function name and duration of calls slower than a threshold.
+pub type SlowCallback = Box<dyn Fn(&str, Duration) + Send + Sync>;
+
+/// Maps a function name to the category it's summed under in the category table.
+pub type CategoryFn = Box<dyn Fn(&str) -> &'static str + Send + Sync>;
+
 enum ReporterConfig {
     Format(Format),
     Custom(Box<dyn Reporter>),
@@ -286,108 +1314,1127 @@ impl GuardBuilder {
     ///
     /// # Arguments
     ///
-    /// * `caller_name` - A string identifier for this profiling session (e.g., "main", "benchmark")
+    /// * `caller_name` - A string identifier for this profiling session (e.g., "main", "benchmark")
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("my_program").build();
+    /// # }
+    /// ```
+    pub fn new(caller_name: &'static str) -> Self {
+        Self {
+            caller_name,
+            percentiles: vec![95.0],
+            reporter: ReporterConfig::None,
+            limit: 15,
+            percent_basis: PercentBasis::Wall,
+            raw_sink: None,
+            raw_samples_file: None,
+            chrome_trace_file: None,
+            track_process_peak: false,
+            baseline: None,
+            crate_rollup: CrateRollup::Off,
+            categorize: None,
+            highlight_threshold: None,
+            show_trimmed_mean: false,
+            show_uncalled: false,
+            relative_to: None,
+            plain: false,
+            percent_format: PercentFormat::BasisPoints,
+            include_distribution: false,
+            byte_format: ByteFormat::Human,
+            http_port: None,
+            http_tls: None,
+            http_query_timeout: Duration::from_millis(250),
+            max_functions: 0,
+            report_deadline: None,
+            overhead_budget: None,
+            registered: Vec::new(),
+            show_locations: false,
+            include_wrapper: true,
+            metadata: BTreeMap::new(),
+            report_on_idle: None,
+            to_stderr: false,
+            sort_by: SortBy::Total,
+            alloc_backtrace_threshold: 1024 * 1024,
+            inline: false,
+            report_atexit: false,
+            wait_for_guard: false,
+            record_commit: false,
+            on_slow: None,
+            clock: None,
+        }
+    }
+
+    /// Sets the percentiles to display in the profiling report.
+    ///
+    /// Percentiles help identify performance distribution patterns across multiple
+    /// measurements of the same function. Valid values are 0-100, where 0 represents
+    /// the minimum value and 100 represents the maximum. Fractional values like `99.9`
+    /// are supported for tail latency analysis, they're reported as `P99.9` columns
+    /// and `p99_9` JSON keys.
+    ///
+    /// Default: `[95.0]`
+    ///
+    /// # Arguments
+    ///
+    /// * `percentiles` - Slice of percentile values (0-100) to display, integers like
+    ///   `&[50, 95, 99]` are accepted too
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .percentiles(&[50.0, 90.0, 99.0, 99.9])
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn percentiles<P: Copy + Into<f64>>(mut self, percentiles: &[P]) -> Self {
+        self.percentiles = percentiles.iter().map(|&p| p.into()).collect();
+        self
+    }
+
+    /// Sets the maximum number of functions to display in the profiling report.
+    ///
+    /// The report will show only the top N functions sorted by total execution time
+    /// (or total allocations when using allocation profiling features).
+    ///
+    /// The limit applies last, so functions cut off by it are still counted: the table header
+    /// shows `(displayed/total)` with the number of all functions that recorded data, and
+    /// `% Total` stays relative to the same total as without a limit.
+    ///
+    /// Default: `15`
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of functions to display (0 means show all)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .limit(20)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Sets the reference total used to compute the `% Total` column in time profiling mode.
+    ///
+    /// Default: [`PercentBasis::Wall`]
+    ///
+    /// # Arguments
+    ///
+    /// * `percent_basis` - Wall clock time or the sum of all measured durations
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::{GuardBuilder, PercentBasis};
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .percent_basis(PercentBasis::MeasuredSum)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn percent_basis(mut self, percent_basis: PercentBasis) -> Self {
+        self.percent_basis = percent_basis;
+        self
+    }
+
+    /// Captures every raw measurement into a shared in-memory buffer.
+    ///
+    /// The background worker pushes each processed `(function_name, value)` pair in the
+    /// order it was received, before any aggregation. The value is a duration in nanoseconds
+    /// in time mode, or the bytes/allocations count in allocation modes. Useful for asserting
+    /// on exact measurement sequences in tests. When unset, there is no additional overhead.
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - Shared buffer that receives the measurements
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use std::sync::{Arc, Mutex};
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let sink = Arc::new(Mutex::new(Vec::new()));
+    /// let guard = GuardBuilder::new("main")
+    ///     .raw_sink(Arc::clone(&sink))
+    ///     .build();
+    ///
+    /// drop(guard);
+    /// assert!(!sink.lock().unwrap().is_empty());
+    /// # }
+    /// ```
+    pub fn raw_sink(mut self, sink: Arc<Mutex<Vec<(String, u64)>>>) -> Self {
+        self.raw_sink = Some(sink);
+        self
+    }
+
+    /// Streams every raw measurement to a file for offline analysis, e.g. with pandas.
+    ///
+    /// The background worker writes one line per measurement as it arrives, with the function
+    /// name, the value (nanoseconds in time mode, bytes or allocations count in allocation
+    /// modes) and the time since the guard was created in nanoseconds. Files with an `.ndjson`
+    /// or `.jsonl` extension get NDJSON lines like
+    /// `{"function":"main::parse","value":1200,"timestamp_ns":5000}`, other files get CSV with
+    /// a `function,value,timestamp_ns` header.
+    ///
+    /// Every call of a measured function adds a line, so hot functions can produce very large
+    /// files. If the file can't be created, a warning is printed and profiling continues without it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .raw_samples_file("samples.csv")
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn raw_samples_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.raw_samples_file = Some(path.into());
+        self
+    }
+
+    /// Writes every measured call to a file in the Chrome Trace Event format, to view the run
+    /// as a timeline in Perfetto or `chrome://tracing`.
+    ///
+    /// Each call becomes a complete (`X`) event with its name, start timestamp and duration in
+    /// microseconds since the guard was created, the process id and a sequential thread id.
+    /// Like [`GuardBuilder::raw_samples_file`], the background worker writes the events as they
+    /// arrive, so hot functions can produce very large files. If the file can't be created,
+    /// a warning is printed and profiling continues without it.
+    ///
+    /// Only supported in time profiling mode, allocation modes print a warning and ignore it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .chrome_trace_file("trace.json")
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn chrome_trace_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.chrome_trace_file = Some(path.into());
+        self
+    }
+
+    /// Reports the peak resident set size (RSS) of the whole process in the report footer.
+    ///
+    /// The value is read from the OS when the report is generated (`/proc/self/status` on Linux,
+    /// `getrusage` on other Unix systems, `GetProcessMemoryInfo` on Windows) and is not
+    /// available on other platforms.
+    /// It's a ground-truth number that cumulative per-function allocation totals
+    /// can be sanity-checked against.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .track_process_peak(true)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn track_process_peak(mut self, enabled: bool) -> Self {
+        self.track_process_peak = enabled;
+        self
+    }
+
+    /// Compares the results against a baseline report saved earlier in JSON format.
+    ///
+    /// The table report then shows `before → after` values with the relative change for every
+    /// metric, marks functions missing from the baseline as `[new]` and functions no longer
+    /// measured as `[removed]`. It's the same comparison `hotpath profile-pr` posts on pull
+    /// requests, available locally and in `build_with_timeout` sessions.
+    ///
+    /// Baselines can be produced with `Format::Json` or the `HOTPATH_JSON=true` env variable.
+    /// If the file can't be loaded or was recorded in another profiling mode, a warning is
+    /// printed and the regular table is shown.
+    /// Applies only to the table format, JSON formats and custom reporters are not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .baseline("hotpath_baseline.json")
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn baseline(mut self, path: impl Into<PathBuf>) -> Self {
+        self.baseline = Some(path.into());
+        self
+    }
+
+    /// Adds a crate-level rollup to the table report, or replaces the per-function table with it.
+    ///
+    /// See [`CrateRollup`] for details. Applies only to the table format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::{CrateRollup, GuardBuilder};
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .crate_rollup(CrateRollup::Replace)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn crate_rollup(mut self, crate_rollup: CrateRollup) -> Self {
+        self.crate_rollup = crate_rollup;
+        self
+    }
+
+    /// Adds a table summing function totals by the category `categorize` returns for each
+    /// function name, after the per-function table.
+    ///
+    /// Unlike [`GuardBuilder::crate_rollup`], the grouping is up to you, e.g. `"db"`,
+    /// `"network"` and `"compute"`. Each row shows the number of functions, calls, total and
+    /// `% Total` of a category. Applies only to the table format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .categorize(Box::new(|name| {
+    ///         if name.contains("::db::") {
+    ///             "db"
+    ///         } else {
+    ///             "other"
+    ///         }
+    ///     }))
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn categorize(mut self, categorize: CategoryFn) -> Self {
+        self.categorize = Some(categorize);
+        self
+    }
+
+    /// Colors table rows of functions taking at least `threshold` percent of the total red,
+    /// and those above half of it yellow, to make hotspots easy to spot in large tables.
+    ///
+    /// Applies only to the table format. Colors are disabled when `NO_COLOR` is set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .highlight_threshold(20.0)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn highlight_threshold(mut self, threshold: f64) -> Self {
+        self.highlight_threshold = Some(threshold);
+        self
+    }
+
+    /// Adds a `Trimmed Avg` column to the table, the mean of values between the P5 and P95
+    /// percentiles of each function.
+    ///
+    /// A few huge outliers can skew `Avg` badly, the trimmed mean ignores them and shows a more
+    /// representative typical cost for functions with long tails. It's computed from the same
+    /// histograms as percentiles, so it's precise to 3 significant digits.
+    ///
+    /// Applies only to the table format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .show_trimmed_mean(true)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn show_trimmed_mean(mut self, enabled: bool) -> Self {
+        self.show_trimmed_mean = enabled;
+        self
+    }
+
+    /// Lists functions pre-registered with [`GuardBuilder::register`] that recorded no calls
+    /// below the table, default `false`.
+    ///
+    /// Their rows with 0 calls sort last and can be cut off by [`GuardBuilder::limit`], the list
+    /// includes all of them. Turns the report into a lightweight coverage check of instrumented
+    /// code paths, e.g. in integration tests.
+    ///
+    /// Applies only to the table format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .register(&["my_crate::parse", "my_crate::fallback_parse"])
+    ///     .show_uncalled(true)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn show_uncalled(mut self, enabled: bool) -> Self {
+        self.show_uncalled = enabled;
+        self
+    }
+
+    /// Adds a `Relative` column with the total of each function as a multiple of the total
+    /// of the `function` reference, e.g. `3.20x` for a function costing 3.2 times as much.
+    ///
+    /// Useful to communicate relative costs, e.g. in reviews, without absolute numbers that
+    /// depend on the machine. `function` is matched by its full name, like
+    /// `my_crate::db::query`, or by the short name shown in the table. The reference must be
+    /// among the reported functions, otherwise a note is printed instead of the column.
+    ///
+    /// Applies only to the table format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .relative_to("db::query")
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn relative_to(mut self, function: &'static str) -> Self {
+        self.relative_to = Some(function);
+        self
+    }
+
+    /// Writes the table report as plain text, default `false`.
+    ///
+    /// Tables are padded with spaces instead of drawn with borders, and the report contains no
+    /// ANSI styles even when written to a terminal. Suits log collectors that choke on table
+    /// borders, unlike `NO_COLOR`, which only disables colors.
+    ///
+    /// Applies only to the table format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .plain(true)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn plain(mut self, enabled: bool) -> Self {
+        self.plain = enabled;
+        self
+    }
+
+    /// Shows the source location next to the names of measured functions and blocks in the
+    /// table, e.g. `my_crate::parse (src/parser.rs:42)`, to navigate from the report to the code.
+    ///
+    /// Applies to `#[measure]` functions, `measure_block!` and `measure_block_alloc!`. Only the
+    /// table shows locations, names in JSON and the names matched by [`register`](Self::register)
+    /// and `HOTPATH_ONLY` stay unchanged. Off by default to keep names short.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .show_locations(true)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn show_locations(mut self, enabled: bool) -> Self {
+        self.show_locations = enabled;
+        self
+    }
+
+    /// Whether the caller function (e.g. `main`) gets its own row in the report, default `true`.
+    ///
+    /// The caller row spans the whole profiled scope, so in time mode with the default
+    /// [`PercentBasis::Wall`] it always shows `100.00%`. It's used as the basis of `% Total`
+    /// regardless of this setting. In allocation modes the row is always hidden if the guard
+    /// was moved across threads, as its allocations can't be attributed then. Applies to the
+    /// table and JSON output alike, and the row counts towards the [`limit`](Self::limit).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .include_wrapper(false)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn include_wrapper(mut self, include: bool) -> Self {
+        self.include_wrapper = include;
+        self
+    }
+
+    /// Attaches a key-value pair to the report, e.g. the git SHA or hostname of a CI run.
+    ///
+    /// Can be called multiple times, setting an existing key again replaces its value.
+    /// Metadata is included as a `"metadata"` object in the JSON output, including the
+    /// HTTP endpoints, so that archived reports describe the environment they were captured in.
+    /// The table output doesn't show it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::{Format, GuardBuilder};
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .format(Format::Json)
+    ///     .metadata("git_sha", "4f2a91c")
+    ///     .metadata("hostname", "ci-runner-3")
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Prints a report with the configured reporter whenever no measurements arrived for `idle`.
+    ///
+    /// Meant for services running indefinitely: a report is written after each burst of
+    /// activity followed by a quiet period, and profiling continues afterwards. Reports are
+    /// cumulative since the guard was created and don't include the caller function, which is
+    /// only measured when the guard is dropped. The final report is still written on drop.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    /// use std::time::Duration;
+    ///
+    /// let _guard = GuardBuilder::new("server")
+    ///     .report_on_idle(Duration::from_secs(30))
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn report_on_idle(mut self, idle: Duration) -> Self {
+        self.report_on_idle = Some(idle);
+        self
+    }
+
+    /// Caps the number of distinct functions tracked during the session.
+    ///
+    /// Once the cap is reached, measurements of functions seen for the first time are dropped
+    /// instead of creating new entries, a warning is printed and the number of dropped functions
+    /// is shown in the report footer. Protects long-running services from unbounded memory growth
+    /// when names have high cardinality, e.g. with `#[measure(monomorphized)]`.
+    ///
+    /// Default: `0` (unlimited)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .max_functions(1_000)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn max_functions(mut self, max_functions: usize) -> Self {
+        self.max_functions = max_functions;
+        self
+    }
+
+    /// Bounds the time spent computing the final report.
+    ///
+    /// Computing percentiles of thousands of functions can noticeably slow down shutdown.
+    /// Rows are computed in report order (see [`GuardBuilder::sort_by`]) until the deadline
+    /// passes, the remaining functions are left out and their number is shown in the report
+    /// footer. The top function is always reported. Live metrics served over HTTP are not affected.
+    ///
+    /// Default: unbounded
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use std::time::Duration;
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .report_deadline(Duration::from_millis(200))
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn report_deadline(mut self, deadline: Duration) -> Self {
+        self.report_deadline = Some(deadline);
+        self
+    }
+
+    /// Warns that measurements are unreliable if hotpath's own cost exceeds `percent` of the
+    /// total runtime.
+    ///
+    /// Each measured call then also records the time its guard spends outside of the measured
+    /// span, e.g. sending the measurement. If the sum on any single thread exceeds the budget,
+    /// a prominent warning is printed to stderr after the report. It usually means that tiny,
+    /// frequently called functions are measured, and their durations are distorted by the
+    /// profiler. Tracking costs two extra clock reads per call. Time profiling mode only.
+    ///
+    /// Default: disabled
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .overhead_budget(5.0)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn overhead_budget(mut self, percent: f64) -> Self {
+        self.overhead_budget = Some(percent);
+        self
+    }
+
+    /// Pre-registers functions, so that they're reported with 0 calls if never measured.
+    ///
+    /// Names must match measurement names, i.e. `module_path::function_name` for
+    /// `#[measure]` functions and the label for `measure_block!`. Useful to confirm that a
+    /// function was never hit in coverage-style profiling. Combine with `.limit(0)` to make sure
+    /// uncalled functions are not cut off from the report.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .register(&["my_crate::parse", "my_crate::fallback_parse"])
+    ///     .limit(0)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn register(mut self, function_names: &[&'static str]) -> Self {
+        self.registered.extend_from_slice(function_names);
+        self
+    }
+
+    /// Exposes live metrics over HTTP on the given port while the guard is alive.
+    ///
+    /// Serves the same `/metrics` and `/samples` endpoints used by `hotpath console`, plus
+    /// `/metrics/delta` returning only what was measured since the previous `/metrics/delta`
+    /// request. The delta baseline is shared, so concurrent delta consumers interfere with each other.
+    /// `/health` is a cheap liveness probe returning the uptime without querying the stats.
+    /// The `HOTPATH_HTTP_PORT` env variable takes precedence over this setting.
+    /// The server is stopped when the guard is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .http_server(6770)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn http_server(mut self, port: u16) -> Self {
+        self.http_port = Some(port);
+        self
+    }
+
+    /// Serves the [`http_server`](Self::http_server) endpoints over HTTPS, using the PEM encoded
+    /// certificate chain and private key at the given paths.
+    ///
+    /// Makes the metrics endpoint usable beyond localhost without a proxy. The key must be an
+    /// unencrypted PKCS#8 or RSA key. If the files can't be loaded, an error is printed and the
+    /// server is not started. Requires the `hotpath-tls` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # #[cfg(all(feature = "hotpath", feature = "hotpath-tls"))]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .http_server(6770)
+    ///     .http_tls("certs/cert.pem", "certs/key.pem")
+    ///     .build();
+    /// # }
+    /// ```
+    #[cfg(feature = "hotpath-tls")]
+    pub fn http_tls(mut self, cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        self.http_tls = Some((cert_path.into(), key_path.into()));
+        self
+    }
+
+    /// Sets how long the [`http_server`](Self::http_server) waits for the profiling worker to
+    /// answer a `/metrics` or `/samples` request.
+    ///
+    /// A worker busy processing measurements under heavy load may not answer in time. The
+    /// server then responds with the last metrics or samples it successfully fetched, instead
+    /// of empty ones, so that `hotpath console` doesn't flicker. Default: 250ms.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use std::time::Duration;
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .http_server(6770)
+    ///     .http_query_timeout(Duration::from_secs(1))
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn http_query_timeout(mut self, timeout: Duration) -> Self {
+        self.http_query_timeout = timeout;
+        self
+    }
+
+    /// Disables the report printed when the guard is dropped.
+    ///
+    /// Useful when metrics are only consumed live, e.g. via [`GuardBuilder::http_server`].
+    /// Overrides any previously configured format or reporter.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .silent()
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn silent(mut self) -> Self {
+        self.reporter = ReporterConfig::Custom(Box::new(output::SilentReporter));
+        self
+    }
+
+    /// Sets the order of report rows, default [`SortBy::Total`].
+    ///
+    /// With [`SortBy::Max`] functions are ordered by their largest single call, and
+    /// [`limit`](Self::limit) keeps the ones with the biggest spikes. Add the `100.0` percentile
+    /// to show the maximum as a column.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::{GuardBuilder, SortBy};
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .sort_by(SortBy::Max)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn sort_by(mut self, sort_by: SortBy) -> Self {
+        self.sort_by = sort_by;
+        self
+    }
+
+    /// Sets the minimum size in bytes of allocations whose backtrace is captured, default 1 MB.
+    ///
+    /// Only used with the `hotpath-alloc-backtrace` feature, which captures a backtrace of every
+    /// allocation at or above the threshold inside measured functions whenever it's the largest
+    /// one of the current call so far. The largest allocation of each displayed function is
+    /// printed with its backtrace below the table. Capturing a backtrace takes microseconds,
+    /// so a low threshold can slow the program down by orders of magnitude.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .alloc_backtrace_threshold(64 * 1024)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn alloc_backtrace_threshold(mut self, bytes: u64) -> Self {
+        self.alloc_backtrace_threshold = bytes;
+        self
+    }
+
+    /// Writes the report to stderr instead of stdout, default `false`.
+    ///
+    /// Useful for tools that reserve stdout for their actual output. Applies to all built-in
+    /// formats and to custom reporters implementing [`Reporter::write_report`], table colors
+    /// are then enabled based on whether stderr is a terminal.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .to_stderr(true)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn to_stderr(mut self, to_stderr: bool) -> Self {
+        self.to_stderr = to_stderr;
+        self
+    }
+
+    /// Aggregates measurements inline instead of on a background thread, default `false`.
+    ///
+    /// By default each dropped guard sends its measurement over a channel to a worker thread.
+    /// Inline aggregation records it directly into shared stats behind a mutex instead, so no
+    /// thread is spawned and every measurement is recorded by the time its guard is dropped.
+    /// This suits short single-threaded tools, while multi-threaded programs pay for it with
+    /// lock contention. [`GuardBuilder::report_on_idle`] has no effect in this mode.
+    ///
+    /// With the `hotpath-inline` feature the worker thread isn't compiled at all and
+    /// aggregation is always inline, for targets where spawning threads isn't acceptable.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .inline(true)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn inline(mut self, inline: bool) -> Self {
+        self.inline = inline;
+        self
+    }
+
+    /// Writes the report when the program calls [`std::process::exit`] before the guard is
+    /// dropped, default `false`.
+    ///
+    /// `exit` skips destructors, so without this option CLIs that exit early produce no report.
+    /// The report is written from an `atexit` hook instead, with the caller function measured
+    /// until the exit. The hook runs on a helper thread, so in memory profiling modes the top
+    /// level measurement is disabled, like with [`GuardBuilder::build_with_timeout`]. Only
+    /// supported on Unix, a no-op elsewhere.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .report_atexit(true)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn report_atexit(mut self, report_atexit: bool) -> Self {
+        self.report_atexit = report_atexit;
+        self
+    }
+
+    /// Waits for the live guard to be dropped instead of panicking, when another guard is
+    /// already alive, default `false`.
+    ///
+    /// Only one guard can be alive at a time, so tests creating their own guard can't run in
+    /// parallel. With this option their guards are serialized: each test blocks in
+    /// [`GuardBuilder::build`] until the previous guard is dropped, so the remaining tests still
+    /// run in parallel without `--test-threads=1`. Measured code should run only while the
+    /// test's guard is alive, otherwise it's recorded by the guard of another test. Creating
+    /// a second guard on the thread owning the live one still panics.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("test_parse")
+    ///     .wait_for_guard(true)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn wait_for_guard(mut self, enabled: bool) -> Self {
+        self.wait_for_guard = enabled;
+        self
+    }
+
+    /// Embeds the git commit of the profiled code in JSON reports as `commit`, default `false`.
+    ///
+    /// The commit is read once when the guard is created, with `git rev-parse HEAD`, or from
+    /// the `GITHUB_SHA` env variable if that fails, e.g. in a GitHub Actions job without a
+    /// checkout. It's omitted if neither is available. Makes stored reports traceable to the
+    /// exact code version, `hotpath profile-pr` shows the commits of both compared reports.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::{Format, GuardBuilder};
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .format(Format::Json)
+    ///     .record_commit(true)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn record_commit(mut self, enabled: bool) -> Self {
+        self.record_commit = enabled;
+        self
+    }
+
+    /// Calls `callback` with the function name and duration of every call slower than
+    /// `threshold`, as soon as the measurement is processed.
+    ///
+    /// Useful as a tripwire for slow operations, e.g. logging a slow query while the program
+    /// runs instead of only seeing it in the final report. The callback runs on the worker
+    /// thread, or on the measured thread with [`GuardBuilder::inline`] after the stats lock is
+    /// released, so it should return quickly, but may call measured functions. The caller
+    /// function itself is not checked. Only supported in time profiling mode, a no-op in memory
+    /// profiling modes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use std::time::Duration;
+    /// use hotpath::GuardBuilder;
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .on_slow(
+    ///         Duration::from_millis(100),
+    ///         Box::new(|name, duration| eprintln!("slow call: {name} took {duration:?}")),
+    ///     )
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn on_slow(mut self, threshold: Duration, callback: SlowCallback) -> Self {
+        self.on_slow = Some((threshold, callback));
+        self
+    }
+
+    /// Reads the time from `clock` instead of the system clock, to assert exact durations
+    /// in tests.
+    ///
+    /// Applies to durations of measured calls and the total elapsed time of the session, the
+    /// calibrated guard overhead isn't subtracted from them. The clock is called from every
+    /// measuring thread and stays in use until the guard is dropped. Time profiling mode
+    /// reports the durations, allocation modes only the total elapsed time.
+    ///
+    /// Default: the system clock
     ///
     /// # Examples
     ///
     /// ```rust
     /// # #[cfg(feature = "hotpath")]
     /// # {
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    /// use std::sync::Arc;
+    /// use std::time::{Duration, Instant};
     /// use hotpath::GuardBuilder;
     ///
-    /// let _guard = GuardBuilder::new("my_program").build();
+    /// let base = Instant::now();
+    /// let offset_ms = Arc::new(AtomicU64::new(0));
+    /// let clock_offset = Arc::clone(&offset_ms);
+    ///
+    /// let _guard = GuardBuilder::new("main")
+    ///     .clock(Box::new(move || {
+    ///         base + Duration::from_millis(clock_offset.load(Ordering::Relaxed))
+    ///     }))
+    ///     .build();
+    /// // Advance the clock by 10ms
+    /// offset_ms.fetch_add(10, Ordering::Relaxed);
     /// # }
     /// ```
-    pub fn new(caller_name: &'static str) -> Self {
-        Self {
-            caller_name,
-            percentiles: vec![95],
-            reporter: ReporterConfig::None,
-            limit: 15,
-        }
+    pub fn clock(mut self, clock: ClockFn) -> Self {
+        self.clock = Some(clock);
+        self
     }
 
-    /// Sets the percentiles to display in the profiling report.
-    ///
-    /// Percentiles help identify performance distribution patterns across multiple
-    /// measurements of the same function. Valid values are 0-100, where 0 represents
-    /// the minimum value and 100 represents the maximum.
-    ///
-    /// Default: `[95]`
+    /// Sets the output format for the profiling report.
     ///
     /// # Arguments
     ///
-    /// * `percentiles` - Slice of percentile values (0-100) to display
+    /// * `format` - The output format (Table, Json, or JsonPretty)
     ///
     /// # Examples
     ///
     /// ```rust
     /// # #[cfg(feature = "hotpath")]
     /// # {
-    /// use hotpath::GuardBuilder;
+    /// use hotpath::{GuardBuilder, Format};
     ///
     /// let _guard = GuardBuilder::new("main")
-    ///     .percentiles(&[50, 90, 95, 99])
+    ///     .format(Format::JsonPretty)
     ///     .build();
     /// # }
     /// ```
-    pub fn percentiles(mut self, percentiles: &[u8]) -> Self {
-        self.percentiles = percentiles.to_vec();
+    ///
+    /// # See Also
+    ///
+    /// * [`Format`] - Available output formats
+    pub fn format(mut self, format: Format) -> Self {
+        self.reporter = ReporterConfig::Format(format);
         self
     }
 
-    /// Sets the maximum number of functions to display in the profiling report.
+    /// Sets how `percent_total` values are written in JSON reports, default
+    /// [`PercentFormat::BasisPoints`].
     ///
-    /// The report will show only the top N functions sorted by total execution time
-    /// (or total allocations when using allocation profiling features).
+    /// Basis points are integer hundredths of a percent, so `9383` means 93.83%.
+    /// [`PercentFormat::Float`] writes the same value as `93.83`. The `hotpath` CLI and
+    /// [`MetricsJson`] deserialization accept both. Applies to the `Json` and `JsonPretty`
+    /// formats, the HTTP endpoints always use basis points.
     ///
-    /// Default: `15`
+    /// # Examples
     ///
-    /// # Arguments
+    /// ```rust
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// use hotpath::{Format, GuardBuilder, PercentFormat};
     ///
-    /// * `limit` - Maximum number of functions to display (0 means show all)
+    /// let _guard = GuardBuilder::new("main")
+    ///     .format(Format::Json)
+    ///     .percent_format(PercentFormat::Float)
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn percent_format(mut self, percent_format: PercentFormat) -> Self {
+        self.percent_format = percent_format;
+        self
+    }
+
+    /// Adds a `distribution` array to each function of JSON reports, with its values at every
+    /// tenth percentile from P10 to P100.
+    ///
+    /// Gives consumers enough of the shape of each distribution to draw a small histogram or
+    /// sparkline without the full histogram. Values are in the unit of the profiling mode, like
+    /// percentiles. Applies to the `Json` and `JsonPretty` formats.
+    ///
+    /// **Default:** `false`
     ///
     /// # Examples
     ///
     /// ```rust
     /// # #[cfg(feature = "hotpath")]
     /// # {
-    /// use hotpath::GuardBuilder;
+    /// use hotpath::{Format, GuardBuilder};
     ///
     /// let _guard = GuardBuilder::new("main")
-    ///     .limit(20)
+    ///     .format(Format::Json)
+    ///     .include_distribution(true)
     ///     .build();
     /// # }
     /// ```
-    pub fn limit(mut self, limit: usize) -> Self {
-        self.limit = limit;
+    pub fn include_distribution(mut self, enabled: bool) -> Self {
+        self.include_distribution = enabled;
         self
     }
 
-    /// Sets the output format for the profiling report.
+    /// Sets how byte values are rounded in reports and in [`MetricDiff`](crate::MetricDiff) output.
     ///
-    /// # Arguments
+    /// `ByteFormat::Exact` prints plain byte counts and `ByteFormat::Precise(n)` keeps `n`
+    /// decimal places. JSON reports always contain raw byte counts.
     ///
-    /// * `format` - The output format (Table, Json, or JsonPretty)
+    /// **Default:** `ByteFormat::Human` (one decimal place, e.g. `2.0 KB`)
     ///
     /// # Examples
     ///
     /// ```rust
     /// # #[cfg(feature = "hotpath")]
     /// # {
-    /// use hotpath::{GuardBuilder, Format};
+    /// use hotpath::{ByteFormat, GuardBuilder};
     ///
     /// let _guard = GuardBuilder::new("main")
-    ///     .format(Format::JsonPretty)
+    ///     .byte_format(ByteFormat::Precise(3))
     ///     .build();
     /// # }
     /// ```
-    ///
-    /// # See Also
-    ///
-    /// * [`Format`] - Available output formats
-    pub fn format(mut self, format: Format) -> Self {
-        self.reporter = ReporterConfig::Format(format);
+    pub fn byte_format(mut self, byte_format: ByteFormat) -> Self {
+        self.byte_format = byte_format;
         self
     }
 
@@ -407,11 +2454,15 @@ impl GuardBuilder {
     /// ```rust
     /// # #[cfg(feature = "hotpath")]
     /// # {
-    /// use hotpath::{GuardBuilder, Reporter, MetricsProvider};
+    /// use hotpath::{GuardBuilder, MetricsProvider, ReportError, Reporter};
     ///
     /// struct CsvReporter;
     /// impl Reporter for CsvReporter {
-    ///     fn report(&self, metrics: &dyn MetricsProvider<'_>) -> Result<(), Box<dyn std::error::Error>> {
+    ///     fn write_report(
+    ///         &self,
+    ///         metrics: &dyn MetricsProvider<'_>,
+    ///         _out: &mut dyn std::io::Write,
+    ///     ) -> Result<(), ReportError> {
     ///         // Write metrics to CSV file
     ///         Ok(())
     ///     }
@@ -453,28 +2504,7 @@ impl GuardBuilder {
     /// # }
     /// ```
     pub fn build(self) -> HotPath {
-        let reporter: Box<dyn Reporter> = match self.reporter {
-            ReporterConfig::Format(format) => match format {
-                Format::Table => Box::new(output::TableReporter),
-                Format::Json => Box::new(output::JsonReporter),
-                Format::JsonPretty => Box::new(output::JsonPrettyReporter),
-            },
-            ReporterConfig::Custom(reporter) => reporter,
-            ReporterConfig::None => Box::new(output::TableReporter),
-        };
-
-        let recent_samples_limit = std::env::var("HOTPATH_RECENT_SAMPLES")
-            .ok()
-            .and_then(|s| s.parse::<usize>().ok())
-            .unwrap_or(50);
-
-        HotPath::new(
-            self.caller_name,
-            &self.percentiles,
-            self.limit,
-            reporter,
-            recent_samples_limit,
-        )
+        HotPath::new(self)
     }
 
     /// Builds the hotpath profiling guard and automatically drops it after the specified duration and exits the program.
@@ -502,7 +2532,7 @@ impl GuardBuilder {
     /// }
     /// # }
     /// ```
-    pub fn build_with_timeout(self, duration: std::time::Duration) {
+    pub fn build_with_timeout(self, duration: Duration) {
         let guard = self.build();
         thread::spawn(move || {
             thread::sleep(duration);
@@ -512,162 +2542,589 @@ impl GuardBuilder {
     }
 }
 
-impl HotPath {
-    pub fn new(
-        caller_name: &'static str,
-        percentiles: &[u8],
-        limit: usize,
-        _reporter: Box<dyn Reporter>,
-        recent_samples_limit: usize,
-    ) -> Self {
-        let percentiles = percentiles.to_vec();
+/// Starts profiling and exposes live metrics over HTTP on the given port, without printing a report.
+///
+/// Shorthand for `GuardBuilder::new("serve_metrics").silent().http_server(port).build()`,
+/// for the common case of only exposing metrics to `hotpath console` or other HTTP clients.
+/// Dropping the returned guard stops profiling and the server.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "hotpath")]
+/// # {
+/// let metrics = hotpath::serve_metrics(6770);
+/// // ... run the workload ...
+/// drop(metrics);
+/// # }
+/// ```
+pub fn serve_metrics(port: u16) -> HotPath {
+    GuardBuilder::new("serve_metrics")
+        .silent()
+        .http_server(port)
+        .build()
+}
 
-        let arc_swap = HOTPATH_STATE.get_or_init(|| ArcSwapOption::from(None));
+/// `table_reporter`, or a comparison against the baseline file if one was configured and could
+/// be loaded.
+fn table_reporter(
+    baseline: Option<PathBuf>,
+    table_reporter: output::TableReporter,
+) -> Box<dyn Reporter> {
+    let Some(path) = baseline else {
+        return Box::new(table_reporter);
+    };
 
-        if arc_swap.load().is_some() {
-            panic!("More than one _hotpath guard cannot be alive at the same time.");
-        }
+    let loaded = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str::<MetricsJson>(&json).map_err(|e| e.to_string()))
+        .and_then(|baseline| {
+            // Values of different modes aren't comparable, e.g. nanoseconds against bytes
+            let mode = MetricsJson::determine_profiling_mode();
+            if baseline.hotpath_profiling_mode == mode {
+                Ok(baseline)
+            } else {
+                Err(format!(
+                    "it was recorded in {} mode, not {}",
+                    baseline.hotpath_profiling_mode, mode
+                ))
+            }
+        });
 
-        let (tx, rx) = unbounded::<Measurement>();
-        let (shutdown_tx, shutdown_rx) = bounded::<()>(1);
-        let (completion_tx, completion_rx) = bounded::<HashMap<&'static str, FunctionStats>>(1);
-        let (query_tx, query_rx) = unbounded::<QueryRequest>();
-        let start_time = Instant::now();
+    match loaded {
+        Ok(baseline) => Box::new(crate::compare::BaselineReporter {
+            baseline,
+            baseline_name: path.display().to_string(),
+            byte_format: table_reporter.byte_format,
+            to_stderr: table_reporter.to_stderr,
+            plain: table_reporter.plain,
+        }),
+        Err(e) => {
+            eprintln!(
+                "[hotpath] Warning: failed to load baseline {}: {}",
+                path.display(),
+                e
+            );
+            Box::new(table_reporter)
+        }
+    }
+}
 
-        let state_arc = Arc::new(RwLock::new(HotPathState {
-            sender: Some(tx),
-            shutdown_tx: Some(shutdown_tx),
-            completion_rx: Some(Mutex::new(completion_rx)),
-            query_tx: Some(query_tx),
-            start_time,
+impl HotPath {
+    /// Starts profiling with the configuration of `config`, see [`GuardBuilder::build`].
+    pub(crate) fn new(config: GuardBuilder) -> Self {
+        let GuardBuilder {
             caller_name,
-            percentiles: percentiles.clone(),
+            percentiles,
+            reporter,
             limit,
-        }));
+            percent_basis,
+            raw_sink,
+            raw_samples_file,
+            chrome_trace_file,
+            track_process_peak,
+            baseline,
+            crate_rollup,
+            categorize,
+            highlight_threshold,
+            show_trimmed_mean,
+            show_uncalled,
+            relative_to,
+            plain,
+            percent_format,
+            include_distribution,
+            byte_format,
+            http_port,
+            http_tls,
+            http_query_timeout,
+            max_functions,
+            report_deadline,
+            overhead_budget,
+            registered,
+            show_locations,
+            include_wrapper,
+            metadata,
+            report_on_idle,
+            to_stderr,
+            sort_by,
+            alloc_backtrace_threshold: _alloc_backtrace_threshold,
+            inline,
+            report_atexit,
+            wait_for_guard,
+            record_commit,
+            on_slow,
+            clock,
+        } = config;
 
-        let worker_start_time = start_time;
-        let worker_percentiles = percentiles.clone();
-        let worker_caller_name = caller_name;
-        let worker_limit = limit;
-        let worker_recent_samples_limit = recent_samples_limit;
-
-        thread::Builder::new()
-            .name("hotpath-worker".into())
-            .spawn(move || {
-                let mut local_stats = HashMap::<&'static str, FunctionStats>::new();
-
-                loop {
-                    select! {
-                        recv(rx) -> result => {
-                            match result {
-                                Ok(measurement) => {
-                                    process_measurement(&mut local_stats, measurement, worker_recent_samples_limit);
-                                }
-                                Err(_) => break, // Channel disconnected
-                            }
-                        }
-                        recv(shutdown_rx) -> _ => {
-                            // Process remaining messages after shutdown signal
-                            while let Ok(measurement) = rx.try_recv() {
-                                process_measurement(&mut local_stats, measurement, worker_recent_samples_limit);
-                            }
-                            break;
-                        }
-                        recv(query_rx) -> result => {
-                            if let Ok(query_request) = result {
-                                match query_request {
-                                    QueryRequest::GetMetrics(response_tx) => {
-                                        // Create metrics snapshot
-                                        use output::MetricsProvider;
-                                        let total_elapsed = worker_start_time.elapsed();
-                                        let metrics_provider = StatsData::new(
-                                            &local_stats,
-                                            total_elapsed,
-                                            worker_percentiles.clone(),
-                                            worker_caller_name,
-                                            worker_limit,
-                                        );
-                                        let metrics_json = MetricsJson::from(&metrics_provider as &dyn MetricsProvider);
-                                        let _ = response_tx.send(metrics_json);
-                                    }
-                                    QueryRequest::GetSamples { function_name, response_tx } => {
-                                        let response = if let Some(stats) = local_stats.get(function_name.as_str()) {
-                                            let samples: Vec<(u64, u64)> = stats.recent_samples
-                                                .iter()
-                                                .rev()
-                                                .map(|(val, elapsed)| (*val, elapsed.as_nanos() as u64))
-                                                .collect();
-                                            Some(SamplesJson {
-                                                function_name,
-                                                samples,
-                                                count: stats.recent_samples.len(),
-                                            })
-                                        } else {
-                                            None
-                                        };
-                                        let _ = response_tx.send(response);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+        let reporter: Box<dyn Reporter> = match reporter {
+            ReporterConfig::Format(Format::Json) => Box::new(output::JsonReporter {
+                percent_format,
+                include_distribution,
+            }),
+            ReporterConfig::Format(Format::JsonPretty) => Box::new(output::JsonPrettyReporter {
+                percent_format,
+                include_distribution,
+            }),
+            ReporterConfig::Custom(reporter) => reporter,
+            ReporterConfig::Format(Format::Table) | ReporterConfig::None => table_reporter(
+                baseline,
+                output::TableReporter {
+                    crate_rollup,
+                    categorize,
+                    highlight_threshold,
+                    show_trimmed_mean,
+                    show_uncalled,
+                    relative_to,
+                    byte_format,
+                    to_stderr,
+                    plain,
+                },
+            ),
+        };
 
-                // Send stats via completion channel
-                let _ = completion_tx.send(local_stats);
-            })
-            .expect("Failed to spawn hotpath-worker thread");
+        let recent_samples_limit = std::env::var("HOTPATH_RECENT_SAMPLES")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(50);
 
-        arc_swap.store(Some(Arc::clone(&state_arc)));
+        let slot = acquire_guard(wait_for_guard);
+        let arc_swap = HOTPATH_STATE.get_or_init(|| ArcSwapOption::from(None));
 
-        // Start HTTP metrics server if HOTPATH_HTTP_PORT is set
-        if let Ok(port_str) = std::env::var("HOTPATH_HTTP_PORT") {
-            if let Ok(port) = port_str.parse::<u16>() {
-                crate::http_server::start_metrics_server(port);
-            }
+        SHOW_LOCATIONS.store(show_locations, Ordering::Relaxed);
+        if let Some(locations) = LOCATIONS.get() {
+            locations.write().unwrap().clear();
+        }
+        if let Ok(patterns) = std::env::var("HOTPATH_ONLY") {
+            filter::set(&patterns);
         }
+        recursion::clear();
+        io::clear();
+        phases::clear();
+        values::clear();
+        commit::record(record_commit);
+        // A custom clock doesn't measure the guards' actual cost
+        overhead::reset(
+            overhead_budget.is_some()
+                && clock.is_none()
+                && !cfg!(any(
+                    feature = "hotpath-alloc-bytes-total",
+                    feature = "hotpath-alloc-count-total",
+                    feature = "hotpath-alloc-leak"
+                )),
+        );
+        #[cfg(feature = "hotpath-alloc-bytes-total")]
+        alloc_bytes_total::backtrace::set_threshold(_alloc_backtrace_threshold);
+
+        // Measure the guard's own cost so it can be subtracted from recorded durations
+        #[cfg(not(any(
+            feature = "hotpath-alloc-bytes-total",
+            feature = "hotpath-alloc-count-total",
+            feature = "hotpath-alloc-leak"
+        )))]
+        time::guard::calibrate_overhead();
+
+        #[cfg(not(any(
+            feature = "hotpath-alloc-bytes-total",
+            feature = "hotpath-alloc-count-total",
+            feature = "hotpath-alloc-leak"
+        )))]
+        time::polls::reset();
+
+        #[cfg(all(
+            feature = "hotpath-alloc-blocks",
+            not(any(
+                feature = "hotpath-alloc-bytes-total",
+                feature = "hotpath-alloc-count-total",
+                feature = "hotpath-alloc-leak"
+            ))
+        ))]
+        time::alloc_block::reset();
+
+        clock::set(clock);
+        let start_time = clock::now();
 
         // Override reporter with JsonReporter when HOTPATH_JSON env var is enabled
-        let reporter: Box<dyn Reporter> = if std::env::var("HOTPATH_JSON")
+        let reporter: Arc<dyn Reporter> = if std::env::var("HOTPATH_JSON")
             .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
             .unwrap_or(false)
         {
-            Box::new(output::JsonReporter)
+            Arc::new(output::JsonReporter::default())
+        } else {
+            Arc::from(reporter)
+        };
+        let raw_samples = raw_samples_file.and_then(|path| {
+            raw_samples::RawSamplesWriter::create(path.clone())
+                .map_err(|e| {
+                    eprintln!(
+                        "[hotpath] Failed to create raw samples file {}: {}",
+                        path.display(),
+                        e
+                    )
+                })
+                .ok()
+        });
+        let chrome_trace = chrome_trace_file.and_then(|path| {
+            if cfg!(any(
+                feature = "hotpath-alloc-bytes-total",
+                feature = "hotpath-alloc-count-total",
+                feature = "hotpath-alloc-leak"
+            )) {
+                eprintln!(
+                    "[hotpath] Chrome trace is only supported in time profiling mode, {} is not written",
+                    path.display()
+                );
+                return None;
+            }
+            chrome_trace::ChromeTraceWriter::create(path.clone())
+                .map_err(|e| {
+                    eprintln!(
+                        "[hotpath] Failed to create Chrome trace file {}: {}",
+                        path.display(),
+                        e
+                    )
+                })
+                .ok()
+        });
+
+        chrome_trace::set_enabled(chrome_trace.is_some());
+
+        // HOTPATH_HTTP_PORT takes precedence over the configured port
+        let http_port = std::env::var("HOTPATH_HTTP_PORT")
+            .ok()
+            .and_then(|port_str| port_str.parse::<u16>().ok())
+            .or(http_port);
+
+        let mut stats = HashMap::<&'static str, FunctionStats>::with_capacity(registered.len());
+        for name in registered {
+            stats.insert(name, FunctionStats::new_registered(recent_samples_limit));
+        }
+        let aggregator = Aggregator {
+            stats,
+            cardinality: CardinalityLimit::new(max_functions, caller_name),
+            raw_sink,
+            raw_samples,
+            chrome_trace,
+            recent_samples_limit,
+            start_time,
+            percentiles: percentiles.clone(),
+            caller_name,
+            limit,
+            percent_basis,
+            include_wrapper,
+            sort_by,
+            metadata: metadata.clone(),
+            delta_snapshot: HashMap::new(),
+            delta_start: start_time,
+            on_slow: on_slow.map(|(threshold, callback)| (threshold, Arc::new(callback))),
+            call_rates: http_port.map(|_| rates::CallRates::default()),
+        };
+        let dropped_functions = Arc::new(AtomicUsize::new(0));
+
+        #[cfg(feature = "hotpath-inline")]
+        let inline = {
+            let _ = (inline, report_on_idle);
+            true
+        };
+        let (sender, query_tx, shutdown_tx, completion_rx, inline) = if inline {
+            let aggregator = Arc::new(Mutex::new(aggregator));
+            (
+                MeasurementSender::Inline(Arc::clone(&aggregator)),
+                QuerySender::Inline(Arc::clone(&aggregator)),
+                None,
+                None,
+                Some(aggregator),
+            )
         } else {
-            _reporter
+            #[cfg(not(feature = "hotpath-inline"))]
+            {
+                let worker = worker::spawn(
+                    aggregator,
+                    Arc::clone(&reporter),
+                    Arc::clone(&dropped_functions),
+                    report_on_idle,
+                    track_process_peak,
+                    to_stderr,
+                );
+                (
+                    MeasurementSender::Worker(worker.tx),
+                    QuerySender::Worker(worker.query_tx),
+                    Some(worker.shutdown_tx),
+                    Some(Mutex::new(worker.completion_rx)),
+                    None,
+                )
+            }
+            #[cfg(feature = "hotpath-inline")]
+            unreachable!("aggregation is always inline with hotpath-inline")
         };
 
+        let state_arc = Arc::new(RwLock::new(HotPathState {
+            sender: Some(sender),
+            shutdown_tx,
+            completion_rx,
+            query_tx: Some(query_tx),
+            start_time,
+            caller_name,
+            percentiles: percentiles.clone(),
+            limit,
+            percent_basis,
+        }));
+
+        arc_swap.store(Some(Arc::clone(&state_arc)));
+
+        // Start HTTP metrics server
+        let http_server = http_port.and_then(|port| {
+            crate::http_server::start_metrics_server(port, http_tls.as_ref(), http_query_timeout)
+        });
+
         let wrapper_guard = MeasurementGuard::build(caller_name, true, false);
 
-        Self {
+        let finalizer = Arc::new(Mutex::new(Some(Finalizer {
             state: Arc::clone(&state_arc),
             reporter,
             wrapper_guard: Some(wrapper_guard),
+            track_process_peak,
+            include_wrapper,
+            metadata,
+            to_stderr,
+            sort_by,
+            report_deadline,
+            overhead_budget,
+            http_server,
+            dropped_functions,
+            inline,
+        })));
+        if report_atexit {
+            atexit::register(&finalizer);
+        }
+
+        slot.hand_over();
+        Self {
+            state: state_arc,
+            finalizer,
+            byte_format,
+        }
+    }
+
+    /// Renders the results collected so far as a table, without printing them.
+    ///
+    /// Useful to place the report in a host application, e.g. a TUI panel. Measurements
+    /// still in flight are processed first. The text is never styled, and the caller
+    /// function itself is not included, since it's only measured when the guard is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # #[cfg(feature = "hotpath")]
+    /// # {
+    /// let guard = hotpath::GuardBuilder::new("main").build();
+    /// // ... run the workload ...
+    /// let report = guard.render_table();
+    /// # }
+    /// ```
+    pub fn render_table(&self) -> String {
+        let byte_format = self.byte_format;
+        self.render(move |metrics_provider, out| {
+            output::TableReporter {
+                crate_rollup: CrateRollup::Off,
+                categorize: None,
+                highlight_threshold: None,
+                show_trimmed_mean: false,
+                show_uncalled: false,
+                relative_to: None,
+                byte_format,
+                to_stderr: false,
+                plain: false,
+            }
+            .write_report(metrics_provider, out)
+        })
+    }
+
+    /// Renders the results collected so far as JSON in the [`MetricsJson`] format,
+    /// without printing them. See [`HotPath::render_table`].
+    pub fn render_json(&self) -> String {
+        self.render(|metrics_provider, out| {
+            output::JsonReporter::default().write_report(metrics_provider, out)
+        })
+        .trim_end()
+        .to_string()
+    }
+
+    /// Renders current metrics, returns an empty string if profiling has already stopped.
+    fn render(
+        &self,
+        write: impl FnOnce(&dyn MetricsProvider<'_>, &mut dyn std::io::Write) -> Result<(), ReportError>
+            + Send
+            + 'static,
+    ) -> String {
+        let Some(query_tx) = self
+            .state
+            .read()
+            .ok()
+            .and_then(|state| state.query_tx.clone())
+        else {
+            return String::new();
+        };
+        #[cfg(feature = "hotpath-batch-channel")]
+        batch::flush_all();
+
+        let (response_tx, response_rx) = bounded::<String>(1);
+        let render = move |metrics_provider: &dyn MetricsProvider<'_>| {
+            let mut out = Vec::new();
+            let _ = write(metrics_provider, &mut out);
+            let _ = response_tx.send(output::strip_ansi(&String::from_utf8_lossy(&out)));
+        };
+
+        if !query_tx.send(QueryRequest::Render(Box::new(render))) {
+            return String::new();
+        }
+        response_rx.recv().unwrap_or_default()
+    }
+}
+
+/// Destination of measurements sent by dropped guards.
+pub(crate) enum MeasurementSender {
+    #[cfg(not(feature = "hotpath-inline"))]
+    Worker(Sender<worker::WorkerMessage>),
+    Inline(Arc<Mutex<Aggregator>>),
+}
+
+impl MeasurementSender {
+    #[inline]
+    pub(crate) fn send(&self, measurement: Measurement) {
+        match self {
+            #[cfg(not(feature = "hotpath-inline"))]
+            Self::Worker(tx) => {
+                #[cfg(feature = "hotpath-batch-channel")]
+                batch::push(tx, measurement);
+                #[cfg(not(feature = "hotpath-batch-channel"))]
+                let _ = tx.try_send(measurement);
+            }
+            Self::Inline(aggregator) => {
+                let slow = aggregator
+                    .lock()
+                    .ok()
+                    .and_then(|mut aggregator| aggregator.record(measurement));
+                // Outside of the lock, see `SlowCall`
+                if let Some(slow) = slow {
+                    slow.notify();
+                }
+            }
+        }
+    }
+}
+
+/// Destination of [`QueryRequest`]s, answered right away in inline mode.
+#[derive(Clone)]
+pub(crate) enum QuerySender {
+    #[cfg(not(feature = "hotpath-inline"))]
+    Worker(Sender<QueryRequest>),
+    Inline(Arc<Mutex<Aggregator>>),
+}
+
+impl QuerySender {
+    /// Returns false if the query can no longer be answered.
+    pub(crate) fn send(&self, query_request: QueryRequest) -> bool {
+        match self {
+            #[cfg(not(feature = "hotpath-inline"))]
+            Self::Worker(tx) => tx.send(query_request).is_ok(),
+            Self::Inline(aggregator) => {
+                let Ok(mut aggregator) = aggregator.lock() else {
+                    return false;
+                };
+                cfg_if::cfg_if! {
+                    if #[cfg(any(
+                        feature = "hotpath-alloc-bytes-total",
+                        feature = "hotpath-alloc-count-total",
+                        feature = "hotpath-alloc-leak"
+                    ))] {
+                        // Answering allocates, which must not count towards the calling function
+                        alloc::shared::without_tracking(|| aggregator.answer(query_request));
+                    } else {
+                        aggregator.answer(query_request);
+                    }
+                }
+                true
+            }
         }
     }
 }
 
+/// Writes a report to stdout or stderr, printing errors other than a closed stream to stderr.
+fn write_report(
+    reporter: &dyn Reporter,
+    metrics_provider: &dyn MetricsProvider<'_>,
+    to_stderr: bool,
+) {
+    let result = if to_stderr {
+        reporter.write_report(metrics_provider, &mut std::io::stderr().lock())
+    } else {
+        reporter.write_report(metrics_provider, &mut std::io::stdout().lock())
+    };
+
+    match result {
+        Ok(()) => (),
+        // Stream closed early, e.g. piped to `head`, nobody reads the report
+        Err(ReportError::Io(e)) if e.kind() == std::io::ErrorKind::BrokenPipe => {}
+        Err(e) => eprintln!("Failed to report hotpath metrics: {}", e),
+    }
+}
+
 pub struct HotPath {
     state: Arc<RwLock<HotPathState>>,
-    reporter: Box<dyn Reporter>,
+    finalizer: Arc<Mutex<Option<Finalizer>>>,
+    /// Rounding of byte values in [`HotPath::render_table`]
+    byte_format: ByteFormat,
+}
+
+/// Writes the final report, once the guard is dropped or at exit, see
+/// [`GuardBuilder::report_atexit`].
+pub(crate) struct Finalizer {
+    state: Arc<RwLock<HotPathState>>,
+    reporter: Arc<dyn Reporter>,
     wrapper_guard: Option<MeasurementGuard>,
+    track_process_peak: bool,
+    include_wrapper: bool,
+    metadata: BTreeMap<String, String>,
+    to_stderr: bool,
+    sort_by: SortBy,
+    report_deadline: Option<Duration>,
+    overhead_budget: Option<f64>,
+    http_server: Option<crate::http_server::MetricsServer>,
+    dropped_functions: Arc<AtomicUsize>,
+    inline: Option<Arc<Mutex<Aggregator>>>,
 }
 
 impl Drop for HotPath {
     fn drop(&mut self) {
+        let finalizer = self
+            .finalizer
+            .lock()
+            .ok()
+            .and_then(|mut finalizer| finalizer.take());
+        if let Some(finalizer) = finalizer {
+            finalizer.finish();
+        }
+    }
+}
+
+impl Finalizer {
+    pub(crate) fn finish(mut self) {
         let wrapper_guard = self.wrapper_guard.take().unwrap();
         drop(wrapper_guard);
+        #[cfg(feature = "hotpath-batch-channel")]
+        batch::flush_all();
 
         let state: Arc<RwLock<HotPathState>> = Arc::clone(&self.state);
 
         // Signal shutdown and wait for processing thread to complete
         let (shutdown_tx, completion_rx, end_time) = {
             let Ok(mut state_guard) = state.write() else {
+                release_guard();
                 return;
             };
 
             state_guard.sender = None;
-            let end_time = Instant::now();
+            let end_time = clock::now();
 
             let shutdown_tx = state_guard.shutdown_tx.take();
             let completion_rx = state_guard.completion_rx.take();
@@ -678,31 +3135,62 @@ impl Drop for HotPath {
             let _ = tx.send(());
         }
 
-        if let Some(rx_mutex) = completion_rx {
-            if let Ok(rx) = rx_mutex.lock() {
-                if let Ok(stats) = rx.recv() {
-                    if let Ok(state_guard) = state.read() {
-                        let total_elapsed = end_time.duration_since(state_guard.start_time);
-                        let metrics_provider = StatsData::new(
-                            &stats,
-                            total_elapsed,
-                            state_guard.percentiles.clone(),
-                            state_guard.caller_name,
-                            state_guard.limit,
-                        );
-
-                        match self.reporter.report(&metrics_provider) {
-                            Ok(()) => (),
-                            Err(e) => eprintln!("Failed to report hotpath metrics: {}", e),
-                        }
-                    }
+        let stats = match &self.inline {
+            Some(aggregator) => aggregator.lock().ok().map(|mut aggregator| {
+                self.dropped_functions
+                    .store(aggregator.cardinality.dropped(), Ordering::Relaxed);
+                aggregator.finish()
+            }),
+            None => completion_rx
+                .and_then(|rx_mutex| rx_mutex.lock().ok().and_then(|rx| rx.recv().ok())),
+        };
+
+        if let Some(stats) = stats {
+            if let Ok(state_guard) = state.read() {
+                let total_elapsed = end_time.duration_since(state_guard.start_time);
+                let mut metrics_provider = StatsData::new(
+                    &stats,
+                    total_elapsed,
+                    state_guard.percentiles.clone(),
+                    state_guard.caller_name,
+                    state_guard.limit,
+                );
+                metrics_provider.percent_basis = state_guard.percent_basis;
+                if self.track_process_peak {
+                    metrics_provider.process_peak_rss = process::peak_rss_bytes();
+                }
+                metrics_provider.dropped_functions = self.dropped_functions.load(Ordering::Relaxed);
+                metrics_provider.include_wrapper = self.include_wrapper;
+                metrics_provider.sort_by = self.sort_by;
+                metrics_provider.metadata = self.metadata.clone();
+                metrics_provider.report_deadline =
+                    self.report_deadline.map(output::ReportDeadline::new);
+
+                write_report(self.reporter.as_ref(), &metrics_provider, self.to_stderr);
+
+                if let Some(warning) = self
+                    .overhead_budget
+                    .filter(|_| overhead::enabled())
+                    .and_then(|budget| overhead::check(budget, total_elapsed))
+                {
+                    eprintln!("{warning}");
                 }
             }
         }
+        overhead::reset(false);
+        chrome_trace::set_enabled(false);
+        clock::set(None);
 
         if let Some(arc_swap) = HOTPATH_STATE.get() {
             arc_swap.store(None);
         }
+        SHOW_LOCATIONS.store(false, Ordering::Relaxed);
+        filter::clear();
+
+        if let Some(server) = self.http_server.take() {
+            server.stop();
+        }
+        release_guard();
     }
 }
 
@@ -716,4 +3204,188 @@ mod tests {
     fn test_hotpath_is_send_sync() {
         is_send_sync::<HotPath>();
     }
+
+    #[test]
+    fn test_guard_slot_released_on_panic() {
+        let setup = std::panic::catch_unwind(|| {
+            let _slot = acquire_guard(false);
+            panic!("setup failed");
+        });
+        assert!(setup.is_err());
+
+        // Would panic if the slot was still taken
+        acquire_guard(false).hand_over();
+        release_guard();
+    }
+
+    fn with_metrics_provider(f: impl FnOnce(&dyn MetricsProvider<'_>)) {
+        let mut stats = HashMap::new();
+        stats.insert("my_crate::parse", FunctionStats::new_registered(0));
+        let metrics_provider =
+            StatsData::new(&stats, Duration::from_millis(5), vec![95.0], "main", 0);
+        f(&metrics_provider);
+    }
+
+    #[test]
+    fn test_reporters_write_to_sink() {
+        with_metrics_provider(|metrics_provider| {
+            let mut out = Vec::new();
+            output::JsonReporter::default()
+                .write_report(metrics_provider, &mut out)
+                .unwrap();
+            let json: MetricsJson = serde_json::from_slice(&out).unwrap();
+            assert_eq!(json.caller_name, "main");
+            assert!(json.data.0.contains_key("my_crate::parse"));
+
+            let mut out = Vec::new();
+            output::TableReporter {
+                crate_rollup: CrateRollup::Off,
+                categorize: None,
+                highlight_threshold: None,
+                show_trimmed_mean: false,
+                show_uncalled: false,
+                relative_to: None,
+                byte_format: ByteFormat::Human,
+                to_stderr: false,
+                plain: false,
+            }
+            .write_report(metrics_provider, &mut out)
+            .unwrap();
+            let table = output::strip_ansi(&String::from_utf8(out).unwrap());
+            assert!(table.contains("| my_crate::parse | 0 "), "{table}");
+
+            let mut out = Vec::new();
+            output::SilentReporter
+                .write_report(metrics_provider, &mut out)
+                .unwrap();
+            assert!(out.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_plain_table_has_no_borders() {
+        with_metrics_provider(|metrics_provider| {
+            let mut out = Vec::new();
+            output::TableReporter {
+                crate_rollup: CrateRollup::Append,
+                categorize: None,
+                highlight_threshold: Some(1.0),
+                show_trimmed_mean: false,
+                show_uncalled: false,
+                relative_to: None,
+                byte_format: ByteFormat::Human,
+                to_stderr: false,
+                plain: true,
+            }
+            .write_report(metrics_provider, &mut out)
+            .unwrap();
+            let table = String::from_utf8(out).unwrap();
+            assert!(table.contains("my_crate::parse  0 "), "{table}");
+            assert!(!table.contains('|') && !table.contains("+-"), "{table}");
+            assert!(!table.contains('\x1b'), "{table}");
+        });
+    }
+
+    #[cfg(not(any(
+        feature = "hotpath-alloc-bytes-total",
+        feature = "hotpath-alloc-count-total",
+        feature = "hotpath-alloc-leak"
+    )))]
+    #[test]
+    fn test_zero_elapsed_falls_back_to_measured_sum() {
+        let mut stats = HashMap::new();
+        stats.insert(
+            "main",
+            FunctionStats::new_duration(0, Duration::ZERO, true, 0),
+        );
+        stats.insert(
+            "my_crate::parse",
+            FunctionStats::new_duration(300, Duration::ZERO, false, 0),
+        );
+        stats.insert(
+            "my_crate::load",
+            FunctionStats::new_duration(100, Duration::ZERO, false, 0),
+        );
+        let metrics_provider = StatsData::new(&stats, Duration::ZERO, vec![95.0], "main", 0);
+
+        assert!(metrics_provider.elapsed_below_resolution());
+        let percent = |name: &str| metrics_provider.metric_data()[name].last().unwrap().value();
+        assert_eq!(percent("my_crate::parse"), Some(7500));
+        assert_eq!(percent("my_crate::load"), Some(2500));
+
+        let mut out = Vec::new();
+        output::TableReporter {
+            crate_rollup: CrateRollup::Off,
+            categorize: None,
+            highlight_threshold: None,
+            show_trimmed_mean: false,
+            show_uncalled: false,
+            relative_to: None,
+            byte_format: ByteFormat::Human,
+            to_stderr: false,
+            plain: false,
+        }
+        .write_report(&metrics_provider, &mut out)
+        .unwrap();
+        let table = output::strip_ansi(&String::from_utf8(out).unwrap());
+        assert!(
+            table.contains("elapsed time is below the clock resolution"),
+            "{table}"
+        );
+    }
+
+    #[cfg(not(any(
+        feature = "hotpath-alloc-bytes-total",
+        feature = "hotpath-alloc-count-total",
+        feature = "hotpath-alloc-leak"
+    )))]
+    #[test]
+    fn test_merge_function_stats() {
+        let mut stats = FunctionStats::new_duration(100, Duration::ZERO, false, 2);
+        stats.update_duration(200, Duration::from_millis(1));
+        let mut other = FunctionStats::new_duration(10_000, Duration::from_millis(2), false, 2);
+        other.update_duration(20_000, Duration::from_millis(3));
+
+        stats.merge(&other);
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.total_duration_ns, 30_300);
+        assert_eq!(stats.percentile(50.0), Duration::from_nanos(200));
+        assert!(stats.percentile(100.0) >= Duration::from_nanos(20_000));
+        assert_eq!(
+            stats.recent_samples,
+            [
+                (10_000, Duration::from_millis(2)),
+                (20_000, Duration::from_millis(3))
+            ]
+        );
+
+        let mut registered = FunctionStats::new_registered(0);
+        registered.merge(&other);
+        assert!(registered.has_data && registered.registered);
+        assert_eq!(registered.avg_duration_ns(), 15_000);
+    }
+
+    #[test]
+    fn test_write_failure_is_io_report_error() {
+        struct ClosedPipe;
+
+        impl std::io::Write for ClosedPipe {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        with_metrics_provider(|metrics_provider| {
+            let result =
+                output::JsonReporter::default().write_report(metrics_provider, &mut ClosedPipe);
+            assert!(matches!(
+                result,
+                Err(ReportError::Io(e)) if e.kind() == std::io::ErrorKind::BrokenPipe
+            ));
+        });
+    }
 }
@@ -0,0 +1,297 @@
+//! Aggregation of measurements into per-function stats.
+//!
+//! The [`Aggregator`] is the stats engine of a profiling session. It doesn't spawn threads or
+//! own channels, so it runs either inline on the measuring threads, or behind the optional
+//! worker thread of the `worker` module.
+
+use super::{
+    chrome_trace, clock, phases, process_measurement, rates, raw_samples, FunctionStats,
+    Measurement, PercentBasis, QueryRequest, RawSink, SlowCallback, SortBy, StatsData,
+};
+use crate::output::{MetricsJson, MetricsProvider, SamplesJson};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Aggregates measurements into per-function stats, either on the worker thread or inline
+/// on the measuring threads, see [`GuardBuilder::inline`].
+pub(crate) struct Aggregator {
+    pub(crate) stats: HashMap<&'static str, FunctionStats>,
+    pub(crate) cardinality: CardinalityLimit,
+    pub(crate) raw_sink: Option<RawSink>,
+    pub(crate) raw_samples: Option<raw_samples::RawSamplesWriter>,
+    pub(crate) chrome_trace: Option<chrome_trace::ChromeTraceWriter>,
+    pub(crate) recent_samples_limit: usize,
+    pub(crate) start_time: Instant,
+    pub(crate) percentiles: Vec<f64>,
+    pub(crate) caller_name: &'static str,
+    pub(crate) limit: usize,
+    pub(crate) percent_basis: PercentBasis,
+    pub(crate) include_wrapper: bool,
+    pub(crate) sort_by: SortBy,
+    pub(crate) metadata: BTreeMap<String, String>,
+    // Baseline for delta queries, updated on each one
+    pub(crate) delta_snapshot: HashMap<&'static str, FunctionStats>,
+    pub(crate) delta_start: Instant,
+    pub(crate) on_slow: Option<(Duration, Arc<SlowCallback>)>,
+    /// Recorded only with the HTTP metrics server
+    pub(crate) call_rates: Option<rates::CallRates>,
+}
+
+/// Measured call slower than the `on_slow` threshold, returned by [`Aggregator::record`].
+///
+/// The callback runs with [`SlowCall::notify`] after the aggregator is released, so that with
+/// inline aggregation it doesn't block other measuring threads, or deadlock if it measures.
+#[must_use]
+pub(crate) struct SlowCall {
+    callback: Arc<SlowCallback>,
+    name: &'static str,
+    duration: Duration,
+}
+
+impl SlowCall {
+    pub(crate) fn notify(self) {
+        (self.callback)(self.name, self.duration);
+    }
+}
+
+impl Aggregator {
+    #[inline]
+    pub(crate) fn record(&mut self, measurement: Measurement) -> Option<SlowCall> {
+        record_raw(&self.raw_sink, &mut self.raw_samples, &measurement);
+        if let Some(writer) = &mut self.chrome_trace {
+            record_trace_event(writer, &measurement);
+        }
+        let slow = self.on_slow.as_ref().and_then(|(threshold, callback)| {
+            slow_call(&measurement, *threshold).map(|(name, duration)| SlowCall {
+                callback: Arc::clone(callback),
+                name,
+                duration,
+            })
+        });
+        if self.cardinality.admits(&self.stats, &measurement) {
+            if let Some(call_rates) = &mut self.call_rates {
+                call_rates.record(measurement.name_and_value().0, measurement.elapsed());
+            }
+            let (name, value) = phase_value(&measurement);
+            if name != self.caller_name {
+                phases::record(name, value, self.start_time + measurement.elapsed());
+            }
+            process_measurement(&mut self.stats, measurement, self.recent_samples_limit);
+        }
+        slow
+    }
+
+    pub(crate) fn metrics_provider<'a>(
+        &self,
+        stats: &'a HashMap<&'static str, FunctionStats>,
+        total_elapsed: Duration,
+    ) -> StatsData<'a> {
+        let mut metrics_provider = StatsData::new(
+            stats,
+            total_elapsed,
+            self.percentiles.clone(),
+            self.caller_name,
+            self.limit,
+        );
+        metrics_provider.percent_basis = self.percent_basis;
+        metrics_provider.dropped_functions = self.cardinality.dropped();
+        metrics_provider.include_wrapper = self.include_wrapper;
+        metrics_provider.sort_by = self.sort_by;
+        metrics_provider.metadata = self.metadata.clone();
+        metrics_provider
+    }
+
+    pub(crate) fn metrics_json(
+        &self,
+        stats: &HashMap<&'static str, FunctionStats>,
+        total_elapsed: Duration,
+    ) -> MetricsJson {
+        let metrics_provider = self.metrics_provider(stats, total_elapsed);
+        MetricsJson::from(&metrics_provider as &dyn MetricsProvider)
+    }
+
+    pub(crate) fn answer(&mut self, query_request: QueryRequest) {
+        match query_request {
+            QueryRequest::GetMetrics(response_tx) => {
+                let total_elapsed = clock::elapsed(self.start_time);
+                let _ = response_tx.send(self.metrics_json(&self.stats, total_elapsed));
+            }
+            QueryRequest::GetMetricsDelta(response_tx) => {
+                let delta_stats: HashMap<&'static str, FunctionStats> = self
+                    .stats
+                    .iter()
+                    .map(|(name, stats)| (*name, stats.since(self.delta_snapshot.get(name))))
+                    .collect();
+                let _ = response_tx
+                    .send(self.metrics_json(&delta_stats, clock::elapsed(self.delta_start)));
+
+                self.delta_snapshot = self
+                    .stats
+                    .iter()
+                    .map(|(name, stats)| (*name, stats.snapshot()))
+                    .collect();
+                self.delta_start = clock::now();
+            }
+            QueryRequest::Render(render) => {
+                render(&self.metrics_provider(&self.stats, clock::elapsed(self.start_time)));
+            }
+            QueryRequest::GetSamples {
+                function_name,
+                response_tx,
+            } => {
+                let response = self.stats.get(function_name.as_str()).map(|stats| {
+                    let samples: Vec<(u64, u64)> = stats
+                        .recent_samples
+                        .iter()
+                        .rev()
+                        .map(|(val, elapsed)| (*val, elapsed.as_nanos() as u64))
+                        .collect();
+                    SamplesJson {
+                        function_name,
+                        samples,
+                        count: stats.recent_samples.len(),
+                    }
+                });
+                let _ = response_tx.send(response);
+            }
+            QueryRequest::GetCallRates(response_tx) => {
+                let rates = self
+                    .call_rates
+                    .as_ref()
+                    .map(|call_rates| call_rates.snapshot(clock::elapsed(self.start_time)))
+                    .unwrap_or_default();
+                let _ = response_tx.send(rates);
+            }
+        }
+    }
+
+    /// Takes the collected stats and flushes the raw samples and trace files, the process may
+    /// exit right after.
+    pub(crate) fn finish(&mut self) -> HashMap<&'static str, FunctionStats> {
+        self.raw_samples = None;
+        self.chrome_trace = None;
+        std::mem::take(&mut self.stats)
+    }
+}
+
+/// Cap on the number of distinct functions, see [`GuardBuilder::max_functions`].
+pub(crate) struct CardinalityLimit {
+    max_functions: usize,
+    caller_name: &'static str,
+    // Names only, far cheaper than the histograms of admitted functions
+    dropped: HashSet<&'static str>,
+}
+
+impl CardinalityLimit {
+    pub(crate) fn new(max_functions: usize, caller_name: &'static str) -> Self {
+        Self {
+            max_functions,
+            caller_name,
+            dropped: HashSet::new(),
+        }
+    }
+
+    /// Whether the measurement can be recorded. The caller wrapper is always admitted,
+    /// as it's the reference for `% Total`.
+    pub(crate) fn admits(
+        &mut self,
+        stats: &HashMap<&'static str, FunctionStats>,
+        measurement: &Measurement,
+    ) -> bool {
+        let (name, _) = measurement.name_and_value();
+        if self.max_functions == 0
+            || name == self.caller_name
+            || stats.contains_key(name)
+            || stats.len() < self.max_functions
+        {
+            return true;
+        }
+
+        if self.dropped.is_empty() {
+            eprintln!(
+                "[hotpath] Function limit of {} reached, new functions are no longer measured. Configure it with GuardBuilder::max_functions.",
+                self.max_functions
+            );
+        }
+        self.dropped.insert(name);
+        false
+    }
+
+    pub(crate) fn dropped(&self) -> usize {
+        self.dropped.len()
+    }
+}
+
+#[inline]
+fn record_raw(
+    raw_sink: &Option<RawSink>,
+    raw_samples: &mut Option<raw_samples::RawSamplesWriter>,
+    measurement: &Measurement,
+) {
+    if let Some(sink) = raw_sink {
+        if let Ok(mut samples) = sink.lock() {
+            let (name, value) = measurement.name_and_value();
+            samples.push((name.to_string(), value));
+        }
+    }
+    if let Some(writer) = raw_samples {
+        let (name, value) = measurement.name_and_value();
+        writer.write(name, value, measurement.elapsed());
+    }
+}
+
+/// Writes a measured call as a Chrome trace event, see [`GuardBuilder::chrome_trace_file`].
+#[inline]
+fn record_trace_event(writer: &mut chrome_trace::ChromeTraceWriter, measurement: &Measurement) {
+    cfg_if::cfg_if! {
+        if #[cfg(any(
+            feature = "hotpath-alloc-bytes-total",
+            feature = "hotpath-alloc-count-total",
+            feature = "hotpath-alloc-leak"
+        ))] {
+            let _ = (writer, measurement);
+        } else {
+            let Measurement::Duration(duration_ns, elapsed, name, _, _, thread_id, _) = *measurement;
+            writer.write(name, Duration::from_nanos(duration_ns), elapsed, thread_id);
+        }
+    }
+}
+
+/// Name and value of a measured call as reported, with the calibrated overhead subtracted
+/// from durations.
+#[inline]
+fn phase_value(measurement: &Measurement) -> (&'static str, u64) {
+    let (name, value) = measurement.name_and_value();
+    cfg_if::cfg_if! {
+        if #[cfg(any(
+            feature = "hotpath-alloc-bytes-total",
+            feature = "hotpath-alloc-count-total",
+            feature = "hotpath-alloc-leak"
+        ))] {
+            (name, value)
+        } else {
+            (name, value.saturating_sub(super::time::guard::overhead_ns()))
+        }
+    }
+}
+
+/// Name and duration of a measured call slower than `threshold`, see [`GuardBuilder::on_slow`].
+#[inline]
+fn slow_call(measurement: &Measurement, threshold: Duration) -> Option<(&'static str, Duration)> {
+    cfg_if::cfg_if! {
+        if #[cfg(any(
+            feature = "hotpath-alloc-bytes-total",
+            feature = "hotpath-alloc-count-total",
+            feature = "hotpath-alloc-leak"
+        ))] {
+            let _ = (measurement, threshold);
+            None
+        } else {
+            let Measurement::Duration(duration_ns, _, name, wrapper, ..) = *measurement;
+            let duration =
+                Duration::from_nanos(duration_ns.saturating_sub(super::time::guard::overhead_ns()));
+            (!wrapper && duration > threshold).then_some((name, duration))
+        }
+    }
+}
@@ -7,24 +7,65 @@
 // Modifications:
 // - Adjusted to work with hotpath module system
 // - Split into feature-specific dispatching allocator
+// - Deallocation tracking for the leak mode
 
 use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    // Bytes allocated on the current thread, read by `assert_max_alloc!`
+    static ALLOCATED_BYTES: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Bytes allocated on the current thread so far, excluding hotpath's own allocations.
+#[inline]
+pub(crate) fn allocated_bytes() -> u64 {
+    ALLOCATED_BYTES.with(|bytes| bytes.get())
+}
 
 /// Shared global allocator that dispatches to enabled allocation tracking features
 pub struct CountingAllocator;
 
-unsafe impl GlobalAlloc for CountingAllocator {
-    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+/// Records an allocation of `size` bytes.
+///
+/// Only the requested size is counted, never the platform allocator's padding for alignment
+/// or bookkeeping, so identical code reports identical numbers on every target. `alloc_zeroed`
+/// and `realloc` keep their `GlobalAlloc` defaults, which go through `alloc`, so a grown
+/// buffer counts as a new allocation of its full size, whether or not it grows in place.
+#[inline]
+fn track(size: usize) {
+    if !super::shared::is_tracking_suspended() {
+        ALLOCATED_BYTES.with(|bytes| bytes.set(bytes.get() + size as u64));
+
         #[cfg(feature = "hotpath-alloc-bytes-total")]
-        crate::lib_on::alloc_bytes_total::core::track_alloc(layout.size());
+        crate::lib_on::alloc_bytes_total::core::track_alloc(size);
 
         #[cfg(feature = "hotpath-alloc-count-total")]
         crate::lib_on::alloc_count_total::core::track_alloc();
 
+        #[cfg(feature = "hotpath-alloc-leak")]
+        crate::lib_on::alloc_leak::core::track_alloc(size);
+    }
+}
+
+/// Records a deallocation of `size` bytes, only tracked by the leak mode.
+#[inline]
+#[cfg_attr(not(feature = "hotpath-alloc-leak"), allow(unused_variables))]
+fn track_dealloc(size: usize) {
+    #[cfg(feature = "hotpath-alloc-leak")]
+    if !super::shared::is_tracking_suspended() {
+        crate::lib_on::alloc_leak::core::track_dealloc(size);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        track(layout.size());
         unsafe { System.alloc(layout) }
     }
 
     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        track_dealloc(layout.size());
         unsafe {
             System.dealloc(ptr, layout);
         }
@@ -1,3 +1,5 @@
+use std::cell::Cell;
+
 #[allow(dead_code)]
 pub fn format_bytes(bytes: u64) -> String {
     if bytes == 0 {
@@ -26,3 +28,22 @@ pub(crate) fn is_alloc_self_enabled() -> bool {
         .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
         .unwrap_or(false)
 }
+
+thread_local! {
+    static TRACKING_SUSPENDED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f` with allocation tracking disabled on the current thread, so that
+/// hotpath's own allocations (e.g. channel sends) are not attributed to measured functions.
+#[inline]
+pub(crate) fn without_tracking<R>(f: impl FnOnce() -> R) -> R {
+    let previous = TRACKING_SUSPENDED.with(|suspended| suspended.replace(true));
+    let result = f();
+    TRACKING_SUSPENDED.with(|suspended| suspended.set(previous));
+    result
+}
+
+#[inline]
+pub(crate) fn is_tracking_suspended() -> bool {
+    TRACKING_SUSPENDED.with(|suspended| suspended.get())
+}
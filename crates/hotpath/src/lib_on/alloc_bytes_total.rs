@@ -1,3 +1,4 @@
+pub mod backtrace;
 pub mod core;
 pub mod guard;
 pub mod report;
@@ -0,0 +1,211 @@
+//! Backtraces of the largest allocation of each measured scope, captured with the
+//! `hotpath-alloc-backtrace` feature. Without the feature all functions are no-ops.
+
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use super::core::MAX_DEPTH;
+use crate::lib_on::alloc::shared::without_tracking;
+
+/// Frames printed in the report, counted from the first frame outside the allocator
+const MAX_FRAMES: usize = 8;
+
+/// Minimum size of captured allocations, see `GuardBuilder::alloc_backtrace_threshold`
+static THRESHOLD: AtomicU64 = AtomicU64::new(1024 * 1024);
+
+/// Largest single allocation made during a function call, including nested calls
+/// unless `HOTPATH_ALLOC_SELF` is set.
+#[derive(Debug, Clone)]
+pub struct LargestAllocation {
+    pub bytes: u64,
+    pub backtrace: Arc<Backtrace>,
+}
+
+thread_local! {
+    static LARGEST: RefCell<[Option<LargestAllocation>; MAX_DEPTH]> =
+        const { RefCell::new([const { None }; MAX_DEPTH]) };
+}
+
+pub(crate) fn set_threshold(bytes: u64) {
+    THRESHOLD.store(bytes, Ordering::Relaxed);
+}
+
+/// Called by the global allocator for every allocation at the given stack depth.
+#[inline]
+pub fn track_alloc(size: u64, depth: usize) {
+    if !cfg!(feature = "hotpath-alloc-backtrace")
+        || depth == 0
+        || size < THRESHOLD.load(Ordering::Relaxed)
+    {
+        return;
+    }
+    capture(size, depth);
+}
+
+#[cold]
+fn capture(size: u64, depth: usize) {
+    // Capturing allocates, which must neither count towards the function nor recurse into here
+    without_tracking(|| {
+        let is_largest = LARGEST
+            .try_with(|largest| {
+                largest
+                    .try_borrow()
+                    .is_ok_and(|largest| largest[depth].as_ref().is_none_or(|l| size > l.bytes))
+            })
+            .unwrap_or(false);
+        if !is_largest {
+            return;
+        }
+
+        let allocation = LargestAllocation {
+            bytes: size,
+            backtrace: Arc::new(Backtrace::force_capture()),
+        };
+        let _ = LARGEST.try_with(|largest| {
+            if let Ok(mut largest) = largest.try_borrow_mut() {
+                largest[depth] = Some(allocation);
+            }
+        });
+    });
+}
+
+/// Clears the slot of a scope entered at `depth`.
+#[inline]
+pub fn reset(depth: usize) {
+    if cfg!(feature = "hotpath-alloc-backtrace") {
+        without_tracking(|| {
+            let _ = LARGEST.try_with(|largest| largest.borrow_mut()[depth] = None);
+        });
+    }
+}
+
+/// Takes the largest allocation of the scope exiting at `depth`, and keeps it for the parent
+/// scope if it's larger than the parent's own, matching the cumulative byte totals.
+#[inline]
+pub fn take(depth: usize, cumulative: bool) -> Option<LargestAllocation> {
+    if !cfg!(feature = "hotpath-alloc-backtrace") {
+        return None;
+    }
+
+    without_tracking(|| {
+        LARGEST
+            .try_with(|largest| {
+                let mut largest = largest.borrow_mut();
+                let allocation = largest[depth].take()?;
+                if cumulative && depth > 0 {
+                    let parent = &mut largest[depth - 1];
+                    if parent.as_ref().is_none_or(|p| allocation.bytes > p.bytes) {
+                        *parent = Some(allocation.clone());
+                    }
+                }
+                Some(allocation)
+            })
+            .ok()
+            .flatten()
+    })
+}
+
+/// Renders a backtrace without the leading frames of the capture and the allocator itself,
+/// so it starts at the code that requested the allocation.
+pub fn format_backtrace(backtrace: &Backtrace) -> String {
+    let rendered = backtrace.to_string();
+
+    let mut frames: Vec<String> = Vec::new();
+    for line in rendered.lines() {
+        if is_frame_start(line) || frames.is_empty() {
+            frames.push(line.to_string());
+        } else if let Some(frame) = frames.last_mut() {
+            frame.push('\n');
+            frame.push_str(line);
+        }
+    }
+
+    // Frames below the program's entry point are runtime startup
+    if let Some(entry) = frames
+        .iter()
+        .position(|frame| frame_symbol(frame).contains("__rust_begin_short_backtrace"))
+    {
+        frames.truncate(entry);
+    }
+    while frames.last().is_some_and(|frame| is_internal_frame(frame)) {
+        frames.pop();
+    }
+
+    let first_own = frames
+        .iter()
+        .position(|frame| !is_internal_frame(frame))
+        .unwrap_or(0);
+    let mut kept: Vec<&str> = frames[first_own..]
+        .iter()
+        .take(MAX_FRAMES)
+        .map(String::as_str)
+        .collect();
+    let omitted = frames.len() - first_own - kept.len();
+    let note = format!("      ... {omitted} more frames");
+    if omitted > 0 {
+        kept.push(&note);
+    }
+    kept.join("\n")
+}
+
+/// Frame lines look like `  12: my_crate::parse`, followed by indented `at file:line` lines
+fn is_frame_start(line: &str) -> bool {
+    line.trim_start()
+        .split_once(": ")
+        .is_some_and(|(index, _)| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn frame_symbol(frame: &str) -> &str {
+    frame
+        .lines()
+        .next()
+        .and_then(|line| line.split_once(": "))
+        .map_or("", |(_, symbol)| symbol)
+}
+
+/// Frames of the standard library and hotpath, including trait impls like
+/// `<u8 as alloc::vec::spec_from_elem::SpecFromElem>::from_elem`
+fn is_internal_frame(frame: &str) -> bool {
+    let symbol = frame_symbol(frame).trim_start_matches('<');
+    let trait_path = symbol.split_once(" as ").map(|(_, path)| path);
+
+    [
+        "std::",
+        "core::",
+        "alloc::",
+        "hotpath::",
+        "__rust",
+        "__rdl",
+        "__rg",
+    ]
+    .iter()
+    .any(|prefix| {
+        symbol.starts_with(prefix) || trait_path.is_some_and(|path| path.starts_with(prefix))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_internal_frames() {
+        assert!(is_frame_start("   3: my_crate::parse"));
+        assert!(!is_frame_start("             at ./src/main.rs:10:5"));
+        assert!(is_internal_frame(
+            "   0: std::backtrace::Backtrace::force_capture"
+        ));
+        assert!(is_internal_frame(
+            "   4: <hotpath::lib_on::alloc::allocator::CountingAllocator as core::alloc::global::GlobalAlloc>::alloc"
+        ));
+        assert!(is_internal_frame("   6: alloc::vec::from_elem"));
+        assert!(is_internal_frame(
+            "   5: <u8 as alloc::vec::spec_from_elem::SpecFromElem>::from_elem"
+        ));
+        assert!(!is_internal_frame(
+            "   7: my_crate::parse\n             at ./src/main.rs:10:5"
+        ));
+    }
+}
@@ -2,11 +2,14 @@ use std::cell::Cell;
 
 pub const MAX_DEPTH: usize = 64;
 
-/// Minimal allocation info tracking only total bytes
+/// Minimal allocation info tracking total bytes and the number of allocations
 pub struct AllocationInfo {
     /// The total amount of bytes allocated during a [measure()] call.
     pub bytes_total: Cell<u64>,
 
+    /// The number of allocations making up `bytes_total`.
+    pub allocs_total: Cell<u64>,
+
     pub unsupported_async: Cell<bool>,
 }
 
@@ -14,6 +17,8 @@ impl std::ops::AddAssign for AllocationInfo {
     fn add_assign(&mut self, other: Self) {
         self.bytes_total
             .set(self.bytes_total.get() + other.bytes_total.get());
+        self.allocs_total
+            .set(self.allocs_total.get() + other.allocs_total.get());
         self.unsupported_async
             .set(self.unsupported_async.get() | other.unsupported_async.get());
     }
@@ -27,7 +32,7 @@ pub struct AllocationInfoStack {
 thread_local! {
     pub static ALLOCATIONS: AllocationInfoStack = const { AllocationInfoStack {
         depth: Cell::new(0),
-        elements: [const { AllocationInfo { bytes_total: Cell::new(0), unsupported_async: Cell::new(false) } }; MAX_DEPTH],
+        elements: [const { AllocationInfo { bytes_total: Cell::new(0), allocs_total: Cell::new(0), unsupported_async: Cell::new(false) } }; MAX_DEPTH],
     } };
 }
 
@@ -38,5 +43,7 @@ pub fn track_alloc(size: usize) {
         let depth = stack.depth.get() as usize;
         let info = &stack.elements[depth];
         info.bytes_total.set(info.bytes_total.get() + size as u64);
+        info.allocs_total.set(info.allocs_total.get() + 1);
+        super::backtrace::track_alloc(size as u64, depth);
     });
 }
@@ -1,21 +1,48 @@
+use crate::lib_on::recursion::{self, Call};
+
 pub struct MeasurementGuard {
     name: &'static str,
     wrapper: bool,
     unsupported_async: bool,
+    recorded: bool,
+    call: Call,
     thread_id: std::thread::ThreadId,
+    percentiles: Option<&'static [f64]>,
 }
 
 impl MeasurementGuard {
     #[inline]
     pub fn new(name: &'static str, wrapper: bool, unsupported_async: bool) -> Self {
-        if !unsupported_async {
+        Self::new_with_recursion(name, wrapper, unsupported_async, false)
+    }
+
+    /// Like [`Self::new`], but with `track_recursion` only the outermost of recursive calls
+    /// on the current thread is recorded, and nested calls count towards it.
+    #[inline]
+    pub(crate) fn new_with_recursion(
+        name: &'static str,
+        wrapper: bool,
+        unsupported_async: bool,
+        track_recursion: bool,
+    ) -> Self {
+        let call = if track_recursion {
+            recursion::enter(name)
+        } else {
+            Call::Untracked
+        };
+        let recorded =
+            call != Call::Nested && (wrapper || crate::lib_on::filter::is_recorded(name));
+
+        if recorded && !unsupported_async {
             super::core::ALLOCATIONS.with(|stack| {
                 let current_depth = stack.depth.get();
                 stack.depth.set(current_depth + 1);
                 assert!((stack.depth.get() as usize) < super::core::MAX_DEPTH);
                 let depth = stack.depth.get() as usize;
                 stack.elements[depth].bytes_total.set(0);
+                stack.elements[depth].allocs_total.set(0);
                 stack.elements[depth].unsupported_async.set(false);
+                super::backtrace::reset(depth);
             });
         }
 
@@ -23,47 +50,79 @@ impl MeasurementGuard {
             name,
             wrapper,
             unsupported_async,
-            thread_id: std::thread::current().id(),
+            recorded,
+            call,
+            thread_id: super::super::alloc::shared::without_tracking(|| {
+                std::thread::current().id()
+            }),
+            percentiles: None,
         }
     }
+
+    /// Overrides the global percentiles reported for this measurement.
+    #[inline]
+    pub fn with_percentiles(mut self, percentiles: &'static [f64]) -> Self {
+        self.percentiles = Some(percentiles);
+        self
+    }
 }
 
 impl Drop for MeasurementGuard {
     #[inline]
     fn drop(&mut self) {
+        recursion::exit(self.name, self.call);
+
+        if !self.recorded {
+            return;
+        }
+
         let cross_thread = std::thread::current().id() != self.thread_id;
 
-        let (bytes_total, unsupported_async) = if self.unsupported_async || cross_thread {
-            (0, self.unsupported_async)
-        } else {
-            super::core::ALLOCATIONS.with(|stack| {
-                let depth = stack.depth.get() as usize;
-                let bytes = stack.elements[depth].bytes_total.get();
-                let unsup_async = stack.elements[depth].unsupported_async.get();
+        let (bytes_total, allocs_total, unsupported_async, largest_allocation) =
+            if self.unsupported_async || cross_thread {
+                (0, 0, self.unsupported_async, None)
+            } else {
+                super::core::ALLOCATIONS.with(|stack| {
+                    let depth = stack.depth.get() as usize;
+                    let bytes = stack.elements[depth].bytes_total.get();
+                    let allocs = stack.elements[depth].allocs_total.get();
+                    let unsup_async = stack.elements[depth].unsupported_async.get();
 
-                stack.depth.set(stack.depth.get() - 1);
+                    stack.depth.set(stack.depth.get() - 1);
 
-                // If not in exclusive mode, accumulate to parent (cumulative mode)
-                if !super::super::alloc::shared::is_alloc_self_enabled() {
-                    let parent = stack.depth.get() as usize;
-                    stack.elements[parent]
-                        .bytes_total
-                        .set(stack.elements[parent].bytes_total.get() + bytes);
-                    stack.elements[parent]
-                        .unsupported_async
-                        .set(stack.elements[parent].unsupported_async.get() | unsup_async);
-                }
+                    let cumulative = !super::super::alloc::shared::is_alloc_self_enabled();
+                    let largest_allocation = super::backtrace::take(depth, cumulative);
 
-                (bytes, unsup_async)
-            })
-        };
+                    // If not in exclusive mode, accumulate to parent (cumulative mode)
+                    if cumulative {
+                        let parent = stack.depth.get() as usize;
+                        stack.elements[parent]
+                            .bytes_total
+                            .set(stack.elements[parent].bytes_total.get() + bytes);
+                        stack.elements[parent]
+                            .allocs_total
+                            .set(stack.elements[parent].allocs_total.get() + allocs);
+                        stack.elements[parent]
+                            .unsupported_async
+                            .set(stack.elements[parent].unsupported_async.get() | unsup_async);
+                    }
 
-        super::state::send_alloc_measurement(
-            self.name,
-            bytes_total,
-            unsupported_async,
-            self.wrapper,
-            cross_thread,
-        );
+                    (bytes, allocs, unsup_async, largest_allocation)
+                })
+            };
+
+        // Sending may allocate (e.g. channel growth), which must not count towards the parent
+        super::super::alloc::shared::without_tracking(|| {
+            super::state::send_alloc_measurement(
+                self.name,
+                bytes_total,
+                allocs_total,
+                unsupported_async,
+                largest_allocation,
+                self.wrapper,
+                cross_thread,
+                self.percentiles,
+            )
+        });
     }
 }
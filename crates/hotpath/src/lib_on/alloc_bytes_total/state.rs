@@ -4,19 +4,62 @@ use std::collections::{HashMap, VecDeque};
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
+use super::super::shared::{self, ModeStats};
+
+use super::backtrace::LargestAllocation;
+
 pub enum Measurement {
-    Allocation(&'static str, u64, Duration, bool, bool, bool), // function_name, bytes_total, elapsed_since_start, unsupported_async, wrapper, cross_thread
+    Allocation(
+        &'static str,
+        u64,
+        u64,
+        Duration,
+        bool,
+        Option<LargestAllocation>,
+        bool,
+        bool,
+        Option<&'static [f64]>,
+    ), // function_name, bytes_total, allocs_total, elapsed_since_start, unsupported_async, largest_allocation, wrapper, cross_thread, percentiles
+}
+
+impl Measurement {
+    pub fn name_and_value(&self) -> (&'static str, u64) {
+        match self {
+            Measurement::Allocation(name, bytes_total, ..) => (name, *bytes_total),
+        }
+    }
+
+    /// Time since the guard was created when the measurement was sent
+    pub fn elapsed(&self) -> Duration {
+        match self {
+            Measurement::Allocation(_, _, _, elapsed, ..) => *elapsed,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct FunctionStats {
     pub count: u64,
     bytes_total_hist: Option<Histogram<u64>>,
+    /// Number of allocations over all calls
+    pub allocs_total: u64,
     pub has_data: bool,
+    /// Pre-registered with `GuardBuilder::register`, reported even without measurements
+    pub registered: bool,
     pub has_unsupported_async: bool,
     pub wrapper: bool,
     pub cross_thread: bool,
     pub recent_samples: VecDeque<(u64, Duration)>,
+    /// Per-function percentiles overriding the global ones, set with `#[measure(percentiles = [..])]`
+    pub percentiles: Option<&'static [f64]>,
+    /// Largest single allocation with its backtrace, captured with `hotpath-alloc-backtrace`
+    pub largest_allocation: Option<LargestAllocation>,
+}
+
+impl ModeStats for FunctionStats {
+    fn is_uncalled(&self) -> bool {
+        self.registered && !self.has_data
+    }
 }
 
 impl FunctionStats {
@@ -26,6 +69,7 @@ impl FunctionStats {
 
     pub fn new_alloc(
         bytes_total: u64,
+        allocs_total: u64,
         elapsed: Duration,
         unsupported_async: bool,
         wrapper: bool,
@@ -42,16 +86,97 @@ impl FunctionStats {
         let mut s = Self {
             count: 1,
             bytes_total_hist: Some(bytes_total_hist),
+            allocs_total,
             has_data: true,
+            registered: false,
             has_unsupported_async: unsupported_async,
             wrapper,
             cross_thread,
             recent_samples,
+            percentiles: None,
+            largest_allocation: None,
         };
         s.record_alloc(bytes_total);
         s
     }
 
+    /// Empty entry for a function registered up front, before any measurement.
+    pub fn new_registered(recent_samples_limit: usize) -> Self {
+        let bytes_total_hist =
+            Histogram::<u64>::new_with_bounds(Self::LOW_BYTES, Self::HIGH_BYTES, Self::SIGFIGS)
+                .expect("bytes_total histogram init");
+
+        Self {
+            count: 0,
+            bytes_total_hist: Some(bytes_total_hist),
+            allocs_total: 0,
+            has_data: false,
+            registered: true,
+            has_unsupported_async: false,
+            wrapper: false,
+            cross_thread: false,
+            recent_samples: VecDeque::with_capacity(recent_samples_limit),
+            percentiles: None,
+            largest_allocation: None,
+        }
+    }
+
+    /// Copy of the aggregated stats without recent samples, used as a baseline for [`Self::since`].
+    pub fn snapshot(&self) -> Self {
+        Self {
+            recent_samples: VecDeque::new(),
+            bytes_total_hist: self.bytes_total_hist.clone(),
+            largest_allocation: self.largest_allocation.clone(),
+            ..*self
+        }
+    }
+
+    /// Stats accumulated since `previous`, an earlier [`Self::snapshot`] of the same function.
+    pub fn since(&self, previous: Option<&Self>) -> Self {
+        let mut delta = self.snapshot();
+        if let Some(previous) = previous {
+            delta.count = delta.count.saturating_sub(previous.count);
+            delta.allocs_total = delta.allocs_total.saturating_sub(previous.allocs_total);
+            if let (Some(hist), Some(previous_hist)) =
+                (&mut delta.bytes_total_hist, &previous.bytes_total_hist)
+            {
+                let _ = hist.subtract(previous_hist);
+            }
+            delta.has_data = delta.count > 0;
+        }
+        delta
+    }
+
+    /// Combines the stats of `other` into these, e.g. to aggregate several functions or runs.
+    /// Distributions are added, so percentiles reflect the calls of both.
+    pub fn merge(&mut self, other: &FunctionStats) {
+        self.count += other.count;
+        self.allocs_total += other.allocs_total;
+        if let (Some(hist), Some(other_hist)) =
+            (&mut self.bytes_total_hist, &other.bytes_total_hist)
+        {
+            let _ = hist.add(other_hist);
+        }
+        self.has_data |= other.has_data;
+        self.registered |= other.registered;
+        self.has_unsupported_async |= other.has_unsupported_async;
+        self.cross_thread |= other.cross_thread;
+        self.percentiles = self.percentiles.or(other.percentiles);
+        if let Some(allocation) = &other.largest_allocation {
+            if self
+                .largest_allocation
+                .as_ref()
+                .is_none_or(|largest| allocation.bytes > largest.bytes)
+            {
+                self.largest_allocation = Some(allocation.clone());
+            }
+        }
+
+        for &sample in &other.recent_samples {
+            shared::push_recent_sample(&mut self.recent_samples, sample);
+        }
+    }
+
     #[inline]
     fn record_alloc(&mut self, bytes_total: u64) {
         if let Some(ref mut bytes_total_hist) = self.bytes_total_hist {
@@ -62,24 +187,34 @@ impl FunctionStats {
         }
     }
 
+    fn record_largest_allocation(&mut self, allocation: Option<LargestAllocation>) {
+        if let Some(allocation) = allocation {
+            if self
+                .largest_allocation
+                .as_ref()
+                .is_none_or(|largest| allocation.bytes > largest.bytes)
+            {
+                self.largest_allocation = Some(allocation);
+            }
+        }
+    }
+
     pub fn update_alloc(
         &mut self,
         bytes_total: u64,
+        allocs_total: u64,
         elapsed: Duration,
         unsupported_async: bool,
         cross_thread: bool,
     ) {
         self.count += 1;
+        self.allocs_total += allocs_total;
+        self.has_data = true;
         self.has_unsupported_async |= unsupported_async;
         self.cross_thread |= cross_thread;
         self.record_alloc(bytes_total);
 
-        if self.recent_samples.len() == self.recent_samples.capacity()
-            && self.recent_samples.capacity() > 0
-        {
-            self.recent_samples.pop_front();
-        }
-        self.recent_samples.push_back((bytes_total, elapsed));
+        shared::push_recent_sample(&mut self.recent_samples, (bytes_total, elapsed));
     }
 
     #[inline]
@@ -112,17 +247,33 @@ impl FunctionStats {
         }
         self.bytes_total_hist.as_ref().unwrap().mean() as u64
     }
+
+    /// Average size of a single allocation, 0 without allocations
+    #[inline]
+    pub fn avg_alloc_bytes(&self) -> u64 {
+        self.total_bytes()
+            .checked_div(self.allocs_total)
+            .unwrap_or(0)
+    }
+
+    #[inline]
+    pub fn trimmed_avg_bytes(&self) -> u64 {
+        self.bytes_total_hist
+            .as_ref()
+            .map_or(0, crate::output::trimmed_mean)
+    }
 }
 
 pub(crate) struct HotPathState {
-    pub sender: Option<Sender<Measurement>>,
+    pub sender: Option<crate::lib_on::MeasurementSender>,
     pub shutdown_tx: Option<Sender<()>>,
     pub completion_rx: Option<Mutex<Receiver<HashMap<&'static str, FunctionStats>>>>,
-    pub query_tx: Option<Sender<crate::lib_on::QueryRequest>>,
+    pub query_tx: Option<crate::lib_on::QuerySender>,
     pub start_time: Instant,
     pub caller_name: &'static str,
-    pub percentiles: Vec<u8>,
+    pub percentiles: Vec<f64>,
     pub limit: usize,
+    pub percent_basis: crate::lib_on::PercentBasis,
 }
 
 pub(crate) fn process_measurement(
@@ -134,43 +285,58 @@ pub(crate) fn process_measurement(
         Measurement::Allocation(
             name,
             bytes_total,
+            allocs_total,
             elapsed,
             unsupported_async,
+            largest_allocation,
             wrapper,
             cross_thread,
+            percentiles,
         ) => {
             if let Some(s) = stats.get_mut(name) {
-                s.update_alloc(bytes_total, elapsed, unsupported_async, cross_thread);
+                s.update_alloc(
+                    bytes_total,
+                    allocs_total,
+                    elapsed,
+                    unsupported_async,
+                    cross_thread,
+                );
+                s.percentiles = s.percentiles.or(percentiles);
+                s.record_largest_allocation(largest_allocation);
             } else {
-                stats.insert(
-                    name,
-                    FunctionStats::new_alloc(
-                        bytes_total,
-                        elapsed,
-                        unsupported_async,
-                        wrapper,
-                        cross_thread,
-                        recent_samples_limit,
-                    ),
+                let mut s = FunctionStats::new_alloc(
+                    bytes_total,
+                    allocs_total,
+                    elapsed,
+                    unsupported_async,
+                    wrapper,
+                    cross_thread,
+                    recent_samples_limit,
                 );
+                s.percentiles = percentiles;
+                s.record_largest_allocation(largest_allocation);
+                stats.insert(name, s);
             }
         }
     }
 }
 
-use crate::lib_on::HOTPATH_STATE;
+use crate::lib_on::{report_missing_guard, HOTPATH_STATE};
 
+#[allow(clippy::too_many_arguments)]
 pub fn send_alloc_measurement(
     name: &'static str,
     bytes_total: u64,
+    allocs_total: u64,
     unsupported_async: bool,
+    largest_allocation: Option<LargestAllocation>,
     wrapper: bool,
     cross_thread: bool,
+    percentiles: Option<&'static [f64]>,
 ) {
     let Some(arc_swap) = HOTPATH_STATE.get() else {
-        panic!(
-            "GuardBuilder::new(\"main\").build() must be called when --features hotpath-alloc-bytes-total is enabled"
-        );
+        report_missing_guard("hotpath-alloc-bytes-total");
+        return;
     };
 
     let Some(state) = arc_swap.load_full() else {
@@ -184,14 +350,17 @@ pub fn send_alloc_measurement(
         return;
     };
 
-    let elapsed = state_guard.start_time.elapsed();
+    let elapsed = crate::lib_on::clock::elapsed(state_guard.start_time);
     let measurement = Measurement::Allocation(
         name,
         bytes_total,
+        allocs_total,
         elapsed,
         unsupported_async,
+        largest_allocation,
         wrapper,
         cross_thread,
+        percentiles,
     );
-    let _ = sender.try_send(measurement);
+    sender.send(measurement);
 }
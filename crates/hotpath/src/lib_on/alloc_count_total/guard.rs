@@ -1,14 +1,39 @@
+use crate::lib_on::recursion::{self, Call};
+
 pub struct MeasurementGuard {
     name: &'static str,
     wrapper: bool,
     unsupported_async: bool,
+    recorded: bool,
+    call: Call,
     thread_id: std::thread::ThreadId,
+    percentiles: Option<&'static [f64]>,
 }
 
 impl MeasurementGuard {
     #[inline]
     pub fn new(name: &'static str, wrapper: bool, unsupported_async: bool) -> Self {
-        if !unsupported_async {
+        Self::new_with_recursion(name, wrapper, unsupported_async, false)
+    }
+
+    /// Like [`Self::new`], but with `track_recursion` only the outermost of recursive calls
+    /// on the current thread is recorded, and nested calls count towards it.
+    #[inline]
+    pub(crate) fn new_with_recursion(
+        name: &'static str,
+        wrapper: bool,
+        unsupported_async: bool,
+        track_recursion: bool,
+    ) -> Self {
+        let call = if track_recursion {
+            recursion::enter(name)
+        } else {
+            Call::Untracked
+        };
+        let recorded =
+            call != Call::Nested && (wrapper || crate::lib_on::filter::is_recorded(name));
+
+        if recorded && !unsupported_async {
             super::core::ALLOCATIONS.with(|stack| {
                 let current_depth = stack.depth.get();
                 stack.depth.set(current_depth + 1);
@@ -23,14 +48,32 @@ impl MeasurementGuard {
             name,
             wrapper,
             unsupported_async,
-            thread_id: std::thread::current().id(),
+            recorded,
+            call,
+            thread_id: super::super::alloc::shared::without_tracking(|| {
+                std::thread::current().id()
+            }),
+            percentiles: None,
         }
     }
+
+    /// Overrides the global percentiles reported for this measurement.
+    #[inline]
+    pub fn with_percentiles(mut self, percentiles: &'static [f64]) -> Self {
+        self.percentiles = Some(percentiles);
+        self
+    }
 }
 
 impl Drop for MeasurementGuard {
     #[inline]
     fn drop(&mut self) {
+        recursion::exit(self.name, self.call);
+
+        if !self.recorded {
+            return;
+        }
+
         let cross_thread = std::thread::current().id() != self.thread_id;
 
         let (count_total, unsupported_async) = if self.unsupported_async || cross_thread {
@@ -58,12 +101,16 @@ impl Drop for MeasurementGuard {
             })
         };
 
-        super::state::send_alloc_measurement(
-            self.name,
-            count_total,
-            unsupported_async,
-            self.wrapper,
-            cross_thread,
-        );
+        // Sending may allocate (e.g. channel growth), which must not count towards the parent
+        super::super::alloc::shared::without_tracking(|| {
+            super::state::send_alloc_measurement(
+                self.name,
+                count_total,
+                unsupported_async,
+                self.wrapper,
+                cross_thread,
+                self.percentiles,
+            )
+        });
     }
 }
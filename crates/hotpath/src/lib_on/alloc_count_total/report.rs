@@ -1,73 +1,62 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
 
-use super::super::output::{MetricType, MetricsProvider};
+use super::super::output::{
+    percentile_columns, rows_within_deadline, tracks_percentile, MetricType, MetricsProvider,
+    ReportDeadline,
+};
+use super::super::shared;
 use super::state::FunctionStats;
-use crate::ProfilingMode;
+use crate::{PercentBasis, ProfilingMode, SortBy};
 
 pub struct StatsData<'a> {
     pub stats: &'a HashMap<&'static str, FunctionStats>,
     pub total_elapsed: Duration,
-    pub percentiles: Vec<u8>,
+    pub percentiles: Vec<f64>,
     pub caller_name: &'static str,
     pub limit: usize,
+    /// Ignored, allocation modes always use the grand total of measured allocations
+    pub percent_basis: PercentBasis,
+    pub process_peak_rss: Option<u64>,
+    pub dropped_functions: usize,
+    /// Whether the caller wrapper gets its own row, see `GuardBuilder::include_wrapper`
+    pub include_wrapper: bool,
+    pub metadata: BTreeMap<String, String>,
+    pub sort_by: SortBy,
+    /// Bounds the time spent computing rows, see `GuardBuilder::report_deadline`
+    pub report_deadline: Option<ReportDeadline>,
 }
 
-impl<'a> MetricsProvider<'a> for StatsData<'a> {
-    fn new(
-        stats: &'a HashMap<&'static str, FunctionStats>,
-        total_elapsed: Duration,
-        percentiles: Vec<u8>,
-        caller_name: &'static str,
-        limit: usize,
-    ) -> Self {
-        Self {
-            stats,
-            total_elapsed,
-            percentiles,
-            caller_name,
-            limit,
-        }
+impl StatsData<'_> {
+    // A wrapper moved across threads has meaningless totals and is never displayed
+    fn is_displayed(&self, stats: &FunctionStats) -> bool {
+        (stats.has_data || stats.registered)
+            && !(stats.wrapper && (stats.cross_thread || !self.include_wrapper))
     }
 
-    fn percentiles(&self) -> Vec<u8> {
-        self.percentiles.clone()
-    }
-
-    fn profiling_mode(&self) -> ProfilingMode {
-        ProfilingMode::AllocCountTotal
-    }
-
-    fn has_unsupported_async(&self) -> bool {
-        self.stats.values().any(|s| s.has_unsupported_async)
-    }
-
-    fn description(&self) -> String {
-        if super::super::alloc::shared::is_alloc_self_enabled() {
-            "Exclusive heap allocations by each function (excluding nested calls).".to_string()
-        } else {
-            "Total number of heap allocations during each function call (including nested calls)."
-                .to_string()
+    /// Largest single measurement of a function, 0 if it can't be measured
+    fn max_value(&self, stats: &FunctionStats) -> u64 {
+        if stats.has_unsupported_async || stats.cross_thread {
+            return 0;
         }
+        stats.count_total_percentile(100.0)
     }
 
-    fn metric_data(&self) -> HashMap<String, Vec<MetricType>> {
+    /// Report rows of the displayed functions, only the first `limit` of them unless it's 0
+    fn rows(&self, limit: usize) -> HashMap<String, Vec<MetricType>> {
         let mut filtered_stats: Vec<_> = self
             .stats
             .iter()
-            .filter(|(_, s)| s.has_data && !(s.wrapper && s.cross_thread))
+            .filter(|(_, s)| self.is_displayed(s))
             .collect();
 
-        filtered_stats.sort_by(|a, b| b.1.total_count().cmp(&a.1.total_count()));
-
-        let filtered_stats = if self.limit > 0 {
-            filtered_stats
-                .into_iter()
-                .take(self.limit)
-                .collect::<Vec<_>>()
-        } else {
-            filtered_stats
-        };
+        match self.sort_by {
+            SortBy::Total => {
+                filtered_stats.sort_by_key(|(name, s)| (std::cmp::Reverse(s.total_count()), *name))
+            }
+            SortBy::Max => filtered_stats
+                .sort_by_key(|(name, s)| (std::cmp::Reverse(self.max_value(s)), *name)),
+        }
 
         let grand_total_count: u64 = if super::super::alloc::shared::is_alloc_self_enabled() {
             self.stats
@@ -103,9 +92,19 @@ impl<'a> MetricsProvider<'a> for StatsData<'a> {
             }
         };
 
-        filtered_stats
-            .into_iter()
-            .map(|(function_name, stats)| {
+        // Functions cut off by the limit still count towards the grand total
+        let filtered_stats = if limit > 0 {
+            filtered_stats.into_iter().take(limit).collect::<Vec<_>>()
+        } else {
+            filtered_stats
+        };
+
+        let columns = self.percentiles();
+
+        rows_within_deadline(
+            self.report_deadline.as_ref(),
+            filtered_stats,
+            |(function_name, stats)| {
                 let percentage = if grand_total_count > 0 {
                     (stats.total_count() as f64 / grand_total_count as f64) * 100.0
                 } else {
@@ -121,11 +120,13 @@ impl<'a> MetricsProvider<'a> for StatsData<'a> {
                     ]
                 };
 
-                for &p in &self.percentiles {
-                    if stats.has_unsupported_async || stats.cross_thread {
+                for &p in &columns {
+                    if !tracks_percentile(&self.percentiles, stats.percentiles, p) {
+                        metrics.push(MetricType::NotTracked);
+                    } else if stats.has_unsupported_async || stats.cross_thread {
                         metrics.push(MetricType::Unsupported);
                     } else {
-                        let count_total = stats.count_total_percentile(p as f64);
+                        let count_total = stats.count_total_percentile(p);
                         metrics.push(MetricType::AllocCount(count_total));
                     }
                 }
@@ -139,8 +140,64 @@ impl<'a> MetricsProvider<'a> for StatsData<'a> {
                 }
 
                 (function_name.to_string(), metrics)
-            })
-            .collect()
+            },
+        )
+        .into_iter()
+        .collect()
+    }
+}
+
+impl<'a> MetricsProvider<'a> for StatsData<'a> {
+    fn new(
+        stats: &'a HashMap<&'static str, FunctionStats>,
+        total_elapsed: Duration,
+        percentiles: Vec<f64>,
+        caller_name: &'static str,
+        limit: usize,
+    ) -> Self {
+        Self {
+            stats,
+            total_elapsed,
+            percentiles,
+            caller_name,
+            limit,
+            percent_basis: PercentBasis::Wall,
+            process_peak_rss: None,
+            dropped_functions: 0,
+            include_wrapper: true,
+            metadata: BTreeMap::new(),
+            sort_by: SortBy::Total,
+            report_deadline: None,
+        }
+    }
+
+    fn percentiles(&self) -> Vec<f64> {
+        percentile_columns(
+            &self.percentiles,
+            self.stats.values().map(|s| s.percentiles),
+        )
+    }
+
+    fn profiling_mode(&self) -> ProfilingMode {
+        ProfilingMode::AllocCountTotal
+    }
+
+    fn has_unsupported_async(&self) -> bool {
+        self.stats.values().any(|s| s.has_unsupported_async)
+    }
+
+    fn description(&self) -> String {
+        self.profiling_mode()
+            .description_with_alloc_self(super::super::alloc::shared::is_alloc_self_enabled())
+            .to_string()
+    }
+
+    fn metric_data(&self) -> HashMap<String, Vec<MetricType>> {
+        self.rows(self.limit)
+    }
+
+    fn all_metric_data(&self) -> HashMap<String, Vec<MetricType>> {
+        self.rows(0)
     }
 
     fn total_elapsed(&self) -> u64 {
@@ -151,19 +208,73 @@ impl<'a> MetricsProvider<'a> for StatsData<'a> {
         self.caller_name
     }
 
-    fn entry_counts(&self) -> (usize, usize) {
-        let total_count = self
-            .stats
-            .iter()
-            .filter(|(_, s)| s.has_data && !(s.wrapper && s.cross_thread))
-            .count();
+    fn process_peak_rss(&self) -> Option<u64> {
+        self.process_peak_rss
+    }
 
-        let displayed_count = if self.limit > 0 && self.limit < total_count {
-            self.limit
-        } else {
-            total_count
-        };
+    fn dropped_functions(&self) -> usize {
+        self.dropped_functions
+    }
+
+    fn deadline_truncated(&self) -> usize {
+        self.report_deadline
+            .as_ref()
+            .map_or(0, ReportDeadline::truncated)
+    }
+
+    fn uncalled_functions(&self) -> Vec<String> {
+        shared::uncalled_functions(self.stats)
+    }
+
+    fn metadata(&self) -> BTreeMap<String, String> {
+        self.metadata.clone()
+    }
+
+    fn max_sort_keys(&self) -> Option<HashMap<String, u64>> {
+        (self.sort_by == SortBy::Max).then(|| {
+            self.stats
+                .iter()
+                .filter(|(_, s)| self.is_displayed(s))
+                .map(|(name, s)| (name.to_string(), self.max_value(s)))
+                .collect()
+        })
+    }
 
-        (displayed_count, total_count)
+    fn distributions(&self) -> HashMap<String, Vec<MetricType>> {
+        shared::distributions(
+            self.stats,
+            |s| self.is_displayed(s),
+            |s, p| {
+                if s.has_unsupported_async || s.cross_thread {
+                    MetricType::Unsupported
+                } else {
+                    MetricType::AllocCount(s.count_total_percentile(p))
+                }
+            },
+        )
+    }
+
+    fn trimmed_means(&self) -> HashMap<String, MetricType> {
+        self.stats
+            .iter()
+            .filter(|(_, s)| self.is_displayed(s))
+            .map(|(name, s)| {
+                let trimmed_mean = if s.has_unsupported_async || s.cross_thread {
+                    MetricType::Unsupported
+                } else {
+                    MetricType::AllocCount(s.trimmed_avg_count())
+                };
+                (name.to_string(), trimmed_mean)
+            })
+            .collect()
+    }
+
+    fn entry_counts(&self) -> (usize, usize) {
+        shared::entry_counts(
+            self.stats,
+            |s| self.is_displayed(s),
+            self.limit,
+            self.deadline_truncated(),
+        )
     }
 }
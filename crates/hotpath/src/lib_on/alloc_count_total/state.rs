@@ -4,8 +4,33 @@ use std::collections::{HashMap, VecDeque};
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
+use super::super::shared::{self, ModeStats};
+
 pub enum Measurement {
-    Allocation(&'static str, u64, Duration, bool, bool, bool), // function_name, count_total, elapsed_since_start, unsupported_async, wrapper, cross_thread
+    Allocation(
+        &'static str,
+        u64,
+        Duration,
+        bool,
+        bool,
+        bool,
+        Option<&'static [f64]>,
+    ), // function_name, count_total, elapsed_since_start, unsupported_async, wrapper, cross_thread, percentiles
+}
+
+impl Measurement {
+    pub fn name_and_value(&self) -> (&'static str, u64) {
+        match self {
+            Measurement::Allocation(name, count_total, ..) => (name, *count_total),
+        }
+    }
+
+    /// Time since the guard was created when the measurement was sent
+    pub fn elapsed(&self) -> Duration {
+        match self {
+            Measurement::Allocation(_, _, elapsed, ..) => *elapsed,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -13,10 +38,20 @@ pub struct FunctionStats {
     pub count: u64,
     count_total_hist: Option<Histogram<u64>>,
     pub has_data: bool,
+    /// Pre-registered with `GuardBuilder::register`, reported even without measurements
+    pub registered: bool,
     pub has_unsupported_async: bool,
     pub wrapper: bool,
     pub cross_thread: bool,
     pub recent_samples: VecDeque<(u64, Duration)>,
+    /// Per-function percentiles overriding the global ones, set with `#[measure(percentiles = [..])]`
+    pub percentiles: Option<&'static [f64]>,
+}
+
+impl ModeStats for FunctionStats {
+    fn is_uncalled(&self) -> bool {
+        self.registered && !self.has_data
+    }
 }
 
 impl FunctionStats {
@@ -43,15 +78,80 @@ impl FunctionStats {
             count: 1,
             count_total_hist: Some(count_total_hist),
             has_data: true,
+            registered: false,
             has_unsupported_async: unsupported_async,
             wrapper,
             cross_thread,
             recent_samples,
+            percentiles: None,
         };
         s.record_alloc(count_total);
         s
     }
 
+    /// Empty entry for a function registered up front, before any measurement.
+    pub fn new_registered(recent_samples_limit: usize) -> Self {
+        let count_total_hist =
+            Histogram::<u64>::new_with_bounds(Self::LOW_COUNT, Self::HIGH_COUNT, Self::SIGFIGS)
+                .expect("count_total histogram init");
+
+        Self {
+            count: 0,
+            count_total_hist: Some(count_total_hist),
+            has_data: false,
+            registered: true,
+            has_unsupported_async: false,
+            wrapper: false,
+            cross_thread: false,
+            recent_samples: VecDeque::with_capacity(recent_samples_limit),
+            percentiles: None,
+        }
+    }
+
+    /// Copy of the aggregated stats without recent samples, used as a baseline for [`Self::since`].
+    pub fn snapshot(&self) -> Self {
+        Self {
+            recent_samples: VecDeque::new(),
+            count_total_hist: self.count_total_hist.clone(),
+            ..*self
+        }
+    }
+
+    /// Stats accumulated since `previous`, an earlier [`Self::snapshot`] of the same function.
+    pub fn since(&self, previous: Option<&Self>) -> Self {
+        let mut delta = self.snapshot();
+        if let Some(previous) = previous {
+            delta.count = delta.count.saturating_sub(previous.count);
+            if let (Some(hist), Some(previous_hist)) =
+                (&mut delta.count_total_hist, &previous.count_total_hist)
+            {
+                let _ = hist.subtract(previous_hist);
+            }
+            delta.has_data = delta.count > 0;
+        }
+        delta
+    }
+
+    /// Combines the stats of `other` into these, e.g. to aggregate several functions or runs.
+    /// Distributions are added, so percentiles reflect the calls of both.
+    pub fn merge(&mut self, other: &FunctionStats) {
+        self.count += other.count;
+        if let (Some(hist), Some(other_hist)) =
+            (&mut self.count_total_hist, &other.count_total_hist)
+        {
+            let _ = hist.add(other_hist);
+        }
+        self.has_data |= other.has_data;
+        self.registered |= other.registered;
+        self.has_unsupported_async |= other.has_unsupported_async;
+        self.cross_thread |= other.cross_thread;
+        self.percentiles = self.percentiles.or(other.percentiles);
+
+        for &sample in &other.recent_samples {
+            shared::push_recent_sample(&mut self.recent_samples, sample);
+        }
+    }
+
     #[inline]
     fn record_alloc(&mut self, count_total: u64) {
         if let Some(ref mut count_total_hist) = self.count_total_hist {
@@ -70,16 +170,12 @@ impl FunctionStats {
         cross_thread: bool,
     ) {
         self.count += 1;
+        self.has_data = true;
         self.has_unsupported_async |= unsupported_async;
         self.cross_thread |= cross_thread;
         self.record_alloc(count_total);
 
-        if self.recent_samples.len() == self.recent_samples.capacity()
-            && self.recent_samples.capacity() > 0
-        {
-            self.recent_samples.pop_front();
-        }
-        self.recent_samples.push_back((count_total, elapsed));
+        shared::push_recent_sample(&mut self.recent_samples, (count_total, elapsed));
     }
 
     #[inline]
@@ -112,17 +208,25 @@ impl FunctionStats {
         }
         self.count_total_hist.as_ref().unwrap().mean() as u64
     }
+
+    #[inline]
+    pub fn trimmed_avg_count(&self) -> u64 {
+        self.count_total_hist
+            .as_ref()
+            .map_or(0, crate::output::trimmed_mean)
+    }
 }
 
 pub(crate) struct HotPathState {
-    pub sender: Option<Sender<Measurement>>,
+    pub sender: Option<crate::lib_on::MeasurementSender>,
     pub shutdown_tx: Option<Sender<()>>,
     pub completion_rx: Option<Mutex<Receiver<HashMap<&'static str, FunctionStats>>>>,
-    pub query_tx: Option<Sender<crate::lib_on::QueryRequest>>,
+    pub query_tx: Option<crate::lib_on::QuerySender>,
     pub start_time: Instant,
     pub caller_name: &'static str,
-    pub percentiles: Vec<u8>,
+    pub percentiles: Vec<f64>,
     pub limit: usize,
+    pub percent_basis: crate::lib_on::PercentBasis,
 }
 
 pub(crate) fn process_measurement(
@@ -138,27 +242,28 @@ pub(crate) fn process_measurement(
             unsupported_async,
             wrapper,
             cross_thread,
+            percentiles,
         ) => {
             if let Some(s) = stats.get_mut(name) {
                 s.update_alloc(count_total, elapsed, unsupported_async, cross_thread);
+                s.percentiles = s.percentiles.or(percentiles);
             } else {
-                stats.insert(
-                    name,
-                    FunctionStats::new_alloc(
-                        count_total,
-                        elapsed,
-                        unsupported_async,
-                        wrapper,
-                        cross_thread,
-                        recent_samples_limit,
-                    ),
+                let mut s = FunctionStats::new_alloc(
+                    count_total,
+                    elapsed,
+                    unsupported_async,
+                    wrapper,
+                    cross_thread,
+                    recent_samples_limit,
                 );
+                s.percentiles = percentiles;
+                stats.insert(name, s);
             }
         }
     }
 }
 
-use crate::lib_on::HOTPATH_STATE;
+use crate::lib_on::{report_missing_guard, HOTPATH_STATE};
 
 pub fn send_alloc_measurement(
     name: &'static str,
@@ -166,11 +271,11 @@ pub fn send_alloc_measurement(
     unsupported_async: bool,
     wrapper: bool,
     cross_thread: bool,
+    percentiles: Option<&'static [f64]>,
 ) {
     let Some(arc_swap) = HOTPATH_STATE.get() else {
-        panic!(
-            "GuardBuilder::new(\"main\").build() must be called when --features hotpath-alloc-count-total is enabled"
-        );
+        report_missing_guard("hotpath-alloc-count-total");
+        return;
     };
 
     let Some(state) = arc_swap.load_full() else {
@@ -184,7 +289,7 @@ pub fn send_alloc_measurement(
         return;
     };
 
-    let elapsed = state_guard.start_time.elapsed();
+    let elapsed = crate::lib_on::clock::elapsed(state_guard.start_time);
     let measurement = Measurement::Allocation(
         name,
         count_total,
@@ -192,6 +297,7 @@ pub fn send_alloc_measurement(
         unsupported_async,
         wrapper,
         cross_thread,
+        percentiles,
     );
-    let _ = sender.try_send(measurement);
+    sender.send(measurement);
 }
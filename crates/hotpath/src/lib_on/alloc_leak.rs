@@ -0,0 +1,4 @@
+pub mod core;
+pub mod guard;
+pub mod report;
+pub mod state;
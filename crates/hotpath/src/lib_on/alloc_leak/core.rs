@@ -0,0 +1,70 @@
+use std::cell::Cell;
+
+pub const MAX_DEPTH: usize = 64;
+
+/// Allocation info tracking bytes allocated and freed
+pub struct AllocationInfo {
+    /// The total amount of bytes allocated during a [measure()] call.
+    pub bytes_allocated: Cell<u64>,
+
+    /// The total amount of bytes freed during a [measure()] call, including memory
+    /// allocated before the call started.
+    pub bytes_freed: Cell<u64>,
+
+    pub unsupported_async: Cell<bool>,
+}
+
+impl AllocationInfo {
+    /// Bytes allocated and not freed again, never below zero even if the scope
+    /// freed memory allocated before it started.
+    #[inline]
+    pub fn bytes_retained(&self) -> u64 {
+        self.bytes_allocated
+            .get()
+            .saturating_sub(self.bytes_freed.get())
+    }
+}
+
+impl std::ops::AddAssign for AllocationInfo {
+    fn add_assign(&mut self, other: Self) {
+        self.bytes_allocated
+            .set(self.bytes_allocated.get() + other.bytes_allocated.get());
+        self.bytes_freed
+            .set(self.bytes_freed.get() + other.bytes_freed.get());
+        self.unsupported_async
+            .set(self.unsupported_async.get() | other.unsupported_async.get());
+    }
+}
+
+pub struct AllocationInfoStack {
+    pub depth: Cell<u32>,
+    pub elements: [AllocationInfo; MAX_DEPTH],
+}
+
+thread_local! {
+    pub static ALLOCATIONS: AllocationInfoStack = const { AllocationInfoStack {
+        depth: Cell::new(0),
+        elements: [const { AllocationInfo { bytes_allocated: Cell::new(0), bytes_freed: Cell::new(0), unsupported_async: Cell::new(false) } }; MAX_DEPTH],
+    } };
+}
+
+/// Called by the shared global allocator to track allocations
+#[inline]
+pub fn track_alloc(size: usize) {
+    ALLOCATIONS.with(|stack| {
+        let depth = stack.depth.get() as usize;
+        let info = &stack.elements[depth];
+        info.bytes_allocated
+            .set(info.bytes_allocated.get() + size as u64);
+    });
+}
+
+/// Called by the shared global allocator to track deallocations
+#[inline]
+pub fn track_dealloc(size: usize) {
+    ALLOCATIONS.with(|stack| {
+        let depth = stack.depth.get() as usize;
+        let info = &stack.elements[depth];
+        info.bytes_freed.set(info.bytes_freed.get() + size as u64);
+    });
+}
@@ -0,0 +1,280 @@
+use crate::{PercentBasis, ProfilingMode, SortBy};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use super::super::output::{
+    percentile_columns, rows_within_deadline, tracks_percentile, MetricType, MetricsProvider,
+    ReportDeadline,
+};
+use super::super::shared;
+use super::state::FunctionStats;
+
+pub struct StatsData<'a> {
+    pub stats: &'a HashMap<&'static str, FunctionStats>,
+    pub total_elapsed: Duration,
+    pub percentiles: Vec<f64>,
+    pub caller_name: &'static str,
+    pub limit: usize,
+    /// Ignored, allocation modes always use the grand total of measured allocations
+    pub percent_basis: PercentBasis,
+    pub process_peak_rss: Option<u64>,
+    pub dropped_functions: usize,
+    /// Whether the caller wrapper gets its own row, see `GuardBuilder::include_wrapper`
+    pub include_wrapper: bool,
+    pub metadata: BTreeMap<String, String>,
+    pub sort_by: SortBy,
+    /// Bounds the time spent computing rows, see `GuardBuilder::report_deadline`
+    pub report_deadline: Option<ReportDeadline>,
+}
+
+impl StatsData<'_> {
+    // A wrapper moved across threads has meaningless totals and is never displayed
+    fn is_displayed(&self, stats: &FunctionStats) -> bool {
+        (stats.has_data || stats.registered)
+            && !(stats.wrapper && (stats.cross_thread || !self.include_wrapper))
+    }
+
+    /// Largest single measurement of a function, 0 if it can't be measured
+    fn max_value(&self, stats: &FunctionStats) -> u64 {
+        if stats.has_unsupported_async || stats.cross_thread {
+            return 0;
+        }
+        stats.bytes_retained_percentile(100.0)
+    }
+
+    /// Report rows of the displayed functions, only the first `limit` of them unless it's 0
+    fn rows(&self, limit: usize) -> HashMap<String, Vec<MetricType>> {
+        let mut filtered_stats: Vec<_> = self
+            .stats
+            .iter()
+            .filter(|(_, s)| self.is_displayed(s))
+            .collect();
+
+        match self.sort_by {
+            SortBy::Total => {
+                filtered_stats.sort_by_key(|(name, s)| (std::cmp::Reverse(s.total_bytes()), *name))
+            }
+            SortBy::Max => filtered_stats
+                .sort_by_key(|(name, s)| (std::cmp::Reverse(self.max_value(s)), *name)),
+        }
+
+        let grand_total_bytes: u64 = if super::super::alloc::shared::is_alloc_self_enabled() {
+            self.stats
+                .iter()
+                .filter(|(_, s)| s.has_data)
+                .map(|(_, stats)| stats.total_bytes())
+                .sum()
+        } else {
+            let has_cross_thread_wrapper =
+                self.stats.iter().any(|(_, s)| s.wrapper && s.cross_thread);
+
+            if has_cross_thread_wrapper {
+                // If wrapper was moved across threads, use sum of all functions
+                filtered_stats
+                    .iter()
+                    .filter(|(_, s)| !s.wrapper)
+                    .map(|(_, stats)| stats.total_bytes())
+                    .sum()
+            } else {
+                // Use wrapper total if available
+                let wrapper_total_bytes = self
+                    .stats
+                    .iter()
+                    .find(|(_, s)| s.wrapper)
+                    .map(|(_, s)| s.total_bytes());
+
+                wrapper_total_bytes.unwrap_or_else(|| {
+                    filtered_stats
+                        .iter()
+                        .map(|(_, stats)| stats.total_bytes())
+                        .sum()
+                })
+            }
+        };
+
+        // Functions cut off by the limit still count towards the grand total
+        let filtered_stats = if limit > 0 {
+            filtered_stats.into_iter().take(limit).collect::<Vec<_>>()
+        } else {
+            filtered_stats
+        };
+
+        let columns = self.percentiles();
+
+        rows_within_deadline(
+            self.report_deadline.as_ref(),
+            filtered_stats,
+            |(function_name, stats)| {
+                let percentage = if grand_total_bytes > 0 {
+                    (stats.total_bytes() as f64 / grand_total_bytes as f64) * 100.0
+                } else {
+                    0.0
+                };
+
+                let mut metrics = if stats.has_unsupported_async || stats.cross_thread {
+                    vec![MetricType::CallsCount(stats.count), MetricType::Unsupported]
+                } else {
+                    vec![
+                        MetricType::CallsCount(stats.count),
+                        MetricType::AllocBytes(stats.avg_bytes()),
+                    ]
+                };
+
+                for &p in &columns {
+                    if !tracks_percentile(&self.percentiles, stats.percentiles, p) {
+                        metrics.push(MetricType::NotTracked);
+                    } else if stats.has_unsupported_async || stats.cross_thread {
+                        metrics.push(MetricType::Unsupported);
+                    } else {
+                        let bytes_retained = stats.bytes_retained_percentile(p);
+                        metrics.push(MetricType::AllocBytes(bytes_retained));
+                    }
+                }
+
+                if stats.has_unsupported_async || stats.cross_thread {
+                    metrics.push(MetricType::Unsupported);
+                    metrics.push(MetricType::Unsupported);
+                } else {
+                    metrics.push(MetricType::AllocBytes(stats.total_bytes()));
+                    metrics.push(MetricType::Percentage((percentage * 100.0) as u64));
+                }
+
+                (function_name.to_string(), metrics)
+            },
+        )
+        .into_iter()
+        .collect()
+    }
+}
+
+impl<'a> MetricsProvider<'a> for StatsData<'a> {
+    fn new(
+        stats: &'a HashMap<&'static str, FunctionStats>,
+        total_elapsed: Duration,
+        percentiles: Vec<f64>,
+        caller_name: &'static str,
+        limit: usize,
+    ) -> Self {
+        Self {
+            stats,
+            total_elapsed,
+            percentiles,
+            caller_name,
+            limit,
+            percent_basis: PercentBasis::Wall,
+            process_peak_rss: None,
+            dropped_functions: 0,
+            include_wrapper: true,
+            metadata: BTreeMap::new(),
+            sort_by: SortBy::Total,
+            report_deadline: None,
+        }
+    }
+
+    fn profiling_mode(&self) -> ProfilingMode {
+        ProfilingMode::AllocLeak
+    }
+
+    fn description(&self) -> String {
+        self.profiling_mode()
+            .description_with_alloc_self(super::super::alloc::shared::is_alloc_self_enabled())
+            .to_string()
+    }
+
+    fn percentiles(&self) -> Vec<f64> {
+        percentile_columns(
+            &self.percentiles,
+            self.stats.values().map(|s| s.percentiles),
+        )
+    }
+
+    fn has_unsupported_async(&self) -> bool {
+        self.stats.values().any(|s| s.has_unsupported_async)
+    }
+
+    fn metric_data(&self) -> HashMap<String, Vec<MetricType>> {
+        self.rows(self.limit)
+    }
+
+    fn all_metric_data(&self) -> HashMap<String, Vec<MetricType>> {
+        self.rows(0)
+    }
+
+    fn total_elapsed(&self) -> u64 {
+        self.total_elapsed.as_nanos() as u64
+    }
+
+    fn caller_name(&self) -> &str {
+        self.caller_name
+    }
+
+    fn process_peak_rss(&self) -> Option<u64> {
+        self.process_peak_rss
+    }
+
+    fn dropped_functions(&self) -> usize {
+        self.dropped_functions
+    }
+
+    fn deadline_truncated(&self) -> usize {
+        self.report_deadline
+            .as_ref()
+            .map_or(0, ReportDeadline::truncated)
+    }
+
+    fn uncalled_functions(&self) -> Vec<String> {
+        shared::uncalled_functions(self.stats)
+    }
+
+    fn metadata(&self) -> BTreeMap<String, String> {
+        self.metadata.clone()
+    }
+
+    fn max_sort_keys(&self) -> Option<HashMap<String, u64>> {
+        (self.sort_by == SortBy::Max).then(|| {
+            self.stats
+                .iter()
+                .filter(|(_, s)| self.is_displayed(s))
+                .map(|(name, s)| (name.to_string(), self.max_value(s)))
+                .collect()
+        })
+    }
+
+    fn distributions(&self) -> HashMap<String, Vec<MetricType>> {
+        shared::distributions(
+            self.stats,
+            |s| self.is_displayed(s),
+            |s, p| {
+                if s.has_unsupported_async || s.cross_thread {
+                    MetricType::Unsupported
+                } else {
+                    MetricType::AllocBytes(s.bytes_retained_percentile(p))
+                }
+            },
+        )
+    }
+
+    fn trimmed_means(&self) -> HashMap<String, MetricType> {
+        self.stats
+            .iter()
+            .filter(|(_, s)| self.is_displayed(s))
+            .map(|(name, s)| {
+                let trimmed_mean = if s.has_unsupported_async || s.cross_thread {
+                    MetricType::Unsupported
+                } else {
+                    MetricType::AllocBytes(s.trimmed_avg_bytes())
+                };
+                (name.to_string(), trimmed_mean)
+            })
+            .collect()
+    }
+
+    fn entry_counts(&self) -> (usize, usize) {
+        shared::entry_counts(
+            self.stats,
+            |s| self.is_displayed(s),
+            self.limit,
+            self.deadline_truncated(),
+        )
+    }
+}
@@ -0,0 +1,304 @@
+use crossbeam_channel::{Receiver, Sender};
+use hdrhistogram::Histogram;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::super::shared::{self, ModeStats};
+
+pub enum Measurement {
+    Allocation(
+        &'static str,
+        u64,
+        Duration,
+        bool,
+        bool,
+        bool,
+        Option<&'static [f64]>,
+    ), // function_name, bytes_retained, elapsed_since_start, unsupported_async, wrapper, cross_thread, percentiles
+}
+
+impl Measurement {
+    pub fn name_and_value(&self) -> (&'static str, u64) {
+        match self {
+            Measurement::Allocation(name, bytes_retained, ..) => (name, *bytes_retained),
+        }
+    }
+
+    /// Time since the guard was created when the measurement was sent
+    pub fn elapsed(&self) -> Duration {
+        match self {
+            Measurement::Allocation(_, _, elapsed, ..) => *elapsed,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionStats {
+    pub count: u64,
+    bytes_retained_hist: Option<Histogram<u64>>,
+    pub has_data: bool,
+    /// Pre-registered with `GuardBuilder::register`, reported even without measurements
+    pub registered: bool,
+    pub has_unsupported_async: bool,
+    pub wrapper: bool,
+    pub cross_thread: bool,
+    pub recent_samples: VecDeque<(u64, Duration)>,
+    /// Per-function percentiles overriding the global ones, set with `#[measure(percentiles = [..])]`
+    pub percentiles: Option<&'static [f64]>,
+}
+
+impl ModeStats for FunctionStats {
+    fn is_uncalled(&self) -> bool {
+        self.registered && !self.has_data
+    }
+}
+
+impl FunctionStats {
+    const LOW_BYTES: u64 = 1;
+    const HIGH_BYTES: u64 = 1_000_000_000; // 1GB
+    const SIGFIGS: u8 = 3;
+
+    pub fn new_alloc(
+        bytes_retained: u64,
+        elapsed: Duration,
+        unsupported_async: bool,
+        wrapper: bool,
+        cross_thread: bool,
+        recent_samples_limit: usize,
+    ) -> Self {
+        let bytes_retained_hist =
+            Histogram::<u64>::new_with_bounds(Self::LOW_BYTES, Self::HIGH_BYTES, Self::SIGFIGS)
+                .expect("bytes_retained histogram init");
+
+        let mut recent_samples = VecDeque::with_capacity(recent_samples_limit);
+        recent_samples.push_back((bytes_retained, elapsed));
+
+        let mut s = Self {
+            count: 1,
+            bytes_retained_hist: Some(bytes_retained_hist),
+            has_data: true,
+            registered: false,
+            has_unsupported_async: unsupported_async,
+            wrapper,
+            cross_thread,
+            recent_samples,
+            percentiles: None,
+        };
+        s.record_alloc(bytes_retained);
+        s
+    }
+
+    /// Empty entry for a function registered up front, before any measurement.
+    pub fn new_registered(recent_samples_limit: usize) -> Self {
+        let bytes_retained_hist =
+            Histogram::<u64>::new_with_bounds(Self::LOW_BYTES, Self::HIGH_BYTES, Self::SIGFIGS)
+                .expect("bytes_retained histogram init");
+
+        Self {
+            count: 0,
+            bytes_retained_hist: Some(bytes_retained_hist),
+            has_data: false,
+            registered: true,
+            has_unsupported_async: false,
+            wrapper: false,
+            cross_thread: false,
+            recent_samples: VecDeque::with_capacity(recent_samples_limit),
+            percentiles: None,
+        }
+    }
+
+    /// Copy of the aggregated stats without recent samples, used as a baseline for [`Self::since`].
+    pub fn snapshot(&self) -> Self {
+        Self {
+            recent_samples: VecDeque::new(),
+            bytes_retained_hist: self.bytes_retained_hist.clone(),
+            ..*self
+        }
+    }
+
+    /// Stats accumulated since `previous`, an earlier [`Self::snapshot`] of the same function.
+    pub fn since(&self, previous: Option<&Self>) -> Self {
+        let mut delta = self.snapshot();
+        if let Some(previous) = previous {
+            delta.count = delta.count.saturating_sub(previous.count);
+            if let (Some(hist), Some(previous_hist)) = (
+                &mut delta.bytes_retained_hist,
+                &previous.bytes_retained_hist,
+            ) {
+                let _ = hist.subtract(previous_hist);
+            }
+            delta.has_data = delta.count > 0;
+        }
+        delta
+    }
+
+    /// Combines the stats of `other` into these, e.g. to aggregate several functions or runs.
+    /// Distributions are added, so percentiles reflect the calls of both.
+    pub fn merge(&mut self, other: &FunctionStats) {
+        self.count += other.count;
+        if let (Some(hist), Some(other_hist)) =
+            (&mut self.bytes_retained_hist, &other.bytes_retained_hist)
+        {
+            let _ = hist.add(other_hist);
+        }
+        self.has_data |= other.has_data;
+        self.registered |= other.registered;
+        self.has_unsupported_async |= other.has_unsupported_async;
+        self.cross_thread |= other.cross_thread;
+        self.percentiles = self.percentiles.or(other.percentiles);
+
+        for &sample in &other.recent_samples {
+            shared::push_recent_sample(&mut self.recent_samples, sample);
+        }
+    }
+
+    #[inline]
+    fn record_alloc(&mut self, bytes_retained: u64) {
+        if let Some(ref mut bytes_retained_hist) = self.bytes_retained_hist {
+            if bytes_retained > 0 {
+                let clamped_total = bytes_retained.clamp(Self::LOW_BYTES, Self::HIGH_BYTES);
+                bytes_retained_hist.record(clamped_total).unwrap();
+            }
+        }
+    }
+
+    pub fn update_alloc(
+        &mut self,
+        bytes_retained: u64,
+        elapsed: Duration,
+        unsupported_async: bool,
+        cross_thread: bool,
+    ) {
+        self.count += 1;
+        self.has_data = true;
+        self.has_unsupported_async |= unsupported_async;
+        self.cross_thread |= cross_thread;
+        self.record_alloc(bytes_retained);
+
+        shared::push_recent_sample(&mut self.recent_samples, (bytes_retained, elapsed));
+    }
+
+    #[inline]
+    pub fn bytes_retained_percentile(&self, p: f64) -> u64 {
+        if self.count == 0 || self.bytes_retained_hist.is_none() {
+            return 0;
+        }
+        let p = p.clamp(0.0, 100.0);
+        self.bytes_retained_hist
+            .as_ref()
+            .unwrap()
+            .value_at_percentile(p)
+    }
+
+    #[inline]
+    pub fn total_bytes(&self) -> u64 {
+        if self.count == 0 || self.bytes_retained_hist.is_none() {
+            return 0;
+        }
+        // Bytes retained across all calls, summed up as mean * count
+        let hist = self.bytes_retained_hist.as_ref().unwrap();
+        let mean = hist.mean();
+        (mean * self.count as f64) as u64
+    }
+
+    #[inline]
+    pub fn avg_bytes(&self) -> u64 {
+        if self.count == 0 || self.bytes_retained_hist.is_none() {
+            return 0;
+        }
+        self.bytes_retained_hist.as_ref().unwrap().mean() as u64
+    }
+
+    #[inline]
+    pub fn trimmed_avg_bytes(&self) -> u64 {
+        self.bytes_retained_hist
+            .as_ref()
+            .map_or(0, crate::output::trimmed_mean)
+    }
+}
+
+pub(crate) struct HotPathState {
+    pub sender: Option<crate::lib_on::MeasurementSender>,
+    pub shutdown_tx: Option<Sender<()>>,
+    pub completion_rx: Option<Mutex<Receiver<HashMap<&'static str, FunctionStats>>>>,
+    pub query_tx: Option<crate::lib_on::QuerySender>,
+    pub start_time: Instant,
+    pub caller_name: &'static str,
+    pub percentiles: Vec<f64>,
+    pub limit: usize,
+    pub percent_basis: crate::lib_on::PercentBasis,
+}
+
+pub(crate) fn process_measurement(
+    stats: &mut HashMap<&'static str, FunctionStats>,
+    m: Measurement,
+    recent_samples_limit: usize,
+) {
+    match m {
+        Measurement::Allocation(
+            name,
+            bytes_retained,
+            elapsed,
+            unsupported_async,
+            wrapper,
+            cross_thread,
+            percentiles,
+        ) => {
+            if let Some(s) = stats.get_mut(name) {
+                s.update_alloc(bytes_retained, elapsed, unsupported_async, cross_thread);
+                s.percentiles = s.percentiles.or(percentiles);
+            } else {
+                let mut s = FunctionStats::new_alloc(
+                    bytes_retained,
+                    elapsed,
+                    unsupported_async,
+                    wrapper,
+                    cross_thread,
+                    recent_samples_limit,
+                );
+                s.percentiles = percentiles;
+                stats.insert(name, s);
+            }
+        }
+    }
+}
+
+use crate::lib_on::{report_missing_guard, HOTPATH_STATE};
+
+pub fn send_alloc_measurement(
+    name: &'static str,
+    bytes_retained: u64,
+    unsupported_async: bool,
+    wrapper: bool,
+    cross_thread: bool,
+    percentiles: Option<&'static [f64]>,
+) {
+    let Some(arc_swap) = HOTPATH_STATE.get() else {
+        report_missing_guard("hotpath-alloc-leak");
+        return;
+    };
+
+    let Some(state) = arc_swap.load_full() else {
+        return;
+    };
+
+    let Ok(state_guard) = state.read() else {
+        return;
+    };
+    let Some(sender) = state_guard.sender.as_ref() else {
+        return;
+    };
+
+    let elapsed = crate::lib_on::clock::elapsed(state_guard.start_time);
+    let measurement = Measurement::Allocation(
+        name,
+        bytes_retained,
+        elapsed,
+        unsupported_async,
+        wrapper,
+        cross_thread,
+        percentiles,
+    );
+    sender.send(measurement);
+}
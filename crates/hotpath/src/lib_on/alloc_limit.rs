@@ -0,0 +1,59 @@
+//! Allocation budget of `assert_max_alloc!` blocks, read from the counting global allocator.
+//!
+//! Bytes are counted per thread, so allocations made by other threads or tasks running
+//! concurrently on other threads don't count towards a block. A block resuming on another
+//! thread, e.g. after an `.await`, compares the counters of two threads, its count is
+//! meaningless but never wraps around.
+
+cfg_if::cfg_if! {
+    if #[cfg(any(
+        feature = "hotpath-alloc-bytes-total",
+        feature = "hotpath-alloc-count-total",
+        feature = "hotpath-alloc-leak"
+    ))] {
+        struct Counter {
+            start_bytes: u64,
+        }
+
+        impl Counter {
+            #[inline]
+            fn start() -> Self {
+                Self {
+                    start_bytes: super::alloc::allocator::allocated_bytes(),
+                }
+            }
+
+            #[inline]
+            fn bytes(&self) -> u64 {
+                super::alloc::allocator::allocated_bytes().saturating_sub(self.start_bytes)
+            }
+        }
+    } else {
+        use super::time::alloc_block::ByteCounter as Counter;
+    }
+}
+
+#[doc(hidden)]
+pub struct AllocLimitGuard {
+    counter: Counter,
+}
+
+impl AllocLimitGuard {
+    #[inline]
+    pub fn start() -> Self {
+        Self {
+            counter: Counter::start(),
+        }
+    }
+
+    /// Panics if the block allocated more than `max_bytes` since [`Self::start`].
+    #[track_caller]
+    pub fn check(self, label: &str, max_bytes: u64) {
+        let bytes = self.counter.bytes();
+        if bytes > max_bytes {
+            panic!(
+                "assert_max_alloc!(\"{label}\") failed: allocated {bytes} bytes, expected at most {max_bytes} bytes"
+            );
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Last-resort report for programs calling `std::process::exit` before the guard is dropped,
+//! enabled with `GuardBuilder::report_atexit`.
+
+use std::sync::{Arc, Mutex, Weak};
+
+use super::Finalizer;
+
+/// Finalizer of the current guard, already taken if the guard was dropped normally
+static PENDING: Mutex<Option<Weak<Mutex<Option<Finalizer>>>>> = Mutex::new(None);
+
+pub(crate) fn register(finalizer: &Arc<Mutex<Option<Finalizer>>>) {
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = Some(Arc::downgrade(finalizer));
+    }
+
+    #[cfg(unix)]
+    {
+        static REGISTER: std::sync::Once = std::sync::Once::new();
+        REGISTER.call_once(|| {
+            if unsafe { libc::atexit(report_at_exit) } != 0 {
+                eprintln!("[hotpath] Failed to register the atexit report hook");
+            }
+        });
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn report_at_exit() {
+    let finalizer = PENDING
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.take())
+        .and_then(|pending| pending.upgrade())
+        .and_then(|finalizer| finalizer.lock().ok()?.take());
+
+    if let Some(finalizer) = finalizer {
+        // Thread locals of the exiting thread are already destroyed at this point
+        let _ = std::thread::spawn(move || finalizer.finish()).join();
+    }
+}
@@ -0,0 +1,139 @@
+//! Thread-local batching of measurements sent to the worker thread, enabled with the
+//! `hotpath-batch-channel` feature. Each thread buffers its measurements and sends them
+//! in batches, paying for one channel operation per batch instead of one per call.
+//!
+//! Buffers are registered globally, so that the final report can drain the buffers of
+//! threads that are still running, e.g. tokio workers or a report written from another
+//! thread with `GuardBuilder::report_atexit`.
+
+use crossbeam_channel::Sender;
+use std::sync::{Arc, Mutex, Weak};
+
+use super::Measurement;
+
+/// Measurements buffered per thread before they're sent
+pub(crate) const BATCH_SIZE: usize = 64;
+
+#[derive(Default)]
+struct Batch {
+    tx: Option<Sender<Vec<Measurement>>>,
+    measurements: Vec<Measurement>,
+}
+
+impl Batch {
+    fn flush(&mut self) {
+        if self.measurements.is_empty() {
+            return;
+        }
+        let batch = std::mem::replace(&mut self.measurements, Vec::with_capacity(BATCH_SIZE));
+        if let Some(tx) = &self.tx {
+            let _ = tx.try_send(batch);
+        }
+    }
+}
+
+// Buffers of all threads, only locked when a thread starts measuring and by `flush_all`
+static BUFFERS: Mutex<Vec<Weak<Mutex<Batch>>>> = Mutex::new(Vec::new());
+
+struct Pending(Arc<Mutex<Batch>>);
+
+impl Pending {
+    fn register() -> Self {
+        let batch = Arc::new(Mutex::new(Batch::default()));
+        if let Ok(mut buffers) = BUFFERS.lock() {
+            buffers.retain(|buffer| buffer.strong_count() > 0);
+            buffers.push(Arc::downgrade(&batch));
+        }
+        Self(batch)
+    }
+}
+
+// Threads exiting before the guard is dropped send their last batch
+impl Drop for Pending {
+    fn drop(&mut self) {
+        if let Ok(mut batch) = self.0.lock() {
+            batch.flush();
+        }
+    }
+}
+
+thread_local! {
+    static PENDING: Pending = Pending::register();
+}
+
+/// Buffers a measurement, sending the batch once it's full.
+#[inline]
+pub(crate) fn push(tx: &Sender<Vec<Measurement>>, measurement: Measurement) {
+    let mut measurement = Some(measurement);
+    let _ = PENDING.try_with(|pending| {
+        // Only contended while `flush_all` drains this thread's buffer
+        let Ok(mut batch) = pending.0.lock() else {
+            return;
+        };
+        if !batch.tx.as_ref().is_some_and(|own| own.same_channel(tx)) {
+            // Leftovers of a previous guard, sent to its disconnected channel
+            batch.flush();
+            batch.tx = Some(tx.clone());
+        }
+        batch.measurements.extend(measurement.take());
+        if batch.measurements.len() >= BATCH_SIZE {
+            batch.flush();
+        }
+    });
+
+    // Thread locals of an exiting thread are already destroyed, send it on its own
+    if let Some(measurement) = measurement {
+        let _ = tx.try_send(vec![measurement]);
+    }
+}
+
+/// Sends the measurements buffered on every thread.
+pub(crate) fn flush_all() {
+    let buffers: Vec<_> = match BUFFERS.lock() {
+        Ok(buffers) => buffers.iter().filter_map(Weak::upgrade).collect(),
+        Err(_) => return,
+    };
+    for buffer in buffers {
+        if let Ok(mut batch) = buffer.lock() {
+            batch.flush();
+        }
+    }
+}
+
+#[cfg(all(
+    test,
+    not(any(
+        feature = "hotpath-alloc-bytes-total",
+        feature = "hotpath-alloc-count-total",
+        feature = "hotpath-alloc-leak"
+    ))
+))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_flush_all_drains_other_threads() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let (sent_tx, sent_rx) = crossbeam_channel::bounded(0);
+        let (done_tx, done_rx) = crossbeam_channel::bounded::<()>(0);
+
+        let worker = std::thread::spawn(move || {
+            let measurement =
+                Measurement::Duration(1, Duration::ZERO, "batch::tests::f", false, None, 1, None);
+            push(&tx, measurement);
+            sent_tx.send(()).unwrap();
+            // Keeps the thread, and its buffer, alive until the main thread flushed it
+            done_rx.recv().unwrap();
+        });
+
+        sent_rx.recv().unwrap();
+        assert!(rx.try_recv().is_err());
+
+        flush_all();
+        assert_eq!(rx.try_recv().unwrap().len(), 1);
+
+        done_tx.send(()).unwrap();
+        worker.join().unwrap();
+    }
+}
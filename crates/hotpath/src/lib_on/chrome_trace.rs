@@ -0,0 +1,134 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether a trace is written in the current profiling session.
+pub(crate) fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether measurements need the id of the thread they were made on, only used by the trace.
+#[inline]
+pub(crate) fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Streams every measured call as a Chrome Trace Event, see `GuardBuilder::chrome_trace_file`.
+///
+/// Events are written in the JSON array format, the array is closed when the writer is dropped.
+/// Viewers also accept a trace without the closing bracket, e.g. after a crash.
+pub(crate) struct ChromeTraceWriter {
+    path: PathBuf,
+    out: Option<BufWriter<File>>,
+    pid: u32,
+    events: u64,
+}
+
+impl ChromeTraceWriter {
+    pub(crate) fn create(path: PathBuf) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(&path)?);
+        write!(out, "[")?;
+
+        Ok(Self {
+            path,
+            out: Some(out),
+            pid: std::process::id(),
+            events: 0,
+        })
+    }
+
+    /// Appends a complete (`X`) event of a call that ended `end` after the guard was created,
+    /// writing stops after the first error.
+    pub(crate) fn write(&mut self, name: &str, duration: Duration, end: Duration, tid: u64) {
+        let Some(out) = self.out.as_mut() else {
+            return;
+        };
+
+        let separator = if self.events == 0 { "" } else { "," };
+        // Timestamps are in microseconds, fractions keep nanosecond precision
+        let result = writeln!(
+            out,
+            "{}{{\"name\":{},\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":{},\"tid\":{}}}",
+            separator,
+            serde_json::Value::from(name),
+            end.saturating_sub(duration).as_nanos() as f64 / 1000.0,
+            duration.as_nanos() as f64 / 1000.0,
+            self.pid,
+            tid
+        );
+
+        match result {
+            Ok(()) => self.events += 1,
+            Err(e) => {
+                eprintln!(
+                    "[hotpath] Failed to write Chrome trace to {}, no more events are recorded: {}",
+                    self.path.display(),
+                    e
+                );
+                self.out = None;
+            }
+        }
+    }
+}
+
+impl Drop for ChromeTraceWriter {
+    fn drop(&mut self) {
+        if let Some(mut out) = self.out.take() {
+            if let Err(e) = writeln!(out, "]").and_then(|_| out.flush()) {
+                eprintln!(
+                    "[hotpath] Failed to write Chrome trace to {}: {}",
+                    self.path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chrome_trace_events() {
+        let path = std::env::temp_dir().join(format!(
+            "hotpath_test_chrome_trace_{}.json",
+            std::process::id()
+        ));
+
+        {
+            let mut writer = ChromeTraceWriter::create(path.clone()).unwrap();
+            writer.write(
+                "mod::process<A, B>",
+                Duration::from_nanos(1_500),
+                Duration::from_micros(10),
+                1,
+            );
+            writer.write(
+                "say \"hi\"",
+                Duration::from_micros(2),
+                Duration::from_micros(3),
+                2,
+            );
+        }
+
+        let trace: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let events = trace.as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["name"], "mod::process<A, B>");
+        assert_eq!(events[0]["ph"], "X");
+        assert_eq!(events[0]["ts"], 8.5);
+        assert_eq!(events[0]["dur"], 1.5);
+        assert_eq!(events[0]["pid"], std::process::id());
+        assert_eq!(events[0]["tid"], 1);
+        assert_eq!(events[1]["name"], "say \"hi\"");
+        assert_eq!(events[1]["tid"], 2);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,38 @@
+//! Clock of a profiling session, replaceable with `GuardBuilder::clock` for deterministic tests.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// Clock returning the current time, see [`GuardBuilder::clock`](crate::GuardBuilder::clock).
+pub type ClockFn = Box<dyn Fn() -> Instant + Send + Sync>;
+
+static CUSTOM: AtomicBool = AtomicBool::new(false);
+static CLOCK: RwLock<Option<ClockFn>> = RwLock::new(None);
+
+/// Replaces the clock for the next profiling session, `None` restores the real one.
+pub(crate) fn set(clock: Option<ClockFn>) {
+    CUSTOM.store(clock.is_some(), Ordering::Relaxed);
+    *CLOCK.write().unwrap_or_else(|e| e.into_inner()) = clock;
+}
+
+/// Whether a custom clock is set, the calibrated overhead isn't subtracted from its durations.
+#[inline]
+pub(crate) fn is_custom() -> bool {
+    CUSTOM.load(Ordering::Relaxed)
+}
+
+/// Current time of the custom clock if one is set, of the real clock otherwise.
+pub(crate) fn now() -> Instant {
+    if is_custom() {
+        if let Some(clock) = CLOCK.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            return clock();
+        }
+    }
+    Instant::now()
+}
+
+/// Time passed since `start`, zero if the custom clock went backwards.
+pub(crate) fn elapsed(start: Instant) -> Duration {
+    now().saturating_duration_since(start)
+}
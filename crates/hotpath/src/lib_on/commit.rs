@@ -0,0 +1,34 @@
+//! Git commit of the profiled code, embedded in JSON reports with `GuardBuilder::record_commit`.
+
+use std::process::Command;
+use std::sync::Mutex;
+
+static COMMIT: Mutex<Option<String>> = Mutex::new(None);
+
+/// The output of `git rev-parse HEAD`, `GITHUB_SHA` when `git` isn't available (e.g. a
+/// GitHub Actions job without a checkout), `None` if neither is set.
+fn detect() -> Option<String> {
+    let from_git = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+
+    from_git
+        .or_else(|| std::env::var("GITHUB_SHA").ok())
+        .map(|sha| sha.trim().to_string())
+        .filter(|sha| !sha.is_empty())
+}
+
+/// Detects the commit once per guard, so reports don't spawn `git` at shutdown.
+pub(crate) fn record(enabled: bool) {
+    let commit = if enabled { detect() } else { None };
+    if let Ok(mut current) = COMMIT.lock() {
+        *current = commit;
+    }
+}
+
+pub(crate) fn get() -> Option<String> {
+    COMMIT.lock().ok().and_then(|commit| commit.clone())
+}
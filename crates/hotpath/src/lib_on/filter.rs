@@ -0,0 +1,133 @@
+//! Runtime allowlist of recorded functions, configured with the `HOTPATH_ONLY` env var.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+static FILTER_ACTIVE: AtomicBool = AtomicBool::new(false);
+static FILTER: RwLock<Option<FunctionFilter>> = RwLock::new(None);
+
+struct FunctionFilter {
+    patterns: Vec<String>,
+    // Keyed by the address and length of the interned name, so lookups don't hash its contents
+    decisions: HashMap<(usize, usize), bool>,
+}
+
+/// Restricts recording to functions matching any of the comma-separated `patterns`,
+/// e.g. `myapp::db::*,myapp::cache::get`. Empty patterns clear the filter.
+pub(crate) fn set(patterns: &str) {
+    let patterns: Vec<String> = patterns
+        .split(',')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(String::from)
+        .collect();
+
+    let active = !patterns.is_empty();
+    if let Ok(mut filter) = FILTER.write() {
+        *filter = active.then(|| FunctionFilter {
+            patterns,
+            decisions: HashMap::new(),
+        });
+    }
+    FILTER_ACTIVE.store(active, Ordering::Relaxed);
+}
+
+pub(crate) fn clear() {
+    set("");
+}
+
+/// Whether measurements of `name` are recorded, always true without a filter.
+/// Each name is matched once, later checks are a cache lookup.
+#[inline]
+pub(crate) fn is_recorded(name: &'static str) -> bool {
+    if !FILTER_ACTIVE.load(Ordering::Relaxed) {
+        return true;
+    }
+    is_recorded_slow(name)
+}
+
+#[cold]
+fn is_recorded_slow(name: &'static str) -> bool {
+    cfg_if::cfg_if! {
+        if #[cfg(any(
+            feature = "hotpath-alloc-bytes-total",
+            feature = "hotpath-alloc-count-total",
+            feature = "hotpath-alloc-leak"
+        ))] {
+            // Matching a new name allocates, which must not count towards the measured function
+            super::alloc::shared::without_tracking(|| lookup(name))
+        } else {
+            lookup(name)
+        }
+    }
+}
+
+fn lookup(name: &'static str) -> bool {
+    let key = (name.as_ptr() as usize, name.len());
+
+    if let Ok(filter) = FILTER.read() {
+        match filter.as_ref() {
+            Some(filter) => {
+                if let Some(&recorded) = filter.decisions.get(&key) {
+                    return recorded;
+                }
+            }
+            None => return true,
+        }
+    }
+
+    let Ok(mut filter) = FILTER.write() else {
+        return true;
+    };
+    let Some(filter) = filter.as_mut() else {
+        return true;
+    };
+
+    let recorded = filter.patterns.iter().any(|pattern| matches(pattern, name));
+    filter.decisions.insert(key, recorded);
+    recorded
+}
+
+/// Glob match where `*` stands for any sequence of characters, including `::`.
+fn matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard, the whole name has to match
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("myapp::db::*", "myapp::db::query"));
+        assert!(matches("myapp::db::*", "myapp::db::pool::get"));
+        assert!(!matches("myapp::db::*", "myapp::dbx::query"));
+        assert!(matches("myapp::db::query", "myapp::db::query"));
+        assert!(!matches("myapp::db::query", "myapp::db::query_all"));
+        assert!(matches("*::parse", "myapp::json::parse"));
+        assert!(!matches("*::parse", "myapp::json::parse_all"));
+        assert!(matches("myapp::*::get*", "myapp::cache::get_or_insert"));
+        assert!(matches("a*a", "aa"));
+        assert!(!matches("ab*ba", "aba"));
+        assert!(matches("*", "anything"));
+    }
+}
@@ -0,0 +1,83 @@
+//! Byte counts of `measure_io!` blocks, reported next to the timing or allocation results.
+//!
+//! The counts are supplied by the user, e.g. the return value of `Read::read`, so they work
+//! in every profiling mode and are aggregated separately from the regular measurements.
+
+use crate::output::MetricType;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct IoStats {
+    calls: u64,
+    bytes_total: u64,
+}
+
+static BLOCKS: Mutex<BTreeMap<&'static str, IoStats>> = Mutex::new(BTreeMap::new());
+
+/// Records `bytes` read or written by a call of the `name` block.
+#[doc(hidden)]
+pub fn record_io(name: &'static str, bytes: usize) {
+    super::recursion::untracked(|| {
+        if let Ok(mut blocks) = BLOCKS.lock() {
+            let stats = blocks.entry(name).or_default();
+            stats.calls += 1;
+            stats.bytes_total += bytes as u64;
+        }
+    });
+}
+
+/// Clears results of previous profiling sessions.
+pub(crate) fn clear() {
+    if let Ok(mut blocks) = BLOCKS.lock() {
+        blocks.clear();
+    }
+}
+
+/// Aggregated block results as `[Calls, Avg bytes, Total bytes]` rows.
+pub(crate) fn snapshot() -> Vec<(String, Vec<MetricType>)> {
+    let Ok(blocks) = BLOCKS.lock() else {
+        return Vec::new();
+    };
+
+    let mut rows: Vec<_> = blocks
+        .iter()
+        .map(|(name, stats)| {
+            (
+                name.to_string(),
+                vec![
+                    MetricType::CallsCount(stats.calls),
+                    MetricType::AllocBytes(stats.bytes_total.checked_div(stats.calls).unwrap_or(0)),
+                    MetricType::AllocBytes(stats.bytes_total),
+                ],
+            )
+        })
+        .collect();
+
+    rows.sort_by(|(name_a, metrics_a), (name_b, metrics_b)| {
+        let total_a = metrics_a[2].value().unwrap_or(0);
+        let total_b = metrics_b[2].value().unwrap_or(0);
+        total_b.cmp(&total_a).then_with(|| name_a.cmp(name_b))
+    });
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_snapshot() {
+        record_io("io::tests::read", 100);
+        record_io("io::tests::read", 300);
+
+        let (_, metrics) = snapshot()
+            .into_iter()
+            .find(|(name, _)| name == "io::tests::read")
+            .unwrap();
+        assert_eq!(metrics[0].value(), Some(2));
+        assert_eq!(metrics[1].value(), Some(200));
+        assert_eq!(metrics[2].value(), Some(400));
+    }
+}
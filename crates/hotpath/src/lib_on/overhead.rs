@@ -0,0 +1,116 @@
+//! Self-measured cost of the measurement guards, checked against `GuardBuilder::overhead_budget`.
+//!
+//! Only tracked in time profiling mode, where the guards' own cost inflates the reported
+//! durations. Allocation modes don't count the profiler's allocations, so they're unaffected.
+//!
+//! The cost is summed per thread, and the busiest thread is compared against the wall time,
+//! so that many threads each spending a little don't add up past the runtime.
+
+use crate::output::format_duration;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Incremented by [`reset`], so that threads drop the cost of previous sessions
+static SESSION: AtomicU64 = AtomicU64::new(0);
+static MAX_THREAD_SPENT_NS: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Session and cost tracked on this thread
+    static THREAD_SPENT_NS: Cell<(u64, u64)> = const { Cell::new((0, 0)) };
+}
+
+/// Clears the cost tracked so far and tracks the next profiling session if `enabled`.
+pub(crate) fn reset(enabled: bool) {
+    SESSION.fetch_add(1, Ordering::Relaxed);
+    MAX_THREAD_SPENT_NS.store(0, Ordering::Relaxed);
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+#[inline]
+pub(crate) fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Adds the setup and teardown cost of a single guard to the current thread.
+#[inline]
+pub(crate) fn add(ns: u64) {
+    let session = SESSION.load(Ordering::Relaxed);
+    let spent_ns = THREAD_SPENT_NS
+        .try_with(|spent| {
+            let (spent_session, spent_ns) = spent.get();
+            let spent_ns = if spent_session == session {
+                spent_ns + ns
+            } else {
+                ns
+            };
+            spent.set((session, spent_ns));
+            spent_ns
+        })
+        .unwrap_or(ns);
+    MAX_THREAD_SPENT_NS.fetch_max(spent_ns, Ordering::Relaxed);
+}
+
+/// Warning printed when the guards' cost on any thread exceeds `budget` percent of
+/// `total_elapsed`.
+pub(crate) fn check(budget: f64, total_elapsed: Duration) -> Option<String> {
+    exceeded(
+        MAX_THREAD_SPENT_NS.load(Ordering::Relaxed),
+        budget,
+        total_elapsed,
+    )
+}
+
+fn exceeded(spent_ns: u64, budget: f64, total_elapsed: Duration) -> Option<String> {
+    let total_ns = total_elapsed.as_nanos() as f64;
+    if total_ns == 0.0 {
+        return None;
+    }
+    let percent = spent_ns as f64 / total_ns * 100.0;
+    (percent > budget).then(|| {
+        format!(
+            "[hotpath] WARNING: profiling overhead of {} on the busiest thread is {:.1}% of the total runtime, above the budget of {}%. Measurements are unreliable, measure fewer or coarser functions.",
+            format_duration(spent_ns),
+            percent,
+            budget
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_busiest_thread() {
+        reset(true);
+        add(3_000_000);
+        std::thread::spawn(|| {
+            add(2_000_000);
+            add(2_000_000);
+        })
+        .join()
+        .unwrap();
+        add(2_000_000);
+        assert_eq!(MAX_THREAD_SPENT_NS.load(Ordering::Relaxed), 5_000_000);
+
+        // Costs of a previous session don't count
+        reset(true);
+        add(1_000);
+        assert_eq!(MAX_THREAD_SPENT_NS.load(Ordering::Relaxed), 1_000);
+        reset(false);
+    }
+
+    #[test]
+    fn test_exceeded() {
+        let total = Duration::from_millis(100);
+        assert!(exceeded(4_000_000, 5.0, total).is_none());
+
+        let warning = exceeded(20_000_000, 5.0, total).unwrap();
+        assert!(warning.contains("20.0% of the total runtime"), "{warning}");
+        assert!(warning.contains("budget of 5%"), "{warning}");
+
+        assert!(exceeded(1, 5.0, Duration::ZERO).is_none());
+    }
+}
@@ -0,0 +1,186 @@
+//! Program phases set with [`phase`], e.g. "init", "serve" and "shutdown".
+//!
+//! Phase changes are kept on a timeline, so that the worker attributes each measurement to
+//! the phase that was active when the call finished, even if it processes it later.
+
+use crate::output::MetricType;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+#[derive(Default)]
+struct PhaseStats {
+    calls: u64,
+    total: u64,
+}
+
+struct Timeline {
+    /// Phase active since each instant, `None` after the last [`PhaseGuard`] was dropped
+    changes: Vec<(Instant, Option<&'static str>)>,
+    /// Phases of the live guards by id, in the order they were started. The last one is active.
+    guards: Vec<(u64, &'static str)>,
+    next_id: u64,
+}
+
+impl Timeline {
+    fn current(&self) -> Option<&'static str> {
+        self.guards.last().map(|(_, phase)| *phase)
+    }
+}
+
+static TIMELINE: Mutex<Timeline> = Mutex::new(Timeline {
+    changes: Vec::new(),
+    guards: Vec::new(),
+    next_id: 0,
+});
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static STATS: Mutex<Vec<(&'static str, HashMap<&'static str, PhaseStats>)>> =
+    Mutex::new(Vec::new());
+
+/// Marks the start of a program phase. Measurements of calls finishing while the returned
+/// guard is alive are additionally reported per phase, in a table grouped by phase.
+///
+/// Dropping the guard of the active phase restores the previous one, so phases can be nested
+/// or switched by replacing the guard, e.g. `phase = hotpath::phase("serve")`. Functions
+/// measured outside of any phase are only in the regular report.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "hotpath")]
+/// # {
+/// let _hotpath = hotpath::GuardBuilder::new("main").build();
+///
+/// let phase = hotpath::phase("init");
+/// // ... load the configuration ...
+/// drop(phase);
+///
+/// let _phase = hotpath::phase("serve");
+/// // ... handle requests ...
+/// # }
+/// ```
+#[must_use = "the phase ends when the guard is dropped"]
+pub fn phase(name: &'static str) -> PhaseGuard {
+    let mut timeline = TIMELINE.lock().unwrap_or_else(|e| e.into_inner());
+    let id = timeline.next_id;
+    timeline.next_id += 1;
+    timeline.guards.push((id, name));
+    timeline.changes.push((super::clock::now(), Some(name)));
+    ACTIVE.store(true, Ordering::Relaxed);
+
+    PhaseGuard { id }
+}
+
+/// Guard of a phase started with [`phase`]. Dropping it ends the phase, and restores the
+/// previous one if it's the active phase.
+pub struct PhaseGuard {
+    id: u64,
+}
+
+impl Drop for PhaseGuard {
+    fn drop(&mut self) {
+        let mut timeline = TIMELINE.lock().unwrap_or_else(|e| e.into_inner());
+        let before = timeline.current();
+        timeline.guards.retain(|(id, _)| *id != self.id);
+        // A replaced guard dropped after its successor started leaves the active phase as is
+        let after = timeline.current();
+        if after != before {
+            timeline.changes.push((super::clock::now(), after));
+        }
+    }
+}
+
+/// Phase that was active at `at`.
+fn phase_at(at: Instant) -> Option<&'static str> {
+    let timeline = TIMELINE.lock().ok()?;
+    let changes = &timeline.changes;
+    let index = changes.partition_point(|(since, _)| *since <= at);
+    index.checked_sub(1).and_then(|index| changes[index].1)
+}
+
+/// Records a call of `name` with a mode specific `value` that finished at `at`.
+pub(crate) fn record(name: &'static str, value: u64, at: Instant) {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return;
+    }
+    let Some(phase) = phase_at(at) else {
+        return;
+    };
+
+    let Ok(mut phases) = STATS.lock() else {
+        return;
+    };
+    let functions = match phases.iter().position(|(name, _)| *name == phase) {
+        Some(index) => &mut phases[index].1,
+        None => {
+            phases.push((phase, HashMap::new()));
+            &mut phases.last_mut().unwrap().1
+        }
+    };
+    let stats = functions.entry(name).or_default();
+    stats.calls += 1;
+    stats.total += value;
+}
+
+/// Clears results of previous profiling sessions. The current phase is kept, so a phase
+/// started before the guard applies to it too.
+pub(crate) fn clear() {
+    if let Ok(mut phases) = STATS.lock() {
+        phases.clear();
+    }
+}
+
+/// Results as `(phase, function, [Calls, Avg, Total])` rows, phases in the order they were
+/// first measured and functions by total, highest first. `metric` wraps the values in the
+/// unit of the profiling mode.
+pub(crate) fn snapshot(metric: fn(u64) -> MetricType) -> Vec<(String, String, Vec<MetricType>)> {
+    let Ok(phases) = STATS.lock() else {
+        return Vec::new();
+    };
+
+    phases
+        .iter()
+        .flat_map(|(phase, functions)| {
+            let mut functions: Vec<_> = functions.iter().collect();
+            functions.sort_by(|a, b| b.1.total.cmp(&a.1.total).then(a.0.cmp(b.0)));
+            functions.into_iter().map(move |(name, stats)| {
+                (
+                    phase.to_string(),
+                    name.to_string(),
+                    vec![
+                        MetricType::CallsCount(stats.calls),
+                        metric(stats.total.checked_div(stats.calls).unwrap_or(0)),
+                        metric(stats.total),
+                    ],
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::clock;
+    use super::*;
+
+    #[test]
+    // The first guard is only read by its drop when it's replaced
+    #[allow(unused_assignments)]
+    fn test_replaced_guard_keeps_new_phase() {
+        let mut guard = phase("phases::tests::init");
+        assert_eq!(phase_at(clock::now()), Some("phases::tests::init"));
+
+        // The old guard is dropped after the new phase started
+        guard = phase("phases::tests::serve");
+        assert_eq!(phase_at(clock::now()), Some("phases::tests::serve"));
+
+        let nested = phase("phases::tests::nested");
+        assert_eq!(phase_at(clock::now()), Some("phases::tests::nested"));
+        drop(nested);
+        assert_eq!(phase_at(clock::now()), Some("phases::tests::serve"));
+
+        drop(guard);
+        assert_eq!(phase_at(clock::now()), None);
+    }
+}
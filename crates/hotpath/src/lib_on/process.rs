@@ -0,0 +1,59 @@
+/// Peak resident set size of the current process in bytes, `None` if unsupported on this platform.
+// getrusage ru_maxrss is preserved across execve on Linux, so it can report the peak
+// of the parent process (e.g. `cargo run`), use the VmHWM high water mark instead
+#[cfg(target_os = "linux")]
+pub(crate) fn peak_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kb: u64 = line
+        .trim_start_matches("VmHWM:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+/// Peak resident set size of the current process in bytes, `None` if unsupported on this platform.
+#[cfg(all(unix, not(target_os = "linux")))]
+pub(crate) fn peak_rss_bytes() -> Option<u64> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    // SAFETY: getrusage only writes into the provided struct
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: initialized by the successful getrusage call above
+    let max_rss = unsafe { usage.assume_init() }.ru_maxrss as u64;
+
+    // ru_maxrss is reported in bytes on macOS and in kilobytes on other BSDs
+    if cfg!(target_os = "macos") {
+        Some(max_rss)
+    } else {
+        Some(max_rss * 1024)
+    }
+}
+
+/// Peak resident set size of the current process in bytes, `None` if unsupported on this platform.
+#[cfg(windows)]
+pub(crate) fn peak_rss_bytes() -> Option<u64> {
+    use windows_sys::Win32::System::ProcessStatus::{
+        GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS,
+    };
+    use windows_sys::Win32::System::Threading::GetCurrentProcess;
+
+    let mut counters = PROCESS_MEMORY_COUNTERS {
+        cb: std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
+        ..Default::default()
+    };
+
+    // SAFETY: the pseudo handle of the current process is always valid
+    let ok = unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, counters.cb) };
+    (ok != 0).then_some(counters.PeakWorkingSetSize as u64)
+}
+
+/// Peak resident set size of the current process in bytes, `None` if unsupported on this platform.
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn peak_rss_bytes() -> Option<u64> {
+    None
+}
@@ -0,0 +1,117 @@
+//! Call counts per function bucketed into fixed time windows, served on `/rates` so that
+//! `hotpath console` can show whether a function's load is steady or bursty.
+
+use crate::output::CallRatesJson;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// Width of a single bucket
+pub(crate) const BUCKET: Duration = Duration::from_secs(1);
+/// Number of most recent buckets kept per function
+pub(crate) const BUCKETS: usize = 60;
+
+/// Most recent buckets of a single function, the last one covers `newest`.
+#[derive(Debug, Default, Clone)]
+struct Buckets {
+    counts: VecDeque<u64>,
+    newest: u64,
+}
+
+impl Buckets {
+    /// Appends empty buckets up to bucket `index`, dropping the ones that fall out of the window.
+    fn advance(&mut self, index: u64) {
+        if self.counts.is_empty() {
+            self.counts.push_back(0);
+            self.newest = index;
+            return;
+        }
+        if index <= self.newest {
+            return;
+        }
+        let missing = (index - self.newest).min(BUCKETS as u64);
+        for _ in 0..missing {
+            self.counts.push_back(0);
+        }
+        while self.counts.len() > BUCKETS {
+            self.counts.pop_front();
+        }
+        self.newest = index;
+    }
+
+    fn record(&mut self, index: u64) {
+        self.advance(index);
+        // Measurements from other threads may arrive slightly out of order
+        let age = (self.newest - index) as usize;
+        if let Some(position) = self.counts.len().checked_sub(age + 1) {
+            self.counts[position] += 1;
+        }
+    }
+}
+
+/// Call counts of all measured functions.
+#[derive(Debug, Default)]
+pub(crate) struct CallRates {
+    functions: HashMap<&'static str, Buckets>,
+}
+
+fn bucket_index(elapsed: Duration) -> u64 {
+    (elapsed.as_nanos() / BUCKET.as_nanos()) as u64
+}
+
+impl CallRates {
+    /// Counts a call of `name` measured `elapsed` after the guard was created.
+    pub(crate) fn record(&mut self, name: &'static str, elapsed: Duration) {
+        self.functions
+            .entry(name)
+            .or_default()
+            .record(bucket_index(elapsed));
+    }
+
+    /// Counts of all functions, oldest bucket first, aligned so that the last bucket of each
+    /// covers `elapsed`. Functions without recent calls end in zeros.
+    pub(crate) fn snapshot(&self, elapsed: Duration) -> CallRatesJson {
+        let current = bucket_index(elapsed);
+        let rates = self
+            .functions
+            .iter()
+            .map(|(name, buckets)| {
+                let mut buckets = buckets.clone();
+                buckets.advance(current);
+                (name.to_string(), buckets.counts.into())
+            })
+            .collect();
+
+        CallRatesJson {
+            bucket_ns: BUCKET.as_nanos() as u64,
+            rates,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_rates_buckets() {
+        let mut rates = CallRates::default();
+        let secs = Duration::from_secs;
+
+        rates.record("app::work", secs(2));
+        rates.record("app::work", Duration::from_millis(2500));
+        rates.record("app::work", secs(4));
+        // Out of order measurement of an earlier bucket
+        rates.record("app::work", secs(3));
+        rates.record("app::idle", secs(1));
+
+        let snapshot = rates.snapshot(secs(5));
+        assert_eq!(snapshot.bucket_ns, 1_000_000_000);
+        assert_eq!(snapshot.rates["app::work"], vec![2, 1, 1, 0]);
+        assert_eq!(snapshot.rates["app::idle"], vec![1, 0, 0, 0, 0]);
+
+        // Buckets older than the window are dropped
+        let snapshot = rates.snapshot(secs(200));
+        assert_eq!(snapshot.rates["app::work"].len(), BUCKETS);
+        assert!(snapshot.rates["app::work"].iter().all(|count| *count == 0));
+    }
+}
@@ -0,0 +1,122 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RawSamplesFormat {
+    Csv,
+    Ndjson,
+}
+
+impl RawSamplesFormat {
+    /// NDJSON for `.ndjson` and `.jsonl` files, CSV otherwise
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ndjson" | "jsonl") => Self::Ndjson,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// Streams every raw measurement to a file, see `GuardBuilder::raw_samples_file`.
+pub(crate) struct RawSamplesWriter {
+    path: PathBuf,
+    format: RawSamplesFormat,
+    out: Option<BufWriter<File>>,
+}
+
+impl RawSamplesWriter {
+    pub(crate) fn create(path: PathBuf) -> io::Result<Self> {
+        let format = RawSamplesFormat::from_path(&path);
+        let mut out = BufWriter::new(File::create(&path)?);
+        if format == RawSamplesFormat::Csv {
+            writeln!(out, "function,value,timestamp_ns")?;
+        }
+
+        Ok(Self {
+            path,
+            format,
+            out: Some(out),
+        })
+    }
+
+    /// Appends a sample, writing stops after the first error.
+    pub(crate) fn write(&mut self, name: &str, value: u64, elapsed: Duration) {
+        let Some(out) = self.out.as_mut() else {
+            return;
+        };
+
+        let timestamp_ns = elapsed.as_nanos() as u64;
+        let result = match self.format {
+            // Names can contain commas, e.g. `process<A, B>`, so they're always quoted
+            RawSamplesFormat::Csv => writeln!(
+                out,
+                "\"{}\",{},{}",
+                name.replace('"', "\"\""),
+                value,
+                timestamp_ns
+            ),
+            RawSamplesFormat::Ndjson => writeln!(
+                out,
+                "{{\"function\":{},\"value\":{},\"timestamp_ns\":{}}}",
+                serde_json::Value::from(name),
+                value,
+                timestamp_ns
+            ),
+        };
+
+        if let Err(e) = result {
+            eprintln!(
+                "[hotpath] Failed to write raw samples to {}, no more samples are recorded: {}",
+                self.path.display(),
+                e
+            );
+            self.out = None;
+        }
+    }
+}
+
+impl Drop for RawSamplesWriter {
+    fn drop(&mut self) {
+        if let Some(mut out) = self.out.take() {
+            if let Err(e) = out.flush() {
+                eprintln!(
+                    "[hotpath] Failed to write raw samples to {}: {}",
+                    self.path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_samples_formats() {
+        let dir = std::env::temp_dir();
+        let csv_path = dir.join("hotpath_test_raw_samples.csv");
+        let ndjson_path = dir.join("hotpath_test_raw_samples.ndjson");
+
+        for path in [&csv_path, &ndjson_path] {
+            let mut writer = RawSamplesWriter::create(path.clone()).unwrap();
+            writer.write("mod::process<A, B>", 1200, Duration::from_micros(5));
+            writer.write("say \"hi\"", 7, Duration::from_micros(9));
+        }
+
+        assert_eq!(
+            std::fs::read_to_string(&csv_path).unwrap(),
+            "function,value,timestamp_ns\n\"mod::process<A, B>\",1200,5000\n\"say \"\"hi\"\"\",7,9000\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&ndjson_path).unwrap(),
+            "{\"function\":\"mod::process<A, B>\",\"value\":1200,\"timestamp_ns\":5000}\n{\"function\":\"say \\\"hi\\\"\",\"value\":7,\"timestamp_ns\":9000}\n"
+        );
+
+        let _ = std::fs::remove_file(csv_path);
+        let _ = std::fs::remove_file(ndjson_path);
+    }
+}
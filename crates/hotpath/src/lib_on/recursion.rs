@@ -0,0 +1,150 @@
+//! Detection of recursive re-entry of measured functions, so only the outermost call is recorded.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+/// Role of a guard in the recursion of its function on the current thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Call {
+    /// Async functions, blocks and the caller wrapper, which can't recurse on a single thread
+    Untracked,
+    Outermost,
+    /// Re-entry of a function already running on this thread, never recorded
+    Nested,
+}
+
+struct ActiveCall {
+    depth: u32,
+    max_depth: u32,
+}
+
+thread_local! {
+    /// Functions running on this thread by name, with their current recursion depth
+    static ACTIVE: RefCell<HashMap<&'static str, ActiveCall>> = RefCell::new(HashMap::new());
+}
+
+/// Deepest recursion of each function whose outermost call has returned
+static MAX_DEPTHS: Mutex<BTreeMap<&'static str, u32>> = Mutex::new(BTreeMap::new());
+
+#[inline]
+pub(crate) fn untracked<R>(f: impl FnOnce() -> R) -> R {
+    cfg_if::cfg_if! {
+        if #[cfg(any(
+            feature = "hotpath-alloc-bytes-total",
+            feature = "hotpath-alloc-count-total",
+            feature = "hotpath-alloc-leak"
+        ))] {
+            // Growing the map of active calls must not count towards the measured function
+            super::alloc::shared::without_tracking(f)
+        } else {
+            f()
+        }
+    }
+}
+
+/// Registers a call of `name` on the current thread.
+#[inline]
+pub(crate) fn enter(name: &'static str) -> Call {
+    untracked(|| {
+        ACTIVE
+            .try_with(|active| {
+                let mut active = active.borrow_mut();
+                if let Some(call) = active.get_mut(name) {
+                    call.depth += 1;
+                    call.max_depth = call.max_depth.max(call.depth);
+                    return Call::Nested;
+                }
+                active.insert(
+                    name,
+                    ActiveCall {
+                        depth: 1,
+                        max_depth: 1,
+                    },
+                );
+                Call::Outermost
+            })
+            .unwrap_or(Call::Untracked)
+    })
+}
+
+/// Unregisters a call of `name` when its guard is dropped.
+#[inline]
+pub(crate) fn exit(name: &'static str, call: Call) {
+    if call == Call::Untracked {
+        return;
+    }
+
+    let max_depth = ACTIVE
+        .try_with(|active| {
+            let mut active = active.borrow_mut();
+            if call == Call::Nested {
+                if let Some(call) = active.get_mut(name) {
+                    call.depth -= 1;
+                }
+                return None;
+            }
+            active.remove(name).map(|call| call.max_depth)
+        })
+        .ok()
+        .flatten();
+
+    if let Some(max_depth) = max_depth.filter(|&depth| depth > 1) {
+        record_max_depth(name, max_depth);
+    }
+}
+
+#[cold]
+fn record_max_depth(name: &'static str, max_depth: u32) {
+    untracked(|| {
+        if let Ok(mut max_depths) = MAX_DEPTHS.lock() {
+            let depth = max_depths.entry(name).or_insert(0);
+            *depth = (*depth).max(max_depth);
+        }
+    });
+}
+
+/// Functions that recursed, with their deepest recursion
+pub(crate) fn max_depths() -> Vec<(String, u32)> {
+    MAX_DEPTHS
+        .lock()
+        .map(|max_depths| {
+            max_depths
+                .iter()
+                .map(|(name, depth)| (name.to_string(), *depth))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn clear() {
+    if let Ok(mut max_depths) = MAX_DEPTHS.lock() {
+        max_depths.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recursion_depth() {
+        let name = "recursion::tests::walk";
+        assert_eq!(enter(name), Call::Outermost);
+        assert_eq!(enter("recursion::tests::leaf"), Call::Outermost);
+        exit("recursion::tests::leaf", Call::Outermost);
+        assert_eq!(enter(name), Call::Nested);
+        assert_eq!(enter(name), Call::Nested);
+        exit(name, Call::Nested);
+        exit(name, Call::Nested);
+        exit(name, Call::Outermost);
+
+        assert_eq!(enter(name), Call::Outermost);
+        exit(name, Call::Outermost);
+
+        assert!(max_depths().contains(&(name.to_string(), 3)));
+        assert!(!max_depths()
+            .iter()
+            .any(|(name, _)| name == "recursion::tests::leaf"));
+    }
+}
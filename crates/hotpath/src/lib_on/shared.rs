@@ -0,0 +1,77 @@
+//! Helpers shared by the stats and reports of every profiling mode.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use crate::output::{MetricType, DISTRIBUTION_PERCENTILES};
+
+/// Per-function stats of a profiling mode, as read by the shared report helpers.
+pub(crate) trait ModeStats {
+    /// Registered up front with `GuardBuilder::register`, but recorded no calls
+    fn is_uncalled(&self) -> bool;
+}
+
+/// Appends a `(value, elapsed)` sample, evicting the oldest one when `samples` is at its capacity.
+#[inline]
+pub(crate) fn push_recent_sample(samples: &mut VecDeque<(u64, Duration)>, sample: (u64, Duration)) {
+    if samples.len() == samples.capacity() && samples.capacity() > 0 {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+/// Names of the registered functions that recorded no calls, sorted.
+pub(crate) fn uncalled_functions<S: ModeStats>(stats: &HashMap<&'static str, S>) -> Vec<String> {
+    let mut uncalled: Vec<String> = stats
+        .iter()
+        .filter(|(_, s)| s.is_uncalled())
+        .map(|(name, _)| name.to_string())
+        .collect();
+    uncalled.sort();
+    uncalled
+}
+
+/// `(displayed, total)` counts of [`MetricsProvider::entry_counts`](crate::MetricsProvider::entry_counts).
+///
+/// Registered functions that were never called don't count, they're listed separately. They
+/// have the lowest totals, so the rows cut by `limit` or the report deadline are the first
+/// to go.
+pub(crate) fn entry_counts<S: ModeStats>(
+    stats: &HashMap<&'static str, S>,
+    is_displayed: impl Fn(&S) -> bool,
+    limit: usize,
+    deadline_truncated: usize,
+) -> (usize, usize) {
+    let rows = stats.values().filter(|s| is_displayed(s)).count();
+    let called = stats
+        .values()
+        .filter(|s| is_displayed(s) && !s.is_uncalled())
+        .count();
+
+    let displayed_rows = if limit > 0 && limit < rows {
+        limit
+    } else {
+        rows
+    };
+
+    ((displayed_rows - deadline_truncated).min(called), called)
+}
+
+/// Values of each displayed function at [`DISTRIBUTION_PERCENTILES`], read with `percentile`.
+pub(crate) fn distributions<S>(
+    stats: &HashMap<&'static str, S>,
+    is_displayed: impl Fn(&S) -> bool,
+    percentile: impl Fn(&S, f64) -> MetricType,
+) -> HashMap<String, Vec<MetricType>> {
+    stats
+        .iter()
+        .filter(|(_, s)| is_displayed(s))
+        .map(|(name, s)| {
+            let values = DISTRIBUTION_PERCENTILES
+                .iter()
+                .map(|&p| percentile(s, p))
+                .collect();
+            (name.to_string(), values)
+        })
+        .collect()
+}
@@ -1,3 +1,6 @@
+#[cfg(any(feature = "hotpath-alloc-blocks", feature = "hotpath-time-alloc-count"))]
+pub mod alloc_block;
 pub mod guard;
+pub mod polls;
 pub mod report;
 pub mod state;
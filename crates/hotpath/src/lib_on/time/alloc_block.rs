@@ -0,0 +1,198 @@
+//! Allocation accounting for `measure_block_alloc!` in time profiling mode.
+//!
+//! Enabled with the `hotpath-alloc-blocks` feature, which installs [`BlockCountingAllocator`]
+//! as the global allocator. Allocations are only counted while at least one block is active
+//! on the current thread, and results are aggregated separately from timing measurements.
+//!
+//! The `hotpath-time-alloc-count` feature installs the same allocator, but counts all
+//! allocations, so that measured functions also send how many allocations they made with
+//! their measurements.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+#[cfg(feature = "hotpath-alloc-blocks")]
+use crate::output::MetricType;
+#[cfg(feature = "hotpath-alloc-blocks")]
+use std::collections::HashMap;
+#[cfg(feature = "hotpath-alloc-blocks")]
+use std::sync::Mutex;
+
+thread_local! {
+    static DEPTH: Cell<u32> = const { Cell::new(0) };
+    static BYTES_TOTAL: Cell<u64> = const { Cell::new(0) };
+    static COUNT_TOTAL: Cell<u64> = const { Cell::new(0) };
+    static SUSPENDED: Cell<bool> = const { Cell::new(false) };
+}
+
+#[cfg(feature = "hotpath-alloc-blocks")]
+#[derive(Default)]
+struct BlockStats {
+    calls: u64,
+    bytes_total: u64,
+    count_total: u64,
+}
+
+#[cfg(feature = "hotpath-alloc-blocks")]
+static BLOCKS: Mutex<Option<HashMap<&'static str, BlockStats>>> = Mutex::new(None);
+
+#[inline]
+fn counting() -> bool {
+    !SUSPENDED.with(|suspended| suspended.get())
+        && (cfg!(feature = "hotpath-time-alloc-count") || DEPTH.with(|depth| depth.get()) > 0)
+}
+
+/// Runs `f` without counting its allocations, so that recording results doesn't count
+/// towards enclosing blocks or functions.
+#[inline]
+pub(crate) fn untracked<R>(f: impl FnOnce() -> R) -> R {
+    let suspended = SUSPENDED.with(|suspended| suspended.replace(true));
+    let result = f();
+    SUSPENDED.with(|s| s.set(suspended));
+    result
+}
+
+#[cfg(any(feature = "hotpath-alloc-blocks", feature = "hotpath-time-alloc-count"))]
+/// Bytes allocated on the current thread while it's alive, see `assert_max_alloc!`.
+pub(crate) struct ByteCounter {
+    start_bytes: u64,
+}
+
+#[cfg(any(feature = "hotpath-alloc-blocks", feature = "hotpath-time-alloc-count"))]
+impl ByteCounter {
+    #[inline]
+    pub(crate) fn start() -> Self {
+        DEPTH.with(|depth| depth.set(depth.get() + 1));
+        Self {
+            start_bytes: BYTES_TOTAL.with(|bytes| bytes.get()),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn bytes(&self) -> u64 {
+        // Saturating, the counter may be dropped on another thread than it started on
+        BYTES_TOTAL
+            .with(|bytes| bytes.get())
+            .saturating_sub(self.start_bytes)
+    }
+}
+
+#[cfg(any(feature = "hotpath-alloc-blocks", feature = "hotpath-time-alloc-count"))]
+impl Drop for ByteCounter {
+    #[inline]
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get().saturating_sub(1)));
+    }
+}
+
+#[cfg(feature = "hotpath-time-alloc-count")]
+/// Allocations counted on the current thread so far.
+#[inline]
+pub(crate) fn allocations() -> u64 {
+    COUNT_TOTAL.with(|count| count.get())
+}
+
+/// Global allocator counting allocations made inside active `measure_block_alloc!` blocks.
+pub struct BlockCountingAllocator;
+
+unsafe impl GlobalAlloc for BlockCountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if counting() {
+            BYTES_TOTAL.with(|bytes| bytes.set(bytes.get() + layout.size() as u64));
+            COUNT_TOTAL.with(|count| count.set(count.get() + 1));
+        }
+
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe {
+            System.dealloc(ptr, layout);
+        }
+    }
+}
+
+#[cfg(feature = "hotpath-alloc-blocks")]
+#[doc(hidden)]
+pub struct AllocBlockGuard {
+    name: &'static str,
+    start_bytes: u64,
+    start_count: u64,
+}
+
+#[cfg(feature = "hotpath-alloc-blocks")]
+impl AllocBlockGuard {
+    #[inline]
+    pub fn new(name: &'static str) -> Self {
+        DEPTH.with(|depth| depth.set(depth.get() + 1));
+
+        Self {
+            name,
+            start_bytes: BYTES_TOTAL.with(|bytes| bytes.get()),
+            start_count: COUNT_TOTAL.with(|count| count.get()),
+        }
+    }
+}
+
+#[cfg(feature = "hotpath-alloc-blocks")]
+impl Drop for AllocBlockGuard {
+    #[inline]
+    fn drop(&mut self) {
+        let bytes_total = BYTES_TOTAL.with(|bytes| bytes.get()) - self.start_bytes;
+        let count_total = COUNT_TOTAL.with(|count| count.get()) - self.start_count;
+
+        untracked(|| {
+            if let Ok(mut blocks) = BLOCKS.lock() {
+                let stats = blocks
+                    .get_or_insert_with(HashMap::new)
+                    .entry(self.name)
+                    .or_default();
+                stats.calls += 1;
+                stats.bytes_total += bytes_total;
+                stats.count_total += count_total;
+            }
+        });
+
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+#[cfg(feature = "hotpath-alloc-blocks")]
+/// Clears results of previous profiling sessions.
+pub(crate) fn reset() {
+    if let Ok(mut blocks) = BLOCKS.lock() {
+        *blocks = None;
+    }
+}
+
+#[cfg(feature = "hotpath-alloc-blocks")]
+/// Aggregated block results as `[Calls, Avg bytes, Total bytes, Total allocations]` rows.
+pub(crate) fn snapshot() -> Vec<(String, Vec<MetricType>)> {
+    let Ok(blocks) = BLOCKS.lock() else {
+        return Vec::new();
+    };
+
+    let mut rows: Vec<_> = blocks
+        .iter()
+        .flatten()
+        .map(|(name, stats)| {
+            (
+                name.to_string(),
+                vec![
+                    MetricType::CallsCount(stats.calls),
+                    MetricType::AllocBytes(stats.bytes_total.checked_div(stats.calls).unwrap_or(0)),
+                    MetricType::AllocBytes(stats.bytes_total),
+                    MetricType::AllocCount(stats.count_total),
+                ],
+            )
+        })
+        .collect();
+
+    rows.sort_by(|(name_a, metrics_a), (name_b, metrics_b)| {
+        let total_a = metrics_a[2].value().unwrap_or(0);
+        let total_b = metrics_b[2].value().unwrap_or(0);
+        total_b.cmp(&total_a).then_with(|| name_a.cmp(name_b))
+    });
+
+    rows
+}
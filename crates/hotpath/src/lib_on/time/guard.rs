@@ -1,31 +1,242 @@
-#[cfg(target_os = "linux")]
+// Coarse clock reads a cached timestamp: much cheaper per call, but resolution is
+// limited to the OS tick (typically 1-4ms), so short calls are recorded as zero
+#[cfg(feature = "hotpath-coarse-clock")]
+use coarsetime::Instant;
+
+#[cfg(all(not(feature = "hotpath-coarse-clock"), target_os = "linux"))]
 use quanta::Instant;
 
-#[cfg(not(target_os = "linux"))]
+#[cfg(all(not(feature = "hotpath-coarse-clock"), not(target_os = "linux")))]
 use std::time::Instant;
 
+use crate::lib_on::clock;
+use crate::lib_on::recursion::{self, Call};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+#[cfg(feature = "hotpath-coarse-clock")]
+#[inline]
+fn elapsed_since(start: Instant) -> Duration {
+    start.elapsed().into()
+}
+
+#[cfg(not(feature = "hotpath-coarse-clock"))]
+#[inline]
+fn elapsed_since(start: Instant) -> Duration {
+    start.elapsed()
+}
+
+/// Start of a measured span, read from the clock set with `GuardBuilder::clock` if there is one.
+#[derive(Clone, Copy)]
+enum Start {
+    Native(Instant),
+    Custom(std::time::Instant),
+}
+
+impl Start {
+    #[inline]
+    fn now() -> Self {
+        if clock::is_custom() {
+            Start::Custom(clock::now())
+        } else {
+            Start::Native(Instant::now())
+        }
+    }
+
+    #[inline]
+    fn elapsed(self) -> Duration {
+        match self {
+            Start::Native(start) => elapsed_since(start),
+            Start::Custom(start) => clock::elapsed(start),
+        }
+    }
+}
+
+use std::sync::OnceLock;
+
+static OVERHEAD_NS: OnceLock<u64> = OnceLock::new();
+const CALIBRATION_ROUNDS: usize = 10_000;
+
+/// Measures the median cost of an empty guard timing span, the part of the guard's own cost
+/// that ends up in recorded durations. Calibrated once per process.
+pub(crate) fn calibrate_overhead() -> u64 {
+    *OVERHEAD_NS.get_or_init(|| {
+        let mut samples: Vec<u64> = (0..CALIBRATION_ROUNDS)
+            .map(|_| {
+                let start = std::hint::black_box(Start::now());
+                start.elapsed().as_nanos() as u64
+            })
+            .collect();
+        samples.sort_unstable();
+        samples[samples.len() / 2]
+    })
+}
+
+/// Calibrated per-call overhead, 0 if calibration has not run yet or durations are read from
+/// a custom clock.
+#[inline]
+pub(crate) fn overhead_ns() -> u64 {
+    if clock::is_custom() {
+        return 0;
+    }
+    OVERHEAD_NS.get().copied().unwrap_or(0)
+}
+
 #[doc(hidden)]
 pub struct MeasurementGuard {
     name: &'static str,
-    start: Instant,
+    start: Start,
     wrapper: bool,
+    recorded: bool,
+    call: Call,
+    percentiles: Option<&'static [f64]>,
+    // Set when the guard's own cost is tracked, see GuardBuilder::overhead_budget
+    setup_start: Option<Instant>,
+    #[cfg(feature = "hotpath-time-alloc-count")]
+    start_allocations: u64,
+    #[cfg(feature = "hotpath-time-alloc-count")]
+    start_thread: u64,
 }
 
 impl MeasurementGuard {
     #[inline]
     pub fn new(name: &'static str, wrapper: bool, _unsupported_sync: bool) -> Self {
+        Self::new_with_recursion(name, wrapper, _unsupported_sync, false)
+    }
+
+    /// Like [`Self::new`], but with `track_recursion` only the outermost of recursive calls
+    /// on the current thread is recorded, and nested calls count towards it.
+    #[inline]
+    pub(crate) fn new_with_recursion(
+        name: &'static str,
+        wrapper: bool,
+        _unsupported_sync: bool,
+        track_recursion: bool,
+    ) -> Self {
+        let setup_start = crate::lib_on::overhead::enabled().then(Instant::now);
+        let call = if track_recursion {
+            recursion::enter(name)
+        } else {
+            Call::Untracked
+        };
         Self {
             name,
-            start: Instant::now(),
             wrapper,
+            recorded: call != Call::Nested && (wrapper || crate::lib_on::filter::is_recorded(name)),
+            call,
+            percentiles: None,
+            setup_start,
+            #[cfg(feature = "hotpath-time-alloc-count")]
+            start_allocations: super::alloc_block::allocations(),
+            #[cfg(feature = "hotpath-time-alloc-count")]
+            start_thread: super::state::thread_id(),
+            // Last, so that the bookkeeping above isn't part of the measurement
+            start: Start::now(),
         }
     }
+
+    /// Overrides the global percentiles reported for this measurement.
+    #[inline]
+    pub fn with_percentiles(mut self, percentiles: &'static [f64]) -> Self {
+        self.percentiles = Some(percentiles);
+        self
+    }
 }
 
 impl Drop for MeasurementGuard {
     #[inline]
     fn drop(&mut self) {
+        if !self.recorded {
+            recursion::exit(self.name, self.call);
+            return;
+        }
         let dur = self.start.elapsed();
-        super::state::send_duration_measurement(self.name, dur, self.wrapper);
+        recursion::exit(self.name, self.call);
+
+        #[cfg(feature = "hotpath-time-alloc-count")]
+        {
+            // Allocations are counted per thread, so those of an async function finishing on
+            // another thread than it started on can't be attributed and are reported as N/A
+            let allocations = (super::state::thread_id() == self.start_thread)
+                .then(|| super::alloc_block::allocations().saturating_sub(self.start_allocations));
+            super::alloc_block::untracked(|| {
+                super::state::send_duration_measurement(
+                    self.name,
+                    dur,
+                    self.wrapper,
+                    self.percentiles,
+                    allocations,
+                );
+            });
+        }
+
+        #[cfg(not(feature = "hotpath-time-alloc-count"))]
+        super::state::send_duration_measurement(
+            self.name,
+            dur,
+            self.wrapper,
+            self.percentiles,
+            None,
+        );
+
+        if let Some(setup_start) = self.setup_start {
+            // Everything but the measured span, plus the clock read inside of it
+            let total = elapsed_since(setup_start);
+            let cost = total.saturating_sub(dur).as_nanos() as u64 + overhead_ns();
+            crate::lib_on::overhead::add(cost);
+        }
+    }
+}
+
+/// Future wrapper of a `#[measure(poll_time)]` function, measuring only the time spent in `poll`.
+///
+/// The sum of all polls is recorded as a single measurement when the future is dropped,
+/// so time spent suspended at `.await` points is excluded.
+#[doc(hidden)]
+pub struct PollTimed<F> {
+    future: F,
+    name: &'static str,
+    busy: Duration,
+    polls: u64,
+}
+
+impl<F> PollTimed<F> {
+    #[inline]
+    pub fn new(name: &'static str, future: F) -> Self {
+        Self {
+            future,
+            name,
+            busy: Duration::ZERO,
+            polls: 0,
+        }
+    }
+}
+
+impl<F: Future> Future for PollTimed<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        // SAFETY: `future` is structurally pinned, it's never moved out of `self`
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+
+        let start = Start::now();
+        let result = future.poll(cx);
+        this.busy += start.elapsed();
+        this.polls += 1;
+
+        result
+    }
+}
+
+impl<F> Drop for PollTimed<F> {
+    fn drop(&mut self) {
+        if self.polls > 0 && crate::lib_on::filter::is_recorded(self.name) {
+            super::polls::record(self.name, self.polls);
+            // Polls may run on different threads, their allocations can't be attributed
+            super::state::send_duration_measurement(self.name, self.busy, false, None, None);
+        }
     }
 }
@@ -0,0 +1,63 @@
+//! Number of times the futures of `#[measure(poll_time)]` functions are polled, shown in the
+//! `Avg Polls` column of their `::poll` rows.
+
+use crate::output::MetricType;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct PollStats {
+    calls: u64,
+    polls_total: u64,
+}
+
+static FUTURES: Mutex<Option<HashMap<&'static str, PollStats>>> = Mutex::new(None);
+
+/// Adds a call whose future was polled `polls` times.
+pub(crate) fn record(name: &'static str, polls: u64) {
+    if let Ok(mut futures) = FUTURES.lock() {
+        let stats = futures
+            .get_or_insert_with(HashMap::new)
+            .entry(name)
+            .or_default();
+        stats.calls += 1;
+        stats.polls_total += polls;
+    }
+}
+
+/// Clears results of previous profiling sessions.
+pub(crate) fn reset() {
+    if let Ok(mut futures) = FUTURES.lock() {
+        *futures = None;
+    }
+}
+
+/// Average number of polls per call of each measured future.
+pub(crate) fn snapshot() -> HashMap<String, MetricType> {
+    let Ok(futures) = FUTURES.lock() else {
+        return HashMap::new();
+    };
+
+    futures
+        .iter()
+        .flatten()
+        .map(|(name, stats)| {
+            let avg = stats.polls_total.checked_div(stats.calls).unwrap_or(0);
+            (name.to_string(), MetricType::CallsCount(avg))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polls_snapshot() {
+        record("polls::tests::fetch", 3);
+        record("polls::tests::fetch", 5);
+
+        let polls = snapshot();
+        assert_eq!(polls["polls::tests::fetch"].value(), Some(4));
+    }
+}
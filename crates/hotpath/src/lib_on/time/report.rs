@@ -1,70 +1,96 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
 
-use crate::ProfilingMode;
+use crate::{PercentBasis, ProfilingMode, SortBy};
 
-use super::super::output::{MetricType, MetricsProvider};
+use super::super::output::{
+    percentile_columns, rows_within_deadline, tracks_percentile, MetricType, MetricsProvider,
+    ReportDeadline,
+};
+use super::super::shared;
 use super::state::FunctionStats;
 
 pub struct StatsData<'a> {
     pub stats: &'a HashMap<&'static str, FunctionStats>,
     pub total_elapsed: Duration,
-    pub percentiles: Vec<u8>,
+    pub percentiles: Vec<f64>,
     pub caller_name: &'static str,
     pub limit: usize,
+    pub percent_basis: PercentBasis,
+    pub process_peak_rss: Option<u64>,
+    pub dropped_functions: usize,
+    /// Whether the caller wrapper gets its own row, see `GuardBuilder::include_wrapper`
+    pub include_wrapper: bool,
+    pub metadata: BTreeMap<String, String>,
+    pub sort_by: SortBy,
+    /// Bounds the time spent computing rows, see `GuardBuilder::report_deadline`
+    pub report_deadline: Option<ReportDeadline>,
 }
 
-impl<'a> MetricsProvider<'a> for StatsData<'a> {
-    fn new(
-        stats: &'a HashMap<&'static str, FunctionStats>,
-        total_elapsed: Duration,
-        percentiles: Vec<u8>,
-        caller_name: &'static str,
-        limit: usize,
-    ) -> Self {
-        Self {
-            stats,
-            total_elapsed,
-            percentiles,
-            caller_name,
-            limit,
-        }
+impl StatsData<'_> {
+    fn is_displayed(&self, stats: &FunctionStats) -> bool {
+        (stats.has_data || stats.registered) && (self.include_wrapper || !stats.wrapper)
     }
 
-    fn percentiles(&self) -> Vec<u8> {
-        self.percentiles.clone()
+    /// Reference of `% Total` with [`PercentBasis::Wall`], the caller's duration if it was
+    /// measured already, and the time since the guard was created otherwise
+    fn wall_total(&self) -> u64 {
+        self.stats
+            .values()
+            .find(|s| s.wrapper)
+            .map(|s| s.total_duration_ns)
+            .unwrap_or(self.total_elapsed.as_nanos() as u64)
     }
 
-    fn description(&self) -> String {
-        "Execution duration of functions.".to_string()
+    fn measured_sum(&self) -> u64 {
+        self.stats
+            .values()
+            .filter(|s| s.has_data && !s.wrapper)
+            .map(|s| s.total_duration_ns)
+            .sum()
     }
 
-    fn profiling_mode(&self) -> ProfilingMode {
-        ProfilingMode::Timing
+    /// Largest single measurement of a function
+    fn max_value(&self, stats: &FunctionStats) -> u64 {
+        stats.percentile(100.0).as_nanos() as u64
     }
 
-    fn metric_data(&self) -> HashMap<String, Vec<MetricType>> {
-        let wrapper_total = self
+    /// Report rows of the displayed functions, only the first `limit` of them unless it's 0
+    fn rows(&self, limit: usize) -> HashMap<String, Vec<MetricType>> {
+        let reference_total =
+            if self.percent_basis == PercentBasis::Wall && !self.elapsed_below_resolution() {
+                self.wall_total()
+            } else {
+                self.measured_sum()
+            };
+
+        let mut entries: Vec<_> = self
             .stats
             .iter()
-            .find(|(_, s)| s.wrapper)
-            .map(|(_, s)| s.total_duration_ns);
+            .filter(|(_, s)| self.is_displayed(s))
+            .collect();
 
-        let reference_total = wrapper_total.unwrap_or(self.total_elapsed.as_nanos() as u64);
-
-        let mut entries: Vec<_> = self.stats.iter().filter(|(_, s)| s.has_data).collect();
-
-        entries.sort_by(|a, b| b.1.total_duration_ns.cmp(&a.1.total_duration_ns));
+        match self.sort_by {
+            SortBy::Total => {
+                entries.sort_by_key(|(name, s)| (std::cmp::Reverse(s.total_duration_ns), *name))
+            }
+            SortBy::Max => {
+                entries.sort_by_key(|(name, s)| (std::cmp::Reverse(self.max_value(s)), *name))
+            }
+        }
 
-        let entries = if self.limit > 0 {
-            entries.into_iter().take(self.limit).collect::<Vec<_>>()
+        let entries = if limit > 0 {
+            entries.into_iter().take(limit).collect::<Vec<_>>()
         } else {
             entries
         };
 
-        entries
-            .into_iter()
-            .map(|(function_name, stats)| {
+        let columns = self.percentiles();
+
+        rows_within_deadline(
+            self.report_deadline.as_ref(),
+            entries,
+            |(function_name, stats)| {
                 let percentage = if reference_total > 0 {
                     (stats.total_duration_ns as f64 / reference_total as f64) * 100.0
                 } else {
@@ -76,17 +102,71 @@ impl<'a> MetricsProvider<'a> for StatsData<'a> {
                     MetricType::DurationNs(stats.avg_duration_ns()),
                 ];
 
-                for p in self.percentiles.iter() {
-                    let value = stats.percentile(*p as f64);
-                    metrics.push(MetricType::DurationNs(value.as_nanos() as u64));
+                for &p in &columns {
+                    if tracks_percentile(&self.percentiles, stats.percentiles, p) {
+                        let value = stats.percentile(p);
+                        metrics.push(MetricType::DurationNs(value.as_nanos() as u64));
+                    } else {
+                        metrics.push(MetricType::NotTracked);
+                    }
                 }
 
                 metrics.push(MetricType::DurationNs(stats.total_duration_ns));
                 metrics.push(MetricType::Percentage((percentage * 100.0) as u64));
 
                 (function_name.to_string(), metrics)
-            })
-            .collect()
+            },
+        )
+        .into_iter()
+        .collect()
+    }
+}
+
+impl<'a> MetricsProvider<'a> for StatsData<'a> {
+    fn new(
+        stats: &'a HashMap<&'static str, FunctionStats>,
+        total_elapsed: Duration,
+        percentiles: Vec<f64>,
+        caller_name: &'static str,
+        limit: usize,
+    ) -> Self {
+        Self {
+            stats,
+            total_elapsed,
+            percentiles,
+            caller_name,
+            limit,
+            percent_basis: PercentBasis::Wall,
+            process_peak_rss: None,
+            dropped_functions: 0,
+            include_wrapper: true,
+            metadata: BTreeMap::new(),
+            sort_by: SortBy::Total,
+            report_deadline: None,
+        }
+    }
+
+    fn percentiles(&self) -> Vec<f64> {
+        percentile_columns(
+            &self.percentiles,
+            self.stats.values().map(|s| s.percentiles),
+        )
+    }
+
+    fn description(&self) -> String {
+        self.profiling_mode().description().to_string()
+    }
+
+    fn profiling_mode(&self) -> ProfilingMode {
+        ProfilingMode::Timing
+    }
+
+    fn metric_data(&self) -> HashMap<String, Vec<MetricType>> {
+        self.rows(self.limit)
+    }
+
+    fn all_metric_data(&self) -> HashMap<String, Vec<MetricType>> {
+        self.rows(0)
     }
 
     fn total_elapsed(&self) -> u64 {
@@ -97,15 +177,134 @@ impl<'a> MetricsProvider<'a> for StatsData<'a> {
         self.caller_name
     }
 
+    fn process_peak_rss(&self) -> Option<u64> {
+        self.process_peak_rss
+    }
+
+    fn elapsed_below_resolution(&self) -> bool {
+        self.percent_basis == PercentBasis::Wall
+            && self.wall_total() == 0
+            && self.measured_sum() > 0
+    }
+
+    fn dropped_functions(&self) -> usize {
+        self.dropped_functions
+    }
+
+    fn deadline_truncated(&self) -> usize {
+        self.report_deadline
+            .as_ref()
+            .map_or(0, ReportDeadline::truncated)
+    }
+
+    fn uncalled_functions(&self) -> Vec<String> {
+        shared::uncalled_functions(self.stats)
+    }
+
+    fn metadata(&self) -> BTreeMap<String, String> {
+        self.metadata.clone()
+    }
+
+    #[cfg(feature = "hotpath-alloc-blocks")]
+    fn alloc_blocks(&self) -> Vec<(String, Vec<MetricType>)> {
+        super::alloc_block::snapshot()
+    }
+
+    #[cfg(feature = "hotpath-time-alloc-count")]
+    fn function_allocations(&self) -> HashMap<String, MetricType> {
+        self.stats
+            .iter()
+            .filter(|(_, s)| self.is_displayed(s))
+            .map(|(name, s)| {
+                let allocations = s
+                    .allocations
+                    .map_or(MetricType::Unsupported, MetricType::AllocCount);
+                (name.to_string(), allocations)
+            })
+            .collect()
+    }
+
+    fn max_sort_keys(&self) -> Option<HashMap<String, u64>> {
+        (self.sort_by == SortBy::Max).then(|| {
+            self.stats
+                .iter()
+                .filter(|(_, s)| self.is_displayed(s))
+                .map(|(name, s)| (name.to_string(), self.max_value(s)))
+                .collect()
+        })
+    }
+
+    fn distributions(&self) -> HashMap<String, Vec<MetricType>> {
+        shared::distributions(
+            self.stats,
+            |s| self.is_displayed(s),
+            |s, p| MetricType::DurationNs(s.percentile(p).as_nanos() as u64),
+        )
+    }
+
+    fn trimmed_means(&self) -> HashMap<String, MetricType> {
+        self.stats
+            .iter()
+            .filter(|(_, s)| self.is_displayed(s))
+            .map(|(name, s)| {
+                let trimmed_mean = MetricType::DurationNs(s.trimmed_avg_duration_ns());
+                (name.to_string(), trimmed_mean)
+            })
+            .collect()
+    }
+
+    fn overhead_ns(&self) -> u64 {
+        super::guard::overhead_ns()
+    }
+
+    fn poll_counts(&self) -> HashMap<String, MetricType> {
+        super::polls::snapshot()
+    }
+
     fn entry_counts(&self) -> (usize, usize) {
-        let total_count = self.stats.iter().filter(|(_, s)| s.has_data).count();
+        shared::entry_counts(
+            self.stats,
+            |s| self.is_displayed(s),
+            self.limit,
+            self.deadline_truncated(),
+        )
+    }
+}
 
-        let displayed_count = if self.limit > 0 && self.limit < total_count {
-            self.limit
-        } else {
-            total_count
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(total_ns: u64, wrapper: bool) -> FunctionStats {
+        FunctionStats::new_duration(total_ns, Duration::ZERO, wrapper, 0)
+    }
+
+    #[test]
+    fn test_limit_keeps_total_and_percent_basis() {
+        let stats = HashMap::from([
+            ("main", stats(1_000, true)),
+            ("large", stats(600, false)),
+            ("medium", stats(300, false)),
+            ("small", stats(100, false)),
+            ("registered", FunctionStats::new_registered(0)),
+        ]);
+        let mut data = StatsData::new(&stats, Duration::from_nanos(2_000), vec![], "main", 2);
+        data.percent_basis = PercentBasis::MeasuredSum;
+        data.include_wrapper = false;
+
+        // Every function that recorded data, but not the hidden wrapper or the uncalled one
+        assert_eq!(data.entry_counts(), (2, 3));
+
+        // Relative to all measured functions, including the ones cut off by the limit
+        let rows = data.metric_data();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows["large"][3].value(), Some(6_000));
+        assert_eq!(rows["medium"][3].value(), Some(3_000));
+
+        data.percent_basis = PercentBasis::Wall;
+        assert_eq!(data.metric_data()["large"][3].value(), Some(6_000));
 
-        (displayed_count, total_count)
+        data.limit = 0;
+        assert_eq!(data.entry_counts(), (3, 3));
     }
 }
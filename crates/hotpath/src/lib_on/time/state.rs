@@ -1,11 +1,49 @@
 use crossbeam_channel::{Receiver, Sender};
 use hdrhistogram::Histogram;
 use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
+use super::super::shared::{self, ModeStats};
+
 pub enum Measurement {
-    Duration(u64, Duration, &'static str, bool), // duration_ns, elapsed_since_start, function_name, wrapper
+    Duration(
+        u64,
+        Duration,
+        &'static str,
+        bool,
+        Option<&'static [f64]>,
+        u64,
+        Option<u64>,
+    ), // duration_ns, elapsed_since_start, function_name, wrapper, percentiles, thread_id (0 without a Chrome trace), allocations
+}
+
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static THREAD_ID: u64 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Small sequential id of the current thread, stable for its lifetime
+#[inline]
+pub(crate) fn thread_id() -> u64 {
+    THREAD_ID.try_with(|id| *id).unwrap_or(0)
+}
+
+impl Measurement {
+    pub fn name_and_value(&self) -> (&'static str, u64) {
+        match self {
+            Measurement::Duration(duration_ns, _, name, ..) => (name, *duration_ns),
+        }
+    }
+
+    /// Time since the guard was created when the measurement was sent
+    pub fn elapsed(&self) -> Duration {
+        match self {
+            Measurement::Duration(_, elapsed, ..) => *elapsed,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -14,8 +52,21 @@ pub struct FunctionStats {
     pub count: u64,
     hist: Option<Histogram<u64>>,
     pub has_data: bool,
+    /// Pre-registered with `GuardBuilder::register`, reported even without measurements
+    pub registered: bool,
     pub wrapper: bool,
     pub recent_samples: VecDeque<(u64, Duration)>,
+    /// Per-function percentiles overriding the global ones, set with `#[measure(percentiles = [..])]`
+    pub percentiles: Option<&'static [f64]>,
+    /// Allocations made by all calls with the `hotpath-time-alloc-count` feature, `None` once
+    /// a call finished on another thread than it started on
+    pub allocations: Option<u64>,
+}
+
+impl ModeStats for FunctionStats {
+    fn is_uncalled(&self) -> bool {
+        self.registered && !self.has_data
+    }
 }
 
 impl FunctionStats {
@@ -40,13 +91,81 @@ impl FunctionStats {
             count: 1,
             hist: Some(hist),
             has_data: true,
+            registered: false,
             wrapper,
             recent_samples,
+            percentiles: None,
+            allocations: Some(0),
         };
         s.record_time(first_ns);
         s
     }
 
+    /// Empty entry for a function registered up front, before any measurement.
+    pub fn new_registered(recent_samples_limit: usize) -> Self {
+        let hist = Histogram::<u64>::new_with_bounds(Self::LOW_NS, Self::HIGH_NS, Self::SIGFIGS)
+            .expect("hdrhistogram init");
+
+        Self {
+            total_duration_ns: 0,
+            count: 0,
+            hist: Some(hist),
+            has_data: false,
+            registered: true,
+            wrapper: false,
+            recent_samples: VecDeque::with_capacity(recent_samples_limit),
+            percentiles: None,
+            allocations: Some(0),
+        }
+    }
+
+    /// Copy of the aggregated stats without recent samples, used as a baseline for [`Self::since`].
+    pub fn snapshot(&self) -> Self {
+        Self {
+            recent_samples: VecDeque::new(),
+            hist: self.hist.clone(),
+            ..*self
+        }
+    }
+
+    /// Stats accumulated since `previous`, an earlier [`Self::snapshot`] of the same function.
+    pub fn since(&self, previous: Option<&Self>) -> Self {
+        let mut delta = self.snapshot();
+        if let Some(previous) = previous {
+            delta.count = delta.count.saturating_sub(previous.count);
+            delta.total_duration_ns = delta
+                .total_duration_ns
+                .saturating_sub(previous.total_duration_ns);
+            if let (Some(hist), Some(previous_hist)) = (&mut delta.hist, &previous.hist) {
+                let _ = hist.subtract(previous_hist);
+            }
+            delta.allocations = delta
+                .allocations
+                .zip(previous.allocations)
+                .map(|(allocations, previous)| allocations.saturating_sub(previous));
+            delta.has_data = delta.count > 0;
+        }
+        delta
+    }
+
+    /// Combines the stats of `other` into these, e.g. to aggregate several functions or runs.
+    /// Distributions are added, so percentiles reflect the calls of both.
+    pub fn merge(&mut self, other: &FunctionStats) {
+        self.count += other.count;
+        self.total_duration_ns += other.total_duration_ns;
+        if let (Some(hist), Some(other_hist)) = (&mut self.hist, &other.hist) {
+            let _ = hist.add(other_hist);
+        }
+        self.has_data |= other.has_data;
+        self.registered |= other.registered;
+        self.percentiles = self.percentiles.or(other.percentiles);
+        self.add_allocations(other.allocations);
+
+        for &sample in &other.recent_samples {
+            shared::push_recent_sample(&mut self.recent_samples, sample);
+        }
+    }
+
     #[inline]
     fn record_time(&mut self, ns: u64) {
         if let Some(ref mut hist) = self.hist {
@@ -58,22 +177,26 @@ impl FunctionStats {
     pub fn update_duration(&mut self, duration_ns: u64, elapsed: Duration) {
         self.total_duration_ns += duration_ns;
         self.count += 1;
+        self.has_data = true;
         self.record_time(duration_ns);
 
-        if self.recent_samples.len() == self.recent_samples.capacity()
-            && self.recent_samples.capacity() > 0
-        {
-            self.recent_samples.pop_front();
-        }
-        self.recent_samples.push_back((duration_ns, elapsed));
+        shared::push_recent_sample(&mut self.recent_samples, (duration_ns, elapsed));
+    }
+
+    /// Adds the allocations of a call, `None` if they couldn't be attributed to it.
+    pub fn add_allocations(&mut self, allocations: Option<u64>) {
+        self.allocations = self
+            .allocations
+            .zip(allocations)
+            .map(|(total, allocations)| total + allocations);
     }
 
     pub fn avg_duration_ns(&self) -> u64 {
-        if self.count == 0 {
-            0
-        } else {
-            self.total_duration_ns / self.count
-        }
+        self.total_duration_ns.checked_div(self.count).unwrap_or(0)
+    }
+
+    pub fn trimmed_avg_duration_ns(&self) -> u64 {
+        self.hist.as_ref().map_or(0, crate::output::trimmed_mean)
     }
 
     #[inline]
@@ -88,14 +211,15 @@ impl FunctionStats {
 }
 
 pub(crate) struct HotPathState {
-    pub sender: Option<Sender<Measurement>>,
+    pub sender: Option<crate::lib_on::MeasurementSender>,
     pub shutdown_tx: Option<Sender<()>>,
     pub completion_rx: Option<Mutex<Receiver<HashMap<&'static str, FunctionStats>>>>,
-    pub query_tx: Option<Sender<super::super::QueryRequest>>,
+    pub query_tx: Option<crate::lib_on::QuerySender>,
     pub start_time: Instant,
     pub caller_name: &'static str,
-    pub percentiles: Vec<u8>,
+    pub percentiles: Vec<f64>,
     pub limit: usize,
+    pub percent_basis: crate::lib_on::PercentBasis,
 }
 
 pub(crate) fn process_measurement(
@@ -104,31 +228,39 @@ pub(crate) fn process_measurement(
     recent_samples_limit: usize,
 ) {
     match m {
-        Measurement::Duration(duration_ns, elapsed, name, wrapper) => {
+        Measurement::Duration(duration_ns, elapsed, name, wrapper, percentiles, _, allocations) => {
+            let duration_ns = duration_ns.saturating_sub(super::guard::overhead_ns());
             if let Some(s) = stats.get_mut(name) {
                 s.update_duration(duration_ns, elapsed);
+                s.add_allocations(allocations);
+                s.percentiles = s.percentiles.or(percentiles);
             } else {
-                stats.insert(
-                    name,
-                    FunctionStats::new_duration(
-                        duration_ns,
-                        elapsed,
-                        wrapper,
-                        recent_samples_limit,
-                    ),
+                let mut s = FunctionStats::new_duration(
+                    duration_ns,
+                    elapsed,
+                    wrapper,
+                    recent_samples_limit,
                 );
+                s.percentiles = percentiles;
+                s.add_allocations(allocations);
+                stats.insert(name, s);
             }
         }
     }
 }
 
-use super::super::HOTPATH_STATE;
+use super::super::{report_missing_guard, HOTPATH_STATE};
 
-pub fn send_duration_measurement(name: &'static str, duration: Duration, wrapper: bool) {
+pub fn send_duration_measurement(
+    name: &'static str,
+    duration: Duration,
+    wrapper: bool,
+    percentiles: Option<&'static [f64]>,
+    allocations: Option<u64>,
+) {
     let Some(arc_swap) = HOTPATH_STATE.get() else {
-        panic!(
-            "GuardBuilder::new(\"main\").build() must be called when --features hotpath is enabled"
-        );
+        report_missing_guard("hotpath");
+        return;
     };
 
     let Some(state) = arc_swap.load_full() else {
@@ -142,7 +274,20 @@ pub fn send_duration_measurement(name: &'static str, duration: Duration, wrapper
         return;
     };
 
-    let elapsed = state_guard.start_time.elapsed();
-    let measurement = Measurement::Duration(duration.as_nanos() as u64, elapsed, name, wrapper);
-    let _ = sender.try_send(measurement);
+    let elapsed = crate::lib_on::clock::elapsed(state_guard.start_time);
+    let measurement = Measurement::Duration(
+        duration.as_nanos() as u64,
+        elapsed,
+        name,
+        wrapper,
+        percentiles,
+        // Only the Chrome trace shows threads
+        if super::super::chrome_trace::enabled() {
+            thread_id()
+        } else {
+            0
+        },
+        allocations,
+    );
+    sender.send(measurement);
 }
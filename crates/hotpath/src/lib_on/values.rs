@@ -0,0 +1,98 @@
+//! Distributions of user values recorded with `record_value!`, e.g. rows processed per batch.
+//!
+//! Values are aggregated per label into histograms like function measurements, and reported
+//! in a separate table in every profiling mode.
+
+use hdrhistogram::Histogram;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+struct ValueStats {
+    unit: &'static str,
+    total: u64,
+    // Exact, the histogram only keeps values with 3 significant figures
+    max: u64,
+    hist: Histogram<u64>,
+}
+
+static VALUES: Mutex<BTreeMap<&'static str, ValueStats>> = Mutex::new(BTreeMap::new());
+
+const SIGFIGS: u8 = 3;
+
+/// Records `value` of the `name` label, measured in `unit`.
+#[doc(hidden)]
+pub fn record_value(name: &'static str, unit: &'static str, value: u64) {
+    super::recursion::untracked(|| {
+        let Ok(mut values) = VALUES.lock() else {
+            return;
+        };
+        let stats = values.entry(name).or_insert_with(|| ValueStats {
+            unit,
+            total: 0,
+            max: 0,
+            hist: Histogram::new(SIGFIGS).expect("hdrhistogram init"),
+        });
+        stats.total = stats.total.saturating_add(value);
+        stats.max = stats.max.max(value);
+        let _ = stats.hist.record(value);
+    });
+}
+
+/// Clears results of previous profiling sessions.
+pub(crate) fn clear() {
+    if let Ok(mut values) = VALUES.lock() {
+        values.clear();
+    }
+}
+
+/// Recorded labels as `(label, unit, [Calls, Avg, percentiles.., Max, Total])` rows,
+/// sorted by total.
+pub(crate) fn snapshot(percentiles: &[f64]) -> Vec<(String, String, Vec<u64>)> {
+    let Ok(values) = VALUES.lock() else {
+        return Vec::new();
+    };
+
+    let mut rows: Vec<_> = values
+        .iter()
+        .map(|(name, stats)| {
+            let calls = stats.hist.len();
+            let mut row = vec![calls, stats.total.checked_div(calls).unwrap_or(0)];
+            row.extend(
+                percentiles
+                    .iter()
+                    .map(|&p| stats.hist.value_at_percentile(p.clamp(0.0, 100.0))),
+            );
+            row.push(stats.max);
+            row.push(stats.total);
+            (name.to_string(), stats.unit.to_string(), row)
+        })
+        .collect();
+
+    rows.sort_by(|(name_a, _, row_a), (name_b, _, row_b)| {
+        row_b
+            .last()
+            .cmp(&row_a.last())
+            .then_with(|| name_a.cmp(name_b))
+    });
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_snapshot() {
+        for value in [10, 20, 30, 40] {
+            record_value("values::tests::rows", "rows", value);
+        }
+
+        let (_, unit, row) = snapshot(&[50.0])
+            .into_iter()
+            .find(|(name, ..)| name == "values::tests::rows")
+            .unwrap();
+        assert_eq!(unit, "rows");
+        assert_eq!(row, [4, 25, 20, 40, 100]);
+    }
+}
@@ -0,0 +1,140 @@
+//! Background worker thread that aggregates measurements sent over a channel, the default
+//! collector. Not compiled with the `hotpath-inline` feature, which aggregates on the
+//! measuring threads instead.
+
+use super::aggregator::Aggregator;
+use super::{clock, process, write_report, FunctionStats, Measurement, QueryRequest};
+use crate::Reporter;
+use crossbeam_channel::{after, bounded, never, select, unbounded, Receiver, Sender};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Message received by the worker thread, a batch of measurements with the
+/// `hotpath-batch-channel` feature
+#[cfg(feature = "hotpath-batch-channel")]
+pub(crate) type WorkerMessage = Vec<Measurement>;
+#[cfg(not(feature = "hotpath-batch-channel"))]
+pub(crate) type WorkerMessage = Measurement;
+
+impl Aggregator {
+    #[inline]
+    fn record_message(&mut self, message: WorkerMessage) {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "hotpath-batch-channel")] {
+                for measurement in message {
+                    if let Some(slow) = self.record(measurement) {
+                        slow.notify();
+                    }
+                }
+            } else {
+                if let Some(slow) = self.record(message) {
+                    slow.notify();
+                }
+            }
+        }
+    }
+}
+
+/// Channels of a running worker thread.
+pub(crate) struct Worker {
+    pub(crate) tx: Sender<WorkerMessage>,
+    pub(crate) query_tx: Sender<QueryRequest>,
+    pub(crate) shutdown_tx: Sender<()>,
+    pub(crate) completion_rx: Receiver<HashMap<&'static str, FunctionStats>>,
+}
+
+/// Moves the aggregator to a new worker thread, which sends back the final stats after a
+/// shutdown signal.
+pub(crate) fn spawn(
+    mut aggregator: Aggregator,
+    reporter: Arc<dyn Reporter>,
+    dropped_functions: Arc<AtomicUsize>,
+    report_on_idle: Option<Duration>,
+    track_process_peak: bool,
+    to_stderr: bool,
+) -> Worker {
+    let (tx, rx) = unbounded::<WorkerMessage>();
+    let (shutdown_tx, shutdown_rx) = bounded::<()>(1);
+    let (completion_tx, completion_rx) = bounded::<HashMap<&'static str, FunctionStats>>(1);
+    let (query_tx, query_rx) = unbounded::<QueryRequest>();
+
+    let start_time = aggregator.start_time;
+
+    thread::Builder::new()
+        .name("hotpath-worker".into())
+        .spawn(move || {
+            // Armed by the first measurement after a report, see GuardBuilder::report_on_idle
+            let mut idle_timer = never();
+            let mut idle_armed = false;
+            let mut last_measurement = start_time;
+
+            loop {
+                select! {
+                    recv(rx) -> result => {
+                        match result {
+                            Ok(message) => {
+                                if let Some(idle) = report_on_idle {
+                                    last_measurement = clock::now();
+                                    if !idle_armed {
+                                        idle_timer = after(idle);
+                                        idle_armed = true;
+                                    }
+                                }
+                                aggregator.record_message(message);
+                            }
+                            Err(_) => break, // Channel disconnected
+                        }
+                    }
+                    recv(idle_timer) -> _ => {
+                        let idle = report_on_idle.unwrap_or_default();
+                        let quiet = clock::elapsed(last_measurement);
+                        if quiet < idle {
+                            idle_timer = after(idle - quiet);
+                        } else {
+                            idle_timer = never();
+                            idle_armed = false;
+
+                            let mut metrics_provider = aggregator
+                                .metrics_provider(&aggregator.stats, clock::elapsed(start_time));
+                            if track_process_peak {
+                                metrics_provider.process_peak_rss = process::peak_rss_bytes();
+                            }
+                            write_report(reporter.as_ref(), &metrics_provider, to_stderr);
+                        }
+                    }
+                    recv(shutdown_rx) -> _ => {
+                        // Process remaining messages after shutdown signal
+                        while let Ok(message) = rx.try_recv() {
+                            aggregator.record_message(message);
+                        }
+                        break;
+                    }
+                    recv(query_rx) -> result => {
+                        if let Ok(query_request) = result {
+                            if let QueryRequest::Render(_) = query_request {
+                                while let Ok(message) = rx.try_recv() {
+                                    aggregator.record_message(message);
+                                }
+                            }
+                            aggregator.answer(query_request);
+                        }
+                    }
+                }
+            }
+
+            dropped_functions.store(aggregator.cardinality.dropped(), Ordering::Relaxed);
+            // Send stats via completion channel
+            let _ = completion_tx.send(aggregator.finish());
+        })
+        .expect("Failed to spawn hotpath-worker thread");
+
+    Worker {
+        tx,
+        query_tx,
+        shutdown_tx,
+        completion_rx,
+    }
+}
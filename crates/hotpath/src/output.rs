@@ -1,13 +1,16 @@
 use crate::FunctionStats;
 use colored::*;
-use prettytable::{color, Attr, Cell, Row, Table};
+use hdrhistogram::Histogram;
+use prettytable::{color, format::FormatBuilder, Attr, Cell, Row, Table};
 use serde::{
     ser::{SerializeMap, Serializer},
     Deserialize, Serialize,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
-use std::time::Duration;
+use std::io::{self, IsTerminal, Write};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
 /// Represents different types of profiling metrics with their values.
 ///
@@ -23,6 +26,7 @@ use std::time::Duration;
 /// * `AllocCount(u64)` - Allocation count
 /// * `Percentage(u64)` - Percentage as basis points (1% = 100, formatted as percentage)
 /// * `Unsupported` - For N/A values (e.g., async functions when allocation profiling not supported)
+/// * `NotTracked` - For percentiles not tracked by a function with its own `percentiles = [..]`
 ///
 /// # Examples
 ///
@@ -45,6 +49,30 @@ pub enum MetricType {
     AllocCount(u64), // Allocation count
     Percentage(u64), // Percentage as basis points (1% = 100)
     Unsupported,     // For N/A values (async functions when not supported)
+    NotTracked,      // For percentiles not tracked by a function
+}
+
+impl MetricType {
+    /// Raw numeric value of the metric, `None` for [`MetricType::Unsupported`] and [`MetricType::NotTracked`].
+    pub fn value(&self) -> Option<u64> {
+        match self {
+            MetricType::CallsCount(v)
+            | MetricType::DurationNs(v)
+            | MetricType::AllocBytes(v)
+            | MetricType::AllocCount(v)
+            | MetricType::Percentage(v) => Some(*v),
+            MetricType::Unsupported | MetricType::NotTracked => None,
+        }
+    }
+
+    /// Formats the metric like its `Display` implementation, with byte values rounded as
+    /// `byte_format`.
+    pub fn format(&self, byte_format: ByteFormat) -> String {
+        match self {
+            MetricType::AllocBytes(bytes) => format_bytes_with(*bytes, byte_format),
+            metric => metric.to_string(),
+        }
+    }
 }
 
 impl Serialize for MetricType {
@@ -58,7 +86,7 @@ impl Serialize for MetricType {
             MetricType::AllocBytes(bytes) => serializer.serialize_u64(*bytes),
             MetricType::AllocCount(count) => serializer.serialize_u64(*count),
             MetricType::Percentage(basis_points) => serializer.serialize_u64(*basis_points),
-            MetricType::Unsupported => serializer.serialize_none(),
+            MetricType::Unsupported | MetricType::NotTracked => serializer.serialize_none(),
         }
     }
 }
@@ -84,6 +112,9 @@ impl fmt::Display for MetricType {
             MetricType::Unsupported => {
                 write!(f, "N/A*")
             }
+            MetricType::NotTracked => {
+                write!(f, "N/A")
+            }
         }
     }
 }
@@ -101,11 +132,75 @@ pub fn format_duration(ns: u64) -> String {
     }
 }
 
+/// Formats the total elapsed time of a report header, e.g. `261.71µs`, `42.10s` or `1h 2m 5s`.
+/// Runs of a minute or longer are shown in hours, minutes and whole seconds.
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 60 {
+        return format!("{elapsed:.2?}");
+    }
+
+    let (hours, minutes, seconds) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    let mut parts = Vec::with_capacity(3);
+    if hours > 0 {
+        parts.push(format!("{hours}h"));
+    }
+    if minutes > 0 {
+        parts.push(format!("{minutes}m"));
+    }
+    if seconds > 0 {
+        parts.push(format!("{seconds}s"));
+    }
+    parts.join(" ")
+}
+
+/// Rounding of byte values in reports, set with `GuardBuilder::byte_format`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ByteFormat {
+    /// Largest fitting unit with one decimal place, e.g. `2.0 KB`
+    #[default]
+    Human,
+    /// Plain byte count, e.g. `2048 B`
+    Exact,
+    /// Largest fitting unit with the given number of decimal places, e.g. `1.953 KB` for `Precise(3)`
+    Precise(u8),
+}
+
+impl std::str::FromStr for ByteFormat {
+    type Err = String;
+
+    /// Parses `human`, `exact` or `precise:<digits>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Self::Human),
+            "exact" => Ok(Self::Exact),
+            _ => s
+                .strip_prefix("precise:")
+                .and_then(|digits| digits.parse().ok())
+                .map(Self::Precise)
+                .ok_or_else(|| {
+                    format!("invalid byte format '{s}', expected human, exact or precise:<digits>")
+                }),
+        }
+    }
+}
+
 /// Formats a byte count into a human-readable string with appropriate units.
 pub fn format_bytes(bytes: u64) -> String {
+    format_bytes_with(bytes, ByteFormat::Human)
+}
+
+/// Formats a byte count with the given [`ByteFormat`].
+pub fn format_bytes_with(bytes: u64, format: ByteFormat) -> String {
     const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
     const THRESHOLD: f64 = 1024.0;
 
+    let precision = match format {
+        ByteFormat::Human => 1,
+        ByteFormat::Exact => return format!("{bytes} B"),
+        ByteFormat::Precise(digits) => digits as usize,
+    };
+
     if bytes == 0 {
         return "0 B".to_string();
     }
@@ -117,37 +212,112 @@ pub fn format_bytes(bytes: u64) -> String {
     if unit_index == 0 {
         format!("{} {}", bytes, UNITS[unit_index])
     } else {
-        format!("{:.1} {}", unit_value, UNITS[unit_index])
+        format!("{:.*} {}", precision, unit_value, UNITS[unit_index])
     }
 }
 
 pub fn shorten_function_name(function_name: &str) -> String {
-    let parts: Vec<&str> = function_name.split("::").collect();
+    // Keep generic arguments of monomorphized names intact, e.g. `process<alloc::string::String>`
+    let (path, generics) = function_name
+        .find('<')
+        .map_or((function_name, ""), |i| function_name.split_at(i));
+
+    let parts: Vec<&str> = path.split("::").collect();
     if parts.len() > 2 {
-        parts[parts.len() - 2..].join("::")
+        format!("{}{}", parts[parts.len() - 2..].join("::"), generics)
     } else {
         function_name.to_string()
     }
 }
 
+/// Error returned by [`Reporter`] methods.
+///
+/// Lets callers tell a failed write apart from a reporter-specific failure, e.g. to ignore
+/// a closed stdout pipe. The `?` operator converts [`io::Error`] and [`serde_json::Error`]
+/// automatically, other errors can be wrapped with [`ReportError::custom`].
+#[derive(Debug)]
+pub enum ReportError {
+    /// Writing the report to its destination failed.
+    Io(io::Error),
+    /// Serializing the metrics failed.
+    Serialization(serde_json::Error),
+    /// Any other error raised by a custom reporter.
+    Custom(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl ReportError {
+    /// Wraps any error or message as [`ReportError::Custom`].
+    pub fn custom(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self::Custom(error.into())
+    }
+}
+
+impl fmt::Display for ReportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportError::Io(e) => write!(f, "I/O error: {}", e),
+            ReportError::Serialization(e) => write!(f, "serialization error: {}", e),
+            ReportError::Custom(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReportError::Io(e) => Some(e),
+            ReportError::Serialization(e) => Some(e),
+            ReportError::Custom(e) => Some(e.as_ref()),
+        }
+    }
+}
+
+impl From<io::Error> for ReportError {
+    fn from(e: io::Error) -> Self {
+        ReportError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ReportError {
+    fn from(e: serde_json::Error) -> Self {
+        ReportError::Serialization(e)
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for ReportError {
+    fn from(e: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        ReportError::Custom(e)
+    }
+}
+
 /// Trait for implementing custom profiling report output.
 ///
 /// Implement this trait to control how profiling results are displayed or stored.
 /// Custom reporters can integrate hotpath with logging systems, CI pipelines,
 /// monitoring tools, or custom file formats.
 ///
+/// Reporters writing text should implement [`write_report`](Reporter::write_report), which
+/// receives the output sink, so that they can be unit-tested against an in-memory buffer.
+/// When the guard is dropped, the report is written to stdout. Reporters implementing only
+/// [`report`](Reporter::report) keep working, but ignore the sink.
+/// At least one of the two methods must be implemented.
+///
 /// # Examples
 ///
 /// ```rust
-/// use hotpath::{Reporter, MetricsProvider};
-/// use std::error::Error;
+/// use hotpath::{MetricsProvider, ReportError, Reporter};
+/// use std::io::Write;
 ///
 /// struct SimpleLogger;
 ///
 /// impl Reporter for SimpleLogger {
-///     fn report(&self, metrics: &dyn MetricsProvider<'_>) -> Result<(), Box<dyn Error>> {
-///         println!("Profiling {} complete", metrics.caller_name());
-///         println!("Functions measured: {}", metrics.metric_data().len());
+///     fn write_report(
+///         &self,
+///         metrics: &dyn MetricsProvider<'_>,
+///         out: &mut dyn Write,
+///     ) -> Result<(), ReportError> {
+///         writeln!(out, "Profiling {} complete", metrics.caller_name())?;
+///         writeln!(out, "Functions measured: {}", metrics.metric_data().len())?;
 ///         Ok(())
 ///     }
 /// }
@@ -158,10 +328,20 @@ pub fn shorten_function_name(function_name: &str) -> String {
 /// * [`MetricsProvider`] - Trait for accessing profiling metrics data
 /// * [`GuardBuilder::reporter`](crate::GuardBuilder::reporter) - Method to set custom reporter
 pub trait Reporter: Send + Sync {
-    fn report(
+    /// Writes the report to `out`, stdout or stderr with `GuardBuilder::to_stderr`.
+    /// Reporters writing elsewhere, e.g. to a file, can ignore it.
+    fn write_report(
         &self,
         metrics_provider: &dyn MetricsProvider<'_>,
-    ) -> Result<(), Box<dyn std::error::Error>>;
+        out: &mut dyn Write,
+    ) -> Result<(), ReportError>;
+
+    /// Writes the report to stdout.
+    ///
+    /// Defaults to calling [`write_report`](Reporter::write_report) with a locked stdout.
+    fn report(&self, metrics_provider: &dyn MetricsProvider<'_>) -> Result<(), ReportError> {
+        self.write_report(metrics_provider, &mut io::stdout().lock())
+    }
 }
 
 /// Profiling mode indicating what type of measurements were collected.
@@ -174,13 +354,61 @@ pub trait Reporter: Send + Sync {
 /// * `Timing` - Time-based profiling (execution duration)
 /// * `AllocBytesTotal` - Total bytes allocated per function call
 /// * `AllocCountTotal` - Total allocation count per function call
+/// * `AllocLeak` - Bytes allocated and not freed again per function call
 #[allow(dead_code)]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub enum ProfilingMode {
     Timing,
     AllocBytesTotal,
     AllocCountTotal,
+    AllocLeak,
+}
+
+impl ProfilingMode {
+    /// Human-readable description of the metrics collected in this mode, as shown in reports.
+    ///
+    /// For allocation modes this describes the default cumulative measurements,
+    /// see [`ProfilingMode::description_with_alloc_self`] for the `HOTPATH_ALLOC_SELF` variant.
+    pub fn description(&self) -> &'static str {
+        self.description_with_alloc_self(false)
+    }
+
+    /// Same as [`ProfilingMode::description`], but describes exclusive allocation measurements
+    /// (excluding nested calls) if `alloc_self` is set. Timing mode is not affected.
+    pub fn description_with_alloc_self(&self, alloc_self: bool) -> &'static str {
+        match (self, alloc_self) {
+            (ProfilingMode::Timing, _) => "Execution duration of functions.",
+            (ProfilingMode::AllocBytesTotal, false) => {
+                "Cumulative bytes allocated during each function call (including nested calls)."
+            }
+            (ProfilingMode::AllocBytesTotal, true) => {
+                "Exclusive bytes allocated by each function (excluding nested calls)."
+            }
+            (ProfilingMode::AllocCountTotal, false) => {
+                "Total number of heap allocations during each function call (including nested calls)."
+            }
+            (ProfilingMode::AllocCountTotal, true) => {
+                "Exclusive heap allocations by each function (excluding nested calls)."
+            }
+            (ProfilingMode::AllocLeak, false) => {
+                "Bytes allocated and not freed during each function call (including nested calls)."
+            }
+            (ProfilingMode::AllocLeak, true) => {
+                "Exclusive bytes allocated and not freed by each function (excluding nested calls)."
+            }
+        }
+    }
+
+    /// Wraps a raw measured value in the unit of this mode.
+    #[cfg(not(feature = "hotpath-off"))]
+    pub(crate) fn metric(&self) -> fn(u64) -> MetricType {
+        match self {
+            ProfilingMode::Timing => MetricType::DurationNs,
+            ProfilingMode::AllocBytesTotal | ProfilingMode::AllocLeak => MetricType::AllocBytes,
+            ProfilingMode::AllocCountTotal => MetricType::AllocCount,
+        }
+    }
 }
 
 impl fmt::Display for ProfilingMode {
@@ -189,6 +417,7 @@ impl fmt::Display for ProfilingMode {
             ProfilingMode::Timing => write!(f, "timing"),
             ProfilingMode::AllocBytesTotal => write!(f, "alloc_bytes_total"),
             ProfilingMode::AllocCountTotal => write!(f, "alloc_count_total"),
+            ProfilingMode::AllocLeak => write!(f, "alloc_leak"),
         }
     }
 }
@@ -202,24 +431,82 @@ pub struct SamplesJson {
     pub count: usize,
 }
 
+/// Response containing recent call counts per function, served on `/rates`.
+/// Counts are bucketed into windows of `bucket_ns`, oldest first, the last one is the current window.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CallRatesJson {
+    pub bucket_ns: u64,
+    pub rates: HashMap<String, Vec<u64>>,
+}
+
 /// JSON representation of profiling metrics.
 #[derive(Debug, Clone)]
 pub struct MetricsJson {
+    /// Version of the JSON format, `0` for reports written before versioning was introduced
+    pub schema_version: u32,
     pub hotpath_profiling_mode: ProfilingMode,
     pub total_elapsed: u64,
     pub description: String,
     pub caller_name: String,
-    pub percentiles: Vec<u8>,
+    pub percentiles: Vec<f64>,
     pub data: MetricsDataJson,
+    /// Key-value pairs set with `GuardBuilder::metadata`, omitted from the JSON if empty
+    pub metadata: BTreeMap<String, String>,
+    /// Git commit recorded with `GuardBuilder::record_commit`, omitted from the JSON if `None`
+    pub commit: Option<String>,
+    /// Set by the HTTP server when the profiling worker didn't answer in time and the last
+    /// successfully fetched metrics are served instead, omitted from the JSON if `false`
+    pub stale: bool,
+}
+
+impl MetricsJson {
+    /// Version of the JSON format written by this crate version.
+    ///
+    /// * `1` - `schema_version` added
+    /// * `2` - optional `metadata`, `commit`, `stale`, `distribution` and `allocs` fields,
+    ///   float `percent_total` with `GuardBuilder::percent_format`, and fractional percentile
+    ///   keys like `p99_9`
+    pub const SCHEMA_VERSION: u32 = 2;
+
+    /// Metrics in the current [`MetricsJson::SCHEMA_VERSION`], without metadata or a commit.
+    pub fn new(
+        hotpath_profiling_mode: ProfilingMode,
+        total_elapsed: u64,
+        description: String,
+        caller_name: String,
+        percentiles: Vec<f64>,
+        data: MetricsDataJson,
+    ) -> Self {
+        Self {
+            schema_version: Self::SCHEMA_VERSION,
+            hotpath_profiling_mode,
+            total_elapsed,
+            description,
+            caller_name,
+            percentiles,
+            data,
+            metadata: BTreeMap::new(),
+            commit: None,
+            stale: false,
+        }
+    }
 }
 
 #[derive(Deserialize)]
 struct MetricsJsonRaw {
+    #[serde(default)]
+    schema_version: u32,
     hotpath_profiling_mode: ProfilingMode,
     total_elapsed: u64,
     description: String,
     caller_name: String,
     output: serde_json::Value,
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+    #[serde(default)]
+    commit: Option<String>,
+    #[serde(default)]
+    stale: bool,
 }
 
 impl TryFrom<MetricsJsonRaw> for MetricsJson {
@@ -237,12 +524,16 @@ impl TryFrom<MetricsJsonRaw> for MetricsJson {
         .map_err(serde::de::Error::custom)?;
 
         Ok(MetricsJson {
+            schema_version: raw.schema_version,
             hotpath_profiling_mode: raw.hotpath_profiling_mode,
             total_elapsed: raw.total_elapsed,
             description: raw.description,
             caller_name: raw.caller_name,
             percentiles,
             data: output,
+            metadata: raw.metadata,
+            commit: raw.commit,
+            stale: raw.stale,
         })
     }
 }
@@ -261,7 +552,213 @@ impl<'de> Deserialize<'de> for MetricsJson {
 #[derive(Debug, Clone)]
 pub struct MetricsDataJson(pub HashMap<String, Vec<MetricType>>);
 
-fn build_headers(percentiles: &[u8]) -> Vec<String> {
+/// Flat per-function row of a report, see [`MetricsJson::to_records`].
+///
+/// Values are nanoseconds, bytes or allocation counts depending on the profiling mode,
+/// `None` where a metric is N/A. Serializes to a flat object with the keys of the JSON report,
+/// e.g. `{"function": "...", "calls": 3, "avg": 120, "p95": 150, "total": 360, "percent_total": 4200}`,
+/// so a list of records maps directly to columns of a dataframe or CSV file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricRecord {
+    pub function: String,
+    pub calls: u64,
+    pub avg: Option<u64>,
+    /// `(percentile, value)` pairs in the order of [`MetricsJson::percentiles`]
+    pub percentiles: Vec<(f64, Option<u64>)>,
+    pub total: Option<u64>,
+    /// Share of the report total in basis points, `9618` for 96.18%
+    pub percent_total: Option<u64>,
+}
+
+impl MetricRecord {
+    /// Value of the given percentile, `None` if it's N/A or not part of the report.
+    pub fn percentile(&self, percentile: f64) -> Option<u64> {
+        self.percentiles
+            .iter()
+            .find(|(p, _)| *p == percentile)
+            .and_then(|(_, value)| *value)
+    }
+}
+
+impl Serialize for MetricRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.percentiles.len() + 5))?;
+        map.serialize_entry("function", &self.function)?;
+        map.serialize_entry("calls", &self.calls)?;
+        map.serialize_entry("avg", &self.avg)?;
+        for (percentile, value) in &self.percentiles {
+            map.serialize_entry(&header_to_json_key(&format!("P{}", percentile)), value)?;
+        }
+        map.serialize_entry("total", &self.total)?;
+        map.serialize_entry("percent_total", &self.percent_total)?;
+        map.end()
+    }
+}
+
+impl MetricsJson {
+    /// Flattens the report into one [`MetricRecord`] per function, ordered like the table:
+    /// by `% Total` descending, then by name.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use hotpath::MetricsJson;
+    ///
+    /// let json = std::fs::read_to_string("report.json").unwrap();
+    /// let report: MetricsJson = serde_json::from_str(&json).unwrap();
+    /// for record in report.to_records() {
+    ///     println!("{}: {} calls, p95 {:?}", record.function, record.calls, record.percentile(95.0));
+    /// }
+    /// ```
+    pub fn to_records(&self) -> Vec<MetricRecord> {
+        let total_index = self.percentiles.len() + 2;
+
+        let mut records: Vec<MetricRecord> = self
+            .data
+            .0
+            .iter()
+            .map(|(function, row)| {
+                let value = |index: usize| row.get(index).and_then(MetricType::value);
+                MetricRecord {
+                    function: function.clone(),
+                    calls: value(0).unwrap_or(0),
+                    avg: value(1),
+                    percentiles: self
+                        .percentiles
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &percentile)| (percentile, value(i + 2)))
+                        .collect(),
+                    total: value(total_index),
+                    percent_total: value(total_index + 1),
+                }
+            })
+            .collect();
+
+        records.sort_by(|a, b| {
+            b.percent_total
+                .cmp(&a.percent_total)
+                .then_with(|| a.function.cmp(&b.function))
+        });
+        records
+    }
+}
+
+impl From<&MetricsJson> for Vec<MetricRecord> {
+    fn from(metrics: &MetricsJson) -> Self {
+        metrics.to_records()
+    }
+}
+
+/// Percentile columns of a report: the global percentiles followed by any extra ones
+/// requested with `#[measure(percentiles = [..])]`, in ascending order.
+pub(crate) fn percentile_columns<'s>(
+    global: &[f64],
+    overrides: impl Iterator<Item = Option<&'s [f64]>>,
+) -> Vec<f64> {
+    let mut extra: Vec<f64> = overrides
+        .flatten()
+        .flatten()
+        .copied()
+        .filter(|p| !global.contains(p))
+        .collect();
+    extra.sort_by(f64::total_cmp);
+    extra.dedup();
+
+    global.iter().copied().chain(extra).collect()
+}
+
+/// Percentile range averaged by the `Trimmed Avg` column, values outside of it are ignored.
+pub(crate) const TRIMMED_MEAN_RANGE: (f64, f64) = (5.0, 95.0);
+
+/// Mean of the values recorded in `hist` between the [`TRIMMED_MEAN_RANGE`] percentiles.
+pub(crate) fn trimmed_mean(hist: &Histogram<u64>) -> u64 {
+    if hist.is_empty() {
+        return 0;
+    }
+
+    let low = hist.value_at_percentile(TRIMMED_MEAN_RANGE.0);
+    let high = hist.value_at_percentile(TRIMMED_MEAN_RANGE.1);
+
+    let (sum, count) = hist
+        .iter_recorded()
+        .filter(|v| (low..=high).contains(&v.value_iterated_to()))
+        .fold((0u128, 0u128), |(sum, count), v| {
+            let value = hist.median_equivalent(v.value_iterated_to());
+            (
+                sum + value as u128 * v.count_at_value() as u128,
+                count + v.count_at_value() as u128,
+            )
+        });
+
+    sum.checked_div(count).unwrap_or(0) as u64
+}
+
+/// Whether a function reports the given percentile, its own percentiles replace the global ones.
+/// Percentiles of the `distribution` arrays of the JSON report, see `GuardBuilder::include_distribution`
+pub(crate) const DISTRIBUTION_PERCENTILES: [f64; 10] =
+    [10.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0, 90.0, 100.0];
+
+pub(crate) fn tracks_percentile(global: &[f64], own: Option<&[f64]>, percentile: f64) -> bool {
+    own.unwrap_or(global).contains(&percentile)
+}
+
+/// Time budget for computing report rows, see `GuardBuilder::report_deadline`.
+///
+/// Rows are computed in report order until the deadline passes. The number of rows reached
+/// by the first computation is kept, so every part of a report shows the same functions.
+#[derive(Debug)]
+pub(crate) struct ReportDeadline {
+    at: Instant,
+    // (computed rows, entries) of the first computation
+    rows: OnceLock<(usize, usize)>,
+}
+
+impl ReportDeadline {
+    pub(crate) fn new(budget: Duration) -> Self {
+        Self {
+            at: Instant::now() + budget,
+            rows: OnceLock::new(),
+        }
+    }
+
+    /// Number of entries left out because the deadline passed
+    pub(crate) fn truncated(&self) -> usize {
+        self.rows
+            .get()
+            .map_or(0, |(rows, entries)| entries.saturating_sub(*rows))
+    }
+}
+
+/// Maps report entries to rows, stopping once `deadline` passes. The first row is always computed.
+pub(crate) fn rows_within_deadline<T, R>(
+    deadline: Option<&ReportDeadline>,
+    entries: Vec<T>,
+    mut row: impl FnMut(T) -> R,
+) -> Vec<R> {
+    let Some(deadline) = deadline else {
+        return entries.into_iter().map(row).collect();
+    };
+    if let Some(&(rows, _)) = deadline.rows.get() {
+        return entries.into_iter().take(rows).map(row).collect();
+    }
+
+    let entries_count = entries.len();
+    let mut rows = Vec::with_capacity(entries_count);
+    for entry in entries {
+        if !rows.is_empty() && Instant::now() >= deadline.at {
+            break;
+        }
+        rows.push(row(entry));
+    }
+    let _ = deadline.rows.set((rows.len(), entries_count));
+    rows
+}
+
+fn build_headers(percentiles: &[f64]) -> Vec<String> {
     let mut headers = vec![
         "Function".to_string(),
         "Calls".to_string(),
@@ -278,9 +775,53 @@ fn build_headers(percentiles: &[u8]) -> Vec<String> {
     headers
 }
 
+/// JSON key of a table header, e.g. `% Total` -> `percent_total` and `P99.9` -> `p99_9`.
+pub(crate) fn header_to_json_key(header: &str) -> String {
+    header
+        .to_lowercase()
+        .replace(' ', "_")
+        .replace('%', "percent")
+        .replace('.', "_")
+}
+
+/// Parses the part of a `p<percentile>` JSON key after the `p` back into a percentile.
+///
+/// The decimal point is written as `_`, e.g. `p0_5` -> 0.5, `p1_5` -> 1.5 and `p99_9` -> 99.9.
+fn parse_percentile_key(key: &str) -> Option<f64> {
+    let (whole, fraction) = match key.split_once('_') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (key, None),
+    };
+    let is_digits = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    if !is_digits(whole) || !fraction.is_none_or(is_digits) {
+        return None;
+    }
+
+    match fraction {
+        Some(fraction) => format!("{whole}.{fraction}").parse().ok(),
+        None => whole.parse().ok(),
+    }
+}
+
+/// Representation of `percent_total` values in JSON reports.
+///
+/// Both are accepted when deserializing [`MetricsJson`], an integer is read as basis points
+/// and a float as percent. Set with `GuardBuilder::percent_format`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PercentFormat {
+    /// Integer hundredths of a percent, e.g. `9383` for 93.83%
+    #[default]
+    BasisPoints,
+    /// Float percent between 0 and 100, e.g. `93.83`
+    Float,
+}
+
 struct MetricsDataSerializer<'a> {
     data: &'a HashMap<String, Vec<MetricType>>,
     headers: &'a [String],
+    percent_format: PercentFormat,
+    distributions: Option<&'a HashMap<String, Vec<MetricType>>>,
+    allocations: Option<&'a HashMap<String, MetricType>>,
 }
 
 impl<'a> Serialize for MetricsDataSerializer<'a> {
@@ -294,6 +835,13 @@ impl<'a> Serialize for MetricsDataSerializer<'a> {
             let function_serializer = FunctionDataSerializer {
                 headers: self.headers,
                 row,
+                percent_format: self.percent_format,
+                distribution: self
+                    .distributions
+                    .and_then(|distributions| distributions.get(function_name)),
+                allocations: self
+                    .allocations
+                    .and_then(|allocations| allocations.get(function_name)),
             };
 
             map.serialize_entry(function_name, &function_serializer)?;
@@ -304,25 +852,68 @@ impl<'a> Serialize for MetricsDataSerializer<'a> {
 }
 
 impl Serialize for MetricsJson {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        MetricsJsonSerializer {
+            json: self,
+            percent_format: PercentFormat::BasisPoints,
+            distributions: None,
+            allocations: None,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Serializes [`MetricsJson`] with percentages in the given format.
+pub(crate) struct MetricsJsonSerializer<'a> {
+    pub(crate) json: &'a MetricsJson,
+    pub(crate) percent_format: PercentFormat,
+    /// Added to each function as a `distribution` array, see `GuardBuilder::include_distribution`
+    pub(crate) distributions: Option<&'a HashMap<String, Vec<MetricType>>>,
+    /// Added to each function as `allocs`, see [`MetricsProvider::function_allocations`]
+    pub(crate) allocations: Option<&'a HashMap<String, MetricType>>,
+}
+
+impl Serialize for MetricsJsonSerializer<'_> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
         use serde::ser::SerializeStruct;
 
-        let headers = build_headers(&self.percentiles);
-        let mut state = serializer.serialize_struct("MetricsJson", 5)?;
+        let json = self.json;
+        let headers = build_headers(&json.percentiles);
+        let len = 6
+            + usize::from(!json.metadata.is_empty())
+            + usize::from(json.commit.is_some())
+            + usize::from(json.stale);
+        let mut state = serializer.serialize_struct("MetricsJson", len)?;
 
-        state.serialize_field("hotpath_profiling_mode", &self.hotpath_profiling_mode)?;
-        state.serialize_field("total_elapsed", &self.total_elapsed)?;
-        state.serialize_field("description", &self.description)?;
-        state.serialize_field("caller_name", &self.caller_name)?;
+        state.serialize_field("schema_version", &json.schema_version)?;
+        state.serialize_field("hotpath_profiling_mode", &json.hotpath_profiling_mode)?;
+        state.serialize_field("total_elapsed", &json.total_elapsed)?;
+        state.serialize_field("description", &json.description)?;
+        state.serialize_field("caller_name", &json.caller_name)?;
 
         let output_serializer = MetricsDataSerializer {
-            data: &self.data.0,
+            data: &json.data.0,
             headers: &headers,
+            percent_format: self.percent_format,
+            distributions: self.distributions,
+            allocations: self.allocations,
         };
         state.serialize_field("output", &output_serializer)?;
+        if !json.metadata.is_empty() {
+            state.serialize_field("metadata", &json.metadata)?;
+        }
+        if let Some(commit) = &json.commit {
+            state.serialize_field("commit", commit)?;
+        }
+        if json.stale {
+            state.serialize_field("stale", &json.stale)?;
+        }
 
         state.end()
     }
@@ -330,7 +921,7 @@ impl Serialize for MetricsJson {
 
 fn extract_percentiles_from_json(
     value: &serde_json::Value,
-) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
     let map = value
         .as_object()
         .ok_or("Expected object for output field")?;
@@ -340,18 +931,12 @@ fn extract_percentiles_from_json(
             .as_object()
             .ok_or("Expected object for function data")?;
 
-        let mut percentiles: Vec<u8> = function_obj
+        let mut percentiles: Vec<f64> = function_obj
             .keys()
-            .filter_map(|key| {
-                if key.starts_with('p') && key[1..].chars().all(|c| c.is_ascii_digit()) {
-                    key[1..].parse::<u8>().ok()
-                } else {
-                    None
-                }
-            })
+            .filter_map(|key| key.strip_prefix('p').and_then(parse_percentile_key))
             .collect();
 
-        percentiles.sort_unstable();
+        percentiles.sort_by(f64::total_cmp);
         Ok(percentiles)
     } else {
         Ok(Vec::new())
@@ -362,7 +947,7 @@ impl MetricsDataJson {
     pub fn deserialize_with_mode(
         value: serde_json::Value,
         profiling_mode: &ProfilingMode,
-        percentiles: &[u8],
+        percentiles: &[f64],
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let map = value
             .as_object()
@@ -378,13 +963,20 @@ impl MetricsDataJson {
 
             let mut row = Vec::new();
             for header in headers.iter().skip(1) {
-                // Convert header to JSON key format (lowercase, replace spaces and %)
-                let key = header
-                    .to_lowercase()
-                    .replace(' ', "_")
-                    .replace('%', "percent");
+                let key = header_to_json_key(header);
 
                 if let Some(value) = function_obj.get(&key) {
+                    // N/A cells are serialized as null
+                    if value.is_null() {
+                        row.push(MetricType::Unsupported);
+                        continue;
+                    }
+                    // Written as a float percent with `PercentFormat::Float`
+                    if key == "percent_total" && value.is_f64() {
+                        let percent = value.as_f64().ok_or("Expected f64 value")?;
+                        row.push(MetricType::Percentage((percent * 100.0).round() as u64));
+                        continue;
+                    }
                     let value_u64 = value.as_u64().ok_or("Expected u64 value")?;
                     let metric_type = create_metric_type(&key, value_u64, profiling_mode);
                     row.push(metric_type);
@@ -402,16 +994,24 @@ fn create_metric_type(field_name: &str, value: u64, profiling_mode: &ProfilingMo
         "calls" => MetricType::CallsCount(value),
         "percent_total" => MetricType::Percentage(value),
         // Percentiles
-        name if name.starts_with('p') && name[1..].chars().all(|c| c.is_ascii_digit()) => {
+        name if name
+            .strip_prefix('p')
+            .and_then(parse_percentile_key)
+            .is_some() =>
+        {
             match profiling_mode {
                 ProfilingMode::Timing => MetricType::DurationNs(value),
-                ProfilingMode::AllocBytesTotal => MetricType::AllocBytes(value),
+                ProfilingMode::AllocBytesTotal | ProfilingMode::AllocLeak => {
+                    MetricType::AllocBytes(value)
+                }
                 ProfilingMode::AllocCountTotal => MetricType::AllocCount(value),
             }
         }
         "avg" | "total" => match profiling_mode {
             ProfilingMode::Timing => MetricType::DurationNs(value),
-            ProfilingMode::AllocBytesTotal => MetricType::AllocBytes(value),
+            ProfilingMode::AllocBytesTotal | ProfilingMode::AllocLeak => {
+                MetricType::AllocBytes(value)
+            }
             ProfilingMode::AllocCountTotal => MetricType::AllocCount(value),
         },
         _ => unreachable!(),
@@ -421,6 +1021,9 @@ fn create_metric_type(field_name: &str, value: u64, profiling_mode: &ProfilingMo
 struct FunctionDataSerializer<'a> {
     headers: &'a [String],
     row: &'a [MetricType],
+    percent_format: PercentFormat,
+    distribution: Option<&'a Vec<MetricType>>,
+    allocations: Option<&'a MetricType>,
 }
 
 impl<'a> Serialize for FunctionDataSerializer<'a> {
@@ -428,18 +1031,31 @@ impl<'a> Serialize for FunctionDataSerializer<'a> {
     where
         S: Serializer,
     {
-        let mut map = serializer.serialize_map(Some(self.headers.len() - 1))?;
+        let len = self.headers.len() - 1
+            + usize::from(self.distribution.is_some())
+            + usize::from(self.allocations.is_some());
+        let mut map = serializer.serialize_map(Some(len))?;
 
         for (i, header) in self.headers.iter().enumerate().skip(1) {
             if i - 1 < self.row.len() {
-                let key = header
-                    .to_lowercase()
-                    .replace(' ', "_")
-                    .replace('%', "percent");
-                map.serialize_entry(&key, &self.row[i - 1])?;
+                let key = header_to_json_key(header);
+                match (&self.row[i - 1], self.percent_format) {
+                    (MetricType::Percentage(basis_points), PercentFormat::Float) => {
+                        map.serialize_entry(&key, &(*basis_points as f64 / 100.0))?
+                    }
+                    (metric, _) => map.serialize_entry(&key, metric)?,
+                }
             }
         }
 
+        if let Some(allocations) = self.allocations {
+            map.serialize_entry("allocs", allocations)?;
+        }
+
+        if let Some(distribution) = self.distribution {
+            map.serialize_entry("distribution", distribution)?;
+        }
+
         map.end()
     }
 }
@@ -453,23 +1069,30 @@ impl From<&dyn MetricsProvider<'_>> for MetricsJson {
         let data: HashMap<String, Vec<MetricType>> = sorted_entries.into_iter().collect();
 
         Self {
-            hotpath_profiling_mode,
-            total_elapsed: metrics.total_elapsed(),
-            description: metrics.description(),
-            caller_name: metrics.caller_name().to_string(),
-            percentiles,
-            data: MetricsDataJson(data),
+            metadata: metrics.metadata(),
+            commit: metrics.commit(),
+            ..Self::new(
+                hotpath_profiling_mode,
+                metrics.total_elapsed(),
+                metrics.description(),
+                metrics.caller_name().to_string(),
+                percentiles,
+                MetricsDataJson(data),
+            )
         }
     }
 }
 
 impl MetricsJson {
-    fn determine_profiling_mode() -> ProfilingMode {
+    /// Profiling mode selected with the crate features.
+    pub(crate) fn determine_profiling_mode() -> ProfilingMode {
         cfg_if::cfg_if! {
             if #[cfg(feature = "hotpath-alloc-bytes-total")] {
                 ProfilingMode::AllocBytesTotal
             } else if #[cfg(feature = "hotpath-alloc-count-total")] {
                 ProfilingMode::AllocCountTotal
+            } else if #[cfg(feature = "hotpath-alloc-leak")] {
+                ProfilingMode::AllocLeak
             } else {
                 ProfilingMode::Timing
             }
@@ -477,94 +1100,762 @@ impl MetricsJson {
     }
 }
 
-pub(crate) fn display_table(metrics_provider: &dyn MetricsProvider<'_>) {
-    let use_colors = std::env::var("NO_COLOR").is_err();
+/// Row color for a function taking `percentage` of the total: red at or above `threshold`,
+/// yellow at or above half of it.
+fn highlight_color(percentage: f64, threshold: f64) -> Option<color::Color> {
+    if percentage >= threshold {
+        Some(color::RED)
+    } else if percentage >= threshold / 2.0 {
+        Some(color::YELLOW)
+    } else {
+        None
+    }
+}
 
-    let mut table = Table::new();
+/// Whether reports written to stdout, or stderr if `to_stderr` is set, should be styled:
+/// the stream is a terminal and `NO_COLOR` is unset.
+pub(crate) fn terminal_colors(to_stderr: bool) -> bool {
+    let is_terminal = if to_stderr {
+        io::stderr().is_terminal()
+    } else {
+        io::stdout().is_terminal()
+    };
+    std::env::var("NO_COLOR").is_err() && is_terminal
+}
+
+thread_local! {
+    // Set while a report is written in plain mode, see `write_plain`
+    static PLAIN: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Writes the report of `write` to `out` in plain mode: tables are space-padded, without
+/// borders, and all ANSI styles are removed. See `GuardBuilder::plain`.
+pub(crate) fn write_plain(
+    out: &mut dyn Write,
+    write: impl FnOnce(&mut dyn Write) -> Result<(), ReportError>,
+) -> Result<(), ReportError> {
+    let mut buffer = Vec::new();
+    PLAIN.with(|plain| plain.set(true));
+    let result = write(&mut buffer);
+    PLAIN.with(|plain| plain.set(false));
+    result?;
+
+    for line in strip_ansi(&String::from_utf8_lossy(&buffer)).lines() {
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// Writes `table` to `out`, with terminal styles if `use_colors` is set and the terminal supports them.
+pub(crate) fn write_styled_table(
+    table: &Table,
+    out: &mut dyn Write,
+    use_colors: bool,
+) -> io::Result<()> {
+    if PLAIN.with(|plain| plain.get()) {
+        let mut table = table.clone();
+        table.set_format(FormatBuilder::new().padding(0, 2).build());
+        table.print(out)?;
+        return Ok(());
+    }
+    if use_colors {
+        if let Some(mut terminal) = term::TerminfoTerminal::new(&mut *out) {
+            table.print_term(&mut terminal)?;
+            return Ok(());
+        }
+    }
+    table.print(out)?;
+    Ok(())
+}
+
+/// Removes ANSI styles added by `colored`, which styles text based on stdout and not the actual sink.
+pub(crate) fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip a CSI sequence, e.g. `\x1b[1;34m`
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// Total of the `reference` function set with `GuardBuilder::relative_to`, matched by its full
+/// or its displayed short name. `None` if it's not among `entries`.
+fn reference_total(entries: &[(String, Vec<MetricType>)], reference: &str) -> Option<u64> {
+    entries
+        .iter()
+        .find(|(name, _)| name == reference || shorten_function_name(name) == reference)
+        .and_then(|(_, metrics)| metrics.iter().rev().nth(1)?.value())
+}
+
+/// Cell of the `Relative` column, a total as a multiple of the reference total, e.g. `3.20x`.
+fn format_relative(total: Option<u64>, reference_total: u64) -> String {
+    match total {
+        Some(total) if reference_total > 0 => {
+            format!("{:.2}x", total as f64 / reference_total as f64)
+        }
+        _ => "N/A".to_string(),
+    }
+}
 
-    let header_cells: Vec<Cell> = metrics_provider
-        .headers()
+/// Header row of a report table, bold and, with `use_colors`, cyan.
+pub(crate) fn header_row<S: AsRef<str>>(
+    headers: impl IntoIterator<Item = S>,
+    use_colors: bool,
+) -> Row {
+    let cells = headers
         .into_iter()
         .map(|header| {
+            let cell = Cell::new(header.as_ref()).with_style(Attr::Bold);
             if use_colors {
-                Cell::new(&header)
-                    .with_style(Attr::Bold)
-                    .with_style(Attr::ForegroundColor(color::CYAN))
+                cell.with_style(Attr::ForegroundColor(color::CYAN))
             } else {
-                Cell::new(&header).with_style(Attr::Bold)
+                cell
             }
         })
         .collect();
+    Row::new(cells)
+}
 
-    table.add_row(Row::new(header_cells));
+/// Writes the `[hotpath] {mode} - {description}` line above a report table.
+pub(crate) fn write_title(
+    out: &mut dyn Write,
+    metrics_provider: &dyn MetricsProvider<'_>,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "{} {} - {}",
+        "[hotpath]".blue().bold(),
+        metrics_provider.profiling_mode(),
+        metrics_provider.description()
+    )
+}
 
+pub(crate) fn write_table(
+    out: &mut dyn Write,
+    metrics_provider: &dyn MetricsProvider<'_>,
+    highlight_threshold: Option<f64>,
+    show_trimmed_mean: bool,
+    relative_to: Option<&str>,
+    byte_format: ByteFormat,
+    use_colors: bool,
+) -> io::Result<()> {
+    let mut table = Table::new();
     let sorted_entries = get_sorted_entries(metrics_provider);
 
+    let trimmed_means = if show_trimmed_mean {
+        Some(metrics_provider.trimmed_means())
+    } else {
+        None
+    };
+
+    let alloc_counts = metrics_provider.alloc_counts();
+    let function_allocations = metrics_provider.function_allocations();
+    let poll_counts = metrics_provider.poll_counts();
+    let locations = metrics_provider.locations();
+
+    let mut headers = metrics_provider.headers();
+    if trimmed_means.is_some() {
+        // Right after `Function`, `Calls` and `Avg`
+        headers.insert(3, "Trimmed Avg".to_string());
+    }
+    if !alloc_counts.is_empty() {
+        // Right before `Total` and `% Total`
+        let index = headers.len() - 2;
+        headers.insert(index, "Avg Alloc".to_string());
+        headers.insert(index, "Allocs".to_string());
+    }
+    if !function_allocations.is_empty() {
+        // Right before `Total` and `% Total`
+        let index = headers.len() - 2;
+        headers.insert(index, "Allocs".to_string());
+    }
+    if !poll_counts.is_empty() {
+        // Right before `Total` and `% Total`
+        let index = headers.len() - 2;
+        headers.insert(index, "Avg Polls".to_string());
+    }
+    let reference_total =
+        relative_to.and_then(|reference| reference_total(&sorted_entries, reference));
+    if reference_total.is_some() {
+        headers.push("Relative".to_string());
+    }
+
+    table.add_row(header_row(headers, use_colors));
+
     for (function_name, metrics) in sorted_entries {
+        // The caller wrapper spans the whole run, highlighting it would add no information
+        let highlight = highlight_threshold
+            .filter(|_| use_colors && function_name != metrics_provider.caller_name())
+            .and_then(|threshold| {
+                let percentage = metrics.iter().find_map(|metric| match metric {
+                    MetricType::Percentage(basis_points) => Some(*basis_points as f64 / 100.0),
+                    _ => None,
+                })?;
+                highlight_color(percentage, threshold)
+            });
+
+        let cell = |content: &str| match highlight {
+            Some(color) => Cell::new(content).with_style(Attr::ForegroundColor(color)),
+            None => Cell::new(content),
+        };
+
         let mut row_cells = Vec::new();
 
         let short_name = shorten_function_name(&function_name);
-        row_cells.push(Cell::new(&short_name));
+        match locations.get(&function_name) {
+            Some(location) => row_cells.push(cell(&format!("{short_name} ({location})"))),
+            None => row_cells.push(cell(&short_name)),
+        }
 
         for metric in &metrics {
-            row_cells.push(Cell::new(&metric.to_string()));
+            row_cells.push(cell(&metric.format(byte_format)));
+        }
+
+        if let Some(trimmed_means) = &trimmed_means {
+            let trimmed_mean = trimmed_means
+                .get(&function_name)
+                .unwrap_or(&MetricType::NotTracked);
+            row_cells.insert(3, cell(&trimmed_mean.format(byte_format)));
+        }
+
+        if !alloc_counts.is_empty() {
+            let index = row_cells.len() - 2;
+            let [allocs, avg_alloc] = alloc_counts
+                .get(&function_name)
+                .unwrap_or(&[MetricType::NotTracked, MetricType::NotTracked]);
+            row_cells.insert(index, cell(&avg_alloc.format(byte_format)));
+            row_cells.insert(index, cell(&allocs.to_string()));
+        }
+
+        if !function_allocations.is_empty() {
+            let index = row_cells.len() - 2;
+            let allocations = function_allocations
+                .get(&function_name)
+                .unwrap_or(&MetricType::NotTracked);
+            row_cells.insert(index, cell(&allocations.to_string()));
+        }
+
+        if !poll_counts.is_empty() {
+            let index = row_cells.len() - 2;
+            let polls = poll_counts
+                .get(&function_name)
+                .unwrap_or(&MetricType::NotTracked);
+            row_cells.insert(index, cell(&polls.to_string()));
+        }
+
+        if let Some(reference_total) = reference_total {
+            let total = metrics.iter().rev().nth(1).and_then(MetricType::value);
+            row_cells.push(cell(&format_relative(total, reference_total)));
         }
 
         table.add_row(Row::new(row_cells));
     }
 
-    println!(
-        "{} {} - {}",
-        "[hotpath]".blue().bold(),
-        metrics_provider.profiling_mode(),
-        metrics_provider.description()
-    );
+    write_title(out, metrics_provider)?;
+
+    let overhead_ns = metrics_provider.overhead_ns();
+    let overhead_note = if overhead_ns > 0 {
+        format!(
+            " (calibrated overhead: {}/call)",
+            format_duration(overhead_ns)
+        )
+    } else {
+        String::new()
+    };
 
     let (displayed, total) = metrics_provider.entry_counts();
     if displayed < total {
-        println!(
-            "{}: {:.2?} ({}/{})",
+        writeln!(
+            out,
+            "{}: {} ({}/{}){}",
             metrics_provider.caller_name().yellow().bold(),
-            Duration::from_nanos(metrics_provider.total_elapsed()),
+            format_elapsed(Duration::from_nanos(metrics_provider.total_elapsed())),
             displayed,
-            total
-        );
+            total,
+            overhead_note
+        )?;
     } else {
-        println!(
-            "{}: {:.2?}",
+        writeln!(
+            out,
+            "{}: {}{}",
             metrics_provider.caller_name().yellow().bold(),
-            Duration::from_nanos(metrics_provider.total_elapsed()),
-        );
+            format_elapsed(Duration::from_nanos(metrics_provider.total_elapsed())),
+            overhead_note
+        )?;
     }
 
-    table.printstd();
+    write_styled_table(&table, out, use_colors)?;
 
-    if metrics_provider.has_unsupported_async() {
-        println!();
-        println!(
-            "* {} for async methods is currently only available for tokio {} runtime.",
-            "alloc profiling".yellow().bold(),
-            "current_thread".green().bold()
-        );
-        println!(
-            "  Please use {} to enable it.",
-            "#[tokio::main(flavor = \"current_thread\")]".cyan().bold()
-        );
+    if let Some(reference) = relative_to.filter(|_| reference_total.is_none()) {
+        writeln!(
+            out,
+            "{} relative_to: {} is not among the reported functions",
+            "[hotpath]".blue().bold(),
+            reference.yellow()
+        )?;
     }
-}
 
-pub(crate) fn get_sorted_entries(
-    metrics_provider: &dyn MetricsProvider<'_>,
-) -> Vec<(String, Vec<MetricType>)> {
-    let metric_data = metrics_provider.metric_data();
+    let alloc_blocks = metrics_provider.alloc_blocks();
+    if !alloc_blocks.is_empty() {
+        write_alloc_blocks(
+            out,
+            alloc_blocks,
+            &metrics_provider.locations(),
+            byte_format,
+            use_colors,
+        )?;
+    }
 
-    let mut sorted_entries: Vec<(String, Vec<MetricType>)> = metric_data.into_iter().collect();
-    sorted_entries.sort_by(|(_name_a, metrics_a), (_name_b, metrics_b)| {
-        let key_a = metrics_provider.sort_key(metrics_a);
-        let key_b = metrics_provider.sort_key(metrics_b);
-        key_b
-            .partial_cmp(&key_a)
-            .unwrap_or(std::cmp::Ordering::Equal)
+    let io_blocks = metrics_provider.io_blocks();
+    if !io_blocks.is_empty() {
+        write_io_blocks(out, io_blocks, byte_format, use_colors)?;
+    }
+
+    let phases = metrics_provider.phases();
+    if !phases.is_empty() {
+        write_phases(out, phases, byte_format, use_colors)?;
+    }
+
+    let recorded_values = metrics_provider.recorded_values();
+    if !recorded_values.is_empty() {
+        write_recorded_values(
+            out,
+            recorded_values,
+            &metrics_provider.percentiles(),
+            use_colors,
+        )?;
+    }
+
+    let largest_allocations = metrics_provider.largest_allocations();
+    if !largest_allocations.is_empty() {
+        write_largest_allocations(out, largest_allocations, byte_format)?;
+    }
+
+    if let Some(peak_rss) = metrics_provider.process_peak_rss() {
+        writeln!(
+            out,
+            "{} {}",
+            "Process peak RSS:".bold(),
+            format_bytes_with(peak_rss, byte_format).yellow()
+        )?;
+    }
+
+    if metrics_provider.elapsed_below_resolution() {
+        writeln!(
+            out,
+            "{} elapsed time is below the clock resolution, % Total is relative to the sum of measured functions",
+            "Note:".bold()
+        )?;
+    }
+
+    let recursion_depths = metrics_provider.recursion_depths();
+    if !recursion_depths.is_empty() {
+        let depths: Vec<String> = recursion_depths
+            .iter()
+            .map(|(name, depth)| format!("{} {}", shorten_function_name(name), depth))
+            .collect();
+        writeln!(
+            out,
+            "{} {}",
+            "Max recursion depth (outermost calls recorded):".bold(),
+            depths.join(", ").yellow()
+        )?;
+    }
+
+    let dropped_functions = metrics_provider.dropped_functions();
+    if dropped_functions > 0 {
+        writeln!(
+            out,
+            "{} {}",
+            "Functions dropped due to cardinality limit:".bold(),
+            dropped_functions.to_string().yellow()
+        )?;
+    }
+
+    let deadline_truncated = metrics_provider.deadline_truncated();
+    if deadline_truncated > 0 {
+        writeln!(
+            out,
+            "{} {}",
+            "Functions cut due to report deadline:".bold(),
+            deadline_truncated.to_string().yellow()
+        )?;
+    }
+
+    if metrics_provider.has_unsupported_async() {
+        writeln!(out)?;
+        writeln!(
+            out,
+            "* {} for async methods is currently only available for tokio {} runtime.",
+            "alloc profiling".yellow().bold(),
+            "current_thread".green().bold()
+        )?;
+        writeln!(
+            out,
+            "  Please use {} to enable it.",
+            "#[tokio::main(flavor = \"current_thread\")]".cyan().bold()
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Functions of a crate or category summed up, see [`rollup_by`]
+#[derive(Default)]
+pub(crate) struct RollupRow {
+    /// Number of functions in the group, not a call count
+    functions: usize,
+    calls: u64,
+    total: Option<MetricType>,
+    basis_points: u64,
+}
+
+impl RollupRow {
+    /// Table cells of the `Functions`, `Calls`, `Total` and `% Total` columns
+    fn cells(&self, byte_format: ByteFormat) -> [String; 4] {
+        [
+            self.functions.to_string(),
+            MetricType::CallsCount(self.calls).to_string(),
+            self.total
+                .as_ref()
+                .unwrap_or(&MetricType::Unsupported)
+                .format(byte_format),
+            MetricType::Percentage(self.basis_points).to_string(),
+        ]
+    }
+}
+
+/// Sums per-function metrics by crate name (the first `::` segment of the function name).
+pub(crate) fn crate_rollup(metrics_provider: &dyn MetricsProvider<'_>) -> Vec<(String, RollupRow)> {
+    rollup_by(metrics_provider, &|function_name| {
+        function_name
+            .split("::")
+            .next()
+            .unwrap_or(function_name)
+            .to_string()
+    })
+}
+
+/// Sums per-function metrics by the category `categorize` assigns to each function name.
+pub(crate) fn category_rollup(
+    metrics_provider: &dyn MetricsProvider<'_>,
+    categorize: &crate::CategoryFn,
+) -> Vec<(String, RollupRow)> {
+    rollup_by(metrics_provider, &|function_name| {
+        categorize(function_name).to_string()
+    })
+}
+
+/// Sums per-function metrics into rows keyed by `group`, sorted by total. The wrapper function is skipped because its total already includes
+/// everything else.
+fn rollup_by(
+    metrics_provider: &dyn MetricsProvider<'_>,
+    group: &dyn Fn(&str) -> String,
+) -> Vec<(String, RollupRow)> {
+    let mut rows = HashMap::<String, RollupRow>::new();
+
+    for (function_name, metrics) in metrics_provider.metric_data() {
+        if function_name == metrics_provider.caller_name() {
+            continue;
+        }
+
+        let row = rows.entry(group(&function_name)).or_default();
+        row.functions += 1;
+
+        if let Some(MetricType::CallsCount(calls)) = metrics.first() {
+            row.calls += calls;
+        }
+
+        // Total and % Total are always the last two columns
+        if let [.., total, MetricType::Percentage(basis_points)] = metrics.as_slice() {
+            row.basis_points += basis_points;
+            row.total = match (row.total.take(), total) {
+                (None, total) => Some(total.clone()),
+                (Some(MetricType::DurationNs(a)), MetricType::DurationNs(b)) => {
+                    Some(MetricType::DurationNs(a + b))
+                }
+                (Some(MetricType::AllocBytes(a)), MetricType::AllocBytes(b)) => {
+                    Some(MetricType::AllocBytes(a + b))
+                }
+                (Some(MetricType::AllocCount(a)), MetricType::AllocCount(b)) => {
+                    Some(MetricType::AllocCount(a + b))
+                }
+                (previous, _) => previous,
+            };
+        }
+    }
+
+    let mut rows: Vec<(String, RollupRow)> = rows.into_iter().collect();
+
+    rows.sort_by(|(name_a, row_a), (name_b, row_b)| {
+        let total = |row: &RollupRow| row.total.as_ref().and_then(MetricType::value).unwrap_or(0);
+        total(row_b)
+            .cmp(&total(row_a))
+            .then_with(|| name_a.cmp(name_b))
+    });
+
+    rows
+}
+
+fn write_crate_rollup(
+    out: &mut dyn Write,
+    metrics_provider: &dyn MetricsProvider<'_>,
+    byte_format: ByteFormat,
+    use_colors: bool,
+) -> io::Result<()> {
+    write_rollup(
+        out,
+        metrics_provider,
+        "Crate",
+        "crate",
+        crate_rollup(metrics_provider),
+        byte_format,
+        use_colors,
+    )
+}
+
+fn write_category_rollup(
+    out: &mut dyn Write,
+    metrics_provider: &dyn MetricsProvider<'_>,
+    categorize: &crate::CategoryFn,
+    byte_format: ByteFormat,
+    use_colors: bool,
+) -> io::Result<()> {
+    write_rollup(
+        out,
+        metrics_provider,
+        "Category",
+        "category",
+        category_rollup(metrics_provider, categorize),
+        byte_format,
+        use_colors,
+    )
+}
+
+fn write_rollup(
+    out: &mut dyn Write,
+    metrics_provider: &dyn MetricsProvider<'_>,
+    group_header: &str,
+    group_label: &str,
+    rows: Vec<(String, RollupRow)>,
+    byte_format: ByteFormat,
+    use_colors: bool,
+) -> io::Result<()> {
+    let mut table = Table::new();
+
+    table.add_row(header_row(
+        [group_header, "Functions", "Calls", "Total", "% Total"],
+        use_colors,
+    ));
+
+    for (group_name, row) in rows {
+        let mut row_cells = vec![Cell::new(&group_name)];
+        for cell in row.cells(byte_format) {
+            row_cells.push(Cell::new(&cell));
+        }
+        table.add_row(Row::new(row_cells));
+    }
+
+    writeln!(
+        out,
+        "{} {} - totals by {} (nested measured calls are counted in each function)",
+        "[hotpath]".blue().bold(),
+        metrics_provider.profiling_mode(),
+        group_label,
+    )?;
+
+    write_styled_table(&table, out, use_colors)
+}
+
+/// Writes a table below the report, e.g. of blocks or phases, with one row of formatted
+/// cells per entry, after a `[hotpath] {title}` line.
+fn write_side_table(
+    out: &mut dyn Write,
+    title: &str,
+    headers: &[String],
+    rows: Vec<Vec<String>>,
+    use_colors: bool,
+) -> io::Result<()> {
+    let mut table = Table::new();
+
+    table.add_row(header_row(headers, use_colors));
+
+    for cells in rows {
+        table.add_row(Row::new(cells.iter().map(|cell| Cell::new(cell)).collect()));
+    }
+
+    writeln!(out)?;
+    writeln!(out, "{} {}", "[hotpath]".blue().bold(), title)?;
+    write_styled_table(&table, out, use_colors)
+}
+
+/// Cells of a row with a leading name column
+fn named_row(names: &[&str], metrics: &[MetricType], byte_format: ByteFormat) -> Vec<String> {
+    names
+        .iter()
+        .map(|name| name.to_string())
+        .chain(metrics.iter().map(|metric| metric.format(byte_format)))
+        .collect()
+}
+
+fn table_headers(headers: &[&str]) -> Vec<String> {
+    headers.iter().map(|header| header.to_string()).collect()
+}
+
+fn write_alloc_blocks(
+    out: &mut dyn Write,
+    alloc_blocks: Vec<(String, Vec<MetricType>)>,
+    locations: &HashMap<String, String>,
+    byte_format: ByteFormat,
+    use_colors: bool,
+) -> io::Result<()> {
+    write_side_table(
+        out,
+        "allocation blocks - Bytes and allocations of measure_block_alloc! blocks.",
+        &table_headers(&["Block", "Calls", "Avg", "Total", "Allocs"]),
+        alloc_blocks
+            .iter()
+            .map(|(block_name, metrics)| {
+                let block_name = match locations.get(block_name) {
+                    Some(location) => format!("{block_name} ({location})"),
+                    None => block_name.clone(),
+                };
+                named_row(&[&block_name], metrics, byte_format)
+            })
+            .collect(),
+        use_colors,
+    )
+}
+
+fn write_io_blocks(
+    out: &mut dyn Write,
+    io_blocks: Vec<(String, Vec<MetricType>)>,
+    byte_format: ByteFormat,
+    use_colors: bool,
+) -> io::Result<()> {
+    write_side_table(
+        out,
+        "io blocks - Bytes read or written by measure_io! blocks.",
+        &table_headers(&["Block", "Calls", "Avg", "Total"]),
+        io_blocks
+            .iter()
+            .map(|(block_name, metrics)| named_row(&[block_name], metrics, byte_format))
+            .collect(),
+        use_colors,
+    )
+}
+
+fn write_phases(
+    out: &mut dyn Write,
+    phases: Vec<(String, String, Vec<MetricType>)>,
+    byte_format: ByteFormat,
+    use_colors: bool,
+) -> io::Result<()> {
+    write_side_table(
+        out,
+        "phases - Measurements of functions grouped by the phase active when they finished.",
+        &table_headers(&["Phase", "Function", "Calls", "Avg", "Total"]),
+        phases
+            .iter()
+            .map(|(phase, function_name, metrics)| {
+                named_row(&[phase, function_name], metrics, byte_format)
+            })
+            .collect(),
+        use_colors,
+    )
+}
+
+fn write_recorded_values(
+    out: &mut dyn Write,
+    recorded_values: Vec<(String, String, Vec<u64>)>,
+    percentiles: &[f64],
+    use_colors: bool,
+) -> io::Result<()> {
+    let mut headers = table_headers(&["Value", "Calls", "Avg"]);
+    headers.extend(percentiles.iter().map(|p| format!("P{}", p)));
+    headers.extend(table_headers(&["Max", "Total"]));
+
+    let rows = recorded_values
+        .into_iter()
+        .map(|(label, unit, values)| {
+            let cells = values.into_iter().enumerate().map(|(i, value)| {
+                // Calls are a plain count, the other columns are in the recorded unit
+                if i == 0 || unit.is_empty() {
+                    value.to_string()
+                } else {
+                    format!("{} {}", value, unit)
+                }
+            });
+            std::iter::once(label).chain(cells).collect()
+        })
+        .collect();
+
+    write_side_table(
+        out,
+        "values - Distribution of record_value! values.",
+        &headers,
+        rows,
+        use_colors,
+    )
+}
+
+fn write_largest_allocations(
+    out: &mut dyn Write,
+    largest_allocations: Vec<(String, u64, String)>,
+    byte_format: ByteFormat,
+) -> io::Result<()> {
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{} largest allocations - Backtraces of the largest single allocation of each function.",
+        "[hotpath]".blue().bold(),
+    )?;
+
+    for (function_name, bytes, backtrace) in largest_allocations {
+        writeln!(out)?;
+        writeln!(
+            out,
+            "{}: {}",
+            shorten_function_name(&function_name).yellow().bold(),
+            format_bytes_with(bytes, byte_format)
+        )?;
+        writeln!(out, "{backtrace}")?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn get_sorted_entries(
+    metrics_provider: &dyn MetricsProvider<'_>,
+) -> Vec<(String, Vec<MetricType>)> {
+    let metric_data = metrics_provider.metric_data();
+
+    let max_sort_keys = metrics_provider.max_sort_keys();
+
+    let mut sorted_entries: Vec<(String, Vec<MetricType>)> = metric_data.into_iter().collect();
+    sorted_entries.sort_by(|(name_a, metrics_a), (name_b, metrics_b)| {
+        let ordering = match &max_sort_keys {
+            Some(max) => max.get(name_b).cmp(&max.get(name_a)),
+            None => {
+                let key_a = metrics_provider.sort_key(metrics_a);
+                let key_b = metrics_provider.sort_key(metrics_b);
+                key_b
+                    .partial_cmp(&key_a)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            }
+        };
+        ordering
+            // Tie-break by name so the order is stable across runs
+            .then_with(|| name_a.cmp(name_b))
     });
 
     sorted_entries
@@ -579,17 +1870,21 @@ pub(crate) fn get_sorted_entries(
 /// # Examples
 ///
 /// ```rust
-/// use hotpath::{Reporter, MetricsProvider};
-/// use std::error::Error;
+/// use hotpath::{MetricsProvider, ReportError, Reporter};
+/// use std::io::Write;
 ///
 /// struct CustomReporter;
 ///
 /// impl Reporter for CustomReporter {
-///     fn report(&self, metrics: &dyn MetricsProvider<'_>) -> Result<(), Box<dyn Error>> {
-///         println!("=== {} ===", metrics.description());
+///     fn write_report(
+///         &self,
+///         metrics: &dyn MetricsProvider<'_>,
+///         out: &mut dyn Write,
+///     ) -> Result<(), ReportError> {
+///         writeln!(out, "=== {} ===", metrics.description())?;
 ///
 ///         for (func_name, metric_values) in metrics.metric_data() {
-///             println!("{}: {} values", func_name, metric_values.len());
+///             writeln!(out, "{}: {} values", func_name, metric_values.len())?;
 ///         }
 ///
 ///         Ok(())
@@ -620,10 +1915,16 @@ pub trait MetricsProvider<'a> {
 
         headers
     }
-    fn percentiles(&self) -> Vec<u8>;
+    fn percentiles(&self) -> Vec<f64>;
 
     fn metric_data(&self) -> HashMap<String, Vec<MetricType>>;
 
+    /// Like [`MetricsProvider::metric_data`], with every displayed function regardless of
+    /// `limit`, e.g. for reporters persisting complete results.
+    fn all_metric_data(&self) -> HashMap<String, Vec<MetricType>> {
+        self.metric_data()
+    }
+
     fn sort_key(&self, metrics: &[MetricType]) -> f64 {
         // Sort by percentage, higher percentages first
         if let Some(MetricType::Percentage(basis_points)) = metrics.last() {
@@ -637,12 +1938,177 @@ pub trait MetricsProvider<'a> {
         false // Default implementation for time-based measurements
     }
 
+    /// Calibrated per-call profiling overhead in nanoseconds subtracted from each measurement
+    fn overhead_ns(&self) -> u64 {
+        0 // Allocation measurements are not adjusted
+    }
+
+    /// Allocations of `measure_block_alloc!` blocks measured in time profiling mode,
+    /// as `[Calls, Avg bytes, Total bytes, Total allocations]` rows
+    fn alloc_blocks(&self) -> Vec<(String, Vec<MetricType>)> {
+        Vec::new()
+    }
+
+    /// Allocations made by each displayed function in time profiling mode with the
+    /// `hotpath-time-alloc-count` feature, shown as an `Allocs` column before `Total`.
+    /// [`MetricType::Unsupported`] if a call finished on another thread than it started on.
+    fn function_allocations(&self) -> HashMap<String, MetricType> {
+        HashMap::new()
+    }
+
+    /// Source locations of measured functions and blocks as `file:line`, shown next to their
+    /// names in the table with `GuardBuilder::show_locations`
+    fn locations(&self) -> HashMap<String, String> {
+        #[cfg(not(feature = "hotpath-off"))]
+        {
+            crate::lib_on::locations()
+        }
+        #[cfg(feature = "hotpath-off")]
+        {
+            HashMap::new()
+        }
+    }
+
+    /// Bytes of `measure_io!` blocks as `[Calls, Avg bytes, Total bytes]` rows
+    fn io_blocks(&self) -> Vec<(String, Vec<MetricType>)> {
+        #[cfg(not(feature = "hotpath-off"))]
+        {
+            crate::lib_on::io::snapshot()
+        }
+        #[cfg(feature = "hotpath-off")]
+        {
+            Vec::new()
+        }
+    }
+
+    /// Measurements of functions per phase set with `hotpath::phase`, as
+    /// `(phase, function, [Calls, Avg, Total])` rows in the unit of the profiling mode
+    fn phases(&self) -> Vec<(String, String, Vec<MetricType>)> {
+        #[cfg(not(feature = "hotpath-off"))]
+        {
+            crate::lib_on::phases::snapshot(self.profiling_mode().metric())
+        }
+        #[cfg(feature = "hotpath-off")]
+        {
+            Vec::new()
+        }
+    }
+
+    /// Values recorded with `record_value!` as `(label, unit, [Calls, Avg, percentiles.., Max, Total])`
+    /// rows, with the percentile columns of the report
+    fn recorded_values(&self) -> Vec<(String, String, Vec<u64>)> {
+        #[cfg(not(feature = "hotpath-off"))]
+        {
+            crate::lib_on::values::snapshot(&self.percentiles())
+        }
+        #[cfg(feature = "hotpath-off")]
+        {
+            Vec::new()
+        }
+    }
+
+    /// Largest single allocation of displayed functions as `(function, bytes, backtrace)`,
+    /// captured with the `hotpath-alloc-backtrace` feature
+    fn largest_allocations(&self) -> Vec<(String, u64, String)> {
+        Vec::new()
+    }
+
+    /// Peak resident set size of the whole process in bytes, if tracked
+    fn process_peak_rss(&self) -> Option<u64> {
+        None
+    }
+
+    /// Whether the program finished within the clock resolution, so the wall clock time is zero
+    /// and `% Total` is relative to the sum of measured functions instead
+    fn elapsed_below_resolution(&self) -> bool {
+        false
+    }
+
+    /// Deepest recursion of functions that called themselves. Only their outermost calls
+    /// are recorded, nested calls count towards them
+    fn recursion_depths(&self) -> Vec<(String, u32)> {
+        #[cfg(not(feature = "hotpath-off"))]
+        {
+            crate::lib_on::recursion::max_depths()
+        }
+        #[cfg(feature = "hotpath-off")]
+        {
+            Vec::new()
+        }
+    }
+
+    /// Number of distinct functions not measured because the `max_functions` cap was reached
+    fn dropped_functions(&self) -> usize {
+        0
+    }
+
+    /// Number of functions left out of the report because `GuardBuilder::report_deadline` passed
+    fn deadline_truncated(&self) -> usize {
+        0
+    }
+
+    /// Functions pre-registered with `GuardBuilder::register` that recorded no calls
+    fn uncalled_functions(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Key-value pairs attached to the report with `GuardBuilder::metadata`
+    fn metadata(&self) -> BTreeMap<String, String> {
+        BTreeMap::new()
+    }
+
+    /// Git commit of the profiled code, recorded with `GuardBuilder::record_commit`
+    fn commit(&self) -> Option<String> {
+        #[cfg(not(feature = "hotpath-off"))]
+        {
+            crate::lib_on::commit::get()
+        }
+        #[cfg(feature = "hotpath-off")]
+        {
+            None
+        }
+    }
+
+    /// Number of allocations and average allocation size of each displayed function as
+    /// `[Allocs, Avg Alloc]` cells, shown before `Total` in `hotpath-alloc-bytes-total` mode
+    fn alloc_counts(&self) -> HashMap<String, [MetricType; 2]> {
+        HashMap::new()
+    }
+
+    /// Average number of polls per call of `#[measure(poll_time)]` futures, keyed by the
+    /// names of their `::poll` entries
+    fn poll_counts(&self) -> HashMap<String, MetricType> {
+        HashMap::new()
+    }
+
+    /// Values at every tenth percentile, P10 to P100, of each displayed function, written to
+    /// the JSON report with `GuardBuilder::include_distribution`
+    fn distributions(&self) -> HashMap<String, Vec<MetricType>> {
+        HashMap::new()
+    }
+
+    /// Per-function mean of values between the P5 and P95 percentiles, ignoring outliers
+    fn trimmed_means(&self) -> HashMap<String, MetricType> {
+        HashMap::new()
+    }
+
+    /// Largest single measurement of each displayed function if rows are ordered by it,
+    /// see `SortBy::Max`
+    fn max_sort_keys(&self) -> Option<HashMap<String, u64>> {
+        None
+    }
+
+    /// Number of `(displayed, total)` report rows, shown as `(displayed/total)` in the table
+    /// header when some are cut off. `total` counts every function that recorded data, but
+    /// not registered functions that were never called, or the caller when it's hidden with
+    /// `GuardBuilder::include_wrapper(false)`. `displayed` is how many of them are left after
+    /// `limit` and `report_deadline`.
     fn entry_counts(&self) -> (usize, usize);
 
     fn new(
         stats: &'a HashMap<&'static str, FunctionStats>,
         total_elapsed: Duration,
-        percentiles: Vec<u8>,
+        percentiles: Vec<f64>,
         caller_name: &'static str,
         limit: usize,
     ) -> Self
@@ -652,94 +2118,241 @@ pub trait MetricsProvider<'a> {
     fn total_elapsed(&self) -> u64;
 
     fn caller_name(&self) -> &str;
+
+    /// Collects the displayed functions and run metadata into a [`HotPathReport`](crate::HotPathReport)
+    /// that can be printed, serialized or inspected field by field.
+    fn into_report(self) -> crate::HotPathReport
+    where
+        Self: Sized,
+    {
+        crate::HotPathReport::from(&self as &dyn MetricsProvider<'_>)
+    }
 }
 
-fn display_no_measurements_message(total_elapsed: Duration, caller_name: &str) {
+pub(crate) fn write_no_measurements_message(
+    out: &mut dyn Write,
+    total_elapsed: Duration,
+    caller_name: &str,
+) -> io::Result<()> {
     let title = format!(
-        "\n{} No measurements recorded from {} (Total time: {:.2?})",
+        "\n{} No measurements recorded from {} (Total time: {})",
         "[hotpath]".blue().bold(),
         caller_name.yellow().bold(),
-        total_elapsed
+        format_elapsed(total_elapsed)
     );
-    println!("{title}");
-    println!();
-    println!(
+    writeln!(out, "{title}")?;
+    writeln!(out)?;
+    writeln!(
+        out,
         "To start measuring performance, add the {} macro to your functions:",
         "#[hotpath::measure]".cyan().bold()
-    );
-    println!();
-    println!(
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
         "  {}",
         "#[cfg_attr(feature = \"hotpath\", hotpath::measure)]".cyan()
-    );
-    println!("  {}", "fn your_function() {".dimmed());
-    println!("  {}", "    // your code here".dimmed());
-    println!("  {}", "}".dimmed());
-    println!();
-    println!(
+    )?;
+    writeln!(out, "  {}", "fn your_function() {".dimmed())?;
+    writeln!(out, "  {}", "    // your code here".dimmed())?;
+    writeln!(out, "  {}", "}".dimmed())?;
+    writeln!(out)?;
+    writeln!(
+        out,
         "Or use {} to measure code blocks:",
         "hotpath::measure_block!".cyan().bold()
-    );
-    println!();
-    println!("  {}", "#[cfg(feature = \"hotpath\")]".cyan());
-    println!("  {}", "hotpath::measure_block!(\"label\", {".cyan());
-    println!("  {}", "    // your code here".dimmed());
-    println!("  {}", "});".cyan());
-    println!();
+    )?;
+    writeln!(out)?;
+    writeln!(out, "  {}", "#[cfg(feature = \"hotpath\")]".cyan())?;
+    writeln!(out, "  {}", "hotpath::measure_block!(\"label\", {".cyan())?;
+    writeln!(out, "  {}", "    // your code here".dimmed())?;
+    writeln!(out, "  {}", "});".cyan())?;
+    writeln!(out)
 }
 
-pub(crate) struct TableReporter;
+pub(crate) struct TableReporter {
+    pub(crate) crate_rollup: crate::CrateRollup,
+    pub(crate) categorize: Option<crate::CategoryFn>,
+    pub(crate) highlight_threshold: Option<f64>,
+    pub(crate) show_trimmed_mean: bool,
+    pub(crate) show_uncalled: bool,
+    pub(crate) relative_to: Option<&'static str>,
+    pub(crate) byte_format: ByteFormat,
+    pub(crate) to_stderr: bool,
+    pub(crate) plain: bool,
+}
 
 impl Reporter for TableReporter {
-    fn report(
+    fn write_report(
+        &self,
+        metrics_provider: &dyn MetricsProvider<'_>,
+        out: &mut dyn Write,
+    ) -> Result<(), ReportError> {
+        if self.plain {
+            write_plain(out, |out| self.write_tables(metrics_provider, out, false))
+        } else {
+            self.write_tables(metrics_provider, out, terminal_colors(self.to_stderr))
+        }
+    }
+}
+
+impl TableReporter {
+    fn write_tables(
         &self,
         metrics_provider: &dyn MetricsProvider<'_>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        out: &mut dyn Write,
+        use_colors: bool,
+    ) -> Result<(), ReportError> {
         if metrics_provider.metric_data().is_empty() {
-            display_no_measurements_message(
+            write_no_measurements_message(
+                out,
                 Duration::from_nanos(metrics_provider.total_elapsed()),
                 metrics_provider.caller_name(),
-            );
+            )?;
             return Ok(());
         }
 
-        display_table(metrics_provider);
+        match self.crate_rollup {
+            crate::CrateRollup::Off => write_table(
+                out,
+                metrics_provider,
+                self.highlight_threshold,
+                self.show_trimmed_mean,
+                self.relative_to,
+                self.byte_format,
+                use_colors,
+            )?,
+            crate::CrateRollup::Append => {
+                write_table(
+                    out,
+                    metrics_provider,
+                    self.highlight_threshold,
+                    self.show_trimmed_mean,
+                    self.relative_to,
+                    self.byte_format,
+                    use_colors,
+                )?;
+                writeln!(out)?;
+                write_crate_rollup(out, metrics_provider, self.byte_format, use_colors)?
+            }
+            crate::CrateRollup::Replace => {
+                write_crate_rollup(out, metrics_provider, self.byte_format, use_colors)?
+            }
+        }
+        if let Some(categorize) = &self.categorize {
+            writeln!(out)?;
+            write_category_rollup(
+                out,
+                metrics_provider,
+                categorize,
+                self.byte_format,
+                use_colors,
+            )?;
+        }
+        if self.show_uncalled {
+            write_uncalled_functions(out, metrics_provider)?;
+        }
         Ok(())
     }
 }
 
-pub(crate) struct JsonReporter;
+/// Lists registered functions that were never called, independently of the report limit.
+fn write_uncalled_functions(
+    out: &mut dyn Write,
+    metrics_provider: &dyn MetricsProvider<'_>,
+) -> io::Result<()> {
+    let uncalled = metrics_provider.uncalled_functions();
+    if uncalled.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "{} {}",
+        "Registered functions never called:".bold(),
+        uncalled.len().to_string().yellow()
+    )?;
+    for name in uncalled {
+        writeln!(out, "  - {}", name.yellow())?;
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+pub(crate) struct JsonReporter {
+    pub(crate) percent_format: PercentFormat,
+    pub(crate) include_distribution: bool,
+}
 
 impl Reporter for JsonReporter {
-    fn report(
+    fn write_report(
         &self,
         metrics_provider: &dyn MetricsProvider<'_>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        out: &mut dyn Write,
+    ) -> Result<(), ReportError> {
         if metrics_provider.metric_data().is_empty() {
-            display_no_measurements_message(Duration::ZERO, metrics_provider.caller_name());
+            write_no_measurements_message(out, Duration::ZERO, metrics_provider.caller_name())?;
             return Ok(());
         }
 
         let json = MetricsJson::from(metrics_provider);
-        println!("{}", serde_json::to_string(&json).unwrap());
+        let distributions = self
+            .include_distribution
+            .then(|| metrics_provider.distributions());
+        let allocations = metrics_provider.function_allocations();
+        let serializer = MetricsJsonSerializer {
+            json: &json,
+            percent_format: self.percent_format,
+            distributions: distributions.as_ref(),
+            allocations: (!allocations.is_empty()).then_some(&allocations),
+        };
+        writeln!(out, "{}", serde_json::to_string(&serializer)?)?;
         Ok(())
     }
 }
 
-pub(crate) struct JsonPrettyReporter;
+#[derive(Default)]
+pub(crate) struct JsonPrettyReporter {
+    pub(crate) percent_format: PercentFormat,
+    pub(crate) include_distribution: bool,
+}
 
 impl Reporter for JsonPrettyReporter {
-    fn report(
+    fn write_report(
         &self,
         metrics_provider: &dyn MetricsProvider<'_>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        out: &mut dyn Write,
+    ) -> Result<(), ReportError> {
         if metrics_provider.metric_data().is_empty() {
-            display_no_measurements_message(Duration::ZERO, metrics_provider.caller_name());
+            write_no_measurements_message(out, Duration::ZERO, metrics_provider.caller_name())?;
             return Ok(());
         }
 
         let json = MetricsJson::from(metrics_provider);
-        println!("{}", serde_json::to_string_pretty(&json)?);
+        let distributions = self
+            .include_distribution
+            .then(|| metrics_provider.distributions());
+        let allocations = metrics_provider.function_allocations();
+        let serializer = MetricsJsonSerializer {
+            json: &json,
+            percent_format: self.percent_format,
+            distributions: distributions.as_ref(),
+            allocations: (!allocations.is_empty()).then_some(&allocations),
+        };
+        writeln!(out, "{}", serde_json::to_string_pretty(&serializer)?)?;
+        Ok(())
+    }
+}
+
+/// Reporter discarding the results, used by [`crate::GuardBuilder::silent`].
+pub(crate) struct SilentReporter;
+
+impl Reporter for SilentReporter {
+    fn write_report(
+        &self,
+        _metrics_provider: &dyn MetricsProvider<'_>,
+        _out: &mut dyn Write,
+    ) -> Result<(), ReportError> {
         Ok(())
     }
 }
@@ -748,6 +2361,143 @@ impl Reporter for JsonPrettyReporter {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_highlight_color() {
+        assert_eq!(highlight_color(45.0, 20.0), Some(color::RED));
+        assert_eq!(highlight_color(20.0, 20.0), Some(color::RED));
+        assert_eq!(highlight_color(12.5, 20.0), Some(color::YELLOW));
+        assert_eq!(highlight_color(9.99, 20.0), None);
+    }
+
+    #[test]
+    fn test_format_bytes_with() {
+        assert_eq!(format_bytes_with(2048, ByteFormat::Human), "2.0 KB");
+        assert_eq!(format_bytes_with(2048, ByteFormat::Exact), "2048 B");
+        assert_eq!(format_bytes_with(2000, ByteFormat::Precise(3)), "1.953 KB");
+        assert_eq!(format_bytes_with(512, ByteFormat::Precise(3)), "512 B");
+        assert_eq!(format_bytes_with(0, ByteFormat::Precise(2)), "0 B");
+        assert_eq!(
+            MetricType::AllocBytes(2048).format(ByteFormat::Exact),
+            "2048 B"
+        );
+        assert_eq!(
+            MetricType::AllocCount(2048).format(ByteFormat::Exact),
+            "2048"
+        );
+
+        assert_eq!("exact".parse(), Ok(ByteFormat::Exact));
+        assert_eq!("precise:2".parse(), Ok(ByteFormat::Precise(2)));
+        assert!("precise".parse::<ByteFormat>().is_err());
+    }
+
+    #[test]
+    fn test_rows_within_deadline() {
+        let square = |n: u64| n * n;
+        assert_eq!(rows_within_deadline(None, vec![1, 2, 3], square), [1, 4, 9]);
+
+        let passed = ReportDeadline::new(Duration::ZERO);
+        assert_eq!(
+            rows_within_deadline(Some(&passed), vec![3, 2, 1], square),
+            [9]
+        );
+        assert_eq!(passed.truncated(), 2);
+        // Later computations show the same rows
+        assert_eq!(
+            rows_within_deadline(Some(&passed), vec![3, 2, 1], square),
+            [9]
+        );
+
+        let ahead = ReportDeadline::new(Duration::from_secs(60));
+        assert_eq!(
+            rows_within_deadline(Some(&ahead), vec![1, 2], square),
+            [1, 4]
+        );
+        assert_eq!(ahead.truncated(), 0);
+    }
+
+    #[test]
+    fn test_relative_to_reference_total() {
+        let entries = vec![
+            (
+                "my_crate::db::query".to_string(),
+                vec![
+                    MetricType::CallsCount(2),
+                    MetricType::DurationNs(50),
+                    MetricType::DurationNs(100),
+                    MetricType::Percentage(1_000),
+                ],
+            ),
+            (
+                "my_crate::render".to_string(),
+                vec![
+                    MetricType::CallsCount(1),
+                    MetricType::DurationNs(320),
+                    MetricType::DurationNs(320),
+                    MetricType::Percentage(3_200),
+                ],
+            ),
+        ];
+
+        assert_eq!(reference_total(&entries, "my_crate::db::query"), Some(100));
+        assert_eq!(reference_total(&entries, "db::query"), Some(100));
+        assert_eq!(reference_total(&entries, "query"), None);
+
+        assert_eq!(format_relative(Some(320), 100), "3.20x");
+        assert_eq!(format_relative(Some(100), 100), "1.00x");
+        assert_eq!(format_relative(None, 100), "N/A");
+        assert_eq!(format_relative(Some(320), 0), "N/A");
+    }
+
+    #[test]
+    fn test_trimmed_mean_ignores_outliers() {
+        let mut hist = Histogram::<u64>::new_with_bounds(1, 1_000_000_000, 3).unwrap();
+        for _ in 0..98 {
+            hist.record(100).unwrap();
+        }
+        hist.record(1).unwrap();
+        hist.record(1_000_000).unwrap();
+
+        assert!(hist.mean() > 10_000.0);
+        assert_eq!(trimmed_mean(&hist), 100);
+        assert_eq!(trimmed_mean(&Histogram::<u64>::new(3).unwrap()), 0);
+    }
+
+    #[test]
+    fn test_profiling_mode_description() {
+        assert_eq!(
+            ProfilingMode::Timing.description(),
+            ProfilingMode::Timing.description_with_alloc_self(true)
+        );
+        assert!(ProfilingMode::AllocBytesTotal
+            .description()
+            .starts_with("Cumulative bytes"));
+        assert!(ProfilingMode::AllocCountTotal
+            .description_with_alloc_self(true)
+            .starts_with("Exclusive heap allocations"));
+        assert!(ProfilingMode::AllocLeak
+            .description()
+            .starts_with("Bytes allocated and not freed"));
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_micros(261)), "261.00µs");
+        assert_eq!(format_elapsed(Duration::from_millis(42_100)), "42.10s");
+        assert_eq!(format_elapsed(Duration::from_secs(60)), "1m");
+        assert_eq!(format_elapsed(Duration::from_millis(150_500)), "2m 30s");
+        assert_eq!(format_elapsed(Duration::from_secs(3600)), "1h");
+        assert_eq!(format_elapsed(Duration::from_secs(3725)), "1h 2m 5s");
+    }
+
+    #[test]
+    fn test_shorten_monomorphized_function_name() {
+        assert_eq!(shorten_function_name("a::b::c::process"), "c::process");
+        assert_eq!(
+            shorten_function_name("a::b::process<alloc::string::String, u32>"),
+            "b::process<alloc::string::String, u32>"
+        );
+    }
+
     #[test]
     fn test_deserialize_timing_mode() {
         let json_str = r#"{
@@ -803,6 +2553,72 @@ mod tests {
         assert!(matches!(first_row[4], MetricType::Percentage(_))); // percent_total
     }
 
+    #[test]
+    fn test_to_records() {
+        let json_str = r#"{
+            "hotpath_profiling_mode": "alloc-bytes-total",
+            "total_elapsed": 1000,
+            "caller_name": "basic::main",
+            "description": "Bytes",
+            "output": {
+                "basic::small": {
+                    "calls": 4,
+                    "avg": 10,
+                    "p50": 8,
+                    "p99": 16,
+                    "total": 40,
+                    "percent_total": 400
+                },
+                "basic::run_async": {
+                    "calls": 2,
+                    "avg": null,
+                    "p50": null,
+                    "p99": null,
+                    "total": null,
+                    "percent_total": null
+                },
+                "basic::main": {
+                    "calls": 1,
+                    "avg": 1000,
+                    "p50": 1000,
+                    "p99": 1000,
+                    "total": 1000,
+                    "percent_total": 10000
+                }
+            }
+        }"#;
+
+        let metrics: MetricsJson = serde_json::from_str(json_str).unwrap();
+        let records = metrics.to_records();
+
+        let functions: Vec<&str> = records.iter().map(|r| r.function.as_str()).collect();
+        assert_eq!(
+            functions,
+            ["basic::main", "basic::small", "basic::run_async"]
+        );
+        assert_eq!(
+            records[1],
+            MetricRecord {
+                function: "basic::small".to_string(),
+                calls: 4,
+                avg: Some(10),
+                percentiles: vec![(50.0, Some(8)), (99.0, Some(16))],
+                total: Some(40),
+                percent_total: Some(400),
+            }
+        );
+        assert_eq!(records[1].percentile(99.0), Some(16));
+        assert_eq!(records[1].percentile(95.0), None);
+        assert_eq!(records[2].calls, 2);
+        assert_eq!(records[2].total, None);
+
+        assert_eq!(
+            serde_json::to_string(&records[1]).unwrap(),
+            r#"{"function":"basic::small","calls":4,"avg":10,"p50":8,"p99":16,"total":40,"percent_total":400}"#
+        );
+        assert_eq!(Vec::<MetricRecord>::from(&metrics), records);
+    }
+
     #[test]
     fn test_deserialize_alloc_count_total_mode() {
         let json_str = r#"{
@@ -910,6 +2726,7 @@ mod tests {
     #[test]
     fn test_serialize_deserialize_roundtrip() {
         let original_json_str = r#"{
+            "schema_version": 1,
             "hotpath_profiling_mode": "timing",
             "total_elapsed": 125189584,
             "caller_name": "basic::main",
@@ -922,11 +2739,15 @@ mod tests {
                     "total": 117467210,
                     "percent_total": 9383
                 }
-            }
+            },
+            "commit": "4f2a91c",
+            "stale": true
         }"#;
 
         let metrics: MetricsJson =
             serde_json::from_str(original_json_str).expect("Failed to deserialize");
+        assert_eq!(metrics.commit.as_deref(), Some("4f2a91c"));
+        assert!(metrics.stale);
         let serialized_str = serde_json::to_string(&metrics).expect("Failed to serialize");
 
         let original_json: Value = serde_json::from_str(original_json_str).unwrap();
@@ -955,11 +2776,158 @@ mod tests {
         let metrics: MetricsJson = serde_json::from_str(json_str).expect("Failed to deserialize");
 
         // Verify that the internal structure is correctly parsed
-        assert_eq!(metrics.percentiles, vec![95]);
+        assert_eq!(metrics.percentiles, vec![95.0]);
         assert_eq!(metrics.data.0.len(), 1);
         assert!(metrics.data.0.contains_key("test_function"));
 
         let row = &metrics.data.0["test_function"];
         assert_eq!(row.len(), 5); // calls, avg, p95, total, percent_total
     }
+
+    #[test]
+    fn test_fractional_percentiles_roundtrip() {
+        let json_str = r#"{
+            "schema_version": 1,
+            "hotpath_profiling_mode": "timing",
+            "total_elapsed": 125189584,
+            "caller_name": "basic::main",
+            "description": "Time metrics",
+            "output": {
+                "test_function": {
+                    "calls": 42,
+                    "avg": 1000,
+                    "p0_5": 100,
+                    "p1_5": 200,
+                    "p15": 300,
+                    "p50": 900,
+                    "p99_9": 5000,
+                    "total": 42000,
+                    "percent_total": 100
+                }
+            }
+        }"#;
+
+        let metrics: MetricsJson = serde_json::from_str(json_str).expect("Failed to deserialize");
+        assert_eq!(metrics.percentiles, vec![0.5, 1.5, 15.0, 50.0, 99.9]);
+        assert_eq!(
+            build_headers(&metrics.percentiles)[3..8],
+            ["P0.5", "P1.5", "P15", "P50", "P99.9"]
+        );
+
+        let serialized_str = serde_json::to_string(&metrics).expect("Failed to serialize");
+        let original_json: Value = serde_json::from_str(json_str).unwrap();
+        let serialized_json: Value = serde_json::from_str(&serialized_str).unwrap();
+        assert_eq!(serialized_json, original_json);
+    }
+
+    #[test]
+    fn test_float_percent_format_roundtrip() {
+        let json_str = r#"{
+            "schema_version": 1,
+            "hotpath_profiling_mode": "timing",
+            "total_elapsed": 125189584,
+            "caller_name": "basic::main",
+            "description": "Time metrics",
+            "output": {
+                "test_function": {
+                    "calls": 42,
+                    "avg": 1000,
+                    "p95": 2000,
+                    "total": 42000,
+                    "percent_total": 93.83
+                }
+            }
+        }"#;
+
+        let metrics: MetricsJson = serde_json::from_str(json_str).expect("Failed to deserialize");
+        assert!(matches!(
+            metrics.data.0["test_function"][4],
+            MetricType::Percentage(9383)
+        ));
+
+        let serializer = MetricsJsonSerializer {
+            json: &metrics,
+            percent_format: PercentFormat::Float,
+            distributions: None,
+            allocations: None,
+        };
+        let serialized_json: Value = serde_json::to_value(&serializer).unwrap();
+        let original_json: Value = serde_json::from_str(json_str).unwrap();
+        assert_eq!(serialized_json, original_json);
+
+        let basis_points_json = serde_json::to_value(&metrics).unwrap();
+        assert_eq!(
+            basis_points_json["output"]["test_function"]["percent_total"],
+            9383
+        );
+    }
+
+    #[test]
+    fn test_distribution_serialization() {
+        let json_str = r#"{
+            "schema_version": 1,
+            "hotpath_profiling_mode": "timing",
+            "total_elapsed": 1000,
+            "caller_name": "basic::main",
+            "description": "Time metrics",
+            "output": {
+                "test_function": {
+                    "calls": 2,
+                    "avg": 150,
+                    "p95": 200,
+                    "total": 300,
+                    "percent_total": 3000
+                }
+            }
+        }"#;
+        let metrics: MetricsJson = serde_json::from_str(json_str).unwrap();
+
+        let distribution: Vec<MetricType> = DISTRIBUTION_PERCENTILES
+            .iter()
+            .map(|&p| MetricType::DurationNs(if p <= 50.0 { 100 } else { 200 }))
+            .collect();
+        let distributions = HashMap::from([("test_function".to_string(), distribution)]);
+        let serializer = MetricsJsonSerializer {
+            json: &metrics,
+            percent_format: PercentFormat::BasisPoints,
+            distributions: Some(&distributions),
+            allocations: None,
+        };
+        let serialized = serde_json::to_string(&serializer).unwrap();
+        let serialized_json: Value = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(
+            serialized_json["output"]["test_function"]["distribution"],
+            serde_json::json!([100, 100, 100, 100, 100, 200, 200, 200, 200, 200])
+        );
+
+        // Reports with a distribution still deserialize, the array is skipped
+        let roundtrip: MetricsJson = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(roundtrip.percentiles, vec![95.0]);
+        assert_eq!(roundtrip.data.0["test_function"].len(), 5);
+    }
+
+    #[test]
+    fn test_metadata_roundtrip() {
+        let json_str = r#"{
+            "schema_version": 1,
+            "hotpath_profiling_mode": "timing",
+            "total_elapsed": 1000,
+            "caller_name": "basic::main",
+            "description": "Time metrics",
+            "output": {},
+            "metadata": {"git_sha": "4f2a91c", "hostname": "ci-runner-3"}
+        }"#;
+
+        let mut metrics: MetricsJson =
+            serde_json::from_str(json_str).expect("Failed to deserialize");
+        assert_eq!(metrics.metadata["git_sha"], "4f2a91c");
+
+        let serialized_json: Value = serde_json::to_value(&metrics).unwrap();
+        let original_json: Value = serde_json::from_str(json_str).unwrap();
+        assert_eq!(serialized_json, original_json);
+
+        metrics.metadata.clear();
+        let serialized_json: Value = serde_json::to_value(&metrics).unwrap();
+        assert!(serialized_json.get("metadata").is_none());
+    }
 }
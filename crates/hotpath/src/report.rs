@@ -0,0 +1,261 @@
+use crate::output::{header_to_json_key, shorten_function_name, MetricsJson, MetricsProvider};
+use crate::{format_elapsed, MetricType, ProfilingMode};
+use prettytable::{Attr, Cell, Row, Table};
+use serde::{
+    ser::{SerializeMap, Serializer},
+    Serialize,
+};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+/// Results of a profiling run: one [`FunctionSummary`] per function plus the run metadata.
+///
+/// `Display` renders the report table without terminal styles, and `Serialize` produces
+/// a JSON object with the functions as an array ordered like the table rows. Created with
+/// [`MetricsProvider::into_report`] in custom reporters, or from a loaded [`MetricsJson`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use hotpath::{HotPathReport, MetricsJson};
+///
+/// let json = std::fs::read_to_string("report.json").unwrap();
+/// let metrics: MetricsJson = serde_json::from_str(&json).unwrap();
+/// let report = HotPathReport::from(&metrics);
+/// println!("{report}");
+/// for function in &report.functions {
+///     println!("{}: {} calls, total {}", function.name, function.calls, function.total);
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct HotPathReport {
+    pub profiling_mode: ProfilingMode,
+    pub description: String,
+    pub caller_name: String,
+    /// Wall clock time of the run in nanoseconds
+    pub total_elapsed: u64,
+    pub percentiles: Vec<f64>,
+    /// Ordered by `% Total` descending, then by name
+    pub functions: Vec<FunctionSummary>,
+    /// Key-value pairs set with `GuardBuilder::metadata`
+    pub metadata: BTreeMap<String, String>,
+    /// Git commit recorded with `GuardBuilder::record_commit`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+/// Metrics of a single function in a [`HotPathReport`].
+///
+/// Unlike [`MetricRecord`](crate::MetricRecord), values keep their [`MetricType`], so they
+/// display with units and N/A cells stay distinguishable from missing percentiles.
+#[derive(Debug, Clone)]
+pub struct FunctionSummary {
+    pub name: String,
+    pub calls: u64,
+    pub avg: MetricType,
+    /// `(percentile, value)` pairs in the order of [`HotPathReport::percentiles`]
+    pub percentiles: Vec<(f64, MetricType)>,
+    pub total: MetricType,
+    /// Share of the report total, as [`MetricType::Percentage`] basis points
+    pub percent_total: MetricType,
+}
+
+impl FunctionSummary {
+    /// Value of the given percentile, `None` if it's not part of the report.
+    pub fn percentile(&self, percentile: f64) -> Option<&MetricType> {
+        self.percentiles
+            .iter()
+            .find(|(p, _)| *p == percentile)
+            .map(|(_, value)| value)
+    }
+}
+
+impl Serialize for FunctionSummary {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.percentiles.len() + 5))?;
+        map.serialize_entry("function", &self.name)?;
+        map.serialize_entry("calls", &self.calls)?;
+        map.serialize_entry("avg", &self.avg)?;
+        for (percentile, value) in &self.percentiles {
+            map.serialize_entry(&header_to_json_key(&format!("P{}", percentile)), value)?;
+        }
+        map.serialize_entry("total", &self.total)?;
+        map.serialize_entry("percent_total", &self.percent_total)?;
+        map.end()
+    }
+}
+
+impl From<&MetricsJson> for HotPathReport {
+    fn from(metrics: &MetricsJson) -> Self {
+        let total_index = metrics.percentiles.len() + 2;
+
+        let mut functions: Vec<FunctionSummary> = metrics
+            .data
+            .0
+            .iter()
+            .map(|(name, row)| {
+                let metric =
+                    |index: usize| row.get(index).cloned().unwrap_or(MetricType::NotTracked);
+                FunctionSummary {
+                    name: name.clone(),
+                    calls: metric(0).value().unwrap_or(0),
+                    avg: metric(1),
+                    percentiles: metrics
+                        .percentiles
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &percentile)| (percentile, metric(i + 2)))
+                        .collect(),
+                    total: metric(total_index),
+                    percent_total: metric(total_index + 1),
+                }
+            })
+            .collect();
+
+        functions.sort_by(|a, b| {
+            b.percent_total
+                .value()
+                .cmp(&a.percent_total.value())
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        Self {
+            profiling_mode: metrics.hotpath_profiling_mode.clone(),
+            description: metrics.description.clone(),
+            caller_name: metrics.caller_name.clone(),
+            total_elapsed: metrics.total_elapsed,
+            percentiles: metrics.percentiles.clone(),
+            functions,
+            metadata: metrics.metadata.clone(),
+            commit: metrics.commit.clone(),
+        }
+    }
+}
+
+impl From<&dyn MetricsProvider<'_>> for HotPathReport {
+    fn from(metrics: &dyn MetricsProvider<'_>) -> Self {
+        Self::from(&MetricsJson::from(metrics))
+    }
+}
+
+impl fmt::Display for HotPathReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+
+        let mut headers = vec![
+            "Function".to_string(),
+            "Calls".to_string(),
+            "Avg".to_string(),
+        ];
+        headers.extend(self.percentiles.iter().map(|p| format!("P{}", p)));
+        headers.push("Total".to_string());
+        headers.push("% Total".to_string());
+        table.add_row(Row::new(
+            headers
+                .iter()
+                .map(|header| Cell::new(header).with_style(Attr::Bold))
+                .collect(),
+        ));
+
+        for function in &self.functions {
+            let mut cells = vec![
+                Cell::new(&shorten_function_name(&function.name)),
+                Cell::new(&function.calls.to_string()),
+                Cell::new(&function.avg.to_string()),
+            ];
+            cells.extend(
+                function
+                    .percentiles
+                    .iter()
+                    .map(|(_, value)| Cell::new(&value.to_string())),
+            );
+            cells.push(Cell::new(&function.total.to_string()));
+            cells.push(Cell::new(&function.percent_total.to_string()));
+            table.add_row(Row::new(cells));
+        }
+
+        writeln!(
+            f,
+            "[hotpath] {} - {}",
+            self.profiling_mode, self.description
+        )?;
+        writeln!(
+            f,
+            "{}: {}",
+            self.caller_name,
+            format_elapsed(Duration::from_nanos(self.total_elapsed))
+        )?;
+        write!(f, "{table}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_from_metrics_json() {
+        let json_str = r#"{
+            "hotpath_profiling_mode": "timing",
+            "total_elapsed": 2000000,
+            "caller_name": "basic::main",
+            "description": "Execution duration of functions.",
+            "output": {
+                "basic::parse": {
+                    "calls": 4,
+                    "avg": 1000,
+                    "p95": 1500,
+                    "total": 4000,
+                    "percent_total": 20
+                },
+                "basic::main": {
+                    "calls": 1,
+                    "avg": 2000000,
+                    "p95": 2000000,
+                    "total": 2000000,
+                    "percent_total": 10000
+                }
+            }
+        }"#;
+
+        let metrics: MetricsJson = serde_json::from_str(json_str).unwrap();
+        let report = HotPathReport::from(&metrics);
+
+        let names: Vec<&str> = report.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["basic::main", "basic::parse"]);
+        assert_eq!(report.functions[1].calls, 4);
+        assert_eq!(
+            report.functions[1]
+                .percentile(95.0)
+                .and_then(MetricType::value),
+            Some(1500)
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["profiling_mode"], "timing");
+        assert_eq!(
+            json["functions"][1],
+            serde_json::json!({
+                "function": "basic::parse",
+                "calls": 4,
+                "avg": 1000,
+                "p95": 1500,
+                "total": 4000,
+                "percent_total": 20
+            })
+        );
+
+        let table = report.to_string();
+        assert!(table.starts_with(
+            "[hotpath] timing - Execution duration of functions.\nbasic::main: 2.00ms\n"
+        ));
+        assert!(
+            table.contains("| basic::parse | 4     | 1.00 µs | 1.50 µs | 4.00 µs | 0.20%   |"),
+            "{table}"
+        );
+    }
+}
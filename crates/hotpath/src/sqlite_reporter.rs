@@ -0,0 +1,151 @@
+use crate::output::{MetricType, MetricsProvider, ReportError, Reporter};
+use rusqlite::{params, Connection};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    timestamp INTEGER NOT NULL,
+    git_commit TEXT,
+    caller_name TEXT NOT NULL,
+    mode TEXT NOT NULL,
+    total_elapsed_ns INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS functions (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    function TEXT NOT NULL,
+    calls INTEGER,
+    avg INTEGER,
+    total INTEGER,
+    percent REAL,
+    PRIMARY KEY (run_id, function)
+);
+CREATE TABLE IF NOT EXISTS percentiles (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    function TEXT NOT NULL,
+    percentile REAL NOT NULL,
+    value INTEGER,
+    PRIMARY KEY (run_id, function, percentile)
+);
+CREATE TABLE IF NOT EXISTS metadata (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (run_id, key)
+);
+";
+
+/// Built-in reporter appending the results of each run to a SQLite database, to query
+/// performance trends across many runs, e.g. CI builds, with SQL.
+///
+/// Tables are created on first use:
+///
+/// * `runs` - `id`, `timestamp` (Unix seconds), `git_commit` (see
+///   [`GuardBuilder::record_commit`](crate::GuardBuilder::record_commit)), `caller_name`,
+///   `mode` and `total_elapsed_ns`
+/// * `functions` - `run_id`, `function`, `calls`, `avg`, `total` and `percent` of every
+///   measured function, including the ones cut off by
+///   [`GuardBuilder::limit`](crate::GuardBuilder::limit)
+/// * `percentiles` - `run_id`, `function`, `percentile` and `value`
+/// * `metadata` - `run_id`, `key` and `value` pairs set with
+///   [`GuardBuilder::metadata`](crate::GuardBuilder::metadata)
+///
+/// Values are in the unit of the run's `mode`: nanoseconds in `timing` mode, bytes or
+/// allocation counts in allocation modes. Values not available for a function (e.g.
+/// unsupported async allocations) are `NULL`. Nothing is written to the report sink.
+///
+/// Requires the `hotpath-sqlite` feature.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let _hotpath = hotpath::GuardBuilder::new("main")
+///     .reporter(Box::new(hotpath::SqliteReporter::new("hotpath.db")))
+///     .build();
+/// ```
+pub struct SqliteReporter {
+    path: PathBuf,
+}
+
+impl SqliteReporter {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Reporter for SqliteReporter {
+    fn write_report(
+        &self,
+        metrics_provider: &dyn MetricsProvider<'_>,
+        _out: &mut dyn std::io::Write,
+    ) -> Result<(), ReportError> {
+        let mut connection = Connection::open(&self.path).map_err(ReportError::custom)?;
+        connection
+            .execute_batch(SCHEMA)
+            .map_err(ReportError::custom)?;
+        let transaction = connection.transaction().map_err(ReportError::custom)?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        transaction
+            .execute(
+                "INSERT INTO runs (timestamp, git_commit, caller_name, mode, total_elapsed_ns)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    timestamp,
+                    metrics_provider.commit(),
+                    metrics_provider.caller_name(),
+                    metrics_provider.profiling_mode().to_string(),
+                    metrics_provider.total_elapsed(),
+                ],
+            )
+            .map_err(ReportError::custom)?;
+        let run_id = transaction.last_insert_rowid();
+
+        for (key, value) in metrics_provider.metadata() {
+            transaction
+                .execute(
+                    "INSERT INTO metadata (run_id, key, value) VALUES (?1, ?2, ?3)",
+                    params![run_id, key, value],
+                )
+                .map_err(ReportError::custom)?;
+        }
+
+        let percentiles = metrics_provider.percentiles();
+        let total_index = percentiles.len() + 2;
+        for (function, metrics) in metrics_provider.all_metric_data() {
+            let value = |index: usize| metrics.get(index).and_then(MetricType::value);
+            let percent = value(total_index + 1).map(|basis_points| basis_points as f64 / 100.0);
+            transaction
+                .execute(
+                    "INSERT INTO functions (run_id, function, calls, avg, total, percent)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        run_id,
+                        function,
+                        value(0),
+                        value(1),
+                        value(total_index),
+                        percent
+                    ],
+                )
+                .map_err(ReportError::custom)?;
+
+            for (i, percentile) in percentiles.iter().enumerate() {
+                transaction
+                    .execute(
+                        "INSERT INTO percentiles (run_id, function, percentile, value)
+                         VALUES (?1, ?2, ?3, ?4)",
+                        params![run_id, function, percentile, value(i + 2)],
+                    )
+                    .map_err(ReportError::custom)?;
+            }
+        }
+
+        transaction.commit().map_err(ReportError::custom)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use crate::output::{
+    get_sorted_entries, MetricType, MetricsProvider, ProfilingMode, ReportError, Reporter,
+};
+
+/// Built-in reporter emitting structured [`tracing`] events instead of a formatted table.
+///
+/// All events are emitted under a parent `hotpath` span carrying the caller name and
+/// profiling mode. Each measured function produces one `INFO` event with target `hotpath`
+/// and the following fields:
+///
+/// * timing mode - `function`, `calls`, `avg_ns`, `p95_ns`, `total_ns`, `percent`
+/// * alloc bytes mode - `function`, `calls`, `avg_bytes`, `p95_bytes`, `total_bytes`, `percent`
+/// * alloc count mode - `function`, `calls`, `avg_allocs`, `p95_allocs`, `total_allocs`, `percent`
+/// * alloc leak mode - `function`, `calls`, `avg_retained_bytes`, `p95_retained_bytes`,
+///   `total_retained_bytes`, `percent`
+///
+/// The `p95_*` field is only recorded if `95.0` is one of the configured percentiles.
+/// Values not available for a function (e.g. unsupported async allocations) are omitted.
+///
+/// Requires the `hotpath-tracing` feature.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let _hotpath = hotpath::GuardBuilder::new("main")
+///     .percentiles(&[95.0])
+///     .reporter(Box::new(hotpath::TracingReporter))
+///     .build();
+/// ```
+pub struct TracingReporter;
+
+impl Reporter for TracingReporter {
+    // Events go to the installed tracing subscriber, not to the report sink
+    fn write_report(
+        &self,
+        metrics_provider: &dyn MetricsProvider<'_>,
+        _out: &mut dyn std::io::Write,
+    ) -> Result<(), ReportError> {
+        let mode = metrics_provider.profiling_mode();
+        let span = tracing::info_span!(
+            target: "hotpath",
+            "hotpath",
+            caller = metrics_provider.caller_name(),
+            mode = %mode,
+            total_elapsed_ns = metrics_provider.total_elapsed(),
+        );
+        let _enter = span.enter();
+
+        let percentiles = metrics_provider.percentiles();
+        let p95_index = percentiles.iter().position(|&p| p == 95.0).map(|i| i + 2);
+        let total_index = percentiles.len() + 2;
+
+        for (function, metrics) in get_sorted_entries(metrics_provider) {
+            let calls = metrics.first().and_then(MetricType::value);
+            let avg = metrics.get(1).and_then(MetricType::value);
+            let p95 = p95_index
+                .and_then(|i| metrics.get(i))
+                .and_then(MetricType::value);
+            let total = metrics.get(total_index).and_then(MetricType::value);
+            let percent = metrics
+                .get(total_index + 1)
+                .and_then(MetricType::value)
+                .map(|basis_points| basis_points as f64 / 100.0);
+
+            match mode {
+                ProfilingMode::Timing => tracing::info!(
+                    target: "hotpath",
+                    function = %function,
+                    calls,
+                    avg_ns = avg,
+                    p95_ns = p95,
+                    total_ns = total,
+                    percent,
+                ),
+                ProfilingMode::AllocBytesTotal => tracing::info!(
+                    target: "hotpath",
+                    function = %function,
+                    calls,
+                    avg_bytes = avg,
+                    p95_bytes = p95,
+                    total_bytes = total,
+                    percent,
+                ),
+                ProfilingMode::AllocCountTotal => tracing::info!(
+                    target: "hotpath",
+                    function = %function,
+                    calls,
+                    avg_allocs = avg,
+                    p95_allocs = p95,
+                    total_allocs = total,
+                    percent,
+                ),
+                ProfilingMode::AllocLeak => tracing::info!(
+                    target: "hotpath",
+                    function = %function,
+                    calls,
+                    avg_retained_bytes = avg,
+                    p95_retained_bytes = p95,
+                    total_retained_bytes = total,
+                    percent,
+                ),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -431,6 +431,90 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_tracing_builtin_reporter_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "tracing_builtin_reporter",
+                "--features",
+                "hotpath,hotpath-tracing",
+            ])
+            .env("RUST_LOG", "info")
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        let expected_content = [
+            "hotpath{caller=\"main\" mode=timing",
+            "function=tracing_builtin_reporter::async_function calls=100 avg_ns=",
+            "function=tracing_builtin_reporter::sync_function calls=100 avg_ns=",
+            "p95_ns=",
+            "total_ns=",
+            "percent=",
+        ];
+
+        for expected in expected_content {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_sqlite_reporter_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "sqlite_reporter",
+                "--features",
+                "hotpath,hotpath-sqlite",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        let expected_content = [
+            "runs: 2",
+            "timing sqlite_reporter::async_function calls=100 profile=dev",
+            "timing sqlite_reporter::sync_function calls=100 profile=dev",
+            "p95 rows: 6",
+        ];
+
+        for expected in expected_content {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+        assert!(
+            !stdout.contains("| Function"),
+            "Expected no table output.\n\nGot:\n{stdout}",
+        );
+    }
+
     #[test]
     fn test_json_file_reporter_output() {
         use std::fs;
@@ -691,6 +775,59 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_limit_total_output() {
+        let features = [
+            "",
+            "hotpath-alloc-bytes-total",
+            "hotpath-alloc-count-total",
+            "hotpath-alloc-leak",
+        ];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "limit_total",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+
+            // 3 measured functions, the registered one and the hidden wrapper aren't counted
+            for expected in ["(1/3)", "limit_total::large"] {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+            for not_expected in ["limit_total::medium", "limit_total::registered"] {
+                assert!(
+                    !stdout.contains(not_expected),
+                    "Not expected:\n{not_expected}\n\nGot:\n{stdout}"
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_multithread_alloc_no_panic() {
         let test_cases = [
@@ -804,4 +941,3473 @@ pub mod tests {
             );
         }
     }
+    #[test]
+    fn test_track_return_output() {
+        // Only the returned values are attributed to `::return`, not the scratch buffers, nor
+        // the early return of `early_exit(0)`
+        let cases = [
+            (
+                "hotpath-alloc-bytes-total",
+                [
+                    "total: track_return::build_buffer::return 56320\n",
+                    "total: track_return::early_exit::return 45\n",
+                    "total: track_return::explicit_return::return 1000\n",
+                ],
+            ),
+            (
+                "hotpath-alloc-count-total",
+                [
+                    "total: track_return::build_buffer::return 10\n",
+                    "total: track_return::early_exit::return 9\n",
+                    "total: track_return::explicit_return::return 10\n",
+                ],
+            ),
+        ];
+
+        for (feature, totals) in cases {
+            let features_arg = format!("hotpath,{}", feature);
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "track_return",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            for expected in ["track_return::build_buffer", "build_buffer::return"]
+                .into_iter()
+                .chain(totals)
+            {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "track_return",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            !stdout.contains("::return"),
+            "Not expected:\n::return\n\nGot:\n{stdout}"
+        );
+    }
+    #[test]
+    fn test_percent_basis_measured_sum_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "percent_basis",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let json: serde_json::Value =
+            serde_json::from_str(stdout.trim()).expect("Output is not valid JSON");
+
+        let percent_total = |name: &str| {
+            json["output"][name]["percent_total"]
+                .as_u64()
+                .unwrap_or_else(|| panic!("Missing percent_total for {name}\n\nGot:\n{stdout}"))
+        };
+
+        let sum = percent_total("percent_basis::short_function")
+            + percent_total("percent_basis::long_function");
+
+        assert!(
+            (9990..=10000).contains(&sum),
+            "Expected measured functions to sum up to 100%, got {sum} basis points\n\nGot:\n{stdout}"
+        );
+    }
+    #[test]
+    fn test_raw_sink_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "raw_sink",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let names: Vec<&str> = stdout
+                .lines()
+                .filter_map(|line| line.strip_prefix("raw: "))
+                .filter_map(|line| line.split_whitespace().next())
+                .collect();
+
+            let expected = [
+                "raw_sink::first_function",
+                "raw_sink::second_function",
+                "raw_sink::first_function",
+                "raw_sink::second_function",
+                "raw_sink::first_function",
+                "raw_sink::second_function",
+                "raw_sink::main",
+            ];
+
+            assert_eq!(names, expected, "Got:\n{stdout}");
+        }
+    }
+    #[test]
+    fn test_missing_main_warns_once() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "missing_main",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(
+                stdout.contains("missing_main finished"),
+                "Expected:\nmissing_main finished\n\nGot:\n{stdout}"
+            );
+
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert_eq!(
+                stderr.matches("no hotpath guard is active").count(),
+                1,
+                "Expected a single warning\n\nGot:\n{stderr}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_coarse_clock_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "main_empty",
+                "--features",
+                "hotpath,hotpath-coarse-clock",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = ["main_empty::example_function", "| 5 "];
+        for expected in expected {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_monomorphized_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "monomorphized",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let all_expected = [
+                "monomorphized::process<i32>",
+                "monomorphized::process<alloc::string::String>",
+            ];
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            for expected in all_expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_alloc_here_output() {
+        let features = ["hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = format!("hotpath,{}", feature);
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "alloc_here",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let all_expected = [
+                "alloc_here::build_buffers",
+                "build_buffers::small",
+                "build_buffers::large",
+            ];
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            for expected in all_expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "alloc_here",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            !stdout.contains("build_buffers::small"),
+            "Expected no alloc_here entries in time mode\n\nGot:\n{stdout}",
+        );
+    }
+
+    #[test]
+    fn test_process_peak_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "process_peak",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(
+                stdout.contains("Process peak RSS:") && stdout.contains(" MB"),
+                "Expected:\nProcess peak RSS: <n> MB\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_functions_report_zero_allocations() {
+        let features = ["hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = format!("hotpath,{}", feature);
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "empty_alloc",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = [
+                "total: empty_alloc::empty_function 0\n",
+                "total: empty_alloc::outer_function 0\n",
+                "total: empty_block 0\n",
+            ];
+
+            for expected in expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_baseline_output() {
+        use std::fs;
+
+        let baseline_path =
+            std::env::temp_dir().join(format!("hotpath_test_baseline_{}.json", std::process::id()));
+        fs::write(
+            &baseline_path,
+            r#"{
+                "hotpath_profiling_mode": "timing",
+                "total_elapsed": 1000000,
+                "caller_name": "baseline::main",
+                "description": "Execution duration of functions.",
+                "percentiles": [95],
+                "output": {
+                    "baseline::fast_function": {
+                        "calls": 10,
+                        "avg": 100000,
+                        "p95": 100000,
+                        "total": 1000000,
+                        "percent_total": 1000
+                    },
+                    "baseline::removed_function": {
+                        "calls": 5,
+                        "avg": 1000,
+                        "p95": 1000,
+                        "total": 5000,
+                        "percent_total": 5
+                    }
+                }
+            }"#,
+        )
+        .expect("Failed to write baseline file");
+
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "baseline",
+                "--features",
+                "hotpath",
+                "--",
+                baseline_path.to_str().unwrap(),
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected_content = [
+            "(baseline: ",
+            "| baseline::fast_function",
+            "10 → 10 (+0.0%)",
+            "[new] baseline::slow_function",
+            "[removed] baseline::removed_function",
+        ];
+
+        for expected in expected_content {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+
+        // A timing baseline isn't compared against allocation counts
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "baseline",
+                "--features",
+                "hotpath,hotpath-alloc-count-total",
+                "--",
+                baseline_path.to_str().unwrap(),
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        fs::remove_file(&baseline_path).ok();
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("it was recorded in timing mode, not alloc_count_total"),
+            "Expected a mode mismatch warning\n\nGot:\n{stderr}",
+        );
+        assert!(
+            !stdout.contains("(baseline: ") && stdout.contains("| baseline::slow_function"),
+            "Expected a regular table\n\nGot:\n{stdout}",
+        );
+    }
+
+    #[test]
+    fn test_crate_rollup_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "crate_rollup",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected_content = [
+                "crate_rollup::own_function",
+                "totals by crate",
+                "| Crate ",
+                "| crate_rollup | 2         | 20 ",
+                "| serde_json   | 1         | 10 ",
+            ];
+
+            for expected in expected_content {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_categorize_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "categorize",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected_content = [
+                "categorize::db_query",
+                "totals by category",
+                "| Category ",
+                "| db       | 2         | 20 ",
+                "| network  | 1         | 10 ",
+                "| compute  | 1         | 10 ",
+            ];
+
+            for expected in expected_content {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_assert_max_alloc_output() {
+        let test_cases = [
+            ("hotpath", false),
+            ("hotpath,hotpath-alloc-blocks", true),
+            ("hotpath,hotpath-time-alloc-count", true),
+            ("hotpath,hotpath-alloc-bytes-total", true),
+            ("hotpath,hotpath-alloc-count-total", true),
+            ("hotpath,hotpath-alloc-leak", true),
+        ];
+
+        for (features, checked) in test_cases {
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "assert_max_alloc",
+                    "--features",
+                    features,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let over_budget = if checked {
+                "over budget: assert_max_alloc!(\"build_index\") failed: allocated 8000 bytes, expected at most 1024 bytes"
+            } else {
+                "over budget: unchecked"
+            };
+
+            for expected in ["within budget: 100 values", over_budget] {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot ({features}):\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_measure_block_alloc_output() {
+        let features = [
+            "hotpath-alloc-blocks",
+            "hotpath-alloc-blocks,hotpath-alloc-bytes-total",
+        ];
+
+        for feature in features {
+            let features_arg = format!("hotpath,{}", feature);
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "measure_block_alloc",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = if feature == "hotpath-alloc-blocks" {
+                "| build_index | 10    | 3.3 KB | 33.2 KB | 1010   |"
+            } else {
+                "| build_index "
+            };
+
+            assert!(
+                stdout.contains("measure_block_alloc::sleepy_function")
+                    && stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_time_alloc_count_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "time_alloc_count",
+                "--features",
+                "hotpath,hotpath-time-alloc-count",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            !stdout.contains("[hotpath] allocations"),
+            "Expected no separate allocations table\n\nGot:\n{stdout}",
+        );
+
+        let allocs_column = |name: &str| {
+            let row = stdout
+                .lines()
+                .find(|line| line.starts_with(&format!("| {name} ")))
+                .unwrap_or_else(|| panic!("Missing row {name}\n\nGot:\n{stdout}"));
+            row.split('|').nth(5).unwrap_or_default().trim().to_string()
+        };
+        assert!(stdout.contains("| P95 "), "Got:\n{stdout}");
+        assert!(stdout.contains("| Allocs | Total "), "Got:\n{stdout}");
+        assert_eq!(allocs_column("time_alloc_count::build_index"), "1010");
+        assert_eq!(allocs_column("time_alloc_count::sleepy_function"), "0");
+
+        // Filtered functions are left out of the JSON report along with their allocations
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "time_alloc_count",
+                "--features",
+                "hotpath,hotpath-time-alloc-count",
+            ])
+            .env("HOTPATH_JSON", "true")
+            .env("HOTPATH_ONLY", "time_alloc_count::build_index")
+            .output()
+            .expect("Failed to execute command");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains(r#""allocs":1010"#), "Got:\n{stdout}");
+        assert!(!stdout.contains("sleepy_function"), "Got:\n{stdout}");
+    }
+
+    #[test]
+    fn test_serve_metrics_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "serve_metrics",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = [
+            "HTTP/1.1 200 OK",
+            "sleepy_function exposed: true",
+            "health: HTTP/1.1 200 OK",
+            "health ok: true",
+            "server stopped: true",
+        ];
+
+        for expected in expected {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+
+        assert!(
+            !stdout.contains("[hotpath]"),
+            "Expected no report to be printed\n\nGot:\n{stdout}",
+        );
+    }
+
+    #[test]
+    fn test_function_percentiles_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "function_percentiles",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let header = stdout
+                .lines()
+                .find(|line| line.contains("| Function"))
+                .unwrap_or_default();
+            assert!(
+                header.contains("| P95 ") && header.contains("| P50 ") && header.contains("| P99 "),
+                "Expected P95, P50 and P99 columns\n\nGot:\n{stdout}",
+            );
+
+            let row = |name: &str| {
+                stdout
+                    .lines()
+                    .find(|line| line.contains(name))
+                    .unwrap_or_default()
+                    .split('|')
+                    .map(str::trim)
+                    .filter(|cell| !cell.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            };
+
+            let regular = row("function_percentiles::regular_function");
+            assert!(
+                regular[3] != "N/A" && regular[4] == "N/A" && regular[5] == "N/A",
+                "Expected only P95 for regular_function\n\nGot:\n{stdout}",
+            );
+
+            let tail_sensitive = row("function_percentiles::tail_sensitive_function");
+            assert!(
+                tail_sensitive[3] == "N/A"
+                    && tail_sensitive[4] != "N/A"
+                    && tail_sensitive[5] != "N/A",
+                "Expected only P50 and P99 for tail_sensitive_function\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_fractional_percentiles_output() {
+        for (json, expected) in [(false, "| P99.9 "), (true, "\"p99_9\":")] {
+            let mut command = Command::new("cargo");
+            command.args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "fractional_percentiles",
+                "--features",
+                "hotpath",
+            ]);
+            if json {
+                command.env("HOTPATH_JSON", "true");
+            }
+
+            let output = command.output().expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_report_deadline_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "report_deadline",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(
+                stdout.contains("(1/4)")
+                    && stdout.contains("| report_deadline::main ")
+                    && !stdout.contains("| report_deadline::parse ")
+                    && stdout.contains("Functions cut due to report deadline: 3"),
+                "Expected only the top function and 3 cut\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_overhead_budget_output() {
+        let features = ["", "hotpath-alloc-bytes-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "overhead_budget",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            // Allocation modes aren't distorted by the profiler's cost, so they never warn
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let warned = stderr.contains("[hotpath] WARNING: profiling overhead of")
+                && stderr.contains("above the budget of 5%");
+            assert_eq!(
+                warned,
+                feature.is_empty(),
+                "Unexpected overhead warning for features {features_arg}\n\nGot:\n{stderr}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_parallel_tests_output() {
+        let output = Command::new("cargo")
+            .args([
+                "test",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "parallel_tests",
+                "--features",
+                "hotpath",
+                "--",
+                "--nocapture",
+                "--test-threads=3",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstdout:\n{stdout}\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        for test_name in ["test_first", "test_second", "test_third"] {
+            assert!(
+                stdout
+                    .lines()
+                    .any(|line| line.starts_with(&format!("{test_name}:"))),
+                "Expected a {test_name} report\n\nGot:\n{stdout}",
+            );
+        }
+        assert_eq!(
+            stdout
+                .matches("| parallel_tests::sync_function | 1 ")
+                .count(),
+            3,
+            "Expected one call in each report\n\nGot:\n{stdout}",
+        );
+    }
+
+    #[test]
+    fn test_max_functions_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "max_functions",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+
+            assert!(
+                stdout.contains("max_functions::main")
+                    && stdout.contains("max_functions::describe<u32>")
+                    && !stdout.contains("max_functions::describe<u64>")
+                    && stdout.contains("Functions dropped due to cardinality limit: 2"),
+                "Expected 3 functions and 2 dropped\n\nGot:\n{stdout}",
+            );
+            assert!(
+                stderr.contains("[hotpath] Function limit of 3 reached"),
+                "Expected a cardinality warning\n\nGot:\n{stderr}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_async_early_returns_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "async_early_returns",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let calls = |name: &str| {
+                stdout
+                    .lines()
+                    .find(|line| line.contains(name))
+                    .and_then(|line| line.split('|').nth(2))
+                    .map(|cell| cell.trim().to_string())
+            };
+
+            for (name, expected_calls) in [
+                ("async_early_returns::propagates_error", "3"),
+                ("async_early_returns::returns_error_early", "4"),
+                ("async_early_returns::panics_after_await", "1"),
+            ] {
+                assert_eq!(
+                    calls(name).as_deref(),
+                    Some(expected_calls),
+                    "Expected {name} to be measured {expected_calls} times\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_catch_unwind_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "catch_unwind",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(
+                stdout.contains("call 1: panicked\ncall 2: ok\ncall 3: panicked\n"),
+                "Expected the caller to continue after panics\n\nGot:\n{stdout}",
+            );
+
+            let cells: Vec<String> = stdout
+                .lines()
+                .find(|line| line.contains("catch_unwind::fallible_work"))
+                .unwrap_or_else(|| panic!("Expected a fallible_work row\n\nGot:\n{stdout}"))
+                .split('|')
+                .map(|cell| cell.trim().to_string())
+                .collect();
+            assert_eq!(
+                cells[2], "4",
+                "Expected panicking calls to be counted\n\nGot:\n{stdout}"
+            );
+
+            if feature.is_empty() {
+                // P0 is the shortest call, panicking calls are measured up to the panic
+                let min_ms: f64 = cells[4]
+                    .strip_suffix(" ms")
+                    .and_then(|ms| ms.parse().ok())
+                    .unwrap_or_else(|| panic!("Expected P0 in ms\n\nGot:\n{stdout}"));
+                assert!(
+                    min_ms >= 5.0,
+                    "Expected P0 of at least 5ms\n\nGot:\n{stdout}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_phases_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "phases",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected_content = [
+                "[hotpath] phases",
+                "| init  | phases::load_config    | 1 ",
+                "| init  | phases::handle_request | 1 ",
+                "| serve | phases::handle_request | 4 ",
+            ];
+
+            for expected in expected_content {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_register_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "register",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = [
+                "| register::parse          | 3     |",
+                "| register::fallback_parse | 0     |",
+                "Registered functions never called: 1\n  - register::fallback_parse\n",
+            ];
+
+            for expected in expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_alloc_parity_output() {
+        let cases = [
+            (
+                "hotpath-alloc-bytes-total",
+                [
+                    "total: alloc_parity::with_capacity 1000\n",
+                    "total: alloc_parity::zeroed 1000\n",
+                    "total: alloc_parity::over_aligned 2024\n",
+                    "total: alloc_parity::grow 2000\n",
+                ],
+            ),
+            (
+                "hotpath-alloc-count-total",
+                [
+                    "total: alloc_parity::with_capacity 1\n",
+                    "total: alloc_parity::zeroed 1\n",
+                    "total: alloc_parity::over_aligned 2\n",
+                    "total: alloc_parity::grow 1\n",
+                ],
+            ),
+        ];
+
+        for (feature, expected) in cases {
+            let features_arg = format!("hotpath,{}", feature);
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "alloc_parity",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            for expected in expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_measure_impl_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "measure_impl",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let expected_content = [
+            "| Counter::new ",
+            "| Counter::get ",
+            "| Counter::increment ",
+            "| Counter::into_inner ",
+            "| Counter::async_increment ",
+            "| Gauge::new ",
+            "| Gauge::default ",
+            "measure_impl::main",
+        ];
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        for expected in expected_content {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+
+        let not_expected_content = ["Counter::zero", "Counter::reset"];
+
+        for not_expected in not_expected_content {
+            assert!(
+                !stdout.contains(not_expected),
+                "Not expected:\n{not_expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_metrics_delta_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "metrics_delta",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = [
+                "delta 1: Some(10)\n",
+                "delta 2: Some(3)\n",
+                "delta 3: None\n",
+                "cumulative: Some(13)\n",
+            ];
+
+            for expected in expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_call_rates_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "call_rates",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = ["bucket_ns: 1000000000\n", "calls: 10\n"];
+
+            for expected in expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_show_locations_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "show_locations",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = [
+                "| show_locations::parse (crates/hotpath-test-tokio-async/examples/show_locations.rs:2) | 20 ",
+                "| show_locations::fetch (crates/hotpath-test-tokio-async/examples/show_locations.rs:7) | 10 ",
+                "| custom_block (crates/hotpath-test-tokio-async/examples/show_locations.rs:22) ",
+                "| show_locations::main ",
+            ];
+
+            for expected in expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+
+            // The registered name matches the measured function, it has no row of its own
+            assert_eq!(
+                stdout.matches("show_locations::parse").count(),
+                1,
+                "Expected a single parse row\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "render",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = [
+                "rendered table:\n[hotpath]",
+                "| render::compute | 10 ",
+                "rendered json calls: Some(10)",
+            ];
+
+            for expected in expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+
+            assert_eq!(
+                stdout.matches("[hotpath]").count(),
+                1,
+                "Expected only the rendered report\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_poll_time_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "poll_time",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = [
+            "calls: poll_time::cpu_bound 5\n",
+            "calls: poll_time::cpu_bound::poll 5\n",
+            "calls: poll_time::io_bound 5\n",
+            "calls: poll_time::io_bound::poll 5\n",
+            "io_bound mostly suspended: true\n",
+            "| Avg Polls |",
+        ];
+
+        for expected in expected {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+
+        // Suspended once at the sleep, so polled twice per call
+        let io_poll_row = stdout
+            .lines()
+            .find(|line| line.contains("| io_bound::poll "))
+            .unwrap_or_default();
+        assert!(
+            io_poll_row.contains("| 2 "),
+            "Expected 2 polls per call\n\nGot:\n{stdout}",
+        );
+
+        // Poll time is not measured in allocation modes
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "poll_time",
+                "--features",
+                "hotpath,hotpath-alloc-bytes-total",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            !stdout.contains("::poll"),
+            "Not expected:\n::poll\n\nGot:\n{stdout}",
+        );
+    }
+
+    #[test]
+    fn test_returns_future_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "returns_future",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = [
+            "calls: returns_future::fetch 3\n",
+            "calls: returns_future::fetch_or_retry 3\n",
+            "calls: returns_future::fetch_boxed 4\n",
+            "awaited work measured: true\n",
+        ];
+
+        for expected in expected {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_plain_output() {
+        let features = ["", "hotpath-alloc-bytes-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "plain",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(
+                stdout.contains("plain::parse   10 ")
+                    && !stdout.contains('|')
+                    && !stdout.contains("+-")
+                    && !stdout.contains('\x1b'),
+                "Expected a table without borders or styles\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_clock_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "clock",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        // Durations are read from the fake clock, without the calibrated overhead
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = [
+            "clock::main: 60.00ms\n",
+            "sample: clock::step 10000000\n",
+            "sample: clock::step 20000000\n",
+            "sample: clock::step 30000000\n",
+            "sample: clock::main 60000000\n",
+        ];
+
+        for expected in expected {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_include_wrapper_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "include_wrapper",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let json: serde_json::Value =
+                serde_json::from_str(stdout.trim()).expect("Output is not valid JSON");
+
+            assert!(
+                json["output"].get("include_wrapper::main").is_none(),
+                "Not expected:\ninclude_wrapper::main\n\nGot:\n{stdout}",
+            );
+
+            // Percentages are still relative to the caller total
+            let percent_total = json["output"]["include_wrapper::work"]["percent_total"]
+                .as_u64()
+                .unwrap_or_else(|| panic!("Missing percent_total\n\nGot:\n{stdout}"));
+            assert!(
+                percent_total < 10000,
+                "Expected work to be below 100%, got {percent_total} basis points\n\nGot:\n{stdout}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_debug_reporter_invariants() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath,hotpath-debug".to_string()
+            } else {
+                format!("hotpath,hotpath-debug,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "debug_reporter",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = "hotpath invariants ok: 4 functions checked";
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_relative_to_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "relative_to",
+                "--features",
+                "hotpath",
+            ])
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("| % Total | Relative |"),
+            "Expected Relative column\n\nGot:\n{stdout}"
+        );
+
+        let relative = |function: &str| -> f64 {
+            let line = stdout
+                .lines()
+                .find(|line| line.contains(function))
+                .unwrap_or_else(|| panic!("Expected {function} row\n\nGot:\n{stdout}"));
+            let cell = line
+                .trim_end_matches('|')
+                .rsplit('|')
+                .next()
+                .unwrap()
+                .trim();
+            cell.strip_suffix('x')
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_else(|| panic!("Expected a multiple, got {cell}\n\nGot:\n{stdout}"))
+        };
+
+        assert_eq!(relative("| db::query"), 1.0);
+        // render sleeps 3 times as long as query
+        let render = relative("| relative_to::render");
+        assert!((2.5..=3.5).contains(&render), "Got:\n{stdout}");
+    }
+
+    #[test]
+    fn test_trimmed_mean_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "trimmed_mean",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(
+                stdout.contains("| Avg ") && stdout.contains("| Trimmed Avg |"),
+                "Expected:\nTrimmed Avg column\n\nGot:\n{stdout}",
+            );
+
+            if feature == "hotpath-alloc-bytes-total" {
+                // Trimmed mean ignores the 1 MB outliers
+                let cells: Vec<&str> = stdout
+                    .lines()
+                    .find(|line| line.contains("trimmed_mean::long_tail"))
+                    .unwrap_or_else(|| panic!("Missing long_tail row\n\nGot:\n{stdout}"))
+                    .split('|')
+                    .map(str::trim)
+                    .collect();
+                assert_eq!(cells[4], "64 B", "Got:\n{stdout}");
+                assert_ne!(cells[3], "64 B", "Got:\n{stdout}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_percent_format_float_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "percent_format",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let json: serde_json::Value =
+                serde_json::from_str(stdout.trim()).expect("Output is not valid JSON");
+
+            let percent_total = &json["output"]["percent_format::work"]["percent_total"];
+            assert!(
+                percent_total
+                    .as_f64()
+                    .is_some_and(|p| percent_total.is_f64() && p > 0.0 && p <= 100.0),
+                "Expected float percent, got {percent_total}\n\nGot:\n{stdout}"
+            );
+
+            // Float percentages are read back as basis points
+            let metrics: hotpath::MetricsJson =
+                serde_json::from_str(stdout.trim()).expect("Failed to deserialize");
+            assert!(metrics.data.0.contains_key("percent_format::work"));
+        }
+    }
+
+    #[test]
+    fn test_include_distribution_output() {
+        let features = ["", "hotpath-alloc-bytes-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "include_distribution",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let json: serde_json::Value =
+                serde_json::from_str(stdout.trim()).expect("Output is not valid JSON");
+
+            let distribution = json["output"]["include_distribution::work"]["distribution"]
+                .as_array()
+                .unwrap_or_else(|| panic!("Expected distribution array\n\nGot:\n{stdout}"));
+            assert_eq!(distribution.len(), 10, "Got:\n{stdout}");
+
+            let values: Vec<u64> = distribution.iter().filter_map(|v| v.as_u64()).collect();
+            assert_eq!(values.len(), 10, "Got:\n{stdout}");
+            assert!(
+                values.windows(2).all(|w| w[0] <= w[1]),
+                "Expected ascending values, got {values:?}"
+            );
+            if feature.is_empty() {
+                assert!(
+                    values[9] > values[0],
+                    "Expected a spread of durations, got {values:?}"
+                );
+            }
+
+            let metrics: hotpath::MetricsJson =
+                serde_json::from_str(stdout.trim()).expect("Failed to deserialize");
+            assert!(metrics.data.0.contains_key("include_distribution::work"));
+        }
+    }
+
+    #[test]
+    fn test_console_once_output() {
+        use std::io::{BufRead, BufReader};
+        use std::process::Stdio;
+
+        let mut target = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "console_once",
+                "--features",
+                "hotpath",
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to execute command");
+
+        let mut ready = String::new();
+        BufReader::new(target.stdout.take().unwrap())
+            .read_line(&mut ready)
+            .unwrap();
+        assert_eq!(ready.trim(), "ready");
+
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath",
+                "--bin",
+                "hotpath",
+                "--features",
+                "tui",
+                "--",
+                "console",
+                "--metrics-port",
+                "6783",
+                "--once",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        // Closing stdin stops the target
+        drop(target.stdin.take());
+        target.wait().unwrap();
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = [
+            "[hotpath] timing - Execution duration of functions.",
+            "| Function ",
+            "| console_once::sleepy_function | 10 ",
+        ];
+
+        for expected in expected {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+
+        assert!(
+            !stdout.contains('\x1b'),
+            "Expected no terminal escape codes\n\nGot:\n{stdout}",
+        );
+    }
+
+    #[test]
+    fn test_http_tls_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "http_tls",
+                "--features",
+                "hotpath,hotpath-tls",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = [
+            "https status: 200 OK",
+            "sleepy_function exposed: true",
+            "plain http rejected: true",
+        ];
+
+        for expected in expected {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("Metrics server listening on https://"),
+            "Got:\n{stderr}",
+        );
+    }
+
+    #[test]
+    fn test_alloc_leak_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "alloc_leak",
+                "--features",
+                "hotpath,hotpath-alloc-leak",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = [
+            "total: alloc_leak::leaky_function 10240\n",
+            "total: alloc_leak::balanced_function 0\n",
+            "total: alloc_leak::outer_function 10240\n",
+        ];
+
+        for expected in expected {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_bench_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "bench",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = [
+                "bench::sort: 200 iterations, avg ",
+                "| bench::sort | 200   |",
+            ];
+
+            for expected in expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_metadata_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "metadata",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = r#""metadata":{"git_sha":"9b0e1d7","hostname":"ci-runner-3"}"#;
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_record_commit_output() {
+        let head = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .expect("Failed to execute git");
+        let head = String::from_utf8_lossy(&head.stdout).trim().to_string();
+
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "record_commit",
+                "--features",
+                "hotpath",
+            ])
+            .env("GITHUB_SHA", "4f2a91c3d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0")
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // The checked out commit takes precedence over GITHUB_SHA
+        let expected = format!(r#""commit":"{head}""#);
+        assert!(
+            stdout.contains(&expected),
+            "Expected:\n{expected}\n\nGot:\n{stdout}",
+        );
+    }
+
+    #[test]
+    fn test_measure_lock_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "measure_lock",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let lock_row = stdout
+            .lines()
+            .find(|line| line.contains("| lock:counter "))
+            .unwrap_or_else(|| panic!("Expected a lock:counter row\n\nGot:\n{stdout}"));
+        assert!(lock_row.contains("| 200 "), "Got:\n{lock_row}");
+        assert!(stdout.contains("| lock:final_read "), "Got:\n{stdout}");
+    }
+
+    #[test]
+    fn test_measure_drop_output() {
+        let features = ["", "hotpath-alloc-bytes-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "measure_drop",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected_content = [
+                "Connection::drop              | 5 ",
+                "| temp_dir_cleanup              | 3 ",
+            ];
+
+            for expected in expected_content {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_byte_format_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "byte_format",
+                "--features",
+                "hotpath,hotpath-alloc-bytes-total",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected_content = ["| byte_format::work | 5     | 1.953 KB |", "9.766 KB"];
+
+        for expected in expected_content {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_raw_samples_file_output() {
+        for file_name in ["hotpath_raw_samples.csv", "hotpath_raw_samples.ndjson"] {
+            let path = std::env::temp_dir().join(file_name);
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "raw_samples_file",
+                    "--features",
+                    "hotpath",
+                    "--",
+                ])
+                .arg(&path)
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let lines: Vec<&str> = stdout.lines().collect();
+
+            if file_name.ends_with(".csv") {
+                assert_eq!(lines[0], "function,value,timestamp_ns");
+                let samples = lines
+                    .iter()
+                    .filter(|line| line.starts_with("\"raw_samples_file::sync_function\","))
+                    .count();
+                assert_eq!(samples, 25, "Got:\n{stdout}");
+                assert!(
+                    stdout.contains("\"raw_samples_file::main\","),
+                    "Got:\n{stdout}"
+                );
+            } else {
+                let samples: Vec<serde_json::Value> = lines
+                    .iter()
+                    .map(|line| serde_json::from_str(line).unwrap())
+                    .collect();
+                assert_eq!(samples.len(), 26, "Got:\n{stdout}");
+                assert!(samples
+                    .iter()
+                    .all(|sample| sample["value"].is_u64() && sample["timestamp_ns"].is_u64()));
+                assert_eq!(
+                    samples
+                        .iter()
+                        .filter(|sample| sample["function"] == "raw_samples_file::sync_function")
+                        .count(),
+                    25
+                );
+            }
+
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn test_chrome_trace_output() {
+        let path =
+            std::env::temp_dir().join(format!("hotpath_chrome_trace_{}.json", std::process::id()));
+
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "chrome_trace",
+                "--features",
+                "hotpath",
+                "--",
+            ])
+            .arg(&path)
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let trace: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let events = trace.as_array().unwrap();
+        assert!(events.iter().all(|event| event["ph"] == "X"
+            && event["ts"].is_number()
+            && event["dur"].is_number()
+            && event["pid"].is_u64()
+            && event["tid"].is_u64()));
+
+        let calls: Vec<_> = events
+            .iter()
+            .filter(|event| event["name"] == "chrome_trace::sync_function")
+            .collect();
+        assert_eq!(calls.len(), 20, "Got:\n{stdout}");
+        let threads: std::collections::HashSet<_> =
+            calls.iter().map(|event| event["tid"].as_u64()).collect();
+        assert_eq!(threads.len(), 2, "Got:\n{stdout}");
+        assert!(events
+            .iter()
+            .any(|event| event["name"] == "chrome_trace::main"));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_report_on_idle_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "report_on_idle",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let reports: Vec<serde_json::Value> = stdout
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("Output is not valid JSON"))
+            .collect();
+
+        // Two idle reports followed by the final one on drop
+        assert_eq!(reports.len(), 3, "Got:\n{stdout}");
+        let calls: Vec<_> = reports
+            .iter()
+            .map(|report| report["output"]["report_on_idle::handle_request"]["calls"].clone())
+            .collect();
+        assert_eq!(calls, [10, 20, 20]);
+        assert!(reports[0]["output"]["report_on_idle::main"].is_null());
+        assert!(reports[2]["output"]["report_on_idle::main"].is_object());
+    }
+
+    #[test]
+    fn test_measure_spawn_output() {
+        for features in ["hotpath", "hotpath,hotpath-alloc-bytes-total"] {
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "measure_spawn",
+                    "--features",
+                    features,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let task_row = stdout
+                .lines()
+                .find(|line| line.contains("| fetch_task "))
+                .unwrap_or_else(|| panic!("Expected a fetch_task row\n\nGot:\n{stdout}"));
+            assert!(task_row.contains("| 5 "), "Got:\n{task_row}");
+            assert!(stdout.contains("| flush_task "), "Got:\n{stdout}");
+            assert!(!stdout.contains("N/A*"), "Got:\n{stdout}");
+        }
+    }
+
+    #[test]
+    fn test_main_stream_param() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "main_stream",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(stdout, "0\n2\n4\n6\n8\n");
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("| main_stream::example_function | 5 "),
+            "Got:\n{stderr}"
+        );
+    }
+
+    #[test]
+    fn test_aggregate_output() {
+        let mut report_paths = Vec::new();
+        for i in 0..2 {
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "main_format",
+                    "--features",
+                    "hotpath",
+                ])
+                .output()
+                .expect("Failed to execute command");
+            assert!(output.status.success());
+
+            let path = std::env::temp_dir().join(format!("hotpath_aggregate_{i}.json"));
+            std::fs::write(&path, &output.stdout).unwrap();
+            report_paths.push(path.display().to_string());
+        }
+
+        for format in ["table", "json"] {
+            let mut args = vec![
+                "run",
+                "-p",
+                "hotpath",
+                "--bin",
+                "hotpath",
+                "--",
+                "aggregate",
+                "--format",
+                format,
+            ];
+            args.extend(report_paths.iter().map(String::as_str));
+            let output = Command::new("cargo")
+                .args(args)
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if format == "table" {
+                assert!(
+                    stdout.contains("Aggregated 2 reports of main_format::main"),
+                    "Got:\n{stdout}"
+                );
+                assert!(
+                    stdout.contains("| main_format::example_function | 10 "),
+                    "Got:\n{stdout}"
+                );
+            } else {
+                let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+                let function = &json["output"]["main_format::example_function"];
+                assert_eq!(function["calls"], 10);
+                assert!(function["p95"].is_null(), "Got:\n{stdout}");
+            }
+        }
+
+        for path in report_paths {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn test_zero_overhead_when_disabled() {
+        let binary_path = |features: &[&str]| {
+            let mut args = vec![
+                "build",
+                "--release",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "zero_overhead",
+                "--message-format",
+                "json",
+            ];
+            args.extend(features);
+            let output = Command::new("cargo")
+                .args(args)
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Build failed.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+                .filter(|message| message["target"]["name"] == "zero_overhead")
+                .find_map(|message| message["executable"].as_str().map(String::from))
+                .expect("Expected the example executable path")
+        };
+
+        // Mangled symbols of the hotpath crate and the profiler thread name
+        let markers: [&[u8]; 2] = [b"7hotpath", b"hotpath-worker"];
+        let contains = |binary: &[u8], marker: &[u8]| {
+            binary.windows(marker.len()).any(|window| window == marker)
+        };
+
+        let disabled = std::fs::read(binary_path(&[])).unwrap();
+        for marker in markers {
+            assert!(
+                !contains(&disabled, marker),
+                "Found {:?} in a binary built without the hotpath feature",
+                String::from_utf8_lossy(marker)
+            );
+        }
+
+        // Guards against the markers going stale
+        let enabled = std::fs::read(binary_path(&["--features", "hotpath"])).unwrap();
+        for marker in markers {
+            assert!(
+                contains(&enabled, marker),
+                "Expected {:?} in a binary built with the hotpath feature",
+                String::from_utf8_lossy(marker)
+            );
+        }
+    }
+
+    #[test]
+    fn test_hotpath_only_env() {
+        for features in ["hotpath", "hotpath,hotpath-alloc-count-total"] {
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "basic",
+                    "--features",
+                    features,
+                ])
+                .env("HOTPATH_JSON", "true")
+                .env("HOTPATH_ONLY", "basic::sync_*, custom_block")
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let json_line = stdout
+                .lines()
+                .find(|line| line.starts_with('{'))
+                .unwrap_or_else(|| panic!("Expected a JSON report\n\nGot:\n{stdout}"));
+            let json: serde_json::Value = serde_json::from_str(json_line).unwrap();
+            let mut functions: Vec<&str> = json["output"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(String::as_str)
+                .collect();
+            functions.sort();
+
+            assert_eq!(
+                functions,
+                ["basic::main", "basic::sync_function", "custom_block"],
+                "Got:\n{stdout}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sort_by_max_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "sort_by_max",
+                "--features",
+                "hotpath,hotpath-alloc-bytes-total",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        // The one-time spike is kept over the function with the larger total
+        for expected in [
+            "(2/3)",
+            "sort_by_max::main",
+            "sort_by_max::spike_allocation",
+        ] {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+        assert!(
+            !stdout.contains("sort_by_max::small_allocations"),
+            "Expected small_allocations to be limited out\n\nGot:\n{stdout}",
+        );
+    }
+
+    #[test]
+    fn test_alloc_backtrace_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "alloc_backtrace",
+                "--features",
+                "hotpath,hotpath-alloc-backtrace",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let (_, backtraces) = stdout
+            .split_once("largest allocations")
+            .unwrap_or_else(|| panic!("Expected largest allocations\n\nGot:\n{stdout}"));
+
+        // Backtraces start at the allocating function, not in the allocator
+        let (_, build_cache) = backtraces
+            .split_once("alloc_backtrace::build_cache: 4.0 MB")
+            .unwrap_or_else(|| panic!("Expected build_cache allocation\n\nGot:\n{stdout}"));
+        let first_frame = build_cache.lines().nth(1).unwrap_or_default();
+        assert!(
+            first_frame.ends_with(": alloc_backtrace::build_cache"),
+            "Expected build_cache frame\n\nGot:\n{stdout}"
+        );
+        assert!(
+            backtraces.contains("alloc_backtrace::load_config: 100.0 KB"),
+            "Expected load_config allocation\n\nGot:\n{stdout}"
+        );
+        assert!(
+            !backtraces.contains("CountingAllocator"),
+            "Expected allocator frames to be trimmed\n\nGot:\n{stdout}"
+        );
+    }
+
+    #[test]
+    fn test_recursion_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "fibonacci_alloc",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+
+            // Only the 4 outermost calls from main are recorded
+            let fibonacci_row = stdout
+                .lines()
+                .find(|line| line.contains("fibonacci_alloc::fibonacci "))
+                .unwrap_or_else(|| panic!("Expected fibonacci row\n\nGot:\n{stdout}"));
+            assert!(
+                fibonacci_row.contains("| 4 "),
+                "Expected 4 calls\n\nGot:\n{stdout}"
+            );
+            assert!(
+                stdout.contains(
+                    "Max recursion depth (outermost calls recorded): fibonacci_alloc::fibonacci 12"
+                ),
+                "Expected recursion depth\n\nGot:\n{stdout}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_trivial_program_percentages() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "trivial",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let main_row = stdout
+            .lines()
+            .find(|line| line.contains("| trivial::main "))
+            .unwrap_or_else(|| panic!("Expected main row\n\nGot:\n{stdout}"));
+
+        // Either the wall clock time was measurable, or it fell back to the measured sum
+        assert!(
+            main_row.contains("100.00%")
+                || stdout.contains("elapsed time is below the clock resolution"),
+            "Expected sensible percentages\n\nGot:\n{stdout}"
+        );
+    }
+
+    #[test]
+    fn test_inline_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-leak"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "inline",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = ["recorded before drop: 10", "| inline::compute | 10 "];
+
+            for expected in expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_report_atexit_output() {
+        let features = ["", "hotpath-alloc-bytes-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "report_atexit",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert_eq!(
+                output.status.code(),
+                Some(3),
+                "Expected the program's exit code.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(
+                stdout.contains("| report_atexit::parse_args | 3 "),
+                "Expected report written at exit\n\nGot:\n{stdout}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_named_percentiles_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "named_percentiles",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        for expected in ["| P50 ", "| P99.9 ", "| P100 "] {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_alloc_count_columns_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "sort_by_max",
+                "--features",
+                "hotpath,hotpath-alloc-bytes-total",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("| Allocs | Avg Alloc | Total "),
+            "Expected allocation count columns\n\nGot:\n{stdout}"
+        );
+
+        let spike_row = stdout
+            .lines()
+            .find(|line| line.contains("sort_by_max::spike_allocation"))
+            .unwrap_or_else(|| panic!("Expected spike_allocation row\n\nGot:\n{stdout}"));
+        let cells: Vec<&str> = spike_row.split('|').map(str::trim).collect();
+        assert_eq!(
+            cells[5..7],
+            ["1", "8.0 MB"],
+            "Expected a single 8 MB allocation\n\nGot:\n{stdout}"
+        );
+    }
+
+    #[test]
+    fn test_measure_if_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "measure_if",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = ["| measure_if::parse | 20 ", "| parse_long        | 10 "];
+
+            for expected in expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_measure_io_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "measure_io",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = [
+                "io blocks - Bytes read or written by measure_io! blocks.",
+                "| read_chunk  | 5     | 204 B | 1.0 KB |",
+                "| write_chunk | 4     | 256 B | 1.0 KB |",
+            ];
+
+            for expected in expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_record_value_output() {
+        let features = ["", "hotpath-alloc-bytes-total", "hotpath-alloc-count-total"];
+
+        for feature in features {
+            let features_arg = if feature.is_empty() {
+                "hotpath".to_string()
+            } else {
+                format!("hotpath,{}", feature)
+            };
+
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "record_value",
+                    "--features",
+                    &features_arg,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let expected = [
+                "values - Distribution of record_value! values.",
+                "| Value      | Calls | Avg     | P50     | Max     | Total    |",
+                "| batch_sum  | 22    | 918     | 755     | 2420    | 20200    |",
+                "| batch_size | 22    | 18 rows | 10 rows | 40 rows | 400 rows |",
+            ];
+
+            for expected in expected {
+                assert!(
+                    stdout.contains(expected),
+                    "Expected:\n{expected}\n\nGot:\n{stdout}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_batch_channel_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "channel_overhead",
+                "--features",
+                "hotpath,hotpath-batch-channel",
+            ])
+            .env("HOTPATH_JSON", "true")
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        // Batches of every thread are flushed, including the last partial ones
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = [
+            "backend: batch-channel",
+            "\"channel_overhead::noop\":{\"calls\":2000000,",
+            "\"channel_overhead::main\":{\"calls\":1,",
+        ];
+
+        for expected in expected {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_inline_feature_output() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "channel_overhead",
+                "--features",
+                "hotpath,hotpath-inline",
+            ])
+            .env("HOTPATH_JSON", "true")
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        // Aggregated without the worker thread, which isn't compiled with this feature
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = [
+            "backend: inline",
+            "\"channel_overhead::noop\":{\"calls\":2000000,",
+            "\"channel_overhead::main\":{\"calls\":1,",
+        ];
+
+        for expected in expected {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_on_slow_output() {
+        for features in ["hotpath", "hotpath,hotpath-inline"] {
+            let output = Command::new("cargo")
+                .args([
+                    "run",
+                    "-p",
+                    "hotpath-test-tokio-async",
+                    "--example",
+                    "on_slow",
+                    "--features",
+                    features,
+                ])
+                .output()
+                .expect("Failed to execute command");
+
+            assert!(
+                output.status.success(),
+                "Process did not exit successfully.\n\nstderr:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            // Only the 50ms call crosses the threshold, the caller function is not checked
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert_eq!(
+                stdout.matches("slow call: ").count(),
+                1,
+                "Expected a single slow call with {features}:\n{stdout}",
+            );
+            assert!(
+                stdout.contains("slow call: on_slow::query took more than 20ms"),
+                "Got:\n{stdout}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_main_name_param() {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "-p",
+                "hotpath-test-tokio-async",
+                "--example",
+                "main_name",
+                "--features",
+                "hotpath",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(
+            output.status.success(),
+            "Process did not exit successfully.\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = ["\napp: ", "| app ", "| main_name::example_function | 3 "];
+        for expected in expected {
+            assert!(
+                stdout.contains(expected),
+                "Expected:\n{expected}\n\nGot:\n{stdout}",
+            );
+        }
+        assert!(!stdout.contains("main_name::main"), "Got:\n{stdout}");
+    }
 }